@@ -0,0 +1,112 @@
+/*
+ * C API for the DEX scanner's pricing: quote a swap against a pool and
+ * evaluate a market snapshot with the scanner's strategies. Link against
+ * libdex_scanner_capi (built from capi/ with `cargo build --release`).
+ *
+ * Amounts are whole-token doubles, addresses 20 raw bytes. Values that may
+ * be unknown are NaN when they are. Functions that fail return -1 (or NaN)
+ * and leave a message in dex_last_error().
+ */
+
+#ifndef DEX_SCANNER_H
+#define DEX_SCANNER_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+enum {
+    DEX_VENUE_UNISWAP_V2 = 0,
+    DEX_VENUE_SUSHISWAP = 1,
+    DEX_VENUE_UNISWAP_V3 = 2,
+    DEX_VENUE_AERODROME = 3,
+    DEX_VENUE_PANCAKESWAP = 4,
+    DEX_VENUE_SYNCSWAP = 5,
+    DEX_VENUE_TRADER_JOE = 6,
+    DEX_VENUE_TRADER_JOE_LB = 7,
+    DEX_VENUE_SPOOKYSWAP = 8,
+    DEX_VENUE_EQUALIZER = 9,
+};
+
+/* One pool's reserves, oriented as base/quote. */
+typedef struct {
+    uint32_t venue;
+    uint8_t address[20];
+    double reserve_base;
+    double reserve_quote;
+    /* Swap fee as a fraction of the input; NaN for the venue's default. */
+    double fee;
+} DexPool;
+
+/* A pair's pool on each of the two venues. */
+typedef struct {
+    uint8_t base[20];
+    uint8_t quote[20];
+    DexPool pools[2];
+    /* Gas for an arbitrage on the pair in quote units, NaN if unknown. */
+    double gas_cost;
+    /* USD value of one quote token, NaN if unknown. */
+    double quote_usd;
+} DexPair;
+
+/* The market as of one block. */
+typedef struct {
+    uint64_t block;
+    /* Unix time in milliseconds the reserves were read at. */
+    int64_t read_at_ms;
+    const DexPair *pairs;
+    size_t pairs_len;
+    /* Gas price in gwei, NaN if unknown. */
+    double gas_price_gwei;
+    /* Flash-loan fee rate on the buy leg's input, 0 for own capital. */
+    double flash_loan_fee;
+    /* Minimum USD profit after gas in place of margins, NaN for margins. */
+    double min_profit_usd;
+} DexSnapshot;
+
+/* An arbitrage sized for the most profit. */
+typedef struct {
+    uint8_t base[20];
+    uint8_t quote[20];
+    DexPool buy;
+    DexPool sell;
+    double amount_in;
+    double base_amount;
+    double amount_out;
+    double profit;
+    double net_profit;
+    double gas_cost;
+    double flash_loan_fee;
+    double break_even;
+} DexOpportunity;
+
+/* Selects the chain (a preset or strategy profile name, NULL for
+ * "ethereum") and comma-separated strategies (NULL for "cross-venue").
+ * Call once, before dex_evaluate. Returns 0 or -1. */
+int32_t dex_init(const char *chain, const char *strategies);
+
+/* Output of swapping amount_in through pool after its fee: quote for base
+ * when sell_base, else base for quote. NaN on error. */
+double dex_quote(const DexPool *pool, double amount_in, bool sell_base);
+
+/* Writes up to capacity opportunities found in snapshot to out and returns
+ * how many were found, which can exceed capacity, or -1. Pair tokens must
+ * belong to the chain given to dex_init. */
+int64_t dex_evaluate(const DexSnapshot *snapshot, DexOpportunity *out, size_t capacity);
+
+/* Display name of a DEX_VENUE_* value, or NULL. */
+const char *dex_venue_name(uint32_t venue);
+
+/* The last error on this thread, or NULL. Valid until the thread's next
+ * failing call. */
+const char *dex_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DEX_SCANNER_H */
@@ -0,0 +1,307 @@
+//! C ABI for the scanner's pricing: quote a swap against a pool and
+//! evaluate a market snapshot with the scanner's strategies. Declared in
+//! `include/dex_scanner.h`; amounts are whole-token `double`s, addresses
+//! 20 raw bytes, and an unknown `Option<f64>` crosses as NaN.
+
+use alloy::primitives::Address;
+use anyhow::{bail, ensure, Context, Result};
+use dex_scanner::models::opportunity::{Opportunity, PoolState, Venue};
+use dex_scanner::models::snapshot::{MarketSnapshot, PairQuote};
+use dex_scanner::models::token::{get_token_info, TokenInfo};
+use dex_scanner::preset::{self, Preset};
+use dex_scanner::strategy::{Registry, Strategy};
+use dex_scanner::utils::price;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::OnceLock;
+
+// Venues by the numbers the header gives them; never reorder
+const VENUES: [Venue; 10] = [
+    Venue::UniswapV2,
+    Venue::Sushiswap,
+    Venue::UniswapV3,
+    Venue::Aerodrome,
+    Venue::PancakeSwap,
+    Venue::SyncSwap,
+    Venue::TraderJoe,
+    Venue::TraderJoeLB,
+    Venue::SpookySwap,
+    Venue::Equalizer,
+];
+
+static STRATEGIES: OnceLock<Vec<Box<dyn Strategy>>> = OnceLock::new();
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// One pool's reserves, oriented as base/quote.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct DexPool {
+    pub venue: u32,
+    pub address: [u8; 20],
+    pub reserve_base: f64,
+    pub reserve_quote: f64,
+    /// Swap fee as a fraction of the input; NaN for the venue's default.
+    pub fee: f64,
+}
+
+/// A pair's pool on each of the two venues.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct DexPair {
+    pub base: [u8; 20],
+    pub quote: [u8; 20],
+    pub pools: [DexPool; 2],
+    /// Gas for an arbitrage on the pair in quote units, NaN if unknown.
+    pub gas_cost: f64,
+    /// USD value of one quote token, NaN if unknown.
+    pub quote_usd: f64,
+}
+
+/// The market as of one block.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct DexSnapshot {
+    pub block: u64,
+    /// Unix time in milliseconds the reserves were read at.
+    pub read_at_ms: i64,
+    pub pairs: *const DexPair,
+    pub pairs_len: usize,
+    /// Gas price in gwei, NaN if unknown.
+    pub gas_price_gwei: f64,
+    /// Flash-loan fee rate on the buy leg's input, 0 for own capital.
+    pub flash_loan_fee: f64,
+    /// Minimum USD profit after gas in place of margins, NaN for margins.
+    pub min_profit_usd: f64,
+}
+
+/// An arbitrage sized for the most profit.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct DexOpportunity {
+    pub base: [u8; 20],
+    pub quote: [u8; 20],
+    pub buy: DexPool,
+    pub sell: DexPool,
+    pub amount_in: f64,
+    pub base_amount: f64,
+    pub amount_out: f64,
+    pub profit: f64,
+    /// NaN when gas is unknown.
+    pub net_profit: f64,
+    /// NaN when unknown.
+    pub gas_cost: f64,
+    pub flash_loan_fee: f64,
+    /// NaN when gas is unknown or no size covers it.
+    pub break_even: f64,
+}
+
+fn set_error(e: &anyhow::Error) {
+    let message = CString::new(format!("{:#}", e).replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, turning an error or panic into `fallback` and the thread's
+/// last error.
+fn guard<T>(fallback: T, f: impl FnOnce() -> Result<T>) -> T {
+    let result = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Err(anyhow::anyhow!("panicked: {}", message))
+    });
+    result.unwrap_or_else(|e| {
+        set_error(&e);
+        fallback
+    })
+}
+
+fn venue(id: u32) -> Result<Venue> {
+    VENUES.get(id as usize).copied().with_context(|| format!("Unknown venue {}", id))
+}
+
+fn venue_id(venue: Venue) -> u32 {
+    VENUES.iter().position(|v| *v == venue).unwrap_or_default() as u32
+}
+
+fn optional(value: f64) -> Option<f64> {
+    (!value.is_nan()).then_some(value)
+}
+
+fn token(address: [u8; 20]) -> Result<&'static TokenInfo> {
+    let address = Address::from(address);
+    get_token_info(&address)
+        .with_context(|| format!("{:?} isn't a token of the {} preset", address, preset::active().name))
+}
+
+fn pool_state(pool: &DexPool) -> Result<PoolState> {
+    let venue = venue(pool.venue)?;
+    Ok(PoolState {
+        venue,
+        pool: Address::from(pool.address),
+        reserve_base: pool.reserve_base,
+        reserve_quote: pool.reserve_quote,
+        fee: optional(pool.fee).unwrap_or(venue.default_fee()),
+    })
+}
+
+fn dex_pool(pool: &PoolState) -> DexPool {
+    DexPool {
+        venue: venue_id(pool.venue),
+        address: pool.pool.into(),
+        reserve_base: pool.reserve_base,
+        reserve_quote: pool.reserve_quote,
+        fee: pool.fee,
+    }
+}
+
+fn dex_opportunity(opportunity: &Opportunity) -> DexOpportunity {
+    DexOpportunity {
+        base: opportunity.base.address.into(),
+        quote: opportunity.quote.address.into(),
+        buy: dex_pool(&opportunity.buy),
+        sell: dex_pool(&opportunity.sell),
+        amount_in: opportunity.amount_in,
+        base_amount: opportunity.base_amount,
+        amount_out: opportunity.amount_out,
+        profit: opportunity.profit(),
+        net_profit: opportunity.net_profit().unwrap_or(f64::NAN),
+        gas_cost: opportunity.gas_cost.unwrap_or(f64::NAN),
+        flash_loan_fee: opportunity.flash_loan_fee,
+        break_even: opportunity.break_even.unwrap_or(f64::NAN),
+    }
+}
+
+/// # Safety
+/// `snapshot.pairs` must point to `snapshot.pairs_len` pairs, or be null
+/// with no pairs.
+unsafe fn market_snapshot(snapshot: &DexSnapshot) -> Result<MarketSnapshot> {
+    let pairs = if snapshot.pairs_len == 0 {
+        &[][..]
+    } else {
+        ensure!(!snapshot.pairs.is_null(), "Snapshot has {} pairs but no pointer", snapshot.pairs_len);
+        std::slice::from_raw_parts(snapshot.pairs, snapshot.pairs_len)
+    };
+    let pairs = pairs
+        .iter()
+        .map(|pair| {
+            Ok(PairQuote {
+                base: token(pair.base)?,
+                quote: token(pair.quote)?,
+                pools: [pool_state(&pair.pools[0])?, pool_state(&pair.pools[1])?],
+                gas_cost: optional(pair.gas_cost),
+                quote_usd: optional(pair.quote_usd),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(MarketSnapshot {
+        block: snapshot.block,
+        read_at: chrono::DateTime::from_timestamp_millis(snapshot.read_at_ms).context("Invalid read_at_ms")?,
+        pairs,
+        gas_price_gwei: optional(snapshot.gas_price_gwei),
+        flash_loan_fee: snapshot.flash_loan_fee,
+        min_profit_usd: optional(snapshot.min_profit_usd),
+        threshold_scale: 1.0,
+        btc: None,
+    })
+}
+
+/// Selects the chain (a preset or strategy profile name, as the binary
+/// takes it) and the comma-separated strategies `dex_evaluate` runs. Call
+/// once, before `dex_evaluate`. Returns 0, or -1 with the error in
+/// `dex_last_error`.
+///
+/// # Safety
+/// `chain` and `strategies` must be null or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn dex_init(chain: *const c_char, strategies: *const c_char) -> i32 {
+    guard(-1, || {
+        let chain = if chain.is_null() { "ethereum" } else { CStr::from_ptr(chain).to_str()? };
+        let list = if strategies.is_null() { "cross-venue" } else { CStr::from_ptr(strategies).to_str()? };
+        let selected = Preset::by_name(chain).or_else(|_| Preset::strategy(chain))?;
+        preset::init(selected);
+        if !std::ptr::eq(preset::active(), selected) {
+            bail!("Already initialised for {}", preset::active().name);
+        }
+        let built = Registry::default().build(list)?;
+        if STRATEGIES.set(built).is_err() {
+            bail!("Already initialised");
+        }
+        Ok(0)
+    })
+}
+
+/// Output of swapping `amount_in` through `pool` after its fee: quote for
+/// base when `sell_base`, else base for quote. NaN on error.
+///
+/// # Safety
+/// `pool` must be null or point to a valid `DexPool`.
+#[no_mangle]
+pub unsafe extern "C" fn dex_quote(pool: *const DexPool, amount_in: f64, sell_base: bool) -> f64 {
+    guard(f64::NAN, || {
+        let pool = pool_state(pool.as_ref().context("Null pool")?)?;
+        let (reserve_in, reserve_out) = if sell_base {
+            (pool.reserve_base, pool.reserve_quote)
+        } else {
+            (pool.reserve_quote, pool.reserve_base)
+        };
+        Ok(price::get_amount_out(amount_in, reserve_in, reserve_out, pool.fee))
+    })
+}
+
+/// Evaluates `snapshot` with the strategies chosen in `dex_init`, writing
+/// up to `capacity` opportunities to `out`. Returns how many were found,
+/// which can exceed `capacity`, or -1 with the error in `dex_last_error`.
+/// Pair tokens must belong to the chosen chain.
+///
+/// # Safety
+/// `snapshot` must point to a valid `DexSnapshot`, and `out` to room for
+/// `capacity` opportunities unless `capacity` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn dex_evaluate(
+    snapshot: *const DexSnapshot,
+    out: *mut DexOpportunity,
+    capacity: usize,
+) -> i64 {
+    guard(-1, || {
+        let strategies = STRATEGIES.get().context("dex_init hasn't been called")?;
+        let snapshot = market_snapshot(snapshot.as_ref().context("Null snapshot")?)?;
+        let found = strategies
+            .iter()
+            .flat_map(|strategy| strategy.evaluate(&snapshot))
+            .collect::<Vec<_>>();
+        if capacity > 0 {
+            ensure!(!out.is_null(), "Null output buffer");
+            for (i, opportunity) in found.iter().take(capacity).enumerate() {
+                out.add(i).write(dex_opportunity(opportunity));
+            }
+        }
+        Ok(found.len() as i64)
+    })
+}
+
+/// Display name of venue `id`, or null if there's no such venue. The
+/// string is static.
+#[no_mangle]
+pub extern "C" fn dex_venue_name(id: u32) -> *const c_char {
+    static NAMES: OnceLock<Vec<CString>> = OnceLock::new();
+    let names = NAMES.get_or_init(|| {
+        VENUES
+            .iter()
+            .map(|venue| CString::new(venue.name()).unwrap_or_default())
+            .collect()
+    });
+    names.get(id as usize).map_or(std::ptr::null(), |name| name.as_ptr())
+}
+
+/// The last error on this thread, or null if there was none. Valid until
+/// the thread's next failing call.
+#[no_mangle]
+pub extern "C" fn dex_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(std::ptr::null(), |e| e.as_ptr()))
+}
@@ -0,0 +1,320 @@
+//! Python bindings for the scanner library: fetch a market snapshot, quote
+//! swaps against its pools and evaluate it with the same strategies the
+//! scanner runs. Built with maturin as the `dex_scanner` module.
+
+use dex_scanner::models::opportunity;
+use dex_scanner::models::snapshot::{MarketSnapshot, PairQuote};
+use dex_scanner::strategy::Registry;
+use dex_scanner::utils::price;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+fn runtime_error(e: anyhow::Error) -> PyErr {
+    PyRuntimeError::new_err(format!("{:#}", e))
+}
+
+fn value_error(e: anyhow::Error) -> PyErr {
+    PyValueError::new_err(format!("{:#}", e))
+}
+
+/// One pool's reserves, oriented as base/quote in whole-token units.
+#[pyclass(name = "Pool", module = "dex_scanner", frozen)]
+#[derive(Clone, Copy)]
+struct Pool(opportunity::PoolState);
+
+#[pymethods]
+impl Pool {
+    #[getter]
+    fn venue(&self) -> &'static str {
+        self.0.venue.name()
+    }
+
+    #[getter]
+    fn address(&self) -> String {
+        format!("{:?}", self.0.pool)
+    }
+
+    #[getter]
+    fn reserve_base(&self) -> f64 {
+        self.0.reserve_base
+    }
+
+    #[getter]
+    fn reserve_quote(&self) -> f64 {
+        self.0.reserve_quote
+    }
+
+    /// Swap fee as a fraction of the input, e.g. 0.003.
+    #[getter]
+    fn fee(&self) -> f64 {
+        self.0.fee
+    }
+
+    /// Quote per base.
+    #[getter]
+    fn price(&self) -> f64 {
+        self.0.price()
+    }
+
+    /// Output of swapping `amount_in` through the pool after its fee:
+    /// quote for base when `sell_base`, else base for quote.
+    #[pyo3(signature = (amount_in, sell_base = false))]
+    fn quote(&self, amount_in: f64, sell_base: bool) -> f64 {
+        let (reserve_in, reserve_out) = if sell_base {
+            (self.0.reserve_base, self.0.reserve_quote)
+        } else {
+            (self.0.reserve_quote, self.0.reserve_base)
+        };
+        price::get_amount_out(amount_in, reserve_in, reserve_out, self.0.fee)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Pool({} {:?}, price={})", self.0.venue.name(), self.0.pool, self.0.price())
+    }
+}
+
+/// A pair's pool on each of the two venues.
+#[pyclass(name = "Pair", module = "dex_scanner", frozen)]
+struct Pair(PairQuote);
+
+#[pymethods]
+impl Pair {
+    #[getter]
+    fn base(&self) -> &'static str {
+        self.0.base.symbol
+    }
+
+    #[getter]
+    fn quote(&self) -> &'static str {
+        self.0.quote.symbol
+    }
+
+    #[getter]
+    fn pools(&self) -> Vec<Pool> {
+        self.0.pools.map(Pool).to_vec()
+    }
+
+    /// Gas for an arbitrage on the pair in quote units, if known.
+    #[getter]
+    fn gas_cost(&self) -> Option<f64> {
+        self.0.gas_cost
+    }
+
+    /// USD value of one quote token, if known.
+    #[getter]
+    fn quote_usd(&self) -> Option<f64> {
+        self.0.quote_usd
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Pair({}/{})", self.0.base.symbol, self.0.quote.symbol)
+    }
+}
+
+/// The market as of one block.
+#[pyclass(name = "Snapshot", module = "dex_scanner", frozen)]
+struct Snapshot(MarketSnapshot);
+
+#[pymethods]
+impl Snapshot {
+    #[getter]
+    fn block(&self) -> u64 {
+        self.0.block
+    }
+
+    /// When the reserves were read, as a Unix timestamp in seconds.
+    #[getter]
+    fn read_at(&self) -> f64 {
+        self.0.read_at.timestamp_millis() as f64 / 1000.0
+    }
+
+    #[getter]
+    fn pairs(&self) -> Vec<Pair> {
+        self.0.pairs.iter().copied().map(Pair).collect()
+    }
+
+    /// Gas price in gwei, if known.
+    #[getter]
+    fn gas_price_gwei(&self) -> Option<f64> {
+        self.0.gas_price_gwei
+    }
+
+    #[getter]
+    fn flash_loan_fee(&self) -> f64 {
+        self.0.flash_loan_fee
+    }
+
+    #[getter]
+    fn min_profit_usd(&self) -> Option<f64> {
+        self.0.min_profit_usd
+    }
+
+    /// The snapshot as the scanner archives it, e.g. for `json.loads`.
+    fn to_json(&self) -> String {
+        self.0.to_json().to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Snapshot(block={}, pairs={})", self.0.block, self.0.pairs.len())
+    }
+}
+
+/// An arbitrage sized for the most profit: buy base where it's cheap, sell
+/// it where it's expensive. Amounts are in whole-token units.
+#[pyclass(name = "Opportunity", module = "dex_scanner", frozen)]
+struct Opportunity(opportunity::Opportunity);
+
+#[pymethods]
+impl Opportunity {
+    #[getter]
+    fn base(&self) -> &'static str {
+        self.0.base.symbol
+    }
+
+    #[getter]
+    fn quote(&self) -> &'static str {
+        self.0.quote.symbol
+    }
+
+    #[getter]
+    fn buy(&self) -> Pool {
+        Pool(self.0.buy)
+    }
+
+    #[getter]
+    fn sell(&self) -> Pool {
+        Pool(self.0.sell)
+    }
+
+    /// Quote spent on the buy leg.
+    #[getter]
+    fn amount_in(&self) -> f64 {
+        self.0.amount_in
+    }
+
+    /// Base bought and sold.
+    #[getter]
+    fn base_amount(&self) -> f64 {
+        self.0.base_amount
+    }
+
+    /// Quote received from the sell leg.
+    #[getter]
+    fn amount_out(&self) -> f64 {
+        self.0.amount_out
+    }
+
+    /// Profit before gas and the flash-loan fee.
+    #[getter]
+    fn profit(&self) -> f64 {
+        self.0.profit()
+    }
+
+    /// Profit after gas and the flash-loan fee, if gas is known.
+    #[getter]
+    fn net_profit(&self) -> Option<f64> {
+        self.0.net_profit()
+    }
+
+    #[getter]
+    fn gas_cost(&self) -> Option<f64> {
+        self.0.gas_cost
+    }
+
+    #[getter]
+    fn flash_loan_fee(&self) -> f64 {
+        self.0.flash_loan_fee
+    }
+
+    /// Smallest quote input whose profit covers gas, if any.
+    #[getter]
+    fn break_even(&self) -> Option<f64> {
+        self.0.break_even
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Opportunity({}/{} {} -> {}, profit={})",
+            self.0.base.symbol,
+            self.0.quote.symbol,
+            self.0.buy.venue.name(),
+            self.0.sell.venue.name(),
+            self.0.profit()
+        )
+    }
+}
+
+/// A scanner for one chain. A process scans one chain, so every scanner in
+/// it must use the same one.
+#[pyclass(name = "Scanner", module = "dex_scanner", frozen)]
+struct Scanner {
+    scanner: dex_scanner::Scanner,
+    runtime: tokio::runtime::Runtime,
+}
+
+#[pymethods]
+impl Scanner {
+    /// `chain` is a preset or strategy profile name as the binary takes it,
+    /// `strategies` a comma-separated list as in `STRATEGIES`.
+    #[new]
+    #[pyo3(signature = (rpc_url, chain = "ethereum", strategies = "cross-venue", flash_loan_fee = 0.0, min_profit_usd = None))]
+    fn new(
+        rpc_url: &str,
+        chain: &str,
+        strategies: &str,
+        flash_loan_fee: f64,
+        min_profit_usd: Option<f64>,
+    ) -> PyResult<Self> {
+        let mut builder = dex_scanner::Scanner::builder()
+            .rpc_url(rpc_url)
+            .chain(chain)
+            .flash_loan_fee(flash_loan_fee);
+        if let Some(usd) = min_profit_usd {
+            builder = builder.min_profit_usd(usd);
+        }
+        for strategy in Registry::default().build(strategies).map_err(value_error)? {
+            builder = builder.strategy(strategy);
+        }
+        let scanner = builder.build().map_err(value_error)?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| runtime_error(e.into()))?;
+        Ok(Scanner { scanner, runtime })
+    }
+
+    /// The market as of the latest block. Pairs, fees and gas are read
+    /// afresh on every call.
+    fn snapshot(&self, py: Python<'_>) -> PyResult<Snapshot> {
+        py.detach(|| self.runtime.block_on(self.scanner.snapshot()))
+            .map(Snapshot)
+            .map_err(runtime_error)
+    }
+
+    /// Every opportunity the scanner's strategies find in `snapshot`.
+    fn evaluate(&self, snapshot: &Snapshot) -> Vec<Opportunity> {
+        self.scanner.evaluate(&snapshot.0).into_iter().map(Opportunity).collect()
+    }
+}
+
+/// Sizes an arbitrage across `pair`'s two pools, or `None` when swap fees
+/// eat the whole spread.
+#[pyfunction]
+#[pyo3(signature = (pair, flash_loan_fee = 0.0))]
+fn evaluate_pair(pair: &Pair, flash_loan_fee: f64) -> Option<Opportunity> {
+    let [a, b] = pair.0.pools;
+    opportunity::Opportunity::evaluate(pair.0.base, pair.0.quote, a, b, pair.0.gas_cost, flash_loan_fee)
+        .map(Opportunity)
+}
+
+#[pymodule]
+#[pyo3(name = "dex_scanner")]
+fn dex_scanner_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Scanner>()?;
+    m.add_class::<Snapshot>()?;
+    m.add_class::<Pair>()?;
+    m.add_class::<Pool>()?;
+    m.add_class::<Opportunity>()?;
+    m.add_function(wrap_pyfunction!(evaluate_pair, m)?)?;
+    Ok(())
+}
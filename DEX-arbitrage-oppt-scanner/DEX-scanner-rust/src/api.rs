@@ -0,0 +1,584 @@
+//! REST API over the opportunity history in storage, for dashboards and
+//! scripts that shouldn't need the database itself. Every endpoint takes an
+//! API key, as `Authorization: Bearer <key>` or `X-API-Key: <key>`.
+//!
+//! - `GET /api/opportunities?pair=&from=&to=&limit=&offset=`: opportunities,
+//!   newest first, a page at a time
+//! - `GET /api/pairs?from=&limit=`: pairs by USD profit
+//! - `GET /api/stats?from=&pair=`: totals, per venue combination or for one
+//!   pair
+//! - `GET /events`: Server-Sent Events as they happen, an `opportunity`
+//!   event for each recorded opportunity and a `prices` event each block
+//!
+//! Times are Unix seconds, as in webhook payloads. The OpenAPI document is
+//! served at `/api/openapi.json`, with Swagger UI at `/docs`, both without a
+//! key so integrators can generate clients. Given a certificate, the API is
+//! served over TLS, so keys don't cross the network in the clear.
+
+use anyhow::{bail, Context, Result};
+use axum::extract::{Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{self, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use colored::*;
+use futures::Stream;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::service::TowerToHyperService;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::ServerConfig;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::env;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use subtle::{Choice, ConstantTimeEq};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_rustls::TlsAcceptor;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{IntoParams, Modify, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::storage::{OpportunityRecord, Storage, VenuePairStats};
+use crate::ScanContext;
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+const DEFAULT_TOP_PAIRS: usize = 50;
+// Far past any real history, and keeps offsets well inside SQLite's i64
+const MAX_OFFSET: usize = u32::MAX as usize;
+// Long enough for a slow but honest client, short enough that idle
+// connections can't pile up mid-handshake
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+// Events a slow `/events` client can fall behind by before it misses some
+const EVENT_BUFFER: usize = 256;
+
+/// Where the API listens, the keys it accepts and, to serve HTTPS, its
+/// TLS configuration.
+pub struct ApiServer {
+    pub addr: SocketAddr,
+    pub keys: Vec<String>,
+    pub tls: Option<Arc<ServerConfig>>,
+}
+
+/// TLS configuration from `API_TLS_CERT` and `API_TLS_KEY`, see
+/// [`load_tls`], or `None` without them.
+pub fn tls_from_env() -> Result<Option<Arc<ServerConfig>>> {
+    let var = |name| env::var(name).ok().filter(|s| !s.is_empty());
+    match (var("API_TLS_CERT"), var("API_TLS_KEY")) {
+        (Some(cert), Some(key)) => load_tls(Path::new(&cert), Path::new(&key)).map(Some),
+        (None, None) => Ok(None),
+        _ => bail!("API_TLS_CERT and API_TLS_KEY go together"),
+    }
+}
+
+/// TLS configuration from a PEM certificate chain and private key, e.g. a
+/// Let's Encrypt `fullchain.pem` and `privkey.pem`.
+pub fn load_tls(cert: &Path, key: &Path) -> Result<Arc<ServerConfig>> {
+    let certs = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("Failed to read certificates from {}", cert.display()))?;
+    let key = PrivateKeyDer::from_pem_file(key)
+        .with_context(|| format!("Failed to read private key from {}", key.display()))?;
+    let mut config = ServerConfig::builder_with_provider(Arc::new(rustls::crypto::aws_lc_rs::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("Certificate and private key don't make a TLS configuration")?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+/// Parses `API_KEYS`: comma-separated keys, at least one.
+pub fn parse_keys(value: &str) -> Result<Vec<String>> {
+    let keys = value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    if keys.is_empty() {
+        bail!("API_KEYS has no keys");
+    }
+    Ok(keys)
+}
+
+/// Something streamed to `/events` clients.
+#[derive(Debug, Clone)]
+pub enum Event {
+    Opportunity(OpportunityRecord),
+    Prices(PriceUpdate),
+}
+
+/// Every scanned pair's prices at a block.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PriceUpdate {
+    pub block: u64,
+    /// Unix seconds the prices were read at.
+    pub timestamp: i64,
+    pub pairs: Vec<PairPrices>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PairPrices {
+    /// `BASE/QUOTE`.
+    pub pair: String,
+    pub venues: Vec<VenuePrice>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VenuePrice {
+    pub venue: String,
+    pub pool: String,
+    /// Quote per base.
+    pub price: f64,
+}
+
+/// The channel [`Event`]s are published on: sending never waits, and
+/// without clients connected to `/events` nothing is kept.
+pub fn events() -> broadcast::Sender<Event> {
+    broadcast::channel(EVENT_BUFFER).0
+}
+
+impl Event {
+    fn to_sse(&self) -> Result<sse::Event, axum::Error> {
+        match self {
+            Event::Opportunity(record) => sse::Event::default().event("opportunity").json_data(Opportunity::from(record)),
+            Event::Prices(update) => sse::Event::default().event("prices").json_data(update),
+        }
+    }
+}
+
+/// An error response: its status and a message, sent as [`ErrorBody`].
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        ApiError(StatusCode::BAD_REQUEST, message.into())
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        println!("{} API query failed: {}", "[ERROR]".bright_red(), e);
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, "query failed".to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(ErrorBody { error: self.1 })).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// Runs `query` on the blocking pool, so a slow query or a wait for the
+/// database lock doesn't stall the runtime's workers.
+async fn query_storage<T: Send + 'static>(
+    ctx: &Arc<ScanContext>,
+    query: impl FnOnce(&Storage) -> Result<T> + Send + 'static,
+) -> Result<T, ApiError> {
+    let ctx = Arc::clone(ctx);
+    Ok(tokio::task::spawn_blocking(move || query(&ctx.storage))
+        .await
+        .context("Storage query panicked")??)
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct ErrorBody {
+    error: String,
+}
+
+/// A recorded opportunity, as in webhook payloads.
+#[derive(Debug, Serialize, ToSchema)]
+struct Opportunity {
+    /// UUID, derived from the chain, block, pair and venues.
+    id: String,
+    /// UUID of the scanner run that found it.
+    run_id: String,
+    /// Unix seconds.
+    timestamp: i64,
+    chain: String,
+    /// `BASE/QUOTE`.
+    pair: String,
+    buy_venue: String,
+    sell_venue: String,
+    buy_price: f64,
+    sell_price: f64,
+    /// E.g. 0.005 for 0.5%.
+    spread: f64,
+    /// Quote spent at the profit-maximising size.
+    size: f64,
+    /// Theoretical profit in quote units, before gas.
+    profit: f64,
+    profit_usd: Option<f64>,
+}
+
+impl From<&OpportunityRecord> for Opportunity {
+    fn from(record: &OpportunityRecord) -> Self {
+        Opportunity {
+            id: record.id.to_string(),
+            run_id: record.run_id.to_string(),
+            timestamp: record.timestamp.timestamp(),
+            chain: record.chain.clone(),
+            pair: record.pair.clone(),
+            buy_venue: record.buy_venue.clone(),
+            sell_venue: record.sell_venue.clone(),
+            buy_price: record.buy_price,
+            sell_price: record.sell_price,
+            spread: record.spread,
+            size: record.size,
+            profit: record.profit,
+            profit_usd: record.profit_usd,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct OpportunityPage {
+    opportunities: Vec<Opportunity>,
+    /// `offset` for the next page, absent on the last one.
+    next_offset: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct PairSummary {
+    chain: String,
+    pair: String,
+    count: u64,
+    profit_usd: f64,
+    max_spread: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct PairList {
+    pairs: Vec<PairSummary>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct VenueStats {
+    /// The two venues, in either direction, e.g. `Aerodrome ↔ Uniswap V2`.
+    venues: String,
+    count: u64,
+    profit_usd: f64,
+    max_spread: f64,
+}
+
+impl From<&VenuePairStats> for VenueStats {
+    fn from(stats: &VenuePairStats) -> Self {
+        VenueStats {
+            venues: stats.venues.clone(),
+            count: stats.count,
+            profit_usd: stats.profit_usd,
+            max_spread: stats.max_spread,
+        }
+    }
+}
+
+/// Statistics over every opportunity, or over one pair's.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+enum Stats {
+    Totals {
+        count: u64,
+        profit_usd: f64,
+        max_spread: f64,
+        venues: Vec<VenueStats>,
+    },
+    Pair {
+        pair: String,
+        count: u64,
+        min_spread: f64,
+        avg_spread: f64,
+        max_spread: f64,
+        profit_usd: f64,
+        /// The opportunity with the widest spread, absent without any.
+        widest: Option<Box<Opportunity>>,
+    },
+}
+
+fn time(value: Option<i64>, default: DateTime<Utc>) -> Result<DateTime<Utc>, ApiError> {
+    match value {
+        Some(secs) => DateTime::from_timestamp(secs, 0).ok_or_else(|| ApiError::bad_request("invalid time")),
+        None => Ok(default),
+    }
+}
+
+/// `pair` unless it's empty.
+fn pair_filter(pair: Option<String>) -> Option<String> {
+    pair.map(|p| p.trim().to_string()).filter(|p| !p.is_empty())
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct OpportunitiesQuery {
+    /// `BASE/QUOTE`, ignoring case.
+    pair: Option<String>,
+    /// Unix seconds, inclusive.
+    from: Option<i64>,
+    /// Unix seconds, exclusive.
+    to: Option<i64>,
+    /// Page size, 100 by default and at most 1000.
+    limit: Option<usize>,
+    /// Opportunities skipped, at most 4294967295.
+    offset: Option<usize>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/opportunities",
+    params(OpportunitiesQuery),
+    responses(
+        (status = 200, body = OpportunityPage),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+    ),
+)]
+async fn opportunities(
+    State(ctx): State<Arc<ScanContext>>,
+    Query(query): Query<OpportunitiesQuery>,
+) -> ApiResult<OpportunityPage> {
+    let from = time(query.from, DateTime::UNIX_EPOCH)?;
+    let to = time(query.to, Utc::now() + chrono::Duration::seconds(1))?;
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = query.offset.unwrap_or(0).min(MAX_OFFSET);
+    let pair = pair_filter(query.pair);
+    // One more than asked for tells whether there's another page
+    let mut records = query_storage(&ctx, move |storage| {
+        storage.opportunities_page(pair.as_deref(), from, to, limit + 1, offset)
+    })
+    .await?;
+    let next_offset = (records.len() > limit).then(|| offset.saturating_add(limit));
+    records.truncate(limit);
+    Ok(Json(OpportunityPage {
+        opportunities: records.iter().map(Opportunity::from).collect(),
+        next_offset,
+    }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct PairsQuery {
+    /// Unix seconds, inclusive.
+    from: Option<i64>,
+    /// Pairs returned, 50 by default and at most 1000.
+    limit: Option<usize>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/pairs",
+    params(PairsQuery),
+    responses(
+        (status = 200, body = PairList),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+    ),
+)]
+async fn pairs(State(ctx): State<Arc<ScanContext>>, Query(query): Query<PairsQuery>) -> ApiResult<PairList> {
+    let since = time(query.from, DateTime::UNIX_EPOCH)?;
+    let limit = query.limit.unwrap_or(DEFAULT_TOP_PAIRS).clamp(1, MAX_PAGE_SIZE);
+    let pairs = query_storage(&ctx, move |storage| storage.top_pairs(since, limit))
+        .await?
+        .into_iter()
+        .map(|ranking| PairSummary {
+            chain: ranking.chain,
+            pair: ranking.pair,
+            count: ranking.count,
+            profit_usd: ranking.profit_usd,
+            max_spread: ranking.max_spread,
+        })
+        .collect();
+    Ok(Json(PairList { pairs }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct StatsQuery {
+    /// Unix seconds, inclusive.
+    from: Option<i64>,
+    /// `BASE/QUOTE`, in either order and ignoring case, for one pair's
+    /// statistics.
+    pair: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/stats",
+    params(StatsQuery),
+    responses(
+        (status = 200, body = Stats),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+    ),
+)]
+async fn stats(State(ctx): State<Arc<ScanContext>>, Query(query): Query<StatsQuery>) -> ApiResult<Stats> {
+    let since = time(query.from, DateTime::UNIX_EPOCH)?;
+    if let Some(pair) = pair_filter(query.pair) {
+        let (base, quote) = pair
+            .split_once('/')
+            .map(|(base, quote)| (base.to_string(), quote.to_string()))
+            .ok_or_else(|| ApiError::bad_request("pair is BASE/QUOTE"))?;
+        let stats = query_storage(&ctx, move |storage| storage.pair_stats(&base, &quote, since)).await?;
+        return Ok(Json(Stats::Pair {
+            pair: pair.to_ascii_uppercase(),
+            count: stats.count,
+            min_spread: stats.min_spread,
+            avg_spread: stats.avg_spread,
+            max_spread: stats.max_spread,
+            profit_usd: stats.profit_usd,
+            widest: stats.widest.as_ref().map(|record| Box::new(record.into())),
+        }));
+    }
+
+    let venues = query_storage(&ctx, move |storage| storage.profit_by_venue_pair(since)).await?;
+    Ok(Json(Stats::Totals {
+        count: venues.iter().map(|v| v.count).sum(),
+        profit_usd: venues.iter().map(|v| v.profit_usd).sum(),
+        max_spread: venues.iter().map(|v| v.max_spread).fold(0.0, f64::max),
+        venues: venues.iter().map(VenueStats::from).collect(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/events",
+    responses(
+        (status = 200, description = "`opportunity` events carrying an Opportunity and `prices` events carrying a \
+            PriceUpdate, as JSON", content_type = "text/event-stream"),
+        (status = 401, body = ErrorBody),
+    ),
+)]
+async fn events_stream(
+    State(ctx): State<Arc<ScanContext>>,
+) -> Sse<impl Stream<Item = Result<sse::Event, axum::Error>>> {
+    let stream = futures::stream::unfold(ctx.events.subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event.to_sse(), receiver)),
+                // A client too slow to keep up misses what it fell behind on
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// The key a request presents, if any.
+fn presented_key(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    bearer
+        .or_else(|| headers.get("x-api-key").and_then(|value| value.to_str().ok()))
+        .map(str::trim)
+}
+
+/// Whether `key` is one of `keys`, comparing against every key in constant
+/// time, so response timing doesn't leak how much of a key matched.
+fn is_valid_key(keys: &[String], key: &str) -> bool {
+    keys.iter()
+        .fold(Choice::from(0), |found, k| found | k.as_bytes().ct_eq(key.as_bytes()))
+        .into()
+}
+
+async fn authenticate(State(keys): State<Arc<Vec<String>>>, request: Request, next: Next) -> Response {
+    match presented_key(request.headers()) {
+        Some(key) if is_valid_key(&keys, key) => next.run(request).await,
+        _ => ApiError(StatusCode::UNAUTHORIZED, "missing or invalid API key".to_string()).into_response(),
+    }
+}
+
+/// Declares both ways of presenting a key.
+struct KeySchemes;
+
+impl Modify for KeySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "DEX Scanner API", description = "Opportunities the scanner has recorded."),
+    paths(opportunities, pairs, stats, events_stream),
+    modifiers(&KeySchemes),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+struct ApiDoc;
+
+/// Serves the API on `server.addr` until the listener fails.
+pub async fn serve(ctx: Arc<ScanContext>, server: ApiServer) {
+    let keys = Arc::new(server.keys);
+    let app = Router::new()
+        .route("/api/opportunities", get(opportunities))
+        .route("/api/pairs", get(pairs))
+        .route("/api/stats", get(stats))
+        .route("/events", get(events_stream))
+        .route_layer(middleware::from_fn_with_state(keys, authenticate))
+        .with_state(ctx)
+        .merge(SwaggerUi::new("/docs").url("/api/openapi.json", ApiDoc::openapi()));
+
+    let listener = match TcpListener::bind(server.addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            println!("{} Failed to bind API to {}: {}", "[ERROR]".bright_red(), server.addr, e);
+            return;
+        }
+    };
+    match server.tls {
+        Some(config) => serve_tls(listener, app, TlsAcceptor::from(config)).await,
+        None => {
+            if let Err(e) = axum::serve(listener, app).await {
+                println!("{} API server stopped: {}", "[ERROR]".bright_red(), e);
+            }
+        }
+    }
+}
+
+/// Serves `app` over TLS on `listener`, each connection's handshake in its
+/// own task so a slow client can't hold up the others, and dropped if it
+/// doesn't finish within [`TLS_HANDSHAKE_TIMEOUT`]. Runs forever.
+async fn serve_tls(listener: TcpListener, app: Router, acceptor: TlsAcceptor) {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                // E.g. out of file descriptors, which passes as connections close
+                println!("{} Failed to accept API connection: {}", "[ERROR]".bright_red(), e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+        let acceptor = acceptor.clone();
+        let service = TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            // Failed and stalled handshakes are mostly scanners and plain
+            // HTTP, not worth a log line
+            let Ok(Ok(stream)) = tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await else {
+                return;
+            };
+            let _ = auto::Builder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(stream), service)
+                .await;
+        });
+    }
+}
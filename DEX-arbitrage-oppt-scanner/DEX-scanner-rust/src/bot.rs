@@ -0,0 +1,738 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration as ChronoDuration, Utc};
+use colored::*;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use teloxide::{prelude::*, types::{ParseMode, User}, utils::command::BotCommands, utils::html};
+
+use crate::ack::{self, AckOutcome};
+use crate::i18n::{self, Localize};
+use crate::models::token::{self, TokenInfo};
+use crate::preset;
+use crate::role::Role;
+use crate::storage::shared::SharedState;
+use crate::storage::AuditEntry;
+use crate::subscription::Subscription;
+use crate::utils::format::{format_amount, format_percent, format_time, format_usd};
+use crate::utils::price;
+use crate::{MonitoredPair, ScanContext};
+
+// How often a standby checks whether it has become leader, and the leader
+// whether it still is
+const LEADER_POLL_INTERVAL: Duration = Duration::from_secs(5);
+// Pairs listed by /top
+const TOP_PAIRS: usize = 10;
+// Entries /audit lists by default, and at most
+const AUDIT_ENTRIES: usize = 20;
+const MAX_AUDIT_ENTRIES: usize = 100;
+
+#[derive(BotCommands, Clone)]
+#[command(rename_rule = "lowercase", description = "Scanner commands:")]
+pub enum Command {
+    #[command(description = "show this help")]
+    Help,
+    #[command(description = "send a sample alert to every configured chat")]
+    Test,
+    #[command(description = "24h spread stats, prices and TVL for a pair, e.g. /stats WETH/USDC")]
+    Stats(String),
+    #[command(description = "live price on every venue and the best spread, e.g. /price WETH/USDC")]
+    Price(String),
+    #[command(description = "effective configuration of this instance")]
+    Config,
+    #[command(description = "most profitable pairs over a window (30m, 24h, 7d; default 24h)")]
+    Top(String),
+    #[command(description = "paper-trading balances and PnL over a window (default 24h)")]
+    Paper(String),
+    #[command(description = "get opportunity alerts in this chat, optionally in a language, e.g. /subscribe es")]
+    Subscribe(String),
+    #[command(description = "stop this chat's subscription")]
+    Unsubscribe,
+    #[command(description = "show or set this chat's filters, e.g. /filters pairs=WETH/USDC margin=0.5% profit=50")]
+    Filters(String),
+    #[command(description = "latest commands, acks and setting changes (default 20)")]
+    Audit(String),
+}
+
+impl Command {
+    /// Whether a chat that isn't a configured alert chat may run this, in
+    /// multi-tenant mode: managing its subscription and looking up pairs.
+    fn open_to_subscribers(&self) -> bool {
+        matches!(
+            self,
+            Command::Help
+                | Command::Subscribe(_)
+                | Command::Unsubscribe
+                | Command::Filters(_)
+                | Command::Stats(_)
+                | Command::Price(_)
+                | Command::Top(_)
+        )
+    }
+
+    /// Least role a user in a configured chat needs to run this, see
+    /// [`crate::role::Roles`].
+    fn required_role(&self) -> Role {
+        match self {
+            Command::Test | Command::Audit(_) => Role::Operator,
+            _ => Role::Viewer,
+        }
+    }
+}
+
+/// Answers bot commands while this replica is the leader. Telegram allows a
+/// single `getUpdates` consumer per bot, so standbys don't poll.
+pub async fn run(ctx: Arc<ScanContext>) {
+    if let Err(e) = ctx.bot.set_my_commands(Command::bot_commands()).await {
+        println!("{} Failed to register bot commands: {}", "[ERROR]".bright_red(), e);
+    }
+
+    loop {
+        while !ctx.leader.is_leader() {
+            tokio::time::sleep(LEADER_POLL_INTERVAL).await;
+        }
+
+        let handler = dptree::entry()
+            .branch(
+                Update::filter_message()
+                    .filter_command::<Command>()
+                    .endpoint(handle),
+            )
+            .branch(Update::filter_callback_query().endpoint(handle_ack));
+        let mut dispatcher = Dispatcher::builder(ctx.bot.clone(), handler)
+            .dependencies(dptree::deps![Arc::clone(&ctx)])
+            .build();
+
+        let token = dispatcher.shutdown_token();
+        let watch = Arc::clone(&ctx);
+        tokio::spawn(async move {
+            while watch.leader.is_leader() {
+                tokio::time::sleep(LEADER_POLL_INTERVAL).await;
+            }
+            if let Ok(shutdown) = token.shutdown() {
+                shutdown.await;
+            }
+        });
+
+        dispatcher.dispatch().await;
+    }
+}
+
+/// Claims an alert for whoever tapped its Ack button, then takes the button
+/// off every copy and tells each chat who is on it.
+async fn handle_ack(bot: Bot, query: CallbackQuery, ctx: Arc<ScanContext>) -> ResponseResult<()> {
+    let Some(id) = query.data.as_deref().and_then(ack::parse_callback) else {
+        return Ok(());
+    };
+    let Some(chat) = query
+        .message
+        .as_ref()
+        .and_then(|m| ctx.chats.iter().find(|c| c.id == m.chat.id.0))
+    else {
+        return Ok(());
+    };
+    // Claiming an opportunity commits the team to it
+    if ctx.roles.of(query.from.id.0) < Role::Operator {
+        bot.answer_callback_query(query.id)
+            .text("Only operators can ack alerts")
+            .show_alert(true)
+            .await?;
+        return Ok(());
+    }
+    let name = display_name(&query.from);
+
+    match ctx.acks.ack(id, &name) {
+        AckOutcome::Acked(thread) => {
+            audit(&ctx, Some(&query.from), chat.id, "ack", None, Some(format!("alert thread {}", id)));
+            bot.answer_callback_query(query.id).await?;
+            for &(chat, message) in &thread.messages {
+                if let Err(e) = bot.edit_message_reply_markup(ChatId(chat), message).await {
+                    println!("{} Failed to remove Ack button: {}", "[ERROR]".bright_red(), e);
+                }
+            }
+            for chat in &ctx.chats {
+                let acked_by = i18n::fill(chat.lang.catalog().acked_by, &[("name", &html::escape(&name))]);
+                let mut request = bot
+                    .send_message(ChatId(chat.id), format!("✅ <b>{}</b>", acked_by))
+                    .parse_mode(ParseMode::Html);
+                if let Some(root) = thread.root(chat.id) {
+                    request = request.reply_to_message_id(root);
+                }
+                if let Err(e) = request.await {
+                    println!("{} Failed to send Telegram alert: {}", "[ERROR]".bright_red(), e);
+                }
+            }
+        }
+        AckOutcome::AlreadyAcked(owner) => {
+            bot.answer_callback_query(query.id)
+                .text(i18n::fill(chat.lang.catalog().acked_by, &[("name", &owner)]))
+                .show_alert(true)
+                .await?;
+        }
+        AckOutcome::Unknown => {
+            bot.answer_callback_query(query.id)
+                .text("This alert has expired")
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn handle(bot: Bot, msg: Message, command: Command, ctx: Arc<ScanContext>) -> ResponseResult<()> {
+    // Only chats the scanner alerts may query it, and in multi-tenant mode
+    // any chat may manage its own subscription
+    let configured = ctx.chats.iter().any(|c| c.id == msg.chat.id.0);
+    let open = ctx.multi_tenant && command.open_to_subscribers();
+    if !configured && !open {
+        return Ok(());
+    }
+    let invocation = msg.text().map(str::to_string);
+    if configured {
+        let role = msg.from().map_or(Role::Viewer, |user| ctx.roles.of(user.id.0));
+        let required = command.required_role();
+        if role < required {
+            audit(&ctx, msg.from(), msg.chat.id.0, "denied", None, invocation);
+            bot.send_message(msg.chat.id, format!("This command needs the {} role; you're a {}.", required, role))
+                .await?;
+            return Ok(());
+        }
+    }
+    audit(&ctx, msg.from(), msg.chat.id.0, "command", None, invocation);
+    let subscription = ctx.subscribers.get(msg.chat.id.0);
+
+    match command {
+        Command::Help => {
+            bot.send_message(msg.chat.id, Command::descriptions().to_string())
+                .await?;
+        }
+        Command::Test => {
+            let results = crate::send_test_alerts(&ctx.bot, &ctx.chats).await;
+            let summary = results
+                .iter()
+                .map(|(id, result)| match result {
+                    Ok(()) => format!("✅ {}", id),
+                    Err(e) => format!("❌ {}: {}", id, e),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            bot.send_message(msg.chat.id, format!("Test alert sent:\n{}", summary))
+                .await?;
+        }
+        Command::Price(pair) => {
+            let reply = match pair.trim().split_once('/') {
+                Some((base, quote)) => live_price(&ctx, base.trim(), quote.trim())
+                    .await
+                    .unwrap_or_else(|e| format!("Failed to read prices: {}", html::escape(&e.to_string()))),
+                None => "Usage: /price BASE/QUOTE, e.g. /price WETH/USDC".to_string(),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        Command::Config => {
+            bot.send_message(msg.chat.id, config_summary(&ctx))
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        Command::Stats(pair) => {
+            let reply = match pair.trim().split_once('/') {
+                Some((base, quote)) => pair_stats(&ctx, base.trim(), quote.trim())
+                    .unwrap_or_else(|e| format!("Failed to read stats: {}", e)),
+                None => "Usage: /stats BASE/QUOTE, e.g. /stats WETH/USDC".to_string(),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        Command::Top(window) => {
+            let reply = match parse_window(&window) {
+                Some(window) => top_pairs(&ctx, window)
+                    .unwrap_or_else(|e| format!("Failed to read history: {}", e)),
+                None => "Usage: /top [WINDOW], e.g. /top 24h, /top 7d or /top 30m".to_string(),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        Command::Paper(window) => {
+            let reply = match (&ctx.paper, parse_window(&window)) {
+                (None, _) => "Paper trading is off; set PAPER_TRADING to turn it on.".to_string(),
+                (Some(paper), Some(window)) => paper
+                    .summary(&ctx.storage, Utc::now() - window)
+                    .unwrap_or_else(|e| format!("Failed to read paper trades: {}", e)),
+                (Some(_), None) => "Usage: /paper [WINDOW], e.g. /paper 24h, /paper 7d or /paper 30m".to_string(),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        Command::Subscribe(lang) => {
+            let reply = if configured {
+                "This chat is configured to get every alert already.".to_string()
+            } else {
+                subscribe(&ctx, msg.chat.id.0, &lang)
+                    .unwrap_or_else(|e| format!("Failed to subscribe: {}", html::escape(&e.to_string())))
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        Command::Unsubscribe => {
+            let reply = match ctx.storage.remove_subscription(msg.chat.id.0) {
+                Ok(()) => match ctx.subscribers.remove(msg.chat.id.0) {
+                    Some(_) => "Unsubscribed.".to_string(),
+                    None => "This chat isn't subscribed.".to_string(),
+                },
+                Err(e) => format!("Failed to unsubscribe: {}", e),
+            };
+            bot.send_message(msg.chat.id, reply).await?;
+        }
+        Command::Filters(args) => {
+            let reply = set_filters(&ctx, msg.chat.id.0, &args)
+                .unwrap_or_else(|e| format!("Failed to set filters: {}", html::escape(&e.to_string())));
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        Command::Audit(limit) => {
+            let reply = match limit.trim() {
+                "" => Some(AUDIT_ENTRIES),
+                limit => limit.parse::<usize>().ok().filter(|&n| n > 0),
+            };
+            let reply = match reply {
+                Some(limit) => audit_log(&ctx, limit.min(MAX_AUDIT_ENTRIES))
+                    .unwrap_or_else(|e| format!("Failed to read the audit log: {}", e)),
+                None => "Usage: /audit [COUNT], e.g. /audit 50".to_string(),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+    }
+
+    let updated = ctx.subscribers.get(msg.chat.id.0);
+    if updated != subscription {
+        audit(
+            &ctx,
+            msg.from(),
+            msg.chat.id.0,
+            "subscription",
+            subscription.map(|s| s.summary()),
+            updated.map(|s| s.summary()),
+        );
+    }
+    Ok(())
+}
+
+/// Records an audit entry, logging rather than failing the command if it
+/// can't be stored.
+fn audit(
+    ctx: &ScanContext,
+    user: Option<&User>,
+    chat_id: i64,
+    action: &str,
+    old_value: Option<String>,
+    new_value: Option<String>,
+) {
+    let entry = AuditEntry {
+        timestamp: Utc::now(),
+        user_id: user.map(|user| user.id.0),
+        user: user.map(display_name).unwrap_or_default(),
+        chat_id,
+        action: action.to_string(),
+        old_value,
+        new_value,
+    };
+    if let Err(e) = ctx.storage.record_audit(&entry) {
+        println!("{} Failed to record {} by {} in the audit log: {}",
+            "[ERROR]".bright_red(),
+            entry.action,
+            entry.user,
+            e,
+        );
+    }
+}
+
+fn display_name(user: &User) -> String {
+    match &user.username {
+        Some(username) => format!("@{}", username),
+        None => user.full_name(),
+    }
+}
+
+/// The `limit` latest audit entries, newest first.
+fn audit_log(ctx: &ScanContext, limit: usize) -> Result<String> {
+    let entries = ctx.storage.audit_log(limit)?;
+    if entries.is_empty() {
+        return Ok("The audit log is empty.".to_string());
+    }
+
+    let mut lines = vec!["📜 <b>Audit log</b>\n".to_string()];
+    for entry in entries {
+        let change = match (&entry.old_value, &entry.new_value) {
+            (Some(old), Some(new)) => format!("{} → {}", old, new),
+            (Some(old), None) => format!("{} → none", old),
+            (None, Some(new)) => new.clone(),
+            (None, None) => String::new(),
+        };
+        lines.push(format!(
+            "<i>{}</i> {} in {}: <b>{}</b> <code>{}</code>",
+            format_time(entry.timestamp),
+            html::escape(&entry.user),
+            entry.chat_id,
+            entry.action,
+            html::escape(&change)
+        ));
+    }
+
+    Ok(lines.join("\n"))
+}
+
+/// Subscribes `chat` to opportunity alerts in `lang` (default English), or
+/// changes the language of its subscription, keeping its filters.
+fn subscribe(ctx: &ScanContext, chat: i64, lang: &str) -> Result<String> {
+    let lang = match lang.trim() {
+        "" => i18n::Lang::default(),
+        lang => lang.parse()?,
+    };
+    let subscription = match ctx.subscribers.get(chat) {
+        Some(existing) => Subscription { lang, ..existing },
+        None => Subscription::new(chat, lang),
+    };
+    ctx.storage.save_subscription(&subscription)?;
+    ctx.subscribers.insert(subscription.clone());
+    Ok(format!(
+        "🔔 <b>Subscribed</b> ({})\n\n{}\n\nNarrow it down with /filters, e.g. /filters pairs=WETH/USDC margin=0.5% profit=50",
+        lang.code(),
+        html::escape(&subscription.describe())
+    ))
+}
+
+/// Shows `chat`'s filters, after applying `args` if there are any, see
+/// [`Subscription::apply_filters`].
+fn set_filters(ctx: &ScanContext, chat: i64, args: &str) -> Result<String> {
+    let Some(mut subscription) = ctx.subscribers.get(chat) else {
+        return Ok("This chat isn't subscribed; /subscribe first.".to_string());
+    };
+    if !args.trim().is_empty() {
+        subscription.apply_filters(args)?;
+        ctx.storage.save_subscription(&subscription)?;
+        ctx.subscribers.insert(subscription.clone());
+    }
+    Ok(format!("🔎 <b>Filters</b>\n\n{}", html::escape(&subscription.describe())))
+}
+
+fn find_token(symbol: &str) -> Result<&'static TokenInfo> {
+    token::find_by_symbol(symbol).ok_or_else(|| {
+        anyhow!(
+            "unknown token {} (known: {})",
+            symbol,
+            token::tokens().map(|t| t.symbol).collect::<Vec<_>>().join(", ")
+        )
+    })
+}
+
+/// Reads `base`/`quote` reserves on every venue right now, whether or not
+/// the pair is monitored, and reports each price and the widest gap.
+async fn live_price(ctx: &ScanContext, base: &str, quote: &str) -> Result<String> {
+    let base = find_token(base)?;
+    let quote = find_token(quote)?;
+    if base.address == quote.address {
+        return Ok("Pick two different tokens.".to_string());
+    }
+    // Pools order reserves by token address
+    let (token0, token1) = if base.address < quote.address { (base, quote) } else { (quote, base) };
+
+    let mut lines = vec![format!("💱 <b>{}/{}</b>\n", base.symbol, quote.symbol)];
+    let mut quotes = Vec::new();
+    for (i, venue) in preset::active().venues.iter().enumerate() {
+        let pool = venue.get_pool(&ctx.provider, token0.address, token1.address).await?;
+        if pool.is_zero() {
+            lines.push(format!("{}: no pool", venue.label()));
+            continue;
+        }
+        let pair = MonitoredPair { token0, token1, pool_a: pool, pool_b: pool, venues: [i, i] };
+        let state = crate::pool_state(&ctx.provider, pool, venue, &pair, &ctx.fees).await?;
+        let price = if pair.base().address == base.address || state.price() == 0.0 {
+            state.price()
+        } else {
+            1.0 / state.price()
+        };
+        lines.push(format!(
+            "{}: <code>{} {}</code> · fee {}",
+            venue.label(),
+            format_amount(price),
+            quote.symbol,
+            format_percent(state.fee)
+        ));
+        if price > 0.0 {
+            quotes.push((venue.label(), price, state.fee));
+        }
+    }
+
+    let cheapest = quotes.iter().min_by(|x, y| x.1.total_cmp(&y.1));
+    let dearest = quotes.iter().max_by(|x, y| x.1.total_cmp(&y.1));
+    if let (Some(buy), Some(sell)) = (cheapest, dearest) {
+        if buy.0 != sell.0 {
+            let spread = price::calculate_profit_margin(buy.1, sell.1);
+            lines.push(format!(
+                "\nBest Spread: <b>{}</b> buy {} → sell {} ({} after pool fees)",
+                format_percent(spread),
+                buy.0,
+                sell.0,
+                format_percent(spread - buy.2 - sell.2)
+            ));
+        }
+    }
+    lines.push(format!("<i>Read at {}</i>", format_time(Utc::now())));
+
+    Ok(lines.join("\n"))
+}
+
+/// What this instance is actually running with, after env overrides.
+fn config_summary(ctx: &ScanContext) -> String {
+    let preset = preset::active();
+    let on_off = |on: bool| if on { "on" } else { "off" };
+
+    let mut pairs = ctx.latest.read().unwrap().values().map(|(info, _)| info.pair()).collect::<Vec<_>>();
+    pairs.sort();
+    pairs.dedup();
+
+    [
+        "⚙️ <b>Configuration</b>\n".to_string(),
+        format!("Preset: <b>{}</b>", preset.name),
+        format!("Chain: {} (id {})", preset.chain, preset.chain_id),
+        format!("Venues: {}", preset.venue_names()),
+        format!(
+            "Tokens: {}",
+            preset.scanned_tokens().map(|t| t.symbol).collect::<Vec<_>>().join(", ")
+        ),
+        match ctx.min_profit_usd {
+            Some(usd) => format!("Min Profit: <b>{}</b> after gas (every pair)", format_usd(usd)),
+            None => format!(
+                "Min Profit: <b>{}</b> ({})",
+                format_percent(preset.min_profit_margin),
+                if preset.pair_margins.is_empty() { "every pair" } else { "other pairs" }
+            ),
+        },
+        format!(
+            "Pair Margins: {}",
+            if preset.pair_margins.is_empty() {
+                "none".to_string()
+            } else {
+                preset
+                    .pair_margins
+                    .iter()
+                    .map(|m| format!("{}/{} {}", m.base, m.quote, format_percent(m.margin)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        ),
+        format!("Min Pool TVL: {}", format_usd(ctx.min_tvl_usd)),
+        format!("Alert Cooldown: {}s", ctx.alert_cooldown.as_secs()),
+        format!("Flash-loan Fee: {}", format_percent(ctx.flash_loan_fee)),
+        format!(
+            "Regime: {}{}",
+            match ctx.regime.current() {
+                Some(reading) => crate::format_regime(i18n::Lang::En, &reading),
+                None => "warming up".to_string(),
+            },
+            if ctx.adaptive_thresholds { ", adapts thresholds" } else { "" }
+        ),
+        format!(
+            "Whale Watch: {}",
+            match ctx.whale_min_usd {
+                Some(usd) => format!("swaps from {}", format_usd(usd)),
+                None => "off".to_string(),
+            }
+        ),
+        format!(
+            "Gas Wallets: {}",
+            if ctx.gas_wallets.is_empty() {
+                "none".to_string()
+            } else {
+                format!(
+                    "{} watched, floor {} {}",
+                    ctx.gas_wallets.len(),
+                    format_amount(ctx.gas_wallet_floor),
+                    crate::watcher::balance::native_symbol()
+                )
+            }
+        ),
+        format!(
+            "Token Lists: {}",
+            if ctx.token_lists.is_empty() {
+                "none".to_string()
+            } else {
+                ctx.token_lists.iter().map(|source| html::escape(&source.to_string())).collect::<Vec<_>>().join(", ")
+            }
+        ),
+        format!(
+            "Pairs priced ({}): {}",
+            pairs.len(),
+            if pairs.is_empty() { "none yet".to_string() } else { pairs.join(", ") }
+        ),
+        String::new(),
+        format!(
+            "Ingestion: {}",
+            match &ctx.node_events {
+                Some(path) => format!("reth ExEx (<code>{}</code>)", html::escape(&path.display().to_string())),
+                None => "RPC polling".to_string(),
+            }
+        ),
+        "Execution: off (alert-only)".to_string(),
+        format!("Tenderly Simulation: {}", on_off(ctx.tenderly.is_some())),
+        format!("Etherscan Safety Checks: {}", on_off(ctx.etherscan_api_key.is_some())),
+        format!(
+            "Shared State: {}",
+            match ctx.shared {
+                SharedState::Local(_) => "local",
+                SharedState::Redis { .. } => "Redis",
+            }
+        ),
+        format!("Leader: {}", if ctx.leader.is_leader() { "yes" } else { "no" }),
+        format!("Shard: {}", ctx.shard),
+        format!("Alert Chats: {}", ctx.chats.len()),
+        if ctx.roles.is_empty() {
+            "Roles: off (everyone is an admin)".to_string()
+        } else {
+            format!(
+                "Roles: {} admins, {} operators, everyone else a viewer",
+                ctx.roles.count(Role::Admin),
+                ctx.roles.count(Role::Operator)
+            )
+        },
+        format!(
+            "Subscribers: {}",
+            if ctx.multi_tenant { ctx.subscribers.count().to_string() } else { "off".to_string() }
+        ),
+        format!("Ops Chats: {}", ctx.ops_chats.len()),
+    ]
+    .join("\n")
+}
+
+/// Parses a window like `30m`, `24h` or `7d`. Empty means 24 hours.
+fn parse_window(text: &str) -> Option<ChronoDuration> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Some(ChronoDuration::hours(24));
+    }
+    let (amount, unit) = text.split_at(text.len() - 1);
+    let amount = amount.parse::<i64>().ok().filter(|&n| n > 0)?;
+    match unit.to_ascii_lowercase().as_str() {
+        "m" => ChronoDuration::try_minutes(amount),
+        "h" => ChronoDuration::try_hours(amount),
+        "d" => ChronoDuration::try_days(amount),
+        _ => None,
+    }
+}
+
+/// Pairs ranked by cumulative theoretical profit over `window`.
+fn top_pairs(ctx: &ScanContext, window: ChronoDuration) -> Result<String> {
+    let since = Utc::now() - window;
+    let ranking = ctx.storage.top_pairs(since, TOP_PAIRS)?;
+    if ranking.is_empty() {
+        return Ok(format!("No opportunities since {}.", format_time(since)));
+    }
+
+    let mut lines = vec![format!("🏆 <b>Top pairs</b> since {}\n", format_time(since))];
+    for (rank, entry) in ranking.iter().enumerate() {
+        lines.push(format!(
+            "{}. <b>{}</b> on {}: {} · {} opportunities · max {}",
+            rank + 1,
+            html::escape(&entry.pair),
+            html::escape(&entry.chain),
+            format_usd(entry.profit_usd),
+            entry.count,
+            format_percent(entry.max_spread)
+        ));
+    }
+    lines.push("\n<i>Theoretical profit, before gas</i>".to_string());
+
+    Ok(lines.join("\n"))
+}
+
+/// Last 24h of stored opportunities on a pair, plus its latest prices and
+/// pool TVL.
+fn pair_stats(ctx: &ScanContext, base: &str, quote: &str) -> Result<String> {
+    let since = Utc::now() - ChronoDuration::hours(24);
+    let stats = ctx.storage.pair_stats(base, quote, since)?;
+
+    let latest = ctx.latest.read().unwrap();
+    // One entry per two venues the pair is compared across
+    let quotes = latest
+        .values()
+        .filter(|(info, _)| {
+            [format!("{}/{}", base, quote), format!("{}/{}", quote, base)]
+                .iter()
+                .any(|pair| info.pair().eq_ignore_ascii_case(pair))
+        })
+        .collect::<Vec<_>>();
+
+    if stats.count == 0 && quotes.is_empty() {
+        let mut known = latest.values().map(|(info, _)| info.pair()).collect::<Vec<_>>();
+        known.sort();
+        known.dedup();
+        return Ok(format!(
+            "No data for {}/{} yet. Pairs with prices: {}",
+            html::escape(base),
+            html::escape(quote),
+            if known.is_empty() { "none yet".to_string() } else { known.join(", ") }
+        ));
+    }
+
+    let mut lines = vec![format!(
+        "📈 <b>{}/{}</b> · last 24h\n",
+        html::escape(base),
+        html::escape(quote)
+    )];
+    lines.push(format!("Opportunities: <b>{}</b>", stats.count));
+    if let Some(widest) = &stats.widest {
+        lines.push(format!(
+            "Spread: min {} · avg {} · max {}",
+            format_percent(stats.min_spread),
+            format_percent(stats.avg_spread),
+            format_percent(stats.max_spread)
+        ));
+        lines.push(format!(
+            "Biggest: <b>{}</b> buy {} → sell {} at {}",
+            format_percent(widest.spread),
+            widest.buy_venue,
+            widest.sell_venue,
+            format_time(widest.timestamp)
+        ));
+        lines.push(format!(
+            "Theoretical Profit: <b>{}</b> (before gas)",
+            format_usd(stats.profit_usd)
+        ));
+    }
+
+    if let Some(read_at) = quotes.iter().map(|(_, read_at)| *read_at).max() {
+        lines.push(String::new());
+        let mut shown = HashSet::new();
+        for (info, _) in &quotes {
+            for (state, pool) in [(info.a, info.pool_a), (info.b, info.pool_b)] {
+                if !shown.insert(pool) {
+                    continue;
+                }
+                let tvl = ctx
+                    .metadata
+                    .get(&pool)
+                    .and_then(|m| m.tvl_usd)
+                    .map(format_usd)
+                    .unwrap_or_else(|| "n/a".to_string());
+                lines.push(format!(
+                    "{}: <code>{} {}/{}</code> · TVL {}",
+                    state.venue_label(),
+                    format_amount(state.price()),
+                    info.symbol_b,
+                    info.symbol_a,
+                    tvl
+                ));
+            }
+        }
+        lines.push(format!("<i>Prices as of {}</i>", format_time(read_at)));
+    }
+
+    Ok(lines.join("\n"))
+}
@@ -0,0 +1,307 @@
+use alloy::primitives::Address;
+use alloy::providers::{Provider, MULTICALL3_ADDRESS};
+use alloy::sol;
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use colored::*;
+use std::collections::HashSet;
+use std::env;
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use teloxide::prelude::*;
+
+use crate::filter::AlertFilter;
+use crate::i18n::Lang;
+use crate::models::token;
+use crate::paper::PaperTrader;
+use crate::preset::Preset;
+use crate::role::Roles;
+use crate::shard::Shard;
+use crate::storage::shared::SharedState;
+use crate::strategy::Registry;
+use crate::token_list;
+use crate::utils::format::Locale;
+use crate::utils::secrets;
+use crate::RpcProvider;
+
+sol!(
+    #[sol(rpc)]
+    IERC20,
+    "src/abis/IERC20.json"
+);
+
+/// Counts failed checks so every problem is reported in one run instead of
+/// stopping at the first.
+#[derive(Default)]
+struct Report {
+    failures: usize,
+}
+
+impl Report {
+    fn check<T>(&mut self, name: &str, result: Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                println!("{} {}", "✓".bright_green(), name);
+                Some(value)
+            }
+            Err(e) => {
+                self.failures += 1;
+                println!("{} {}: {:#}", "✗".bright_red(), name, e);
+                None
+            }
+        }
+    }
+}
+
+/// Parses env var `name` if it is set and non-empty.
+fn parse_var<T>(name: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match env::var(name).ok().filter(|s| !s.is_empty()) {
+        Some(value) => value
+            .parse::<T>()
+            .map(Some)
+            .map_err(|e| anyhow!("{:?} is not valid: {}", value, e)),
+        None => Ok(None),
+    }
+}
+
+fn require_var(name: &str) -> Result<String> {
+    env::var(name)
+        .ok()
+        .filter(|s| !s.is_empty())
+        .with_context(|| format!("{} is required", name))
+}
+
+async fn has_code(provider: &RpcProvider, address: Address) -> Result<()> {
+    let code = provider.get_code_at(address).await?;
+    if code.is_empty() {
+        bail!("no contract deployed at {:?} on this chain", address);
+    }
+    Ok(())
+}
+
+/// Validates every setting the scanner reads and the preset's addresses
+/// against the chain `RPC_URL` points at. Errors if anything is wrong.
+pub async fn run(preset: &'static Preset) -> Result<()> {
+    let mut report = Report::default();
+    println!("{} {}\n", "Checking configuration for preset".yellow(), preset.name.bright_white());
+
+    let rpc_url = report.check("RPC_URL", secrets::require("RPC_URL").await);
+    if let Some(path) = env::var("NODE_EVENTS_FILE").ok().filter(|s| !s.is_empty()) {
+        report.check(
+            "NODE_EVENTS_FILE",
+            std::fs::metadata(&path).with_context(|| format!("can't read {}", path)),
+        );
+    }
+    let bot_token = report.check("TELEGRAM_BOT_TOKEN", secrets::require("TELEGRAM_BOT_TOKEN").await);
+    let default_lang = report
+        .check("ALERT_LANGUAGE", parse_var::<Lang>("ALERT_LANGUAGE"))
+        .flatten()
+        .unwrap_or_default();
+    report.check(
+        "TELEGRAM_CHAT_ID",
+        require_var("TELEGRAM_CHAT_ID").and_then(|value| {
+            let chats = crate::parse_chats(&value, default_lang)?;
+            if chats.is_empty() {
+                bail!("no chat ids listed");
+            }
+            Ok(())
+        }),
+    );
+    if let Ok(value) = env::var("TELEGRAM_OPS_CHAT_ID") {
+        report.check("TELEGRAM_OPS_CHAT_ID", crate::parse_chats(&value, default_lang));
+    }
+    report.check("OPS_HEARTBEAT_MINS", parse_var::<u64>("OPS_HEARTBEAT_MINS"));
+    report.check("MIN_POOL_TVL_USD", parse_var::<f64>("MIN_POOL_TVL_USD"));
+    report.check("ALERT_COOLDOWN_SECS", parse_var::<u64>("ALERT_COOLDOWN_SECS"));
+    report.check("FLASH_LOAN_FEE", parse_var::<f64>("FLASH_LOAN_FEE"));
+    report.check("MIN_PROFIT_USD", parse_var::<f64>("MIN_PROFIT_USD"));
+    report.check("BTC_PEG_THRESHOLD", parse_var::<f64>("BTC_PEG_THRESHOLD"));
+    report.check("WHALE_MIN_USD", parse_var::<f64>("WHALE_MIN_USD"));
+    report.check("TELEGRAM_ROLES", parse_var::<Roles>("TELEGRAM_ROLES"));
+    if let Ok(value) = env::var("TOKEN_LISTS") {
+        if let Some(sources) = report.check("TOKEN_LISTS", token_list::parse_sources(&value)) {
+            let client = reqwest::Client::new();
+            for source in sources {
+                report.check(&format!("Token list {}", source), token_list::sync(&client, &source).await);
+            }
+        }
+    }
+    if let Ok(value) = env::var("QUOTE_PRIORITY") {
+        report.check("QUOTE_PRIORITY", token::parse_quote_priority(&value));
+    }
+    report.check("MAX_CLOCK_SKEW_MS", parse_var::<i64>("MAX_CLOCK_SKEW_MS"));
+    report.check("MAX_BLOCK_LAG", parse_var::<u64>("MAX_BLOCK_LAG"));
+    report.check("NEW_POOL_MIN_AGE_SECS", parse_var::<i64>("NEW_POOL_MIN_AGE_SECS"));
+    report.check("PAPER_TRADING", PaperTrader::from_env(0.0));
+    report.check("ALERT_FILTER", AlertFilter::from_env());
+    report.check("SNAPSHOT_RETENTION_DAYS", parse_var::<i64>("SNAPSHOT_RETENTION_DAYS"));
+    let registry = report.check(
+        "STRATEGY_PLUGINS",
+        Registry::with_plugins(&env::var("STRATEGY_PLUGINS").unwrap_or_default()),
+    );
+    if let (Some(registry), Ok(list)) = (registry, env::var("STRATEGIES")) {
+        report.check("STRATEGIES", registry.build(&list));
+    }
+    report.check(
+        "LEADER_LEASE_SECS",
+        parse_var::<u64>("LEADER_LEASE_SECS").and_then(|lease| {
+            ensure!(lease != Some(0), "must be at least 1");
+            Ok(lease)
+        }),
+    );
+    report.check("WEBHOOK_MAX_ATTEMPTS", parse_var::<u32>("WEBHOOK_MAX_ATTEMPTS"));
+    if let Some(Some(_)) = report.check("API_ADDR", parse_var::<SocketAddr>("API_ADDR")) {
+        let keys = secrets::require("API_KEYS").await;
+        report.check("API_KEYS", keys.and_then(|keys| crate::api::parse_keys(&keys)));
+        report.check("API_TLS_CERT, API_TLS_KEY", crate::api::tls_from_env());
+    }
+    report.check(
+        "WATCHED_WALLETS",
+        env::var("WATCHED_WALLETS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .try_for_each(|s| {
+                s.parse::<Address>()
+                    .map(|_| ())
+                    .map_err(|e| anyhow!("{:?} is not an address: {}", s, e))
+            }),
+    );
+    report.check(
+        "GAS_WALLETS",
+        env::var("GAS_WALLETS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .try_for_each(|s| {
+                s.parse::<Address>()
+                    .map(|_| ())
+                    .map_err(|e| anyhow!("{:?} is not an address: {}", s, e))
+            }),
+    );
+    report.check("GAS_WALLET_FLOOR", parse_var::<f64>("GAS_WALLET_FLOOR"));
+    report.check(
+        "LP_POSITIONS",
+        crate::watcher::lp::parse_positions(&env::var("LP_POSITIONS").unwrap_or_default()),
+    );
+    report.check(
+        "TIMEZONE, DATE_FORMAT, separators, CURRENCY_SYMBOL",
+        Locale::from_env(),
+    );
+    report.check("ETHERSCAN_API_KEY", secrets::load("ETHERSCAN_API_KEY").await);
+
+    if let Some(Some(_)) = report.check("TENDERLY_ACCESS_KEY", secrets::load("TENDERLY_ACCESS_KEY").await) {
+        report.check("TENDERLY_ACCOUNT", require_var("TENDERLY_ACCOUNT"));
+        report.check("TENDERLY_PROJECT", require_var("TENDERLY_PROJECT"));
+        report.check(
+            "TENDERLY_FROM",
+            require_var("TENDERLY_FROM").and_then(|from| {
+                from.parse::<Address>()
+                    .map_err(|e| anyhow!("{:?} is not an address: {}", from, e))
+            }),
+        );
+    }
+
+    let redis_url = report.check("REDIS_URL", secrets::load("REDIS_URL").await).flatten();
+    if let Some(url) = &redis_url {
+        let prefix = env::var("REDIS_KEY_PREFIX").unwrap_or_else(|_| "arb-scanner".to_string());
+        report.check("Redis connection", SharedState::redis(url, &prefix).await);
+    }
+    let leader_election = env::var("LEADER_ELECTION")
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false);
+    if leader_election && redis_url.is_none() {
+        report.check::<()>("LEADER_ELECTION", Err(anyhow!("requires REDIS_URL")));
+    }
+    if let Some(Some(shard)) = report.check("SHARD", parse_var::<Shard>("SHARD")) {
+        if shard.is_partial() && redis_url.is_none() {
+            report.check::<()>("SHARD", Err(anyhow!("requires REDIS_URL")));
+        }
+    }
+
+    if let Some(token) = bot_token {
+        report.check(
+            "Telegram bot",
+            Bot::new(token)
+                .get_me()
+                .await
+                .map(|me| println!("  bot is @{}", me.username()))
+                .context("Telegram rejected TELEGRAM_BOT_TOKEN"),
+        );
+    }
+
+    if let Some(url) = rpc_url {
+        let provider = report.check("RPC connection", dex_scanner::connect(&url).await);
+        if let Some(provider) = provider {
+            let chain_ok = report.check(
+                "Chain",
+                provider.get_chain_id().await.map_err(Into::into).and_then(|id| {
+                    if id != preset.chain_id {
+                        bail!(
+                            "RPC_URL serves chain {}, but preset {} expects {}",
+                            id,
+                            preset.name,
+                            preset.chain_id
+                        );
+                    }
+                    Ok(())
+                }),
+            );
+            if chain_ok.is_some() {
+                check_contracts(&mut report, preset, &provider).await;
+            }
+        }
+    }
+
+    println!();
+    if report.failures > 0 {
+        bail!("{} configuration problem(s) found", report.failures);
+    }
+    println!("{}", "Configuration OK".bright_green().bold());
+    Ok(())
+}
+
+async fn check_contracts(report: &mut Report, preset: &Preset, provider: &RpcProvider) {
+    for token in preset.scanned_tokens() {
+        let result = async {
+            has_code(provider, token.address).await?;
+            let decimals = IERC20::new(token.address, provider)
+                .decimals()
+                .call()
+                .await
+                .context("decimals() is not readable")?;
+            if decimals != token.decimals {
+                bail!("decimals() returned {}, token list says {}", decimals, token.decimals);
+            }
+            Ok(())
+        }
+        .await;
+        report.check(&format!("{} {:?}", token.symbol, token.address), result);
+    }
+
+    // Uniswap V3's fee tiers are venues of one factory and router
+    let mut checked = HashSet::new();
+    for venue in preset.venues.iter().filter(|venue| checked.insert(venue.factory)) {
+        report.check(
+            &format!("{} factory {:?}", venue.venue, venue.factory),
+            has_code(provider, venue.factory).await,
+        );
+        if let Some(router) = venue.router {
+            report.check(
+                &format!("{} router {:?}", venue.venue, router),
+                has_code(provider, router).await,
+            );
+        }
+    }
+    let multicall = preset.multicall.unwrap_or(MULTICALL3_ADDRESS);
+    report.check(
+        &format!("Multicall3 {:?}", multicall),
+        has_code(provider, multicall).await,
+    );
+}
@@ -0,0 +1,179 @@
+//! The optional TOML config file: extra tokens, venue addresses, profit
+//! thresholds, the RPC endpoint and Telegram chats, for what's unwieldy to
+//! put in env vars. Env vars still take precedence over it. See
+//! `config.example.toml`.
+
+use alloy::primitives::Address;
+use anyhow::{bail, ensure, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::path::Path;
+
+use dex_scanner::models::token::{self, TokenInfo};
+use dex_scanner::preset::{PairMargin, Preset};
+use dex_scanner::token_list::plain_symbol;
+
+/// Read when `--config` isn't given, if it exists.
+pub const DEFAULT_PATH: &str = "config.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub rpc: Rpc,
+    pub telegram: Telegram,
+    /// Tokens scanned on top of the preset's.
+    pub tokens: Vec<Token>,
+    pub venues: Vec<VenueOverride>,
+    pub min_profit: MinProfit,
+    /// `QUOTE_PRIORITY`, as a list.
+    pub quote_priority: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rpc {
+    /// `RPC_URL`
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Telegram {
+    /// `TELEGRAM_CHAT_ID`
+    pub chat_id: Option<String>,
+    /// `TELEGRAM_OPS_CHAT_ID`
+    pub ops_chat_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Token {
+    pub address: Address,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// Other addresses for one of the preset's venues, e.g. a fork's
+/// deployment.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VenueOverride {
+    /// As shown in alerts, e.g. `"Uniswap V2"`, ignoring case.
+    pub venue: String,
+    pub factory: Address,
+    pub router: Option<Address>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MinProfit {
+    /// Replaces the preset's minimum profit margin.
+    pub margin: Option<f64>,
+    /// Margins for single pairs, keyed `BASE/QUOTE`.
+    pub pairs: BTreeMap<String, f64>,
+}
+
+/// Reads the config file at `path`, or at [`DEFAULT_PATH`] if `path` is
+/// `None` and it exists. Returns `None` if there's no file to read.
+pub fn load(path: Option<&Path>) -> Result<Option<ConfigFile>> {
+    let path = match path {
+        Some(path) => path,
+        None if Path::new(DEFAULT_PATH).exists() => Path::new(DEFAULT_PATH),
+        None => return Ok(None),
+    };
+    let contents = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let file = toml::from_str(&contents).with_context(|| format!("Invalid config file {}", path.display()))?;
+    Ok(Some(file))
+}
+
+impl ConfigFile {
+    /// Sets the env vars the file has values for, unless they're set
+    /// already. Secrets count as set when their `_FILE` or `_VAULT`
+    /// variant is.
+    pub fn apply_env(&self) {
+        let settings = [
+            ("RPC_URL", self.rpc.url.clone()),
+            ("TELEGRAM_CHAT_ID", self.telegram.chat_id.clone()),
+            ("TELEGRAM_OPS_CHAT_ID", self.telegram.ops_chat_id.clone()),
+            ("QUOTE_PRIORITY", self.quote_priority.as_ref().map(|symbols| symbols.join(","))),
+        ];
+        for (name, value) in settings {
+            let set = [name.to_string(), format!("{}_FILE", name), format!("{}_VAULT", name)]
+                .iter()
+                .any(|name| env::var(name).is_ok_and(|v| !v.is_empty()));
+            if let (Some(value), false) = (value, set) {
+                env::set_var(name, value);
+            }
+        }
+    }
+
+    /// `preset` with the file's venue addresses and profit margins. The
+    /// file's tokens are registered on the preset's chain, next to those
+    /// from token lists.
+    pub fn apply(&self, preset: &'static Preset) -> Result<&'static Preset> {
+        let mut tokens = Vec::new();
+        for t in &self.tokens {
+            ensure!(plain_symbol(&t.symbol), "Invalid token symbol {:?}", t.symbol);
+            match preset.tokens.iter().find(|known| known.symbol.eq_ignore_ascii_case(&t.symbol)) {
+                Some(known) if known.address == t.address => continue,
+                Some(known) => bail!("Token {} is already {} on {}", t.symbol, known.address, preset.chain),
+                None => tokens.push(TokenInfo {
+                    address: t.address,
+                    symbol: String::leak(t.symbol.clone()),
+                    decimals: t.decimals,
+                }),
+            }
+        }
+        token::register(preset.chain_id, preset.tokens, tokens);
+
+        let mut venues = preset.venues.clone();
+        for o in &self.venues {
+            // Several of a preset's venues can share a name, e.g. Uniswap V3 fee tiers
+            let matching = venues
+                .iter_mut()
+                .filter(|v| v.venue.name().eq_ignore_ascii_case(&o.venue))
+                .collect::<Vec<_>>();
+            if matching.is_empty() {
+                bail!("Preset {} doesn't use venue {:?} (it uses {})", preset.name, o.venue, preset.venue_names());
+            }
+            for venue in matching {
+                venue.factory = o.factory;
+                if o.router.is_some() {
+                    venue.router = o.router;
+                }
+            }
+        }
+
+        let known = preset.tokens.iter().chain(token::listed(preset.chain_id));
+        let symbol = |symbol: &str| {
+            known
+                .clone()
+                .find(|t| t.symbol.eq_ignore_ascii_case(symbol.trim()))
+                .map(|t| t.symbol)
+                .with_context(|| format!("Unknown token {:?} in min_profit.pairs", symbol.trim()))
+        };
+        let mut pair_margins = Vec::new();
+        for (pair, &margin) in &self.min_profit.pairs {
+            let Some((base, quote)) = pair.split_once('/') else {
+                bail!("Invalid pair {:?} in min_profit.pairs (expected BASE/QUOTE)", pair);
+            };
+            ensure!((0.0..1.0).contains(&margin), "Invalid min_profit margin {} for {}", margin, pair);
+            pair_margins.push(PairMargin { base: symbol(base)?, quote: symbol(quote)?, margin });
+        }
+        let min_profit_margin = match self.min_profit.margin {
+            Some(margin) => {
+                ensure!((0.0..1.0).contains(&margin), "Invalid min_profit margin {}", margin);
+                margin
+            }
+            None => preset.min_profit_margin,
+        };
+
+        Ok(Box::leak(Box::new(Preset {
+            venues,
+            min_profit_margin,
+            pair_margins: Vec::leak(pair_margins),
+            ..preset.clone()
+        })))
+    }
+}
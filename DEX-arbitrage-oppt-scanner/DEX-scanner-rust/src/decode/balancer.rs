@@ -0,0 +1,35 @@
+use alloy::primitives::{Address, U256};
+use alloy::rpc::types::Log;
+
+use super::event::{PoolTokens, SwapEvent};
+use super::{PoolInfo, Protocol, SwapLeg};
+
+/// `Swap(poolId, tokenIn, tokenOut, amountIn, amountOut)` of the Balancer
+/// V2 Vault, which every Balancer pool swaps through.
+pub const SWAP: SwapEvent = SwapEvent {
+    signature: "Swap(bytes32,address,address,uint256,uint256)",
+    tokens: PoolTokens::InLog,
+    decode: decode_log,
+};
+
+/// The pool is the first 20 bytes of the indexed pool ID, the tokens the
+/// other two indexed topics.
+pub fn decode_log(log: &Log, pool: &PoolInfo) -> Option<SwapLeg> {
+    let [_, pool_id, token_in, token_out] = log.topics() else {
+        return None;
+    };
+    let data = &log.data().data;
+    if data.len() < 64 {
+        return None;
+    }
+
+    Some(SwapLeg {
+        protocol: Protocol::Balancer,
+        venue: pool.venue,
+        pool: Address::from_slice(&pool_id[..20]),
+        token_in: Address::from_word(*token_in),
+        token_out: Address::from_word(*token_out),
+        amount_in: U256::from_be_slice(&data[0..32]),
+        amount_out: U256::from_be_slice(&data[32..64]),
+    })
+}
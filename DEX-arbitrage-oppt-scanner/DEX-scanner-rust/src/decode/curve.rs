@@ -0,0 +1,44 @@
+use alloy::primitives::U256;
+use alloy::rpc::types::Log;
+
+use super::event::{PoolTokens, SwapEvent};
+use super::{PoolInfo, Protocol, SwapLeg};
+
+/// `TokenExchange(buyer, sold_id, tokens_sold, bought_id, tokens_bought)`
+/// of stableswap pools, coin indexes as `int128`.
+pub const TOKEN_EXCHANGE: SwapEvent = SwapEvent {
+    signature: "TokenExchange(address,int128,uint256,int128,uint256)",
+    tokens: PoolTokens::Coins,
+    decode: decode_log,
+};
+
+/// The same event on cryptoswap pools, coin indexes as `uint256`.
+pub const TOKEN_EXCHANGE_V2: SwapEvent = SwapEvent {
+    signature: "TokenExchange(address,uint256,uint256,uint256,uint256)",
+    tokens: PoolTokens::Coins,
+    decode: decode_log,
+};
+
+/// Either `TokenExchange`; both encode indexes as one word, and a pool has
+/// at most eight coins.
+pub fn decode_log(log: &Log, pool: &PoolInfo) -> Option<SwapLeg> {
+    let data = &log.data().data;
+    if data.len() < 128 {
+        return None;
+    }
+    let word = |i: usize| U256::from_be_slice(&data[i * 32..(i + 1) * 32]);
+    let coin = |i: usize| {
+        let index = usize::try_from(word(i)).ok()?;
+        pool.tokens.get(index).copied()
+    };
+
+    Some(SwapLeg {
+        protocol: Protocol::Curve,
+        venue: pool.venue,
+        pool: log.address(),
+        token_in: coin(0)?,
+        token_out: coin(2)?,
+        amount_in: word(1),
+        amount_out: word(3),
+    })
+}
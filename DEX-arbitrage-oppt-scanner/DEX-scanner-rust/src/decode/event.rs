@@ -0,0 +1,66 @@
+//! Swap events the decoder understands, by topic. Each protocol's module
+//! describes its own events; more can be registered for pools the built-in
+//! ones don't cover.
+
+use alloy::primitives::{keccak256, B256};
+use alloy::rpc::types::Log;
+use std::collections::HashMap;
+
+use super::{balancer, curve, v2, v3, PoolInfo, SwapLeg};
+
+/// Decodes one log of the event into a leg, given the emitting contract's
+/// tokens as [`SwapEvent::tokens`] resolves them.
+pub type Decode = fn(&Log, &PoolInfo) -> Option<SwapLeg>;
+
+/// Where the tokens of the contract emitting a swap event come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolTokens {
+    /// `token0()` and `token1()`, as on Uniswap V2 pairs and V3 pools.
+    Pair,
+    /// `coins(i)`, as on Curve pools.
+    Coins,
+    /// The event names its tokens itself, as Balancer's Vault does.
+    InLog,
+}
+
+/// A swap event and how to decode it.
+#[derive(Debug, Clone, Copy)]
+pub struct SwapEvent {
+    /// Canonical signature, e.g. `Swap(address,uint256,uint256,uint256,uint256,address)`.
+    pub signature: &'static str,
+    pub tokens: PoolTokens,
+    pub decode: Decode,
+}
+
+impl SwapEvent {
+    pub fn topic(&self) -> B256 {
+        keccak256(self.signature)
+    }
+}
+
+/// Swap events by topic.
+#[derive(Debug, Clone)]
+pub struct EventRegistry(HashMap<B256, SwapEvent>);
+
+impl Default for EventRegistry {
+    /// Uniswap V2 style (including Solidly forks), Uniswap V3, Curve and
+    /// Balancer swaps.
+    fn default() -> Self {
+        let mut registry = EventRegistry(HashMap::new());
+        for event in [v2::SWAP, v2::SOLIDLY_SWAP, v3::SWAP, curve::TOKEN_EXCHANGE, curve::TOKEN_EXCHANGE_V2, balancer::SWAP] {
+            registry.register(event);
+        }
+        registry
+    }
+}
+
+impl EventRegistry {
+    /// Decodes `event` from now on, replacing any event with the same topic.
+    pub fn register(&mut self, event: SwapEvent) {
+        self.0.insert(event.topic(), event);
+    }
+
+    pub fn get(&self, topic: &B256) -> Option<&SwapEvent> {
+        self.0.get(topic)
+    }
+}
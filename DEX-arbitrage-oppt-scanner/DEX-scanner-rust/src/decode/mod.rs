@@ -0,0 +1,249 @@
+//! Classifies DEX swap transactions and decodes them into normalised legs,
+//! independent of which router or aggregator submitted them.
+
+pub mod balancer;
+pub mod curve;
+pub mod event;
+pub mod router;
+pub mod v2;
+pub mod v3;
+
+use alloy::network::{AnyTransactionReceipt, ReceiptResponse};
+use alloy::primitives::{Address, B256, U256};
+use alloy::sol;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::models::opportunity::Venue;
+use crate::models::token::get_token_info;
+use crate::utils::format::format_amount;
+use crate::utils::price::to_units;
+use crate::preset;
+use crate::{IUniswapV2Pair, RpcProvider};
+use event::{EventRegistry, PoolTokens};
+
+sol!(
+    #[sol(rpc)]
+    ICurvePool,
+    "src/abis/ICurvePool.json"
+);
+
+pub const UNISWAP_V3_FACTORY: &str = "0x1F98431c8aD98523631AE4a59f267346ea31F984";
+// Most coins a Curve pool holds
+const MAX_CURVE_COINS: u64 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    V2,
+    V3,
+    Curve,
+    Balancer,
+}
+
+impl Protocol {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Protocol::V2 => "V2",
+            Protocol::V3 => "V3",
+            Protocol::Curve => "Curve",
+            Protocol::Balancer => "Balancer",
+        }
+    }
+}
+
+/// One swap through one pool, normalised to what went in and came out.
+#[derive(Debug, Clone)]
+pub struct SwapLeg {
+    pub protocol: Protocol,
+    pub venue: Option<Venue>,
+    pub pool: Address,
+    pub token_in: Address,
+    pub token_out: Address,
+    pub amount_in: U256,
+    pub amount_out: U256,
+}
+
+/// All swap legs of one transaction, in log order.
+#[derive(Debug, Clone)]
+pub struct DecodedTx {
+    pub hash: B256,
+    pub from: Address,
+    /// Name of the router/aggregator the transaction was sent to, if known.
+    pub router: Option<&'static str>,
+    pub legs: Vec<SwapLeg>,
+}
+
+impl DecodedTx {
+    /// Two or more swaps that start and end in the same token.
+    pub fn is_arb(&self) -> bool {
+        self.legs.len() >= 2
+            && self.legs.first().map(|l| l.token_in) == self.legs.last().map(|l| l.token_out)
+    }
+
+    /// Amount-free route used for aggregation, e.g.
+    /// `WETH→USDC on Uniswap V2, USDC→WETH on Sushiswap`
+    pub fn route(&self) -> String {
+        self.legs
+            .iter()
+            .map(|l| format!("{}→{} on {}", token_label(&l.token_in), token_label(&l.token_out), l.venue_label()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Route with amounts, e.g. `1.5000 WETH→3,012.41 USDC on Uniswap V2, ...`
+    pub fn describe(&self) -> String {
+        let legs = self
+            .legs
+            .iter()
+            .map(|l| {
+                format!(
+                    "{}→{} on {}",
+                    amount_label(l.amount_in, &l.token_in),
+                    amount_label(l.amount_out, &l.token_out),
+                    l.venue_label()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        match self.router {
+            Some(router) => format!("{} via {}", legs, router),
+            None => legs,
+        }
+    }
+}
+
+impl SwapLeg {
+    fn venue_label(&self) -> String {
+        match self.venue {
+            Some(venue) => venue.name().to_string(),
+            None => format!("{} pool {}", self.protocol.name(), token_label(&self.pool)),
+        }
+    }
+}
+
+fn amount_label(amount: U256, token: &Address) -> String {
+    match get_token_info(token) {
+        Some(info) => format!("{} {}", format_amount(to_units(amount, info.decimals)), info.symbol),
+        None => token_label(token),
+    }
+}
+
+/// Symbol for known tokens, shortened address otherwise.
+fn token_label(address: &Address) -> String {
+    match get_token_info(address) {
+        Some(token) => token.symbol.to_string(),
+        None => {
+            let hex = format!("{:?}", address);
+            format!("{}…{}", &hex[..6], &hex[hex.len() - 4..])
+        }
+    }
+}
+
+pub fn venue_for_factory(factory: Address) -> Option<Venue> {
+    let is = |known: &str| known.parse::<Address>().map(|a| a == factory).unwrap_or(false);
+    if let Some(venue) = preset::active().venues.iter().find(|v| v.factory == factory) {
+        Some(venue.venue)
+    } else if is(UNISWAP_V3_FACTORY) {
+        Some(Venue::UniswapV3)
+    } else {
+        None
+    }
+}
+
+/// The contract a swap event came from: its venue, if it's a known one,
+/// and its tokens in the pool's order, none if the event names them.
+#[derive(Debug, Clone, Default)]
+pub struct PoolInfo {
+    pub venue: Option<Venue>,
+    pub tokens: Vec<Address>,
+}
+
+impl PoolInfo {
+    /// `[token0, token1]` of a two-token pool.
+    pub fn pair(&self) -> Option<[Address; 2]> {
+        match self.tokens[..] {
+            [token0, token1] => Some([token0, token1]),
+            _ => None,
+        }
+    }
+}
+
+/// Resolves pool addresses to their tokens and venue, caching the result.
+/// V2 pairs and V3 pools expose the same `token0/token1/factory` getters.
+pub struct PoolResolver {
+    provider: RpcProvider,
+    pools: Mutex<HashMap<Address, PoolInfo>>,
+}
+
+impl PoolResolver {
+    pub fn new(provider: RpcProvider) -> Self {
+        PoolResolver {
+            provider,
+            pools: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn resolve(&self, pool: Address, tokens: PoolTokens) -> Result<PoolInfo> {
+        if let Some(info) = self.pools.lock().unwrap().get(&pool) {
+            return Ok(info.clone());
+        }
+
+        let info = match tokens {
+            PoolTokens::Pair => {
+                let contract = IUniswapV2Pair::new(pool, &self.provider);
+                let token0 = contract.token0().call().await?;
+                let token1 = contract.token1().call().await?;
+                let factory = contract.factory().call().await?;
+                PoolInfo {
+                    venue: venue_for_factory(factory),
+                    tokens: vec![token0, token1],
+                }
+            }
+            PoolTokens::Coins => {
+                // `coins(i)` reverts past the last coin
+                let contract = ICurvePool::new(pool, &self.provider);
+                let mut coins = Vec::new();
+                for i in 0..MAX_CURVE_COINS {
+                    match contract.coins(U256::from(i)).call().await {
+                        Ok(coin) => coins.push(coin),
+                        Err(_) if !coins.is_empty() => break,
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+                PoolInfo { venue: None, tokens: coins }
+            }
+            PoolTokens::InLog => return Ok(PoolInfo::default()),
+        };
+        self.pools.lock().unwrap().insert(pool, info.clone());
+        Ok(info)
+    }
+}
+
+/// Decodes every swap log in `receipt` whose event is in `events`. Logs
+/// from contracts that aren't pools (or can't be resolved) are skipped.
+pub async fn decode_receipt(
+    events: &EventRegistry,
+    resolver: &PoolResolver,
+    receipt: &AnyTransactionReceipt,
+) -> DecodedTx {
+    let mut legs = Vec::new();
+
+    for log in receipt.inner.logs() {
+        let Some(event) = log.topics().first().and_then(|topic| events.get(topic)) else {
+            continue;
+        };
+        let pool = match resolver.resolve(log.address(), event.tokens).await {
+            Ok(pool) => pool,
+            Err(_) => continue,
+        };
+        legs.extend((event.decode)(log, &pool));
+    }
+
+    DecodedTx {
+        hash: receipt.transaction_hash(),
+        from: receipt.from(),
+        router: receipt.to().and_then(router::classify),
+        legs,
+    }
+}
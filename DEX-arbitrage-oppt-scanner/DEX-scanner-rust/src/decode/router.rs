@@ -0,0 +1,26 @@
+use alloy::primitives::Address;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+static ROUTERS: Lazy<HashMap<Address, &'static str>> = Lazy::new(|| {
+    [
+        ("0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D", "Uniswap V2 Router"),
+        ("0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F", "Sushiswap Router"),
+        ("0xE592427A0AEce92De3Edee1F18E0157C05861564", "Uniswap V3 Router"),
+        ("0x68b3465833fb72A70ecDF485E0e4C7bD8665Fc45", "Uniswap SwapRouter02"),
+        ("0x3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD", "Uniswap Universal Router"),
+        ("0x1111111254EEB25477B68fb85Ed929f73A960582", "1inch v5"),
+        ("0x111111125421cA6dc452d289314280a0f8842A65", "1inch v6"),
+        ("0xDef1C0ded9bec7F1a1670819833240f027b25EfF", "0x Exchange Proxy"),
+        ("0xDEF171Fe48CF0115B1d80b88dc8eAB59176FEe57", "ParaSwap"),
+        ("0x9008D19f58AAbD9eD0D60971565AA8510560ab41", "CoW Protocol"),
+    ]
+    .into_iter()
+    .map(|(address, name)| (address.parse().unwrap(), name))
+    .collect()
+});
+
+/// Name of a known router or aggregator contract.
+pub fn classify(to: Address) -> Option<&'static str> {
+    ROUTERS.get(&to).copied()
+}
@@ -0,0 +1,54 @@
+use alloy::primitives::{B256, U256};
+use alloy::rpc::types::Log;
+use once_cell::sync::Lazy;
+
+use super::event::{PoolTokens, SwapEvent};
+use super::{PoolInfo, Protocol, SwapLeg};
+
+pub const SWAP: SwapEvent = SwapEvent {
+    signature: "Swap(address,uint256,uint256,uint256,uint256,address)",
+    tokens: PoolTokens::Pair,
+    decode: decode_log,
+};
+/// `Swap(sender, to, amount0In, amount1In, amount0Out, amount1Out)` of
+/// Aerodrome and Equalizer pools, `sender` and `to` indexed. SyncSwap's
+/// matches Uniswap V2's.
+pub const SOLIDLY_SWAP: SwapEvent = SwapEvent {
+    signature: "Swap(address,address,uint256,uint256,uint256,uint256)",
+    ..SWAP
+};
+
+pub static SWAP_TOPIC: Lazy<B256> = Lazy::new(|| SWAP.topic());
+pub static SOLIDLY_SWAP_TOPIC: Lazy<B256> = Lazy::new(|| SOLIDLY_SWAP.topic());
+
+/// `[amount0In, amount1In, amount0Out, amount1Out]`, the data of both
+/// swap events.
+pub fn amounts(data: &[u8]) -> Option<[U256; 4]> {
+    if data.len() < 128 {
+        return None;
+    }
+    let word = |i: usize| U256::from_be_slice(&data[i * 32..(i + 1) * 32]);
+    Some([word(0), word(1), word(2), word(3)])
+}
+
+/// Either swap event; the amounts are all the data either way.
+pub fn decode_log(log: &Log, pool: &PoolInfo) -> Option<SwapLeg> {
+    let [amount0_in, amount1_in, amount0_out, amount1_out] = amounts(&log.data().data)?;
+    let [token0, token1] = pool.pair()?;
+
+    let (token_in, token_out, amount_in, amount_out) = if amount0_in.is_zero() {
+        (token1, token0, amount1_in, amount0_out)
+    } else {
+        (token0, token1, amount0_in, amount1_out)
+    };
+
+    Some(SwapLeg {
+        protocol: Protocol::V2,
+        venue: pool.venue,
+        pool: log.address(),
+        token_in,
+        token_out,
+        amount_in,
+        amount_out,
+    })
+}
@@ -0,0 +1,39 @@
+use alloy::primitives::I256;
+use alloy::rpc::types::Log;
+
+use super::event::{PoolTokens, SwapEvent};
+use super::{PoolInfo, Protocol, SwapLeg};
+
+pub const SWAP: SwapEvent = SwapEvent {
+    signature: "Swap(address,address,int256,int256,uint160,uint128,int24)",
+    tokens: PoolTokens::Pair,
+    decode: decode_log,
+};
+
+/// `Swap(sender, recipient, amount0, amount1, sqrtPriceX96, liquidity, tick)`;
+/// amounts are signed from the pool's view, positive meaning paid in.
+pub fn decode_log(log: &Log, pool: &PoolInfo) -> Option<SwapLeg> {
+    let data = &log.data().data;
+    if data.len() < 64 {
+        return None;
+    }
+    let amount0 = I256::try_from_be_slice(&data[0..32])?;
+    let amount1 = I256::try_from_be_slice(&data[32..64])?;
+    let [token0, token1] = pool.pair()?;
+
+    let (token_in, token_out, amount_in, amount_out) = if amount0.is_positive() {
+        (token0, token1, amount0, amount1)
+    } else {
+        (token1, token0, amount1, amount0)
+    };
+
+    Some(SwapLeg {
+        protocol: Protocol::V3,
+        venue: pool.venue,
+        pool: log.address(),
+        token_in,
+        token_out,
+        amount_in: amount_in.unsigned_abs(),
+        amount_out: amount_out.unsigned_abs(),
+    })
+}
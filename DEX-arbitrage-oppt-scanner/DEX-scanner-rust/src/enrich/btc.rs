@@ -0,0 +1,183 @@
+use alloy::primitives::Address;
+use alloy::sol;
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde_json::Value;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::models::token::TokenInfo;
+use crate::preset;
+use crate::RpcProvider;
+
+const COINBASE_URL: &str = "https://api.coinbase.com/v2/prices/BTC-USD/spot";
+const KRAKEN_URL: &str = "https://api.kraken.com/0/public/Ticker?pair=XBTUSD";
+const BITSTAMP_URL: &str = "https://www.bitstamp.net/api/v2/ticker/btcusd/";
+// Chainlink BTC/USD answers carry 8 decimals
+const CHAINLINK_DECIMALS: i32 = 8;
+// A round older than the feed's heartbeat plus slack is stale
+const CHAINLINK_MAX_AGE_SECS: i64 = 2 * 3600;
+// One source alone could be a bad print, so the reference waits for two
+const MIN_SOURCES: usize = 2;
+
+sol!(
+    #[sol(rpc)]
+    IAggregatorV3,
+    "src/abis/IAggregatorV3.json"
+);
+
+/// Tokens tracking BTC one to one.
+pub fn is_btc_wrapper(token: &TokenInfo) -> bool {
+    matches!(token.symbol, "WBTC" | "cbBTC" | "BTC.b")
+}
+
+/// Chainlink BTC/USD feed on the active preset's chain, if there is one.
+fn chainlink_feed() -> Option<Address> {
+    let feed = match preset::active().chain_id {
+        1 => "0xF4030086522a5bEEa4988F8cA5B36dbC97BeE88c",
+        8453 => "0x64c911996D3c6aC71f9b455B1E8E7266BcbD848F",
+        43114 => "0x2779D32d5166BAaa2B2b658333bA7e6Ec0C65743",
+        _ => return None,
+    };
+    feed.parse().ok()
+}
+
+/// BTC/USD as the median of the sources that answered.
+#[derive(Debug, Clone)]
+pub struct BtcReference {
+    pub price_usd: f64,
+    pub sources: Vec<&'static str>,
+}
+
+impl BtcReference {
+    /// How far `price_usd`, a BTC wrapper's price, sits from BTC, e.g.
+    /// -0.004 for 0.4% below.
+    pub fn deviation(&self, price_usd: f64) -> f64 {
+        price_usd / self.price_usd - 1.0
+    }
+}
+
+/// Latest BTC reference price, refreshed in the background.
+#[derive(Debug, Clone, Default)]
+pub struct BtcOracle(Arc<RwLock<Option<BtcReference>>>);
+
+impl BtcOracle {
+    pub fn get(&self) -> Option<BtcReference> {
+        self.0.read().unwrap().clone()
+    }
+}
+
+async fn fetch_json(client: &reqwest::Client, url: &str) -> Result<Value> {
+    Ok(client
+        .get(url)
+        .header("accept", "application/json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Value>()
+        .await?)
+}
+
+fn number(value: &Value) -> Option<f64> {
+    value.as_str().and_then(|s| s.parse().ok()).or_else(|| value.as_f64())
+}
+
+async fn coinbase(client: &reqwest::Client) -> Result<f64> {
+    let body = fetch_json(client, COINBASE_URL).await?;
+    number(&body["data"]["amount"]).context("Unexpected Coinbase response")
+}
+
+async fn kraken(client: &reqwest::Client) -> Result<f64> {
+    let body = fetch_json(client, KRAKEN_URL).await?;
+    // The result is keyed by Kraken's own pair name, e.g. XXBTZUSD
+    body["result"]
+        .as_object()
+        .and_then(|pairs| pairs.values().next())
+        .and_then(|ticker| number(&ticker["c"][0]))
+        .context("Unexpected Kraken response")
+}
+
+async fn bitstamp(client: &reqwest::Client) -> Result<f64> {
+    let body = fetch_json(client, BITSTAMP_URL).await?;
+    number(&body["last"]).context("Unexpected Bitstamp response")
+}
+
+async fn chainlink(provider: &RpcProvider, feed: Address) -> Result<f64> {
+    let round = IAggregatorV3::new(feed, provider).latestRoundData().call().await?;
+    let age = chrono::Utc::now().timestamp() - round.updatedAt.to::<u64>() as i64;
+    if age > CHAINLINK_MAX_AGE_SECS {
+        bail!("last round is {}s old", age);
+    }
+    Ok(i128::try_from(round.answer)? as f64 / 10f64.powi(CHAINLINK_DECIMALS))
+}
+
+/// Median of `values`, which must not be empty.
+pub fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Re-reads BTC/USD from Coinbase, Kraken, Bitstamp and, where the chain has
+/// one, Chainlink's feed every `interval`. Sources that fail are left out of
+/// that round; the reference is kept from the last round that reached
+/// `MIN_SOURCES`.
+pub fn spawn_refresh(
+    provider: RpcProvider,
+    oracle: BtcOracle,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        let feed = chainlink_feed();
+        loop {
+            let chainlink = async {
+                match feed {
+                    Some(feed) => Some(chainlink(&provider, feed).await),
+                    None => None,
+                }
+            };
+            let (coinbase, kraken, bitstamp, chainlink) = tokio::join!(
+                coinbase(&client),
+                kraken(&client),
+                bitstamp(&client),
+                chainlink,
+            );
+            let results = [
+                ("Coinbase", Some(coinbase)),
+                ("Kraken", Some(kraken)),
+                ("Bitstamp", Some(bitstamp)),
+                ("Chainlink", chainlink),
+            ];
+
+            let mut sources = Vec::new();
+            let mut prices = Vec::new();
+            for (source, result) in results {
+                match result {
+                    Some(Ok(price)) if price > 0.0 => {
+                        sources.push(source);
+                        prices.push(price);
+                    }
+                    Some(Ok(price)) => {
+                        println!("{} {} BTC price {} ignored", "[ERROR]".bright_red(), source, price)
+                    }
+                    Some(Err(e)) => {
+                        println!("{} Failed to fetch {} BTC price: {}", "[ERROR]".bright_red(), source, e)
+                    }
+                    None => {}
+                }
+            }
+            if sources.len() >= MIN_SOURCES {
+                *oracle.0.write().unwrap() = Some(BtcReference {
+                    price_usd: median(&mut prices),
+                    sources,
+                });
+            }
+            tokio::time::sleep(interval).await;
+        }
+    })
+}
@@ -0,0 +1,107 @@
+use alloy::primitives::Address;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::Deserialize;
+
+use super::PoolMetadata;
+use crate::preset;
+
+const NETWORKS_URL: &str = "https://api.geckoterminal.com/api/v2/networks";
+// The multi-pool endpoint accepts at most 30 addresses per request
+const MAX_POOLS_PER_REQUEST: usize = 30;
+
+#[derive(Debug, Deserialize)]
+struct PoolsResponse {
+    data: Vec<PoolData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PoolData {
+    attributes: PoolAttributes,
+}
+
+#[derive(Debug, Deserialize)]
+struct PoolAttributes {
+    address: String,
+    reserve_in_usd: Option<String>,
+    volume_usd: Option<VolumeUsd>,
+    pool_created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VolumeUsd {
+    h24: Option<String>,
+}
+
+/// Fetches TVL, 24h volume and creation time for the given pools from
+/// Coingecko's on-chain (GeckoTerminal) API. A request that fails is
+/// logged and its pools left out, so one bad chunk doesn't lose the rest.
+pub async fn fetch_pools(client: &reqwest::Client, pools: &[Address]) -> Vec<(Address, PoolMetadata)> {
+    let mut result = Vec::with_capacity(pools.len());
+
+    for chunk in pools.chunks(MAX_POOLS_PER_REQUEST) {
+        match fetch_chunk(client, chunk).await {
+            Ok(entries) => result.extend(entries),
+            Err(e) => println!(
+                "{} Failed to fetch metadata for {} pools from GeckoTerminal: {}",
+                "[ERROR]".bright_red(),
+                chunk.len(),
+                e
+            ),
+        }
+    }
+
+    result
+}
+
+async fn fetch_chunk(client: &reqwest::Client, chunk: &[Address]) -> Result<Vec<(Address, PoolMetadata)>> {
+    let addresses = chunk
+        .iter()
+        .map(|a| format!("{:?}", a))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let response = client
+        .get(format!(
+            "{}/{}/pools/multi/{}",
+            NETWORKS_URL,
+            preset::active().gecko_network,
+            addresses
+        ))
+        .header("accept", "application/json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<PoolsResponse>()
+        .await?;
+
+    let mut result = Vec::with_capacity(chunk.len());
+    for pool in response.data {
+        let attrs = pool.attributes;
+        let address = match attrs.address.parse::<Address>() {
+            Ok(address) => address,
+            Err(_) => continue,
+        };
+
+        let created_at = attrs
+            .pool_created_at
+            .as_deref()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Utc));
+
+        result.push((
+            address,
+            PoolMetadata {
+                tvl_usd: attrs.reserve_in_usd.and_then(|v| v.parse().ok()),
+                volume_24h_usd: attrs
+                    .volume_usd
+                    .and_then(|v| v.h24)
+                    .and_then(|v| v.parse().ok()),
+                created_at,
+            },
+        ));
+    }
+
+    Ok(result)
+}
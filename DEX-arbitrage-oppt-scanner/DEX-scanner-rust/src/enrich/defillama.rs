@@ -0,0 +1,84 @@
+use alloy::primitives::Address;
+use alloy::sol;
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::PoolMetadata;
+use crate::preset;
+use crate::RpcProvider;
+
+const PRICES_URL: &str = "https://coins.llama.fi/prices/current";
+// Keeps the comma-separated coin list well under URL length limits
+const MAX_COINS_PER_REQUEST: usize = 50;
+
+sol!(
+    #[sol(rpc)]
+    IERC20,
+    "src/abis/IERC20.json"
+);
+
+#[derive(Debug, Deserialize)]
+struct PricesResponse {
+    coins: HashMap<String, CoinPrice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinPrice {
+    price: f64,
+    decimals: Option<u8>,
+}
+
+/// USD price and decimals of each of `tokens` DefiLlama prices.
+async fn fetch_prices(client: &reqwest::Client, tokens: &[Address]) -> Result<HashMap<Address, CoinPrice>> {
+    let chain = preset::active().llama_chain;
+    let mut prices = HashMap::with_capacity(tokens.len());
+    for chunk in tokens.chunks(MAX_COINS_PER_REQUEST) {
+        let coins = chunk
+            .iter()
+            .map(|a| format!("{}:{:?}", chain, a))
+            .collect::<Vec<_>>()
+            .join(",");
+        let response = client
+            .get(format!("{}/{}", PRICES_URL, coins))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<PricesResponse>()
+            .await?;
+        for (coin, price) in response.coins {
+            if let Some(token) = coin.rsplit(':').next().and_then(|a| a.parse::<Address>().ok()) {
+                prices.insert(token, price);
+            }
+        }
+    }
+    Ok(prices)
+}
+
+/// TVL of each of `pools`, given with its two tokens, as its token balances
+/// at DefiLlama's prices. Covers pools GeckoTerminal doesn't list; volume
+/// and age stay unknown. Pools with an unpriced token are left out.
+pub async fn fetch_tvl(
+    provider: &RpcProvider,
+    client: &reqwest::Client,
+    pools: &[(Address, [Address; 2])],
+) -> Result<Vec<(Address, PoolMetadata)>> {
+    let mut tokens = pools.iter().flat_map(|(_, tokens)| *tokens).collect::<Vec<_>>();
+    tokens.sort();
+    tokens.dedup();
+    let prices = fetch_prices(client, &tokens).await?;
+
+    let mut result = Vec::with_capacity(pools.len());
+    'pools: for (pool, tokens) in pools {
+        let mut tvl = 0.0;
+        for token in tokens {
+            let Some(CoinPrice { price, decimals: Some(decimals) }) = prices.get(token) else {
+                continue 'pools;
+            };
+            let balance = IERC20::new(*token, provider).balanceOf(*pool).call().await?;
+            tvl += f64::from(balance) / 10f64.powi(*decimals as i32) * price;
+        }
+        result.push((*pool, PoolMetadata { tvl_usd: Some(tvl), ..Default::default() }));
+    }
+    Ok(result)
+}
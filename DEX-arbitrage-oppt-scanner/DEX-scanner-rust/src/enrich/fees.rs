@@ -0,0 +1,88 @@
+use alloy::primitives::Address;
+use alloy::sol;
+use colored::*;
+use std::time::Duration;
+
+use super::Cache;
+use crate::models::opportunity::Venue;
+use crate::univ3;
+use crate::utils::format::format_percent;
+use crate::utils::liquidity_book;
+use crate::RpcProvider;
+
+// Forks that make the swap fee governable expose it on the pair in basis points
+const FEE_DENOMINATOR: f64 = 10_000.0;
+// Anything above 10% is more likely a different unit than a real fee
+const MAX_PLAUSIBLE_FEE: f64 = 0.1;
+
+sol!(
+    #[sol(rpc)]
+    IPairFee,
+    "src/abis/IPairFee.json"
+);
+
+/// Pool address -> swap fee as a fraction of the input, for pools whose fee
+/// differs from their venue's default or can change on-chain.
+pub type FeeCache = Cache<f64>;
+
+impl FeeCache {
+    /// Current swap fee for `pool`, falling back to the venue default until
+    /// the pool's own fee has been read.
+    pub fn fee(&self, pool: &Address, venue: Venue) -> f64 {
+        self.get(pool).unwrap_or_else(|| venue.default_fee())
+    }
+}
+
+/// `pool`'s own swap fee, if it exposes one.
+pub async fn read_fee(provider: &RpcProvider, pool: Address, venue: Venue) -> Option<f64> {
+    match venue {
+        Venue::TraderJoeLB => return liquidity_book::base_fee(provider, pool).await.ok(),
+        Venue::UniswapV3 => return univ3::fee(provider, pool).await.ok(),
+        _ => {}
+    }
+    let raw = IPairFee::new(pool, provider).swapFee().call().await.ok()?;
+    let fee = raw as f64 / FEE_DENOMINATOR;
+    (fee <= MAX_PLAUSIBLE_FEE).then_some(fee)
+}
+
+/// Reads the fee of every pool into `cache`. Pools with a fee hard-coded in
+/// the pair contract (Uniswap V2, Sushiswap) keep their venue default.
+/// Trader Joe LB pairs report their base fee, Uniswap V3 pools their
+/// tier's.
+pub async fn refresh(provider: &RpcProvider, pools: &[(Address, Venue)], cache: &FeeCache) {
+    let mut fees = Vec::new();
+    for (pool, venue) in pools {
+        let fee = read_fee(provider, *pool, *venue)
+            .await
+            .unwrap_or_else(|| venue.default_fee());
+        let previous = cache.fee(pool, *venue);
+        if (fee - previous).abs() > f64::EPSILON {
+            println!(
+                "{} Fee for {} pool {:?} changed from {} to {}",
+                "[INFO]".bright_blue(),
+                venue,
+                pool,
+                format_percent(previous),
+                format_percent(fee)
+            );
+        }
+        fees.push((*pool, fee));
+    }
+    cache.update(fees);
+}
+
+/// Runs [`refresh`] every `interval`, so governance changes on forks with a
+/// settable fee are picked up without a restart.
+pub fn spawn_refresh(
+    provider: RpcProvider,
+    pools: Vec<(Address, Venue)>,
+    cache: FeeCache,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            refresh(&provider, &pools, &cache).await;
+            tokio::time::sleep(interval).await;
+        }
+    })
+}
@@ -0,0 +1,124 @@
+pub mod btc;
+pub mod coingecko;
+pub mod defillama;
+pub mod fees;
+pub mod regime;
+pub mod safety;
+pub mod tenderly;
+pub mod twap;
+
+use alloy::primitives::Address;
+use chrono::{DateTime, Utc};
+use colored::*;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::utils::format::format_usd;
+use crate::RpcProvider;
+
+/// Market attributes of a pool that can't be derived from its reserves alone.
+#[derive(Debug, Clone, Default)]
+pub struct PoolMetadata {
+    pub tvl_usd: Option<f64>,
+    pub volume_24h_usd: Option<f64>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl PoolMetadata {
+    pub fn age_days(&self) -> Option<i64> {
+        self.created_at.map(|t| (Utc::now() - t).num_days())
+    }
+
+    /// One-line summary used in alert messages, e.g. `TVL $1.2M | Vol 24h $310.5K | Age 1204d`.
+    pub fn summary(&self) -> String {
+        let tvl = self.tvl_usd.map(format_usd).unwrap_or_else(|| "n/a".to_string());
+        let volume = self.volume_24h_usd.map(format_usd).unwrap_or_else(|| "n/a".to_string());
+        let age = self
+            .age_days()
+            .map(|d| format!("{}d", d))
+            .unwrap_or_else(|| "n/a".to_string());
+        format!("TVL {} | Vol 24h {} | Age {}", tvl, volume, age)
+    }
+}
+
+/// Address-keyed enrichment data, shared between a refresh task and the monitors.
+#[derive(Debug)]
+pub struct Cache<T>(Arc<RwLock<HashMap<Address, T>>>);
+
+// Manual impls: deriving would needlessly require `T: Clone + Default`
+impl<T> Clone for Cache<T> {
+    fn clone(&self) -> Self {
+        Cache(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Default for Cache<T> {
+    fn default() -> Self {
+        Cache(Arc::new(RwLock::new(HashMap::new())))
+    }
+}
+
+impl<T: Clone> Cache<T> {
+    pub fn get(&self, address: &Address) -> Option<T> {
+        self.0.read().unwrap().get(address).cloned()
+    }
+
+    fn update(&self, entries: Vec<(Address, T)>) {
+        let mut map = self.0.write().unwrap();
+        for (address, value) in entries {
+            map.insert(address, value);
+        }
+    }
+}
+
+/// Pool address -> metadata.
+pub type MetadataCache = Cache<PoolMetadata>;
+
+/// Refreshes metadata every `interval` until the process exits, for the
+/// pools `pools` returns at the time, each with its two tokens, so pools
+/// scanned after startup are covered too. GeckoTerminal is asked first;
+/// pools it has no TVL for are priced through DefiLlama.
+pub fn spawn_refresh(
+    provider: RpcProvider,
+    pools: impl Fn() -> Vec<(Address, [Address; 2])> + Send + 'static,
+    cache: MetadataCache,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        loop {
+            let pools = pools();
+            let addresses = pools.iter().map(|(pool, _)| *pool).collect::<Vec<_>>();
+            let mut entries = coingecko::fetch_pools(&client, &addresses).await;
+            let unlisted = pools
+                .iter()
+                .filter(|(pool, _)| !entries.iter().any(|(p, m)| p == pool && m.tvl_usd.is_some()))
+                .copied()
+                .collect::<Vec<_>>();
+            if !unlisted.is_empty() {
+                match defillama::fetch_tvl(&provider, &client, &unlisted).await {
+                    Ok(priced) => {
+                        for (pool, metadata) in priced {
+                            match entries.iter_mut().find(|(p, _)| *p == pool) {
+                                Some((_, listed)) => listed.tvl_usd = metadata.tvl_usd,
+                                None => entries.push((pool, metadata)),
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        println!("{} Failed to price pools through DefiLlama: {}", "[ERROR]".bright_red(), e);
+                    }
+                }
+            }
+            println!(
+                "{} Refreshed metadata for {}/{} pools",
+                "[INFO]".bright_blue(),
+                entries.len(),
+                pools.len()
+            );
+            cache.update(entries);
+            tokio::time::sleep(interval).await;
+        }
+    })
+}
@@ -0,0 +1,143 @@
+//! Market regime: how volatile and busy the scanned pools are now compared
+//! with the past week. Volatility is the realized volatility of every
+//! pool's one-minute log returns over the last hour, volume the pools'
+//! summed 24h volume; each reading is ranked against the week's readings.
+
+use alloy::primitives::Address;
+use chrono::{DateTime, TimeDelta, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// How often a reading should be taken, see [`RegimeDetector::sample`].
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(300);
+const RETURN_INTERVAL: TimeDelta = TimeDelta::minutes(1);
+const VOLATILITY_WINDOW: TimeDelta = TimeDelta::hours(1);
+const HISTORY: TimeDelta = TimeDelta::days(7);
+// Readings needed before the regime is called, an hour's worth
+const MIN_READINGS: usize = 12;
+// Volatility percentiles from which the market counts as volatile, and up
+// to which it counts as calm
+const VOLATILE_PERCENTILE: f64 = 0.8;
+const CALM_PERCENTILE: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Regime {
+    Calm,
+    Normal,
+    Volatile,
+}
+
+impl Regime {
+    /// Factor thresholds are scaled by when they adapt to the regime: raised
+    /// in a volatile market, where spreads are noisier and close before
+    /// they can be traded, and lowered in a calm one.
+    pub fn threshold_scale(self) -> f64 {
+        match self {
+            Regime::Calm => 0.75,
+            Regime::Normal => 1.0,
+            Regime::Volatile => 1.5,
+        }
+    }
+}
+
+/// The market as of the latest reading.
+#[derive(Debug, Clone, Copy)]
+pub struct Reading {
+    pub regime: Regime,
+    /// Realized volatility per hour, e.g. 0.012 for 1.2%.
+    pub volatility: f64,
+    /// Share of the week's readings with lower volatility.
+    pub volatility_percentile: f64,
+    /// Share of the week's readings with lower volume, if volume is known.
+    pub volume_percentile: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Pool -> when its current return started, and its price then
+    marks: HashMap<Address, (DateTime<Utc>, f64)>,
+    /// Squared log returns of every pool over the volatility window
+    returns: VecDeque<(DateTime<Utc>, f64)>,
+    /// The week's readings as (at, volatility, volume), oldest first
+    history: VecDeque<(DateTime<Utc>, f64, Option<f64>)>,
+    current: Option<Reading>,
+}
+
+impl State {
+    fn prune_returns(&mut self, now: DateTime<Utc>) {
+        while self.returns.front().is_some_and(|(at, _)| *at <= now - VOLATILITY_WINDOW) {
+            self.returns.pop_front();
+        }
+    }
+}
+
+/// Share of `values` below `value`, `None` if there are none.
+fn percentile(values: impl Iterator<Item = f64>, value: f64) -> Option<f64> {
+    let (below, total) = values.fold((0, 0), |(below, total), v| (below + (v < value) as usize, total + 1));
+    (total > 0).then(|| below as f64 / total as f64)
+}
+
+/// Classifies the market from prices recorded by ingestion, one reading
+/// every [`SAMPLE_INTERVAL`].
+#[derive(Debug, Clone, Default)]
+pub struct RegimeDetector(Arc<RwLock<State>>);
+
+impl RegimeDetector {
+    /// Records `pool`'s price as read at `at`. Reads closer together than a
+    /// minute make one return.
+    pub fn record(&self, pool: Address, at: DateTime<Utc>, price: f64) {
+        if !price.is_finite() || price <= 0.0 {
+            return;
+        }
+        let mut state = self.0.write().unwrap();
+        match state.marks.get(&pool).copied() {
+            Some((since, _)) if at - since < RETURN_INTERVAL => return,
+            Some((_, mark)) => {
+                let ret = (price / mark).ln();
+                state.returns.push_back((at, ret * ret));
+            }
+            None => {}
+        }
+        state.marks.insert(pool, (at, price));
+        state.prune_returns(at);
+    }
+
+    /// Takes a reading from the last hour's returns and `volume_usd`, the
+    /// pools' summed 24h volume if known, and ranks it against the week's.
+    /// The regime is only called once there's an hour of readings.
+    pub fn sample(&self, now: DateTime<Utc>, volume_usd: Option<f64>) {
+        let mut state = self.0.write().unwrap();
+        state.prune_returns(now);
+        if state.returns.is_empty() {
+            return;
+        }
+        let variance = state.returns.iter().map(|(_, squared)| squared).sum::<f64>() / state.returns.len() as f64;
+        // Per-minute variance over an hour of minutes
+        let volatility = (variance * (VOLATILITY_WINDOW.num_minutes() / RETURN_INTERVAL.num_minutes()) as f64).sqrt();
+
+        while state.history.front().is_some_and(|(at, ..)| *at <= now - HISTORY) {
+            state.history.pop_front();
+        }
+        if state.history.len() >= MIN_READINGS {
+            let volatility_percentile =
+                percentile(state.history.iter().map(|(_, v, _)| *v), volatility).unwrap_or(0.5);
+            let volume_percentile =
+                volume_usd.and_then(|volume| percentile(state.history.iter().filter_map(|(.., v)| *v), volume));
+            let regime = if volatility_percentile >= VOLATILE_PERCENTILE {
+                Regime::Volatile
+            } else if volatility_percentile <= CALM_PERCENTILE {
+                Regime::Calm
+            } else {
+                Regime::Normal
+            };
+            state.current = Some(Reading { regime, volatility, volatility_percentile, volume_percentile });
+        }
+        state.history.push_back((now, volatility, volume_usd));
+    }
+
+    /// The latest reading, `None` until there's an hour of them.
+    pub fn current(&self) -> Option<Reading> {
+        self.0.read().unwrap().current
+    }
+}
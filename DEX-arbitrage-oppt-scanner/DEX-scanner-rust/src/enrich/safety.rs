@@ -0,0 +1,224 @@
+use alloy::primitives::{address, Address, U256};
+use alloy::providers::Provider;
+use alloy::sol;
+use anyhow::Result;
+use colored::*;
+use serde::Deserialize;
+use std::fmt;
+use std::time::Duration;
+
+use super::Cache;
+use crate::preset;
+use crate::RpcProvider;
+
+// The V2 API serves every Etherscan-family explorer (Basescan, ...) with one key
+pub const ETHERSCAN_API_URL: &str = "https://api.etherscan.io/v2/api";
+const DEAD_ADDRESS: Address = address!("0x000000000000000000000000000000000000dEaD");
+// PUSH4 0x40c10f19, the `mint(address,uint256)` selector as it appears in a dispatcher
+const MINT_SELECTOR_PUSH: [u8; 5] = [0x63, 0x40, 0xc1, 0x0f, 0x19];
+// Share of LP supply that must be burned for liquidity to count as locked
+const MIN_BURNED_LP_SHARE: f64 = 0.5;
+
+sol!(
+    #[sol(rpc)]
+    IERC20,
+    "src/abis/IERC20.json"
+);
+
+sol!(
+    #[sol(rpc)]
+    ITokenAdmin,
+    "src/abis/ITokenAdmin.json"
+);
+
+/// Results of the honeypot/rugpull heuristics for one token. `None` means the
+/// check could not be performed (e.g. the token has no `paused()` function).
+#[derive(Debug, Clone, Default)]
+pub struct SafetyReport {
+    pub owned: Option<bool>,
+    pub mintable: Option<bool>,
+    pub paused: Option<bool>,
+    pub verified: Option<bool>,
+    pub lp_burned_share: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SafetyGrade {
+    A,
+    B,
+    C,
+    D,
+    F,
+}
+
+impl fmt::Display for SafetyGrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl SafetyReport {
+    pub fn flags(&self) -> Vec<&'static str> {
+        let mut flags = Vec::new();
+        if self.paused == Some(true) {
+            flags.push("trading paused");
+        }
+        if self.owned == Some(true) && self.mintable == Some(true) {
+            flags.push("owner can mint");
+        }
+        if self.verified == Some(false) {
+            flags.push("unverified source");
+        }
+        if self.lp_burned_share.is_some_and(|share| share < MIN_BURNED_LP_SHARE) {
+            flags.push("LP unlocked");
+        }
+        flags
+    }
+
+    pub fn grade(&self) -> SafetyGrade {
+        if self.paused == Some(true) {
+            return SafetyGrade::F;
+        }
+        match self.flags().len() {
+            0 => SafetyGrade::A,
+            1 => SafetyGrade::B,
+            2 => SafetyGrade::C,
+            _ => SafetyGrade::D,
+        }
+    }
+
+    /// e.g. `USDC C (owner can mint, LP unlocked)`
+    pub fn summary(&self, symbol: &str) -> String {
+        let flags = self.flags();
+        if flags.is_empty() {
+            format!("{} {}", symbol, self.grade())
+        } else {
+            format!("{} {} ({})", symbol, self.grade(), flags.join(", "))
+        }
+    }
+}
+
+/// Token address -> safety report.
+pub type SafetyCache = Cache<SafetyReport>;
+
+#[derive(Debug, Deserialize)]
+struct EtherscanResponse {
+    result: Vec<EtherscanSource>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EtherscanSource {
+    #[serde(rename = "SourceCode")]
+    source_code: String,
+}
+
+async fn is_verified(client: &reqwest::Client, api_key: &str, token: Address) -> Result<bool> {
+    let chain_id = preset::active().chain_id.to_string();
+    let address = format!("{:?}", token);
+    let response = client
+        .get(ETHERSCAN_API_URL)
+        .query(&[
+            ("chainid", chain_id.as_str()),
+            ("module", "contract"),
+            ("action", "getsourcecode"),
+            ("address", &address),
+            ("apikey", api_key),
+        ])
+        .send()
+        .await?
+        .json::<EtherscanResponse>()
+        .await?;
+
+    Ok(response.result.iter().any(|r| !r.source_code.is_empty()))
+}
+
+/// Share of each pool's LP supply sitting at the zero or dead address; the
+/// best-locked pool is reported.
+async fn lp_burned_share(provider: &RpcProvider, pools: &[Address]) -> Option<f64> {
+    let mut best: Option<f64> = None;
+
+    for pool in pools {
+        let lp = IERC20::new(*pool, provider);
+        let supply = match lp.totalSupply().call().await {
+            Ok(supply) if !supply.is_zero() => supply,
+            _ => continue,
+        };
+
+        let mut burned = U256::ZERO;
+        for holder in [Address::ZERO, DEAD_ADDRESS] {
+            burned += lp.balanceOf(holder).call().await.unwrap_or_default();
+        }
+
+        let share = f64::from(burned) / f64::from(supply);
+        best = Some(best.map_or(share, |b: f64| b.max(share)));
+    }
+
+    best
+}
+
+/// Runs all heuristics for `token`, whose liquidity lives in `pools`.
+pub async fn assess_token(
+    provider: &RpcProvider,
+    client: &reqwest::Client,
+    etherscan_api_key: Option<&str>,
+    token: Address,
+    pools: &[Address],
+) -> SafetyReport {
+    let admin = ITokenAdmin::new(token, provider);
+
+    let owned = admin.owner().call().await.ok().map(|owner| !owner.is_zero());
+    let paused = admin.paused().call().await.ok();
+    let mintable = provider
+        .get_code_at(token)
+        .await
+        .ok()
+        .map(|code| code.windows(MINT_SELECTOR_PUSH.len()).any(|w| w == MINT_SELECTOR_PUSH));
+
+    let verified = match etherscan_api_key {
+        Some(key) => match is_verified(client, key, token).await {
+            Ok(verified) => Some(verified),
+            Err(e) => {
+                println!("{} Etherscan lookup failed for {:?}: {}", "[ERROR]".bright_red(), token, e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    SafetyReport {
+        owned,
+        mintable,
+        paused,
+        verified,
+        lp_burned_share: lp_burned_share(provider, pools).await,
+    }
+}
+
+/// Re-assesses every `(token, pools)` entry every `interval` until the process exits.
+pub fn spawn_refresh(
+    provider: RpcProvider,
+    tokens: Vec<(Address, Vec<Address>)>,
+    etherscan_api_key: Option<String>,
+    cache: SafetyCache,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        loop {
+            let mut reports = Vec::with_capacity(tokens.len());
+            for (token, pools) in &tokens {
+                let report = assess_token(
+                    &provider,
+                    &client,
+                    etherscan_api_key.as_deref(),
+                    *token,
+                    pools,
+                )
+                .await;
+                reports.push((*token, report));
+            }
+            cache.update(reports);
+            tokio::time::sleep(interval).await;
+        }
+    })
+}
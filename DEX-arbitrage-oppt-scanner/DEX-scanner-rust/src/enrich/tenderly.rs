@@ -0,0 +1,179 @@
+use alloy::hex;
+use alloy::primitives::{Address, U256};
+use alloy::sol;
+use alloy::sol_types::SolCall;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::models::opportunity::Opportunity;
+use crate::models::token::TokenInfo;
+use crate::preset;
+use crate::utils::price;
+
+const API_URL: &str = "https://api.tenderly.co/api/v1";
+const SHARE_URL: &str = "https://www.tdly.co/shared/simulation";
+const SWAP_GAS_LIMIT: u64 = 300_000;
+// Sell slightly less than the quoted buy output so float rounding can't make
+// the sell leg revert for want of balance
+const SELL_AMOUNT_HAIRCUT: f64 = 0.999;
+
+sol!(IERC20, "src/abis/IERC20.json");
+
+sol!(IUniswapV2Router02, "src/abis/IUniswapV2Router02.json");
+
+#[derive(Debug, Deserialize)]
+struct BundleResponse {
+    simulation_results: Vec<SimulationResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SimulationResult {
+    simulation: Simulation,
+}
+
+#[derive(Debug, Deserialize)]
+struct Simulation {
+    id: String,
+}
+
+/// Shareable simulations of each leg of an opportunity.
+#[derive(Debug, Clone)]
+pub struct SimulationLinks {
+    pub buy: String,
+    pub sell: String,
+}
+
+/// Tenderly project credentials and the wallet candidate arbs are simulated from.
+pub struct Tenderly {
+    client: reqwest::Client,
+    account: String,
+    project: String,
+    access_key: String,
+    from: Address,
+}
+
+struct Call {
+    to: Address,
+    input: Vec<u8>,
+}
+
+/// Router path of a leg, through `via` when it's routed.
+fn path(token_in: &TokenInfo, via: Option<&TokenInfo>, token_out: &TokenInfo) -> Vec<Address> {
+    [Some(token_in), via, Some(token_out)]
+        .into_iter()
+        .flatten()
+        .map(|token| token.address)
+        .collect()
+}
+
+impl Tenderly {
+    pub fn new(account: String, project: String, access_key: String, from: Address) -> Self {
+        Tenderly {
+            client: reqwest::Client::new(),
+            account,
+            project,
+            access_key,
+            from,
+        }
+    }
+
+    fn project_url(&self) -> String {
+        format!("{}/account/{}/project/{}", API_URL, self.account, self.project)
+    }
+
+    /// Simulates the candidate arb as a bundle (approve + swap on the buy
+    /// router, approve + swap on the sell router) against the latest block and
+    /// returns share links for the two swaps. The `from` wallet needs to hold
+    /// the quote token for the buy leg to succeed; failed simulations are
+    /// still saved so the revert can be inspected.
+    pub async fn simulate(&self, opportunity: &Opportunity) -> Result<SimulationLinks> {
+        let preset = preset::active();
+        let (Some(buy_router), Some(sell_router)) =
+            (preset.router(opportunity.buy.venue), preset.router(opportunity.sell.venue))
+        else {
+            bail!("no V2 router for {} → {}", opportunity.buy.venue, opportunity.sell.venue);
+        };
+
+        let quote = opportunity.quote;
+        let base = opportunity.base;
+        let amount_in = price::from_units(opportunity.amount_in, quote.decimals);
+        let base_amount = price::from_units(opportunity.base_amount * SELL_AMOUNT_HAIRCUT, base.decimals);
+
+        let calls = [
+            self.approve(quote.address, buy_router, amount_in),
+            self.swap(buy_router, amount_in, path(quote, opportunity.buy_via, base)),
+            self.approve(base.address, sell_router, base_amount),
+            self.swap(sell_router, base_amount, path(base, opportunity.sell_via, quote)),
+        ];
+
+        let simulations = calls
+            .iter()
+            .map(|call| {
+                json!({
+                    "network_id": preset.chain_id.to_string(),
+                    "from": format!("{:?}", self.from),
+                    "to": format!("{:?}", call.to),
+                    "input": format!("0x{}", hex::encode(&call.input)),
+                    "gas": SWAP_GAS_LIMIT,
+                    "gas_price": "0",
+                    "value": "0",
+                    "save": true,
+                    "save_if_fails": true,
+                    "simulation_type": "full",
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let response = self
+            .client
+            .post(format!("{}/simulate-bundle", self.project_url()))
+            .header("X-Access-Key", &self.access_key)
+            .json(&json!({ "simulations": simulations }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<BundleResponse>()
+            .await
+            .context("Unexpected Tenderly response")?;
+
+        if response.simulation_results.len() != calls.len() {
+            bail!(
+                "expected {} simulation results, got {}",
+                calls.len(),
+                response.simulation_results.len()
+            );
+        }
+
+        let buy = self.share(&response.simulation_results[1].simulation.id).await?;
+        let sell = self.share(&response.simulation_results[3].simulation.id).await?;
+        Ok(SimulationLinks { buy, sell })
+    }
+
+    async fn share(&self, id: &str) -> Result<String> {
+        self.client
+            .post(format!("{}/simulations/{}/share", self.project_url(), id))
+            .header("X-Access-Key", &self.access_key)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(format!("{}/{}", SHARE_URL, id))
+    }
+
+    fn approve(&self, token: Address, spender: Address, amount: U256) -> Call {
+        let input = IERC20::approveCall { spender, amount }.abi_encode();
+        Call { to: token, input }
+    }
+
+    fn swap(&self, router: Address, amount_in: U256, path: Vec<Address>) -> Call {
+        let input = IUniswapV2Router02::swapExactTokensForTokensCall {
+            amountIn: amount_in,
+            amountOutMin: U256::ZERO,
+            path,
+            to: self.from,
+            deadline: U256::MAX,
+        }
+        .abi_encode();
+        Call { to: router, input }
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::{ensure, Context, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
+use colored::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+
+use crate::models::opportunity::{PoolState, Venue};
+use crate::models::token::TokenInfo;
+use crate::utils::price::to_f64;
+use crate::{IUniswapV2Pair, RpcProvider};
+
+/// Span the TWAP averages over.
+pub const WINDOW: TimeDelta = TimeDelta::minutes(5);
+// Prices kept per pool; a 5 minute window at one read per 2s block is 150
+const MAX_SAMPLES: usize = 1024;
+
+/// A pool's price as read at one block.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    block: u64,
+    at: DateTime<Utc>,
+    price: f64,
+}
+
+/// Venues whose pairs keep Uniswap V2's `price0CumulativeLast`.
+fn has_cumulative_oracle(venue: Venue) -> bool {
+    matches!(
+        venue,
+        Venue::UniswapV2 | Venue::Sushiswap | Venue::PancakeSwap | Venue::TraderJoe | Venue::SpookySwap
+    )
+}
+
+/// Recent prices of every scanned pool, one ring buffer per pool, recorded
+/// by ingestion on every block it reads.
+#[derive(Debug, Clone, Default)]
+pub struct PriceHistory(Arc<RwLock<HashMap<Address, VecDeque<Sample>>>>);
+
+impl PriceHistory {
+    pub fn record(&self, pool: Address, block: u64, at: DateTime<Utc>, price: f64) {
+        let mut history = self.0.write().unwrap();
+        let samples = history.entry(pool).or_default();
+        if samples.back().is_some_and(|last| last.block >= block) {
+            return;
+        }
+        samples.push_back(Sample { block, at, price });
+        // The oldest sample is kept while it's the one in force at the
+        // window's start
+        while samples.len() > MAX_SAMPLES || samples.get(1).is_some_and(|next| next.at <= at - WINDOW) {
+            samples.pop_front();
+        }
+    }
+
+    /// Time-weighted average of the prices recorded for `pool` over the
+    /// window to `now`, each holding until the next. `None` until the pool
+    /// has been watched for the whole window.
+    fn recorded_twap(&self, pool: Address, now: DateTime<Utc>) -> Option<f64> {
+        let history = self.0.read().unwrap();
+        let samples = history.get(&pool)?;
+        let start = now - WINDOW;
+        if samples.front()?.at > start {
+            return None;
+        }
+        let mut weighted = 0.0;
+        for (i, sample) in samples.iter().enumerate() {
+            let from = sample.at.max(start);
+            let until = samples.get(i + 1).map_or(now, |next| next.at);
+            weighted += sample.price * (until - from).num_milliseconds().max(0) as f64;
+        }
+        Some(weighted / WINDOW.num_milliseconds() as f64)
+    }
+
+    /// First and last blocks recorded for `pool` that span the window to
+    /// `now`.
+    fn window_blocks(&self, pool: Address, now: DateTime<Utc>) -> Option<(u64, u64)> {
+        let history = self.0.read().unwrap();
+        let samples = history.get(&pool)?;
+        let first = samples.front().filter(|first| first.at <= now - WINDOW)?;
+        Some((first.block, samples.back()?.block))
+    }
+
+    /// 5-minute TWAP of `pool` in quote per base, from the pair's own
+    /// cumulative price oracle where its venue keeps one, else from the
+    /// prices recorded here.
+    pub async fn twap(
+        &self,
+        provider: &RpcProvider,
+        pool: &PoolState,
+        base: &TokenInfo,
+        quote: &TokenInfo,
+    ) -> Option<f64> {
+        let now = Utc::now();
+        if has_cumulative_oracle(pool.venue) {
+            if let Some((from, to)) = self.window_blocks(pool.pool, now) {
+                match oracle_twap(provider, pool.pool, from, to).await {
+                    Ok(price) => {
+                        // The oracle prices the pool's first token, the lower address
+                        let price = if base.address < quote.address || price == 0.0 { price } else { 1.0 / price };
+                        return Some(price * 10f64.powi(base.decimals as i32 - quote.decimals as i32));
+                    }
+                    Err(e) => println!("{} Failed to read {} price oracle of {:?}, using recorded prices: {}",
+                        "[ERROR]".bright_red(),
+                        pool.venue.name(),
+                        pool.pool,
+                        e,
+                    ),
+                }
+            }
+        }
+        self.recorded_twap(pool.pool, now)
+    }
+}
+
+/// `price0CumulativeLast` of `pool` brought forward to the timestamp of
+/// `block`, as Uniswap's oracle library does, and that timestamp.
+async fn cumulative_at(provider: &RpcProvider, pool: Address, block: u64) -> Result<(U256, u32)> {
+    let contract = IUniswapV2Pair::new(pool, provider);
+    let reserves = contract.getReserves().block(block.into());
+    let cumulative = contract.price0CumulativeLast().block(block.into());
+    let (reserves, cumulative, header) = tokio::try_join!(
+        async { Ok::<_, anyhow::Error>(reserves.call().await?) },
+        async { Ok(cumulative.call().await?) },
+        async { Ok(provider.get_block_by_number(block.into()).await?) }
+    )?;
+    // Pairs keep the timestamp mod 2^32, so it's truncated to match
+    let timestamp = header.context("Block not found")?.header.timestamp as u32;
+
+    let elapsed = timestamp.wrapping_sub(reserves.blockTimestampLast);
+    if elapsed == 0 || reserves.reserve0.is_zero() {
+        return Ok((cumulative, timestamp));
+    }
+    // UQ112x112 price of token0 in token1, accumulated per second
+    let price: U256 = (U256::from(reserves.reserve1) << 112) / U256::from(reserves.reserve0);
+    let accrued = price.overflowing_mul(U256::from(elapsed)).0;
+    Ok((cumulative.overflowing_add(accrued).0, timestamp))
+}
+
+/// Average price of token0 in token1, in raw units, between blocks `from`
+/// and `to` per the pair's cumulative price. The accumulator is meant to
+/// overflow, so differences wrap.
+async fn oracle_twap(provider: &RpcProvider, pool: Address, from: u64, to: u64) -> Result<f64> {
+    let (start, end) = tokio::try_join!(
+        cumulative_at(provider, pool, from),
+        cumulative_at(provider, pool, to)
+    )?;
+    let elapsed = end.1.wrapping_sub(start.1);
+    ensure!(elapsed > 0, "blocks {} and {} share a timestamp", from, to);
+    let average = end.0.overflowing_sub(start.0).0 / U256::from(elapsed);
+    Ok(to_f64(average) / 2f64.powi(112))
+}
@@ -0,0 +1,111 @@
+//! Node-direct ingestion: block notifications a reth execution extension
+//! (ExEx) on the same machine appends to a file, read in place of polling
+//! the RPC node. Each line is one notification as JSON, blocks rolled back
+//! and blocks now canonical, each in ascending order (a reorg has both):
+//!
+//! ```text
+//! {"reverted": [123], "committed": [{"number": 123, "logs": [{"address": "0x…", "topics": ["0x…"], "data": "0x…"}]}]}
+//! ```
+//!
+//! Only logs of scanned pools are needed, so the ExEx may filter the rest.
+
+use alloy::primitives::{keccak256, Address, Bytes, B256, U256};
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt, BufReader};
+
+use crate::preset;
+
+// How long to wait for the ExEx to append more; blocks land every few
+// hundred milliseconds at best, so this adds little latency
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// `Sync(reserve0, reserve1)` of Uniswap V2 and its forks.
+static SYNC: Lazy<B256> = Lazy::new(|| keccak256("Sync(uint112,uint112)"));
+/// `Sync(reserve0, reserve1)` of Aerodrome, Equalizer and SyncSwap pools.
+static SYNC_UINT256: Lazy<B256> = Lazy::new(|| keccak256("Sync(uint256,uint256)"));
+
+#[derive(Debug, Deserialize)]
+pub struct Notification {
+    #[serde(default)]
+    pub reverted: Vec<u64>,
+    #[serde(default)]
+    pub committed: Vec<Block>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Block {
+    pub number: u64,
+    #[serde(default)]
+    pub logs: Vec<Log>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Log {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+}
+
+impl Log {
+    /// `(reserve0, reserve1)` the emitting pool now holds, if this is its
+    /// `Sync`. Every venue but Trader Joe LB logs one on each change.
+    pub fn synced_reserves(&self) -> Option<(U256, U256)> {
+        let topic = self.topics.first()?;
+        if *topic != *SYNC && *topic != *SYNC_UINT256 {
+            return None;
+        }
+        preset::reserves_of(&self.data).ok()
+    }
+}
+
+/// Follows the notification file from where it ended when opened, like
+/// `tail -f`, starting over from the top when the ExEx truncates or
+/// rotates it.
+pub struct Feed {
+    path: PathBuf,
+    reader: BufReader<File>,
+    position: u64,
+    line: String,
+}
+
+impl Feed {
+    pub async fn open(path: &Path) -> Result<Self> {
+        let mut file = File::open(path)
+            .await
+            .with_context(|| format!("Failed to open ExEx notifications {}", path.display()))?;
+        let position = file.seek(SeekFrom::End(0)).await?;
+        Ok(Feed { path: path.to_path_buf(), reader: BufReader::new(file), position, line: String::new() })
+    }
+
+    /// The next notification, waiting for the ExEx to write it.
+    pub async fn next(&mut self) -> Result<Notification> {
+        loop {
+            let read = self.reader.read_line(&mut self.line).await?;
+            self.position += read as u64;
+            // A line without its newline is still being written
+            if self.line.ends_with('\n') {
+                let line = std::mem::take(&mut self.line);
+                if line.trim().is_empty() {
+                    continue;
+                }
+                return serde_json::from_str(&line)
+                    .with_context(|| format!("Invalid ExEx notification {:?}", line.trim()));
+            }
+            if read == 0 {
+                if tokio::fs::metadata(&self.path).await?.len() < self.position {
+                    self.reader = BufReader::new(File::open(&self.path).await?);
+                    self.position = 0;
+                    self.line.clear();
+                    continue;
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
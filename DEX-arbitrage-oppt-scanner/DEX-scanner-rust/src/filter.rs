@@ -0,0 +1,112 @@
+use anyhow::{anyhow, bail, Context, Result};
+use rhai::{Dynamic, Engine, Scope, AST};
+
+// Keeps a runaway script from holding up the alert it's judging
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// What a filter script decided about one alert.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Send,
+    Suppress,
+    /// Send, with the script's note added to the alert.
+    Annotate(String),
+}
+
+/// Everything a filter script can look at, bound as variables of the same
+/// name. Values that aren't known are `()`, which compares false against
+/// anything, so e.g. `min_tvl < 100000` doesn't hold for a pool without TVL.
+pub struct AlertFacts<'a> {
+    pub chain: &'a str,
+    pub strategy: &'a str,
+    pub pair: String,
+    pub base: &'a str,
+    pub quote: &'a str,
+    pub buy_venue: &'a str,
+    pub sell_venue: &'a str,
+    pub spread: f64,
+    /// In quote units, like `profit` and `net_profit`.
+    pub size: f64,
+    pub profit: f64,
+    pub net_profit: Option<f64>,
+    pub profit_usd: Option<f64>,
+    /// TVL of the shallower pool, in USD.
+    pub min_tvl: Option<f64>,
+    /// 24h volume of the quieter pool, in USD.
+    pub min_volume: Option<f64>,
+    /// Age of the younger pool.
+    pub age_days: Option<i64>,
+    /// Safety grades, `"A"` to `"F"`.
+    pub base_grade: Option<String>,
+    pub quote_grade: Option<String>,
+}
+
+fn optional<T: Into<Dynamic>>(value: Option<T>) -> Dynamic {
+    value.map_or(Dynamic::UNIT, Into::into)
+}
+
+/// A Rhai script run against every opportunity before it's alerted on. It
+/// evaluates to `true` to send the alert, `false` to suppress it, or a
+/// string to send it with that string as a note, e.g.
+/// `!(min_tvl < 100000 || age_days < 7)`.
+pub struct AlertFilter {
+    engine: Engine,
+    ast: AST,
+}
+
+impl AlertFilter {
+    pub fn compile(script: &str) -> Result<Self> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine.compile(script).map_err(|e| anyhow!("{}", e))?;
+        Ok(AlertFilter { engine, ast })
+    }
+
+    /// The script in `ALERT_FILTER`, or in the file `ALERT_FILTER_FILE`
+    /// names, if either is set.
+    pub fn from_env() -> Result<Option<Self>> {
+        let inline = std::env::var("ALERT_FILTER").ok().filter(|s| !s.is_empty());
+        let file = std::env::var("ALERT_FILTER_FILE").ok().filter(|s| !s.is_empty());
+        let script = match (inline, file) {
+            (Some(_), Some(_)) => bail!("Set ALERT_FILTER or ALERT_FILTER_FILE, not both"),
+            (Some(script), None) => script,
+            (None, Some(path)) => std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read ALERT_FILTER_FILE {}", path))?,
+            (None, None) => return Ok(None),
+        };
+        Self::compile(&script).map(Some)
+    }
+
+    pub fn judge(&self, facts: &AlertFacts) -> Result<Verdict> {
+        let mut scope = Scope::new();
+        scope.push_constant("chain", facts.chain.to_string());
+        scope.push_constant("strategy", facts.strategy.to_string());
+        scope.push_constant("pair", facts.pair.clone());
+        scope.push_constant("base", facts.base.to_string());
+        scope.push_constant("quote", facts.quote.to_string());
+        scope.push_constant("buy_venue", facts.buy_venue.to_string());
+        scope.push_constant("sell_venue", facts.sell_venue.to_string());
+        scope.push_constant("spread", facts.spread);
+        scope.push_constant("size", facts.size);
+        scope.push_constant("profit", facts.profit);
+        scope.push_constant_dynamic("net_profit", optional(facts.net_profit));
+        scope.push_constant_dynamic("profit_usd", optional(facts.profit_usd));
+        scope.push_constant_dynamic("min_tvl", optional(facts.min_tvl));
+        scope.push_constant_dynamic("min_volume", optional(facts.min_volume));
+        scope.push_constant_dynamic("age_days", optional(facts.age_days));
+        scope.push_constant_dynamic("base_grade", optional(facts.base_grade.clone()));
+        scope.push_constant_dynamic("quote_grade", optional(facts.quote_grade.clone()));
+
+        let result = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast)
+            .map_err(|e| anyhow!("{}", e))?;
+        if let Some(send) = result.clone().try_cast::<bool>() {
+            return Ok(if send { Verdict::Send } else { Verdict::Suppress });
+        }
+        if result.is_string() {
+            return Ok(Verdict::Annotate(result.to_string()));
+        }
+        bail!("filter returned {} instead of a bool or string", result.type_name())
+    }
+}
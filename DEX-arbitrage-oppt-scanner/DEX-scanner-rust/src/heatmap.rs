@@ -0,0 +1,142 @@
+use anyhow::{anyhow, Result};
+use plotters::coord::ranged1d::SegmentValue;
+use plotters::prelude::*;
+use plotters::style::{register_font, FontStyle};
+use std::collections::HashMap;
+use std::sync::Once;
+
+use crate::storage::OpportunityRecord;
+use crate::utils::format::{format_percent, local_hour, timezone_label};
+
+// Pairs shown, most active first; the rest would be unreadably thin rows
+const MAX_PAIRS: usize = 20;
+const WIDTH: u32 = 1200;
+const ROW_HEIGHT: u32 = 32;
+// Caption and hour axis
+const CHROME_HEIGHT: u32 = 110;
+const FONT: &str = "sans-serif";
+
+// Bundled so rendering doesn't depend on the host's fonts
+static FONT_DATA: &[u8] = include_bytes!("./assets/DejaVuSans.ttf");
+static REGISTER_FONT: Once = Once::new();
+
+/// Average spread per local hour of the day, one row per pair.
+struct Grid {
+    pairs: Vec<String>,
+    cells: Vec<[Option<f64>; 24]>,
+    max: f64,
+}
+
+fn grid(records: &[OpportunityRecord]) -> Grid {
+    let mut sums: HashMap<&str, [(f64, u32); 24]> = HashMap::new();
+    for record in records {
+        let hour = local_hour(record.timestamp) as usize;
+        let cell = &mut sums.entry(record.pair.as_str()).or_insert([(0.0, 0); 24])[hour];
+        cell.0 += record.spread;
+        cell.1 += 1;
+    }
+
+    let mut rows = sums.into_iter().collect::<Vec<_>>();
+    rows.sort_by_key(|(pair, cells)| (std::cmp::Reverse(cells.iter().map(|c| c.1).sum::<u32>()), *pair));
+    rows.truncate(MAX_PAIRS);
+
+    let cells = rows
+        .iter()
+        .map(|(_, cells)| cells.map(|(sum, count)| (count > 0).then(|| sum / count as f64)))
+        .collect::<Vec<_>>();
+    let max = cells.iter().flatten().flatten().fold(0.0, |a: f64, b| a.max(*b));
+    Grid {
+        pairs: rows.into_iter().map(|(pair, _)| pair.to_string()).collect(),
+        cells,
+        max,
+    }
+}
+
+/// White for no spread to dark red for the widest.
+fn shade(spread: f64, max: f64) -> RGBColor {
+    let t = if max > 0.0 { (spread / max).clamp(0.0, 1.0) } else { 0.0 };
+    let fade = |full: f64, dark: f64| (full + (dark - full) * t) as u8;
+    RGBColor(fade(255.0, 165.0), fade(245.0, 15.0), fade(220.0, 21.0))
+}
+
+/// PNG heatmap of average opportunity spread by pair and hour of the day,
+/// in the configured timezone, or `None` without opportunities.
+pub fn render(records: &[OpportunityRecord]) -> Result<Option<Vec<u8>>> {
+    if records.is_empty() {
+        return Ok(None);
+    }
+    REGISTER_FONT.call_once(|| {
+        // The font is bundled, so it can only fail if the file is corrupt
+        let _ = register_font(FONT, FontStyle::Normal, FONT_DATA);
+    });
+
+    let grid = grid(records);
+    let rows = grid.pairs.len() as u32;
+    let path = std::env::temp_dir().join(format!("spread-heatmap-{}.png", std::process::id()));
+    {
+        let root = BitMapBackend::new(&path, (WIDTH, CHROME_HEIGHT + rows * ROW_HEIGHT)).into_drawing_area();
+        root.fill(&WHITE).map_err(|e| anyhow!("{}", e))?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption(
+                format!(
+                    "Average spread by hour ({}), darkest {}",
+                    timezone_label(),
+                    format_percent(grid.max)
+                ),
+                (FONT, 22),
+            )
+            .margin(12)
+            .x_label_area_size(30)
+            .y_label_area_size(160)
+            // Segmented ranges include their end as a segment
+            .build_cartesian_2d((0u32..23).into_segmented(), (0u32..rows - 1).into_segmented())
+            .map_err(|e| anyhow!("{}", e))?;
+
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .x_labels(24)
+            .y_labels(rows as usize)
+            .x_label_formatter(&|value| match value {
+                SegmentValue::CenterOf(hour) => format!("{:02}", hour),
+                _ => String::new(),
+            })
+            .y_label_formatter(&|value| match value {
+                // Most active pair on top
+                SegmentValue::CenterOf(row) => rows
+                    .checked_sub(row + 1)
+                    .and_then(|index| grid.pairs.get(index as usize))
+                    .cloned()
+                    .unwrap_or_default(),
+                _ => String::new(),
+            })
+            .label_style((FONT, 14))
+            .draw()
+            .map_err(|e| anyhow!("{}", e))?;
+
+        chart
+            .draw_series(grid.cells.iter().enumerate().flat_map(|(index, cells)| {
+                let row = rows - 1 - index as u32;
+                cells.iter().enumerate().map(move |(hour, spread)| {
+                    let hour = hour as u32;
+                    let color = match spread {
+                        Some(spread) => shade(*spread, grid.max),
+                        None => RGBColor(242, 242, 242),
+                    };
+                    Rectangle::new(
+                        [
+                            (SegmentValue::Exact(hour), SegmentValue::Exact(row)),
+                            (SegmentValue::Exact(hour + 1), SegmentValue::Exact(row + 1)),
+                        ],
+                        color.filled(),
+                    )
+                })
+            }))
+            .map_err(|e| anyhow!("{}", e))?;
+        root.present().map_err(|e| anyhow!("{}", e))?;
+    }
+
+    let png = std::fs::read(&path)?;
+    let _ = std::fs::remove_file(&path);
+    Ok(Some(png))
+}
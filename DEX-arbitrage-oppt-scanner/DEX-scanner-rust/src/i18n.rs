@@ -0,0 +1,365 @@
+pub use scanner_core::i18n::{fill, Lang};
+
+/// Alert strings for one language. `{name}` placeholders are filled with [`fill`].
+pub struct Catalog {
+    pub opportunity_title: &'static str,
+    pub pair: &'static str,
+    /// `{venue}`
+    pub price: &'static str,
+    pub profit_margin: &'static str,
+    /// `{base}`, `{quote}`, `{buy_venue}`, `{buy_price}`, `{sell_venue}`, `{sell_price}`
+    pub direction: &'static str,
+    /// `{venue}`, `{token}`: a leg routed through another token
+    pub via: &'static str,
+    pub size: &'static str,
+    pub expected_profit: &'static str,
+    pub net_profit: &'static str,
+    pub breakdown: &'static str,
+    pub gross_spread: &'static str,
+    pub dex_fees: &'static str,
+    pub price_impact: &'static str,
+    pub gas: &'static str,
+    pub flash_loan_fee: &'static str,
+    pub break_even: &'static str,
+    pub safety: &'static str,
+    pub gas_unknown: &'static str,
+    pub gas_exceeds_profit: &'static str,
+    pub simulation: &'static str,
+    pub btc_reference: &'static str,
+    pub correlated_pairs: &'static str,
+    pub twap_spread: &'static str,
+    pub spot: &'static str,
+    pub regime: &'static str,
+    pub regime_calm: &'static str,
+    pub regime_normal: &'static str,
+    pub regime_volatile: &'static str,
+    pub volume: &'static str,
+    pub trigger: &'static str,
+    /// `{amount}`, `{token}`, `{venue}`, `{move}`
+    pub sold_into: &'static str,
+    pub whale_swap: &'static str,
+    /// `{amount_in}`, `{token_in}`, `{amount_out}`, `{token_out}`, `{venue}`, `{usd}`
+    pub whale_swapped: &'static str,
+    pub low_balance: &'static str,
+    /// `{balance}`, `{floor}`, `{symbol}`
+    pub low_balance_below: &'static str,
+    /// `{token}`, `{price}`, `{reference}`, `{deviation}`
+    pub btc_off_peg: &'static str,
+    pub assets: &'static str,
+    /// `{token}`, `{asset}`
+    pub native_asset: &'static str,
+    /// `{token}`, `{asset}`, `{from}`, `{via}`
+    pub bridged_asset: &'static str,
+    pub lp_rebalance: &'static str,
+    pub lp_position: &'static str,
+    pub lp_owner: &'static str,
+    pub lp_holding: &'static str,
+    /// `{share}`, `{value}`: a V2 position
+    pub lp_share: &'static str,
+    /// `{amounts}`, `{lower}`, `{upper}`, `{value}`: a V3 range position
+    pub lp_range: &'static str,
+    pub lp_out_of_range: &'static str,
+    pub lp_fees: &'static str,
+    /// `{price}`
+    pub lp_loss: &'static str,
+    pub lp_your_pool: &'static str,
+    /// `{profit}`, `{size}`
+    pub lp_nets: &'static str,
+    /// Label of the button that claims an opportunity.
+    pub ack: &'static str,
+    /// `{name}`
+    pub acked_by: &'static str,
+}
+
+static EN: Catalog = Catalog {
+    opportunity_title: "Arbitrage Opportunity!",
+    pair: "Pair",
+    price: "{venue} Price",
+    profit_margin: "Profit Margin",
+    direction: "Buy {base} on {buy_venue} at {buy_price} {quote}, sell on {sell_venue} at {sell_price} {quote}",
+    via: "{venue} via {token}",
+    size: "Size",
+    expected_profit: "Expected Profit",
+    net_profit: "Net Profit",
+    breakdown: "Profit Breakdown",
+    gross_spread: "Gross Spread",
+    dex_fees: "DEX Fees",
+    price_impact: "Price Impact",
+    gas: "Gas (incl. priority fee)",
+    flash_loan_fee: "Flash-loan Fee",
+    break_even: "Break-even Size",
+    safety: "Safety",
+    gas_unknown: "n/a (gas price unknown)",
+    gas_exceeds_profit: "not reachable, gas exceeds max profit",
+    simulation: "Simulation",
+    btc_reference: "BTC Reference",
+    correlated_pairs: "Correlated Pairs",
+    twap_spread: "5m TWAP Spread",
+    spot: "spot",
+    regime: "Regime",
+    regime_calm: "calm",
+    regime_normal: "normal",
+    regime_volatile: "volatile",
+    volume: "volume",
+    trigger: "Trigger",
+    sold_into: "{amount} {token} sold into {venue}, price {move}",
+    whale_swap: "Whale Swap",
+    whale_swapped: "{amount_in} {token_in} swapped for {amount_out} {token_out} on {venue} ({usd})",
+    low_balance: "Low Gas Wallet Balance",
+    low_balance_below: "{balance} {symbol} left, below the {floor} {symbol} floor",
+    btc_off_peg: "{token} off peg: {price} vs BTC at {reference} ({deviation})",
+    assets: "Assets",
+    native_asset: "{token} is native {asset}",
+    bridged_asset: "{token} is {asset} bridged from {from} via {via}",
+    lp_rebalance: "LP Rebalance",
+    lp_position: "Position",
+    lp_owner: "Owner",
+    lp_holding: "Holding",
+    lp_share: "{share} of the pool, {value}",
+    lp_range: "{amounts} in {lower}–{upper}, {value}",
+    lp_out_of_range: "out of range",
+    lp_fees: "Uncollected Fees",
+    lp_loss: "IL since entry at {price}",
+    lp_your_pool: "Your Pool",
+    lp_nets: "Arbitrage against your pool nets {profit} after gas at {size}. Taking it yourself moves your pool back in line instead of leaving the gap to searchers.",
+    ack: "Ack",
+    acked_by: "Acked by {name}",
+};
+
+static ES: Catalog = Catalog {
+    opportunity_title: "¡Oportunidad de arbitraje!",
+    pair: "Par",
+    price: "Precio en {venue}",
+    profit_margin: "Margen de beneficio",
+    direction: "Compra {base} en {buy_venue} a {buy_price} {quote}, vende en {sell_venue} a {sell_price} {quote}",
+    via: "{venue} vía {token}",
+    size: "Tamaño",
+    expected_profit: "Beneficio esperado",
+    net_profit: "Beneficio neto",
+    breakdown: "Desglose del beneficio",
+    gross_spread: "Spread bruto",
+    dex_fees: "Comisiones DEX",
+    price_impact: "Impacto en el precio",
+    gas: "Gas (incl. comisión de prioridad)",
+    flash_loan_fee: "Comisión del préstamo flash",
+    break_even: "Tamaño de equilibrio",
+    safety: "Seguridad",
+    gas_unknown: "n/d (precio del gas desconocido)",
+    gas_exceeds_profit: "inalcanzable, el gas supera el beneficio máximo",
+    simulation: "Simulación",
+    btc_reference: "Referencia BTC",
+    correlated_pairs: "Pares correlacionados",
+    twap_spread: "Diferencial TWAP 5m",
+    spot: "spot",
+    regime: "Régimen",
+    regime_calm: "tranquilo",
+    regime_normal: "normal",
+    regime_volatile: "volátil",
+    volume: "volumen",
+    trigger: "Origen",
+    sold_into: "{amount} {token} vendidos en {venue}, precio {move}",
+    whale_swap: "Swap de ballena",
+    whale_swapped: "{amount_in} {token_in} cambiados por {amount_out} {token_out} en {venue} ({usd})",
+    low_balance: "Saldo bajo en la billetera de gas",
+    low_balance_below: "Quedan {balance} {symbol}, por debajo del mínimo de {floor} {symbol}",
+    btc_off_peg: "{token} fuera de paridad: {price} frente a BTC a {reference} ({deviation})",
+    assets: "Activos",
+    native_asset: "{token} es {asset} nativo",
+    bridged_asset: "{token} es {asset} puenteado desde {from} vía {via}",
+    lp_rebalance: "Rebalanceo de LP",
+    lp_position: "Posición",
+    lp_owner: "Propietario",
+    lp_holding: "Tenencia",
+    lp_share: "{share} del pool, {value}",
+    lp_range: "{amounts} en {lower}–{upper}, {value}",
+    lp_out_of_range: "fuera de rango",
+    lp_fees: "Comisiones sin cobrar",
+    lp_loss: "IL desde la entrada a {price}",
+    lp_your_pool: "Tu pool",
+    lp_nets: "El arbitraje contra tu pool deja {profit} netos tras el gas con {size}. Si lo tomas tú, tu pool vuelve a su precio en vez de dejar la diferencia a los searchers.",
+    ack: "Lo tomo",
+    acked_by: "Tomado por {name}",
+};
+
+static DE: Catalog = Catalog {
+    opportunity_title: "Arbitrage-Gelegenheit!",
+    pair: "Paar",
+    price: "Preis auf {venue}",
+    profit_margin: "Gewinnmarge",
+    direction: "Kaufe {base} auf {buy_venue} zu {buy_price} {quote}, verkaufe auf {sell_venue} zu {sell_price} {quote}",
+    via: "{venue} über {token}",
+    size: "Größe",
+    expected_profit: "Erwarteter Gewinn",
+    net_profit: "Nettogewinn",
+    breakdown: "Gewinnaufschlüsselung",
+    gross_spread: "Brutto-Spread",
+    dex_fees: "DEX-Gebühren",
+    price_impact: "Preisauswirkung",
+    gas: "Gas (inkl. Prioritätsgebühr)",
+    flash_loan_fee: "Flash-Loan-Gebühr",
+    break_even: "Break-even-Größe",
+    safety: "Sicherheit",
+    gas_unknown: "k. A. (Gaspreis unbekannt)",
+    gas_exceeds_profit: "nicht erreichbar, Gas übersteigt den maximalen Gewinn",
+    simulation: "Simulation",
+    btc_reference: "BTC-Referenz",
+    correlated_pairs: "Korrelierte Paare",
+    twap_spread: "TWAP-Spread 5 Min.",
+    spot: "Spot",
+    regime: "Marktphase",
+    regime_calm: "ruhig",
+    regime_normal: "normal",
+    regime_volatile: "volatil",
+    volume: "Volumen",
+    trigger: "Auslöser",
+    sold_into: "{amount} {token} auf {venue} verkauft, Preis {move}",
+    whale_swap: "Wal-Swap",
+    whale_swapped: "{amount_in} {token_in} gegen {amount_out} {token_out} auf {venue} getauscht ({usd})",
+    low_balance: "Niedriges Gas-Wallet-Guthaben",
+    low_balance_below: "Noch {balance} {symbol}, unter der Untergrenze von {floor} {symbol}",
+    btc_off_peg: "{token} ohne Kopplung: {price} gegenüber BTC bei {reference} ({deviation})",
+    assets: "Assets",
+    native_asset: "{token} ist natives {asset}",
+    bridged_asset: "{token} ist {asset}, gebrückt von {from} über {via}",
+    lp_rebalance: "LP-Rebalancing",
+    lp_position: "Position",
+    lp_owner: "Inhaber",
+    lp_holding: "Bestand",
+    lp_share: "{share} des Pools, {value}",
+    lp_range: "{amounts} in {lower}–{upper}, {value}",
+    lp_out_of_range: "außerhalb der Range",
+    lp_fees: "Nicht eingezogene Gebühren",
+    lp_loss: "IL seit Einstieg bei {price}",
+    lp_your_pool: "Ihr Pool",
+    lp_nets: "Arbitrage gegen Ihren Pool bringt netto {profit} nach Gas bei {size}. Führen Sie sie selbst aus, kommt Ihr Pool wieder ins Lot, statt die Lücke Searchern zu überlassen.",
+    ack: "Übernehmen",
+    acked_by: "Übernommen von {name}",
+};
+
+static FR: Catalog = Catalog {
+    opportunity_title: "Opportunité d'arbitrage !",
+    pair: "Paire",
+    price: "Prix sur {venue}",
+    profit_margin: "Marge de profit",
+    direction: "Acheter {base} sur {buy_venue} à {buy_price} {quote}, vendre sur {sell_venue} à {sell_price} {quote}",
+    via: "{venue} via {token}",
+    size: "Taille",
+    expected_profit: "Profit attendu",
+    net_profit: "Profit net",
+    breakdown: "Détail du profit",
+    gross_spread: "Spread brut",
+    dex_fees: "Frais DEX",
+    price_impact: "Impact sur le prix",
+    gas: "Gas (frais de priorité inclus)",
+    flash_loan_fee: "Frais de flash loan",
+    break_even: "Taille de rentabilité",
+    safety: "Sécurité",
+    gas_unknown: "n/d (prix du gas inconnu)",
+    gas_exceeds_profit: "inatteignable, le gas dépasse le profit maximal",
+    simulation: "Simulation",
+    btc_reference: "Référence BTC",
+    correlated_pairs: "Paires corrélées",
+    twap_spread: "Écart TWAP 5 min",
+    spot: "spot",
+    regime: "Régime",
+    regime_calm: "calme",
+    regime_normal: "normal",
+    regime_volatile: "volatil",
+    volume: "volume",
+    trigger: "Déclencheur",
+    sold_into: "{amount} {token} vendus sur {venue}, prix {move}",
+    whale_swap: "Swap de baleine",
+    whale_swapped: "{amount_in} {token_in} échangés contre {amount_out} {token_out} sur {venue} ({usd})",
+    low_balance: "Solde faible du portefeuille de gas",
+    low_balance_below: "Il reste {balance} {symbol}, sous le plancher de {floor} {symbol}",
+    btc_off_peg: "{token} hors parité : {price} contre BTC à {reference} ({deviation})",
+    assets: "Actifs",
+    native_asset: "{token} est du {asset} natif",
+    bridged_asset: "{token} est du {asset} ponté depuis {from} via {via}",
+    lp_rebalance: "Rééquilibrage LP",
+    lp_position: "Position",
+    lp_owner: "Propriétaire",
+    lp_holding: "Détention",
+    lp_share: "{share} du pool, {value}",
+    lp_range: "{amounts} dans {lower}–{upper}, {value}",
+    lp_out_of_range: "hors de la plage",
+    lp_fees: "Frais non collectés",
+    lp_loss: "IL depuis l'entrée à {price}",
+    lp_your_pool: "Votre pool",
+    lp_nets: "L'arbitrage contre votre pool rapporte {profit} net après gas pour {size}. En le prenant vous-même, vous réalignez votre pool au lieu de laisser l'écart aux searchers.",
+    ack: "Je prends",
+    acked_by: "Pris par {name}",
+};
+
+static PT: Catalog = Catalog {
+    opportunity_title: "Oportunidade de arbitragem!",
+    pair: "Par",
+    price: "Preço na {venue}",
+    profit_margin: "Margem de lucro",
+    direction: "Compre {base} na {buy_venue} a {buy_price} {quote}, venda na {sell_venue} a {sell_price} {quote}",
+    via: "{venue} via {token}",
+    size: "Tamanho",
+    expected_profit: "Lucro esperado",
+    net_profit: "Lucro líquido",
+    breakdown: "Detalhamento do lucro",
+    gross_spread: "Spread bruto",
+    dex_fees: "Taxas DEX",
+    price_impact: "Impacto no preço",
+    gas: "Gas (incl. taxa de prioridade)",
+    flash_loan_fee: "Taxa do flash loan",
+    break_even: "Tamanho de equilíbrio",
+    safety: "Segurança",
+    gas_unknown: "n/d (preço do gas desconhecido)",
+    gas_exceeds_profit: "inalcançável, o gas excede o lucro máximo",
+    simulation: "Simulação",
+    btc_reference: "Referência BTC",
+    correlated_pairs: "Pares correlacionados",
+    twap_spread: "Spread TWAP 5m",
+    spot: "spot",
+    regime: "Regime",
+    regime_calm: "calmo",
+    regime_normal: "normal",
+    regime_volatile: "volátil",
+    volume: "volume",
+    trigger: "Gatilho",
+    sold_into: "{amount} {token} vendidos na {venue}, preço {move}",
+    whale_swap: "Swap de baleia",
+    whale_swapped: "{amount_in} {token_in} trocados por {amount_out} {token_out} na {venue} ({usd})",
+    low_balance: "Saldo baixo na carteira de gas",
+    low_balance_below: "Restam {balance} {symbol}, abaixo do mínimo de {floor} {symbol}",
+    btc_off_peg: "{token} fora da paridade: {price} vs BTC a {reference} ({deviation})",
+    assets: "Ativos",
+    native_asset: "{token} é {asset} nativo",
+    bridged_asset: "{token} é {asset} em ponte de {from} via {via}",
+    lp_rebalance: "Rebalanceamento de LP",
+    lp_position: "Posição",
+    lp_owner: "Dono",
+    lp_holding: "Participação",
+    lp_share: "{share} do pool, {value}",
+    lp_range: "{amounts} em {lower}–{upper}, {value}",
+    lp_out_of_range: "fora da faixa",
+    lp_fees: "Taxas não coletadas",
+    lp_loss: "IL desde a entrada a {price}",
+    lp_your_pool: "Seu pool",
+    lp_nets: "A arbitragem contra seu pool rende {profit} líquidos após o gas com {size}. Fazendo-a você mesmo, seu pool volta ao preço em vez de deixar a diferença para os searchers.",
+    ack: "Assumir",
+    acked_by: "Assumido por {name}",
+};
+
+/// Each language's [`Catalog`], as [`Lang`] itself is shared with the
+/// Solana scanner.
+pub trait Localize {
+    fn catalog(self) -> &'static Catalog;
+}
+
+impl Localize for Lang {
+    fn catalog(self) -> &'static Catalog {
+        match self {
+            Lang::En => &EN,
+            Lang::Es => &ES,
+            Lang::De => &DE,
+            Lang::Fr => &FR,
+            Lang::Pt => &PT,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! The DEX scanner's detection engine as a library: presets, pool reading,
+//! strategies and the models they share, plus [`Scanner`] to run them
+//! from another program. The `dex_scanner` binary adds alerting, storage
+//! and operations on top.
+
+use alloy::network::AnyNetwork;
+use alloy::providers::{DynProvider, Provider, ProviderBuilder};
+use alloy::sol;
+use anyhow::{Context, Result};
+
+pub mod enrich;
+pub mod i18n;
+pub mod models;
+pub mod preset;
+pub mod scanner;
+pub mod strategy;
+pub mod token_list;
+pub mod univ3;
+pub mod utils;
+
+pub use scanner::{Scanner, ScannerBuilder};
+
+sol!(
+    #[sol(rpc)]
+    IUniswapV2Factory,
+    "src/abis/IUniswapV2Factory.json"
+);
+
+sol!(
+    #[sol(rpc)]
+    IUniswapV2Pair,
+    "src/abis/IUniswapV2Pair.json"
+);
+
+/// Connection to the chain's node. The transport is erased, so HTTP,
+/// WebSocket and IPC endpoints all give this one type, and so is the
+/// network's transaction format, so blocks from L2s with their own
+/// transaction types (deposits, system transactions) still decode.
+pub type RpcProvider = DynProvider<AnyNetwork>;
+
+/// Connects to the node at `url`: over HTTP for `http://` and `https://`
+/// URLs, WebSocket for `ws://` and `wss://`, and otherwise IPC, taking
+/// `url` as the path of the node's socket (`ipc://` or `file://` prefixed
+/// or not), which must exist.
+pub async fn connect(url: &str) -> Result<RpcProvider> {
+    let provider = ProviderBuilder::new()
+        .disable_recommended_fillers()
+        .network::<AnyNetwork>()
+        .connect(url)
+        .await
+        .context("Failed to connect to the RPC endpoint")?;
+    Ok(provider.erased())
+}
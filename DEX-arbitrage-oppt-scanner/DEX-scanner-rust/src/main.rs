@@ -1,165 +1,345 @@
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
 use anyhow::{Result, Context};
+use clap::{Parser, Subcommand};
 use colored::*;
-use ethers::{
-    contract::Contract,
-    providers::{Provider, Ws, Http},
-    types::{Address, U256, H160, BlockNumber},
-    prelude::*,
-    abi::Abi,
-};
-use std::sync::Arc;
-use teloxide::{prelude::*, types::ParseMode};
-use tokio;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use teloxide::{prelude::*, types::{InputFile, ParseMode}};
 use dotenv::dotenv;
 use std::env;
-use futures::StreamExt;
-use chrono::Local;
-use once_cell::sync::Lazy;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+use uuid::Uuid;
+
+mod ack;
+mod api;
+mod bot;
+mod check;
+mod config;
+mod decode;
+mod exex;
+mod filter;
+mod heatmap;
+mod paper;
+mod pool_index;
+mod report;
+mod research;
+mod role;
+mod shard;
+mod soak;
+mod storage;
+mod subscription;
+mod trigger;
+mod watcher;
+
+use dex_scanner::scanner::{self, MonitoredPair};
+use dex_scanner::{enrich, i18n, models, preset, strategy, token_list, univ3, utils, IUniswapV2Pair, RpcProvider};
+
+use ack::AckBoard;
+use api::ApiServer;
+use enrich::btc::{self, BtcOracle, BtcReference};
+use enrich::regime::{self, Reading, Regime, RegimeDetector};
+use enrich::tenderly::{SimulationLinks, Tenderly};
+use enrich::twap::PriceHistory;
+use enrich::{fees::FeeCache, safety::SafetyCache, MetadataCache, PoolMetadata};
+use filter::{AlertFacts, AlertFilter, Verdict};
+use i18n::{Lang, Localize};
+use models::asset::Issuance;
+use models::opportunity::{Opportunity, PoolState};
+use models::snapshot::{MarketSnapshot, PairQuote};
+use models::token::{self, weth, TokenInfo};
+use paper::PaperTrader;
+use pool_index::PoolIndex;
+use preset::{Preset, VenueConfig};
+use role::Roles;
+use shard::Shard;
+use storage::leader::Leader;
+use storage::shared::{QueuedAlert, SharedState};
+use storage::webhook::WebhookSink;
+use storage::{OpportunityRecord, Storage};
+use strategy::Strategy;
+use subscription::Subscribers;
+use token_list::ListSource;
+use trigger::Trigger;
+use utils::format::{self as fmt, format_amount, format_percent, format_usd};
+use utils::gas::GasOracle;
+use utils::{price, secrets};
+use watcher::lp::LpWatcher;
+use watcher::sync::SyncMonitor;
+use watcher::wallet::WalletWatcher;
 
-const UNISWAP_V2_FACTORY: &str = "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f";
-const SUSHISWAP_FACTORY: &str = "0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac";
 const RETRY_DELAY: Duration = Duration::from_secs(5);
-const MIN_PROFIT_MARGIN: f64 = 0.01; // 1%
+// Roughly Base's block time; Ethereum heads are simply seen several polls late
+const BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(2);
+// Blocks of changed pairs waiting for evaluation; beyond this, newer blocks
+// are merged into one pending batch instead
+const EVALUATION_QUEUE: usize = 4;
+// Blocks a pair's evaluation is remembered for
+const EVAL_CACHE_BLOCKS: u64 = 8;
+const SNAPSHOT_PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+// Correlated pairs shown in an alert, and how stale their spreads may be
+const MAX_SIBLINGS: usize = 4;
+const SIBLING_MAX_AGE: chrono::TimeDelta = chrono::TimeDelta::seconds(60);
+// Alerts waiting to be sent; beyond this, new alerts are dropped
+const ALERT_QUEUE: usize = 256;
+// How often the notifier drains alerts queued by other shards
+const ALERT_QUEUE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const METADATA_REFRESH_INTERVAL: Duration = Duration::from_secs(600);
+const SAFETY_REFRESH_INTERVAL: Duration = Duration::from_secs(3600);
+const GAS_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+const BTC_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+// WBTC usually trades within a few tenths of a percent of BTC
+const DEFAULT_BTC_PEG_THRESHOLD: f64 = 0.01;
+// About a hundred swaps' gas on an L2, a handful on mainnet
+const DEFAULT_GAS_WALLET_FLOOR: f64 = 0.05;
+const DEFAULT_NTP_SERVER: &str = "pool.ntp.org";
+const DEFAULT_MAX_CLOCK_SKEW_MS: i64 = 1000;
+const DEFAULT_MAX_BLOCK_LAG: u64 = 3;
+// New pools are only scanned once this old
+const DEFAULT_NEW_POOL_MIN_AGE_SECS: i64 = 3600;
+const FEE_REFRESH_INTERVAL: Duration = Duration::from_secs(3600);
+const TOKEN_LIST_REFRESH_INTERVAL: Duration = Duration::from_secs(6 * 3600);
+const WATCH_DIGEST_INTERVAL: Duration = Duration::from_secs(3600);
+const LP_SHARE_REFRESH_INTERVAL: Duration = Duration::from_secs(600);
+const REPORT_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+// Heartbeats are off unless OPS_HEARTBEAT_MINS is set
+const DEFAULT_OPS_HEARTBEAT_MINS: u64 = 0;
+const DEFAULT_WEBHOOK_MAX_ATTEMPTS: u32 = 10;
 
-static FACTORY_ABI: Lazy<Abi> = Lazy::new(|| {
-    serde_json::from_slice(include_bytes!("./abis/IUniswapV2Factory.json"))
-        .expect("Failed to parse factory ABI")
-});
+/// Scans DEX pools for cross-venue arbitrage and alerts on Telegram.
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    /// Chain preset with tokens, venues and thresholds: ethereum, base, zksync, linea, avalanche or sonic
+    #[arg(long, global = true, default_value = "ethereum", conflicts_with = "strategy")]
+    preset: String,
 
-static PAIR_ABI: Lazy<Abi> = Lazy::new(|| {
-    serde_json::from_slice(include_bytes!("./abis/IUniswapV2Pair.json"))
-        .expect("Failed to parse pair ABI")
-});
+    /// Strategy profile, run on its own chain: eth-majors, stable-depeg, lst-basis, uni-v2-v3 or
+    /// uni-v3-tiers
+    #[arg(long, global = true)]
+    strategy: Option<String>,
 
-struct TokenInfo {
-    address: Address,
-    symbol: &'static str,
+    /// TOML file with extra tokens, venue addresses, profit margins, RPC and Telegram settings, under env vars [default: config.toml if it exists]
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-static TOKENS: Lazy<Vec<TokenInfo>> = Lazy::new(|| {
-    vec![
-        TokenInfo {
-            address: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".parse().unwrap(),
-            symbol: "WETH",
-        },
-        TokenInfo {
-            address: "0x6B175474E89094C44Da98b954EedeAC495271d0F".parse().unwrap(),
-            symbol: "DAI",
-        },
-        TokenInfo {
-            address: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".parse().unwrap(),
-            symbol: "USDC",
-        },
-        TokenInfo {
-            address: "0xdAC17F958D2ee523a2206206994597C13D831ec7".parse().unwrap(),
-            symbol: "USDT",
-        },
-        TokenInfo {
-            address: "0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599".parse().unwrap(),
-            symbol: "WBTC",
-        },
-    ]
-});
-
-#[derive(Debug, EthEvent)]
-struct SwapEvent {
-    #[ethevent(indexed)]
-    token0: H160,
-    #[ethevent(indexed)]
-    token1: H160,
-    amount0_in: U256,
-    amount1_in: U256,
-    amount0_out: U256,
-    amount1_out: U256,
-}
-
-#[derive(Debug)]
+#[derive(Subcommand)]
+enum Command {
+    /// Validate the configuration and the preset's on-chain addresses, then exit
+    CheckConfig,
+    /// Send a sample alert to every configured chat, then exit
+    NotifyTest,
+    /// Push synthetic swap events through evaluation at a fixed rate and
+    /// report whether the pipeline keeps up, without touching a chain
+    SoakTest(soak::SoakArgs),
+    /// Read a pair's historical reserves on both venues from an archive
+    /// node and report the spread over a date range
+    Research(research::ResearchArgs),
+}
+
+/// A Telegram chat that receives alerts, and the language to send them in.
+#[derive(Debug, Clone, Copy)]
+struct Chat {
+    id: i64,
+    lang: Lang,
+}
+
+/// Parses `TELEGRAM_CHAT_ID`: comma-separated chat ids, each optionally
+/// suffixed with `:<lang>`, e.g. `-1001234:es,5678`.
+fn parse_chats(value: &str, default_lang: Lang) -> Result<Vec<Chat>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|entry| {
+            let (id, lang) = match entry.split_once(':') {
+                Some((id, lang)) => (id, lang.parse()?),
+                None => (entry, default_lang),
+            };
+            let id = id
+                .trim()
+                .parse::<i64>()
+                .with_context(|| format!("Invalid chat id {:?}", id))?;
+            Ok(Chat { id, lang })
+        })
+        .collect()
+}
+
+/// Shared state handed to every pair monitor.
+struct ScanContext {
+    /// Identifies this run in logs, alerts, storage and webhooks.
+    run_id: Uuid,
+    provider: RpcProvider,
+    /// Block notifications file a reth ExEx writes, ingested instead of
+    /// polling `provider` when set.
+    node_events: Option<PathBuf>,
+    bot: Bot,
+    chats: Vec<Chat>,
+    /// What each user in `chats` may do through the bot.
+    roles: Roles,
+    /// Whether any chat may subscribe itself to opportunity alerts.
+    multi_tenant: bool,
+    /// Chats subscribed with /subscribe, sent the opportunities their
+    /// filters pass. Unlike `chats`, they don't get the Ack button.
+    subscribers: Subscribers,
+    /// Chats that get operational notices (startup, scan errors, sync
+    /// warnings, heartbeats): `TELEGRAM_OPS_CHAT_ID`, or the alert chats.
+    ops_chats: Vec<Chat>,
+    /// When this run started, for heartbeats.
+    started_at: chrono::DateTime<chrono::Utc>,
+    /// Every scanned pool by address, kept up as pairs join the scan.
+    pools: PoolIndex,
+    metadata: MetadataCache,
+    safety: SafetyCache,
+    fees: FeeCache,
+    gas: GasOracle,
+    btc: BtcOracle,
+    /// Every scanned pool's recent prices, for TWAPs.
+    history: PriceHistory,
+    regime: RegimeDetector,
+    /// Whether strategy thresholds are scaled by the regime, see
+    /// [`Regime::threshold_scale`].
+    adaptive_thresholds: bool,
+    /// Deviation from the BTC reference at which a BTC wrapper counts as off peg.
+    btc_peg_threshold: f64,
+    /// USD size from which single swaps through scanned pools are alerted
+    /// on, `None` to not watch for them.
+    whale_min_usd: Option<f64>,
+    /// Wallets whose native balance is watched, e.g. an executor's.
+    gas_wallets: Vec<Address>,
+    /// Native balance below which a gas wallet is alerted on.
+    gas_wallet_floor: f64,
+    /// Token lists whose tokens on the chain are scanned too.
+    token_lists: Vec<ListSource>,
+    storage: Storage,
+    /// Whether opportunities are queued for the webhook sink.
+    webhook: bool,
+    shared: SharedState,
+    leader: Arc<Leader>,
+    shard: Shard,
+    alert_cooldown: Duration,
+    tenderly: Option<Tenderly>,
+    etherscan_api_key: Option<String>,
+    min_tvl_usd: f64,
+    /// Flash-loan fee rate charged on the buy leg's input, 0 for own capital.
+    flash_loan_fee: f64,
+    /// Minimum USD profit after gas, in place of margins, if set.
+    min_profit_usd: Option<f64>,
+    strategies: Vec<Box<dyn Strategy>>,
+    alert_filter: Option<AlertFilter>,
+    /// How long market snapshots are archived for, `None` to not archive them.
+    snapshot_retention: Option<chrono::Duration>,
+    /// LP positions watched for rebalancing, if any are configured.
+    lp_watcher: Option<LpWatcher>,
+    /// Virtual portfolio every alerted opportunity is traded against, if
+    /// paper trading is on.
+    paper: Option<PaperTrader>,
+    acks: AckBoard,
+    /// Notification stage, see [`deliver_alerts`].
+    alerts: mpsc::Sender<QueuedAlert>,
+    /// Pairs completed by new pools that passed the sniping guard, waiting
+    /// to join the scanned ones at the next block.
+    new_pairs: Mutex<Vec<MonitoredPair>>,
+    /// Latest prices per pair and venues compared, see [`PriceInfo::key`],
+    /// updated every block, and when they were read.
+    latest: RwLock<HashMap<String, (PriceInfo, chrono::DateTime<chrono::Utc>)>>,
+    /// Opportunities and prices for the REST API's `/events` stream.
+    events: broadcast::Sender<api::Event>,
+}
+
+#[derive(Debug, Clone)]
 struct PriceInfo {
-    token_a: Address,
-    token_b: Address,
+    pool_a: Address,
+    pool_b: Address,
     symbol_a: &'static str,
     symbol_b: &'static str,
-    price_uni: U256,
-    price_sushi: U256,
+    a: PoolState,
+    b: PoolState,
     profit_margin: f64,
 }
 
-fn get_token_symbol(address: &Address) -> &'static str {
-    TOKENS.iter()
-        .find(|t| &t.address == address)
-        .map(|t| t.symbol)
-        .unwrap_or("UNKNOWN")
-}
-
-async fn calculate_prices(
-    provider: Arc<Provider<Http>>,
-    token0: H160,
-    token1: H160,
-    uni_factory: &Contract<Provider<Http>>,
-    sushi_factory: &Contract<Provider<Http>>,
-) -> Result<PriceInfo> {
-    let uni_pair = uni_factory
-        .method::<_, Address>("getPair", (token0, token1))?
-        .call()
-        .await?;
-
-    let sushi_pair = sushi_factory
-        .method::<_, Address>("getPair", (token0, token1))?
-        .call()
-        .await?;
-
-    let uni_reserves: (U256, U256, u32) = Contract::new(
-        uni_pair,
-        PAIR_ABI.clone(),
-        Arc::clone(&provider),
-    )
-    .method("getReserves", ())?
-    .call()
-    .await?;
+impl PriceInfo {
+    /// e.g. `WETH/USDC`.
+    fn pair(&self) -> String {
+        format!("{}/{}", self.symbol_a, self.symbol_b)
+    }
 
-    let sushi_reserves: (U256, U256, u32) = Contract::new(
-        sushi_pair,
-        PAIR_ABI.clone(),
-        Arc::clone(&provider),
-    )
-    .method("getReserves", ())?
-    .call()
-    .await?;
+    /// The pair and both venues, with Uniswap V3's fee tier, e.g.
+    /// `WETH/USDC Uniswap V2 ↔ Uniswap V3 0.05%`, as a pair is compared
+    /// across every two of the preset's venues.
+    fn key(&self) -> String {
+        format!("{} {} ↔ {}", self.pair(), self.a.venue_label(), self.b.venue_label())
+    }
+}
 
-    let uni_price = if uni_reserves.0 > U256::zero() {
-        (uni_reserves.1 * U256::exp10(18)) / uni_reserves.0
-    } else {
-        U256::zero()
-    };
+async fn pool_state(
+    provider: &RpcProvider,
+    pool: Address,
+    venue: &VenueConfig,
+    pair: &MonitoredPair,
+    fees: &FeeCache,
+) -> Result<PoolState> {
+    let reserves = venue.read_reserves(provider, pool, pair.token0.address, None).await?;
+    Ok(to_pool_state(reserves, pool, venue, pair, fees))
+}
 
-    let sushi_price = if sushi_reserves.0 > U256::zero() {
-        (sushi_reserves.1 * U256::exp10(18)) / sushi_reserves.0
-    } else {
-        U256::zero()
-    };
+fn to_pool_state(
+    reserves: (U256, U256),
+    pool: Address,
+    venue: &VenueConfig,
+    pair: &MonitoredPair,
+    fees: &FeeCache,
+) -> PoolState {
+    pair.orient(PoolState {
+        venue: venue.venue,
+        pool,
+        reserve_base: price::to_units(reserves.0, pair.token0.decimals),
+        reserve_quote: price::to_units(reserves.1, pair.token1.decimals),
+        fee: fees.fee(&pool, venue.venue),
+    })
+}
 
-    let profit_margin = if uni_price > sushi_price && sushi_price > U256::zero() {
-        uni_price.as_u128() as f64 / sushi_price.as_u128() as f64 - 1.0
-    } else if uni_price > U256::zero() {
-        sushi_price.as_u128() as f64 / uni_price.as_u128() as f64 - 1.0
-    } else {
-        0.0
-    };
+/// Reads both pools of every pair at `block`, see [`scanner::read_reserves`].
+async fn read_prices(
+    ctx: &ScanContext,
+    multicall: Address,
+    pairs: &[MonitoredPair],
+    block: u64,
+) -> Result<Vec<PriceInfo>> {
+    let reserves = scanner::read_reserves(&ctx.provider, multicall, &preset::active().venues, pairs, block).await?;
+    Ok(to_prices(ctx, pairs, &reserves))
+}
 
-    Ok(PriceInfo {
-        token_a: token0,
-        token_b: token1,
-        symbol_a: get_token_symbol(&token0),
-        symbol_b: get_token_symbol(&token1),
-        price_uni: uni_price,
-        price_sushi: sushi_price,
-        profit_margin,
-    })
+/// Prices of `pairs` from both pools' `(reserve0, reserve1)`, in the same order.
+fn to_prices(ctx: &ScanContext, pairs: &[MonitoredPair], reserves: &[[(U256, U256); 2]]) -> Vec<PriceInfo> {
+    let venues = &preset::active().venues;
+    pairs
+        .iter()
+        .zip(reserves)
+        .map(|(pair, &[reserves_a, reserves_b])| {
+            let a = to_pool_state(reserves_a, pair.pool_a, &venues[pair.venues[0]], pair, &ctx.fees);
+            let b = to_pool_state(reserves_b, pair.pool_b, &venues[pair.venues[1]], pair, &ctx.fees);
+            PriceInfo {
+                pool_a: pair.pool_a,
+                pool_b: pair.pool_b,
+                symbol_a: pair.base().symbol,
+                symbol_b: pair.quote().symbol,
+                a,
+                b,
+                profit_margin: price::calculate_profit_margin(a.price(), b.price()),
+            }
+        })
+        .collect()
 }
 
 async fn init_telegram() -> Result<Bot> {
-    let token = env::var("TELEGRAM_BOT_TOKEN").context("TELEGRAM_BOT_TOKEN not set")?;
+    let token = secrets::require("TELEGRAM_BOT_TOKEN").await?;
     let bot = Bot::new(token);
     Ok(bot)
 }
@@ -171,128 +351,1444 @@ async fn send_telegram_alert(bot: &Bot, chat_id: i64, message: String) -> Result
     Ok(())
 }
 
-async fn monitor_pair(
-    pair: Contract<Provider<Http>>,
-    symbol0: &'static str,
-    symbol1: &'static str,
-    provider: Arc<Provider<Http>>,
-    uni_factory: Contract<Provider<Http>>,
-    sushi_factory: Contract<Provider<Http>>,
-    bot: Bot,
-    chat_id: i64,
-) -> Result<()> {
-    let event_filter = pair.event::<SwapEvent>();
-    let mut stream = event_filter
-        .stream()
-        .await
-        .context("Failed to create event stream")?;
+impl ScanContext {
+    /// Whether this process sends alerts. Shards all alert on their own
+    /// pairs, funneled through the notifier; an unsharded replica alerts
+    /// only while it holds the leader lease.
+    fn should_alert(&self) -> bool {
+        self.shard.is_partial() || self.leader.is_leader()
+    }
+
+    /// Sends an untranslated message to every configured chat. Standby
+    /// replicas stay quiet.
+    async fn broadcast(&self, message: &str) {
+        if !self.leader.is_leader() {
+            return;
+        }
+        for chat in &self.chats {
+            if let Err(e) = send_telegram_alert(&self.bot, chat.id, message.to_string()).await {
+                println!("{} Failed to send Telegram alert: {}", "[ERROR]".bright_red(), e);
+            }
+        }
+    }
+
+    /// Sends an untranslated message to every ops chat. Standby replicas
+    /// stay quiet.
+    async fn broadcast_ops(&self, message: &str) {
+        if !self.leader.is_leader() {
+            return;
+        }
+        for chat in &self.ops_chats {
+            if let Err(e) = send_telegram_alert(&self.bot, chat.id, message.to_string()).await {
+                println!("{} Failed to send Telegram alert: {}", "[ERROR]".bright_red(), e);
+            }
+        }
+    }
+
+    /// Sends `png` to every chat as a photo, if this instance is the leader.
+    async fn broadcast_photo(&self, png: Vec<u8>, caption: &str) {
+        if !self.leader.is_leader() {
+            return;
+        }
+        for chat in &self.chats {
+            let photo = InputFile::memory(png.clone()).file_name("spread-heatmap.png");
+            if let Err(e) = self.bot.send_photo(ChatId(chat.id), photo).caption(caption).await {
+                println!("{} Failed to send Telegram photo: {}", "[ERROR]".bright_red(), e);
+            }
+        }
+    }
+
+    /// Hands an alert to the notification stage, or to the notifier through
+    /// Redis when this shard isn't it. If notification is a full queue
+    /// behind, the alert is dropped rather than stalling evaluation.
+    async fn notify(&self, alert: QueuedAlert) {
+        if !self.leader.is_leader() {
+            if let Err(e) = self.shared.queue_alert(&alert).await {
+                println!("{} {}", "[ERROR]".bright_red(), e);
+            }
+            return;
+        }
+        let key = alert.key.clone();
+        if let Err(e) = self.alerts.try_send(alert) {
+            println!("{} Dropping alert on {}: {}", "[ERROR]".bright_red(), key, e);
+        }
+    }
+
+    /// Sends an alert's messages to their chats. Repeat alerts reply to the
+    /// first one, and stop offering the Ack button once someone has claimed it.
+    async fn deliver_alert(&self, alert: QueuedAlert) {
+        let thread = self.acks.open(&alert.key);
+        for (chat_id, mut message) in alert.messages {
+            let chat = self.chats.iter().find(|chat| chat.id == chat_id);
+            let lang = match chat {
+                Some(chat) => chat.lang,
+                None => self.subscribers.get(chat_id).map(|s| s.lang).unwrap_or_default(),
+            };
+            let text = lang.catalog();
+            // Acks are for the team behind the configured chats
+            let team = chat.is_some();
+            if let (true, Some(owner)) = (team, &thread.acked_by) {
+                let acked_by = i18n::fill(text.acked_by, &[("name", &teloxide::utils::html::escape(owner))]);
+                message = format!("✅ <b>{}</b>\n\n{}", acked_by, message);
+            }
+            let mut request = self.bot
+                .send_message(ChatId(chat_id), message)
+                .parse_mode(ParseMode::Html);
+            if let Some(root) = thread.root(chat_id) {
+                request = request.reply_to_message_id(root);
+            }
+            if team && thread.acked_by.is_none() {
+                request = request.reply_markup(ack::button(text.ack, thread.id));
+            }
+            match request.await {
+                Ok(sent) => self.acks.record(&alert.key, chat_id, sent.id),
+                Err(e) => println!("{} Failed to send Telegram alert: {}", "[ERROR]".bright_red(), e),
+            }
+        }
+    }
+}
+
+/// Sends the alerts other shards queue, whenever this instance is the
+/// leader, so every alert leaves through one bot and one Ack board.
+async fn relay_queued_alerts(ctx: Arc<ScanContext>) {
+    loop {
+        tokio::time::sleep(ALERT_QUEUE_POLL_INTERVAL).await;
+        if !ctx.leader.is_leader() {
+            continue;
+        }
+        match ctx.shared.take_alerts().await {
+            Ok(alerts) => {
+                // Waits for room, leaving the rest queued in Redis
+                for alert in alerts {
+                    if ctx.alerts.send(alert).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(e) => println!("{} {}", "[ERROR]".bright_red(), e),
+        }
+    }
+}
+
+/// Notification stage: sends alerts one at a time, so a slow Telegram only
+/// backs up the alert queue.
+async fn deliver_alerts(ctx: Arc<ScanContext>, mut alerts: mpsc::Receiver<QueuedAlert>) {
+    while let Some(alert) = alerts.recv().await {
+        ctx.deliver_alert(alert).await;
+    }
+}
+
+/// What an alert shows about an opportunity besides its prices and sizing.
+struct AlertNotes<'a> {
+    /// See [`Opportunity::id`].
+    id: Uuid,
+    summary_a: &'a str,
+    summary_b: &'a str,
+    safety: &'a str,
+    /// See [`format_btc_reference`].
+    btc: Option<&'a str>,
+    /// See [`correlated_spreads`].
+    correlated: Option<&'a str>,
+    /// See [`twap_spread`].
+    twap_spread: Option<f64>,
+    /// See [`trigger::triggers`].
+    triggers: &'a [Trigger],
+    regime: Option<Reading>,
+    simulation: Option<&'a SimulationLinks>,
+    /// Note added by the alert filter script.
+    filter: Option<&'a str>,
+}
+
+/// Alert line naming which representation of its asset each of `tokens`
+/// is, e.g. USDC.e as USDC bridged from Ethereum, so wrappers aren't taken
+/// for the asset itself. Empty unless one of them is bridged.
+fn format_assets(lang: Lang, tokens: &[&TokenInfo]) -> String {
+    if !tokens.iter().any(|token| token.flavor().is_some_and(|flavor| flavor.is_bridged())) {
+        return String::new();
+    }
+    let text = lang.catalog();
+    let flavors = tokens
+        .iter()
+        .filter_map(|token| {
+            let flavor = token.flavor()?;
+            Some(match flavor.issuance {
+                Issuance::Native => {
+                    i18n::fill(text.native_asset, &[("token", token.symbol), ("asset", flavor.canonical)])
+                }
+                Issuance::Bridged { via, from } => i18n::fill(
+                    text.bridged_asset,
+                    &[("token", token.symbol), ("asset", flavor.canonical), ("from", from), ("via", via)],
+                ),
+            })
+        })
+        .collect::<Vec<_>>();
+    format!("{}: <i>{}</i>\n", text.assets, flavors.join("; "))
+}
+
+fn format_alert(
+    lang: Lang,
+    price_info: &PriceInfo,
+    opportunity: &Opportunity,
+    notes: &AlertNotes,
+) -> String {
+    let text = lang.catalog();
+    let btc = match notes.btc {
+        Some(line) => format!("{}: <code>{}</code>\n", text.btc_reference, line),
+        None => String::new(),
+    };
+    let correlated = match notes.correlated {
+        Some(line) => format!("{}: <code>{}</code>\n", text.correlated_pairs, line),
+        None => String::new(),
+    };
+    // A TWAP spread close to spot means the mispricing has held for
+    // minutes; a much narrower one, that it's a momentary imbalance
+    let twap = match notes.twap_spread {
+        Some(spread) => format!(
+            "{}: <code>{} · {} {}</code>\n",
+            text.twap_spread,
+            format_percent(spread),
+            text.spot,
+            format_percent(price_info.profit_margin)
+        ),
+        None => String::new(),
+    };
+    let triggers = if notes.triggers.is_empty() {
+        String::new()
+    } else {
+        let moves = notes
+            .triggers
+            .iter()
+            .map(|trigger| {
+                i18n::fill(
+                    text.sold_into,
+                    &[
+                        ("amount", &format_amount(trigger.amount)),
+                        ("token", trigger.sold.symbol),
+                        ("venue", trigger.venue.name()),
+                        ("move", &format_signed_percent(trigger.price_move)),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>();
+        format!("{}: <i>{}</i>\n", text.trigger, moves.join(" · "))
+    };
+    let regime = match notes.regime {
+        Some(reading) => format!("{}: <code>{}</code>\n", text.regime, format_regime(lang, &reading)),
+        None => String::new(),
+    };
+    let simulation = match notes.simulation {
+        Some(links) => format!(
+            "{}: <a href=\"{}\">{}</a> → <a href=\"{}\">{}</a>\n",
+            text.simulation,
+            links.buy,
+            opportunity.buy.venue,
+            links.sell,
+            opportunity.sell.venue
+        ),
+        None => String::new(),
+    };
+    let filter = match notes.filter {
+        Some(note) => format!(
+            "📝 {}\n",
+            note.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+        ),
+        None => String::new(),
+    };
+    let quote = opportunity.quote.symbol;
+    let cost = |amount: f64| format!("-{} {}", format_amount(amount), quote);
+    let waterfall = opportunity.waterfall();
+    let gas = match waterfall.gas {
+        Some(gas) => cost(gas),
+        None => text.gas_unknown.to_string(),
+    };
+    let net_profit = match waterfall.net {
+        Some(net) => format!("{} {}", format_amount(net), quote),
+        None => text.gas_unknown.to_string(),
+    };
+    let break_even = match (opportunity.gas_cost, opportunity.break_even) {
+        (None, _) => text.gas_unknown.to_string(),
+        (Some(_), None) => text.gas_exceeds_profit.to_string(),
+        (Some(_), Some(size)) => format!("{} {}", format_amount(size), opportunity.quote.symbol),
+    };
+
+    format!(
+        "🚨 <b>{}</b>\n\n\
+        {}: <code>{}/{}</code>\n\
+        {}\
+        {}: <code>{} {}/{}</code>\n\
+        {}: <code>{} {}/{}</code>\n\
+        {}: <b>{}</b>\n\
+        {}\n\
+        ➡️ <b>{}</b>\n\
+        {}: <code>{} {} → {} {} → {} {}</code>\n\n\
+        <b>{}</b>\n\
+        {}: <code>+{} {}</code>\n\
+        {}: <code>{}</code>\n\
+        {}: <code>{}</code>\n\
+        {}: <b>{} {}</b>\n\
+        {}: <code>{}</code>\n\
+        {}: <code>{}</code>\n\
+        {}: <b>{}</b>\n\
+        {}: <code>{}</code>\n\n\
+        {}: <code>{}</code>\n\
+        {}: <code>{}</code>\n\
+        {}: <code>{}</code>\n\
+        {}{}{}{}{}{}\n\
+        🆔 <code>{}</code>\n\
+        <i>{}</i>",
+        text.opportunity_title,
+        text.pair,
+        price_info.symbol_a,
+        price_info.symbol_b,
+        format_assets(lang, &[opportunity.base, opportunity.quote]),
+        i18n::fill(text.price, &[("venue", price_info.a.venue.name())]),
+        format_amount(price_info.a.price()),
+        price_info.symbol_b,
+        price_info.symbol_a,
+        i18n::fill(text.price, &[("venue", price_info.b.venue.name())]),
+        format_amount(price_info.b.price()),
+        price_info.symbol_b,
+        price_info.symbol_a,
+        text.profit_margin,
+        format_percent(price_info.profit_margin),
+        triggers,
+        opportunity.direction(lang),
+        text.size,
+        format_amount(opportunity.amount_in),
+        opportunity.quote.symbol,
+        format_amount(opportunity.base_amount),
+        opportunity.base.symbol,
+        format_amount(opportunity.amount_out),
+        opportunity.quote.symbol,
+        text.breakdown,
+        text.gross_spread,
+        format_amount(waterfall.gross),
+        quote,
+        text.dex_fees,
+        cost(waterfall.dex_fees),
+        text.price_impact,
+        cost(waterfall.price_impact),
+        text.expected_profit,
+        format_amount(opportunity.profit()),
+        quote,
+        text.gas,
+        gas,
+        text.flash_loan_fee,
+        cost(waterfall.flash_loan_fee),
+        text.net_profit,
+        net_profit,
+        text.break_even,
+        break_even,
+        price_info.a.venue.name(),
+        notes.summary_a,
+        price_info.b.venue.name(),
+        notes.summary_b,
+        text.safety,
+        notes.safety,
+        twap,
+        regime,
+        btc,
+        correlated,
+        simulation,
+        filter,
+        notes.id,
+        fmt::format_time(chrono::Utc::now())
+    )
+}
+
+/// `reading` as e.g. `volatile · σ 2.10%/h (p92) · volume p85`.
+fn format_regime(lang: Lang, reading: &Reading) -> String {
+    let text = lang.catalog();
+    let regime = match reading.regime {
+        Regime::Calm => text.regime_calm,
+        Regime::Normal => text.regime_normal,
+        Regime::Volatile => text.regime_volatile,
+    };
+    let rank = |percentile: f64| format!("p{:.0}", percentile * 100.0);
+    let mut line = format!(
+        "{} · σ {}/h ({})",
+        regime,
+        format_percent(reading.volatility),
+        rank(reading.volatility_percentile)
+    );
+    if let Some(percentile) = reading.volume_percentile {
+        line.push_str(&format!(" · {} {}", text.volume, rank(percentile)));
+    }
+    line
+}
+
+/// `ratio` as a percentage with its sign, e.g. `+0.42%`.
+fn format_signed_percent(ratio: f64) -> String {
+    let sign = if ratio >= 0.0 { "+" } else { "" };
+    format!("{}{}", sign, format_percent(ratio))
+}
+
+/// USD price of the BTC wrapper in a pair, from the mid of its two pools, if
+/// the pair has one and its other token can be priced.
+fn btc_wrapper_price(ctx: &ScanContext, price_info: &PriceInfo) -> Option<(&'static TokenInfo, f64)> {
+    let [base, quote] = ctx.pools.get(&price_info.pool_a)?.tokens();
+    let mid = (price_info.a.price() + price_info.b.price()) / 2.0;
+    if mid <= 0.0 || !mid.is_finite() {
+        return None;
+    }
+    if btc::is_btc_wrapper(base) {
+        Some((base, ctx.gas.usd_value(mid, quote)?))
+    } else if btc::is_btc_wrapper(quote) {
+        Some((quote, ctx.gas.usd_value(1.0 / mid, base)?))
+    } else {
+        None
+    }
+}
+
+/// Alert line with the current spreads on pairs that share a token with
+/// `pair` and swap the other for one tracking the same asset, e.g. WETH/USDT
+/// and WETH/DAI for WETH/USDC, widest first. Only pairs this instance scans
+/// and has read recently are included.
+fn correlated_spreads(ctx: &ScanContext, pair: &MonitoredPair) -> Option<String> {
+    let same = |a: &TokenInfo, b: &TokenInfo| a.address == b.address;
+    let related = |a: &TokenInfo, b: &TokenInfo| {
+        same(a, b) || (a.tracks().is_some() && a.tracks() == b.tracks())
+    };
+    let now = chrono::Utc::now();
+    let mut siblings = ctx
+        .latest
+        .read()
+        .unwrap()
+        .values()
+        .filter(|(_, read_at)| now - *read_at <= SIBLING_MAX_AGE)
+        .filter_map(|(price_info, _)| {
+            let [a, b] = ctx.pools.get(&price_info.pool_a)?.tokens();
+            let (x, y) = (pair.base(), pair.quote());
+            let shares_one = same(a, x) ^ same(b, y) || same(a, y) ^ same(b, x);
+            let correlated = (related(a, x) && related(b, y)) || (related(a, y) && related(b, x));
+            (shares_one && correlated).then_some((a.symbol, b.symbol, price_info.profit_margin))
+        })
+        .collect::<Vec<_>>();
+    if siblings.is_empty() {
+        return None;
+    }
+    siblings.sort_by(|a, b| b.2.total_cmp(&a.2));
+    Some(
+        siblings
+            .iter()
+            .take(MAX_SIBLINGS)
+            .map(|(a, b, spread)| format!("{}/{} {}", a, b, format_percent(*spread)))
+            .collect::<Vec<_>>()
+            .join(" · "),
+    )
+}
+
+/// Spread between the pair's 5-minute TWAPs on the two venues, `None`
+/// until both pools have one.
+async fn twap_spread(ctx: &ScanContext, pair: &MonitoredPair, price_info: &PriceInfo) -> Option<f64> {
+    let (twap_a, twap_b) = tokio::join!(
+        ctx.history.twap(&ctx.provider, &price_info.a, pair.base(), pair.quote()),
+        ctx.history.twap(&ctx.provider, &price_info.b, pair.base(), pair.quote()),
+    );
+    Some(price::calculate_profit_margin(twap_a?, twap_b?))
+}
+
+/// Alert line placing a BTC wrapper's price against the BTC reference, e.g.
+/// `$67.1K · WBTC -0.42%`.
+fn format_btc_reference(reference: &BtcReference, token: &TokenInfo, price_usd: f64) -> String {
+    format!(
+        "{} · {} {}",
+        format_usd(reference.price_usd),
+        token.symbol,
+        format_signed_percent(reference.deviation(price_usd))
+    )
+}
+
+/// Alerts when a BTC wrapper, priced as the median over the scanned pairs
+/// holding it, strays further than `btc_peg_threshold` from the BTC reference.
+async fn watch_btc_peg(ctx: Arc<ScanContext>) {
+    let preset = preset::active();
+    loop {
+        tokio::time::sleep(BTC_REFRESH_INTERVAL).await;
+        let Some(reference) = ctx.btc.get() else {
+            continue;
+        };
+        let mut prices: HashMap<&'static str, Vec<f64>> = HashMap::new();
+        for (price_info, _) in ctx.latest.read().unwrap().values() {
+            if let Some((token, price)) = btc_wrapper_price(&ctx, price_info) {
+                prices.entry(token.symbol).or_default().push(price);
+            }
+        }
+
+        for (symbol, mut prices) in prices {
+            let price = btc::median(&mut prices);
+            let deviation = reference.deviation(price);
+            if deviation.abs() <= ctx.btc_peg_threshold {
+                continue;
+            }
+            println!("{} {} {} at {}, {} from BTC at {} ({})",
+                "[ALERT]".bright_yellow(),
+                fmt::clock().bright_black(),
+                symbol,
+                format_usd(price),
+                format_signed_percent(deviation),
+                format_usd(reference.price_usd),
+                reference.sources.join(", "),
+            );
+            if !ctx.should_alert() {
+                continue;
+            }
+            let key = format!("{}:{}:btc-peg", preset.chain, symbol);
+            if !ctx.shared.claim_alert(&key, ctx.alert_cooldown).await {
+                continue;
+            }
+            let messages = ctx
+                .chats
+                .iter()
+                .map(|chat| {
+                    let line = i18n::fill(
+                        chat.lang.catalog().btc_off_peg,
+                        &[
+                            ("token", symbol),
+                            ("price", &format_usd(price)),
+                            ("reference", &format_usd(reference.price_usd)),
+                            ("deviation", &format_signed_percent(deviation)),
+                        ],
+                    );
+                    let message = format!(
+                        "⚠️ <b>{}</b>\n\n<i>{}</i>",
+                        line,
+                        fmt::format_time(chrono::Utc::now())
+                    );
+                    (chat.id, message)
+                })
+                .collect();
+            ctx.notify(QueuedAlert { key, messages }).await;
+        }
+    }
+}
+
+/// A made-up WETH opportunity, rendered exactly like a real alert, for
+/// checking that notifications arrive and display correctly.
+fn sample_alert(lang: Lang) -> String {
+    let preset = preset::active();
+    let (venue_a, venue_b) = (&preset.venues[0], &preset.venues[1]);
+    let base = weth();
+    let quote = preset
+        .tokens
+        .iter()
+        .find(|t| t.is_usd_stable())
+        .unwrap_or(base);
+    let a = PoolState {
+        venue: venue_a.venue,
+        pool: Address::ZERO,
+        reserve_base: 1_000.0,
+        reserve_quote: 2_400_000.0,
+        fee: venue_a.venue.default_fee(),
+    };
+    let b = PoolState {
+        venue: venue_b.venue,
+        pool: Address::ZERO,
+        reserve_base: 1_000.0,
+        reserve_quote: 2_460_000.0,
+        fee: venue_b.venue.default_fee(),
+    };
+    let price_info = PriceInfo {
+        pool_a: Address::ZERO,
+        pool_b: Address::ZERO,
+        symbol_a: base.symbol,
+        symbol_b: quote.symbol,
+        a,
+        b,
+        profit_margin: price::calculate_profit_margin(a.price(), b.price()),
+    };
+    let metadata = |tvl_usd, volume_24h_usd| PoolMetadata {
+        tvl_usd: Some(tvl_usd),
+        volume_24h_usd: Some(volume_24h_usd),
+        ..Default::default()
+    };
+    let correlated = preset
+        .tokens
+        .iter()
+        .find(|t| t.is_usd_stable() && t.address != quote.address)
+        .map(|sibling| format!("{}/{} {}", base.symbol, sibling.symbol, format_percent(0.0112)));
+    let alert = match Opportunity::evaluate(base, quote, a, b, Some(5.0), 0.0005) {
+        Some(opportunity) => format_alert(
+            lang,
+            &price_info,
+            &opportunity,
+            &AlertNotes {
+                id: Uuid::new_v4(),
+                summary_a: &metadata(4_800_000.0, 1_200_000.0).summary(),
+                summary_b: &metadata(4_900_000.0, 900_000.0).summary(),
+                safety: &format!("{} A · {} A", base.symbol, quote.symbol),
+                btc: None,
+                correlated: correlated.as_deref(),
+                twap_spread: Some(0.0211),
+                triggers: &[Trigger {
+                    venue: venue_b.venue,
+                    sold: quote,
+                    amount: 29_700.0,
+                    price_move: 0.025,
+                }],
+                regime: Some(Reading {
+                    regime: Regime::Volatile,
+                    volatility: 0.021,
+                    volatility_percentile: 0.92,
+                    volume_percentile: Some(0.85),
+                }),
+                simulation: None,
+                filter: None,
+            },
+        ),
+        None => String::new(),
+    };
+    format!("🧪 <b>TEST</b> · sample data, not a real opportunity\n\n{}", alert)
+}
+
+/// Re-syncs the token lists every [`TOKEN_LIST_REFRESH_INTERVAL`] and
+/// queues the pairs of tokens they add with the scanned ones to join the
+/// scan. Tokens a list drops are kept until restart. Runs forever.
+async fn refresh_token_lists(ctx: Arc<ScanContext>) {
+    let client = reqwest::Client::new();
+    let preset = preset::active();
+    loop {
+        tokio::time::sleep(TOKEN_LIST_REFRESH_INTERVAL).await;
+        let added = token_list::sync_all(&client, &ctx.token_lists).await;
+        // Strategy profiles scan their own few tokens only
+        let scanned = preset.scanned_tokens().collect::<Vec<_>>();
+        for token in added.into_iter().filter(|t| scanned.iter().any(|s| s.address == t.address)) {
+            match scanner::pairs_with(&ctx.provider, &preset.venues, token, &scanned).await {
+                Ok(pairs) => {
+                    if !pairs.is_empty() {
+                        println!("{} {} Scanning {} new pairs of {} from token lists",
+                            "[INFO]".bright_blue(),
+                            fmt::clock().bright_black(),
+                            pairs.len(),
+                            token.symbol,
+                        );
+                    }
+                    ctx.new_pairs.lock().unwrap().extend(pairs);
+                }
+                Err(e) => println!("{} Failed to pair {} from token lists: {}", "[ERROR]".bright_red(), token.symbol, e),
+            }
+        }
+    }
+}
+
+/// Takes a regime reading every [`regime::SAMPLE_INTERVAL`] from the prices
+/// ingestion records and the scanned pools' 24h volume, logging changes.
+/// Runs forever.
+async fn sample_regime(ctx: Arc<ScanContext>) {
+    loop {
+        tokio::time::sleep(regime::SAMPLE_INTERVAL).await;
+        let pools = ctx
+            .latest
+            .read()
+            .unwrap()
+            .values()
+            .flat_map(|(price_info, _)| [price_info.pool_a, price_info.pool_b])
+            .collect::<HashSet<_>>();
+        let volume = pools
+            .iter()
+            .filter_map(|pool| ctx.metadata.get(pool)?.volume_24h_usd)
+            .reduce(|a, b| a + b);
+        let before = ctx.regime.current().map(|reading| reading.regime);
+        ctx.regime.sample(chrono::Utc::now(), volume);
+        let Some(reading) = ctx.regime.current() else {
+            continue;
+        };
+        if before != Some(reading.regime) {
+            println!("{} {} Market regime: {}{}",
+                "[INFO]".bright_blue(),
+                fmt::clock().bright_black(),
+                format_regime(Lang::En, &reading),
+                if ctx.adaptive_thresholds {
+                    format!(", thresholds x{}", reading.regime.threshold_scale())
+                } else {
+                    String::new()
+                },
+            );
+        }
+    }
+}
+
+/// Sends [`sample_alert`] to every chat, regardless of leadership, and
+/// returns each chat's outcome.
+async fn send_test_alerts(bot: &Bot, chats: &[Chat]) -> Vec<(i64, Result<()>)> {
+    let mut results = Vec::with_capacity(chats.len());
+    for chat in chats {
+        let result = send_telegram_alert(bot, chat.id, sample_alert(chat.lang)).await;
+        results.push((chat.id, result));
+    }
+    results
+}
+
+/// Alerts on an opportunity `strategy` found on `pair`, unless its pools
+/// are too shallow or the alert filter suppresses it, and records it.
+async fn alert_opportunity(
+    from_block: u64,
+    block: u64,
+    strategy: &str,
+    pair: &MonitoredPair,
+    price_info: &PriceInfo,
+    opportunity: Opportunity,
+    ctx: &Arc<ScanContext>,
+) {
+    let time = fmt::clock();
+    let preset = preset::active();
+    let id = opportunity.id(preset.chain, block);
+    let meta_a = ctx.metadata.get(&price_info.pool_a).unwrap_or_default();
+    let meta_b = ctx.metadata.get(&price_info.pool_b).unwrap_or_default();
+
+    // Pools without known TVL are let through rather than silently dropped
+    let min_tvl = [meta_a.tvl_usd, meta_b.tvl_usd]
+        .into_iter()
+        .flatten()
+        .fold(f64::INFINITY, f64::min);
+    if min_tvl < ctx.min_tvl_usd {
+        println!("{} {} Skipping {}/{}: pool TVL {} below minimum {}",
+            "[INFO]".bright_blue(),
+            time.bright_black(),
+            price_info.symbol_a,
+            price_info.symbol_b,
+            format_usd(min_tvl),
+            format_usd(ctx.min_tvl_usd),
+        );
+        return;
+    }
 
-    while let Some(event_result) = stream.next().await {
-        match event_result {
-            Ok(event) => {
-                let time = Local::now().format("%H:%M:%S").to_string();
-                println!("{} {} New swap event detected for {}/{}", 
+    let mut filter_note = None;
+    if let Some(filter) = &ctx.alert_filter {
+        let grade = |token: &TokenInfo| ctx.safety.get(&token.address).map(|r| r.grade().to_string());
+        let facts = AlertFacts {
+            chain: preset.chain,
+            strategy,
+            pair: format!("{}/{}", opportunity.base.symbol, opportunity.quote.symbol),
+            base: opportunity.base.symbol,
+            quote: opportunity.quote.symbol,
+            buy_venue: opportunity.buy.venue.name(),
+            sell_venue: opportunity.sell.venue.name(),
+            spread: price_info.profit_margin,
+            size: opportunity.amount_in,
+            profit: opportunity.profit(),
+            net_profit: opportunity.net_profit(),
+            profit_usd: ctx.gas.usd_value(opportunity.profit(), opportunity.quote),
+            min_tvl: min_tvl.is_finite().then_some(min_tvl),
+            min_volume: [meta_a.volume_24h_usd, meta_b.volume_24h_usd]
+                .into_iter()
+                .flatten()
+                .reduce(f64::min),
+            age_days: [meta_a.age_days(), meta_b.age_days()].into_iter().flatten().min(),
+            base_grade: grade(opportunity.base),
+            quote_grade: grade(opportunity.quote),
+        };
+        match filter.judge(&facts) {
+            Ok(Verdict::Send) => {}
+            Ok(Verdict::Suppress) => {
+                println!("{} {} Alert filter suppressed {}/{}",
                     "[INFO]".bright_blue(),
                     time.bright_black(),
-                    symbol0,
-                    symbol1,
+                    price_info.symbol_a,
+                    price_info.symbol_b,
                 );
+                return;
+            }
+            Ok(Verdict::Annotate(note)) => filter_note = Some(note),
+            // A broken filter shouldn't silence the scanner
+            Err(e) => println!("{} Alert filter failed on {}/{}, alerting anyway: {}",
+                "[ERROR]".bright_red(),
+                price_info.symbol_a,
+                price_info.symbol_b,
+                e,
+            ),
+        }
+    }
 
-                match calculate_prices(
-                    Arc::clone(&provider),
-                    event.token0,
-                    event.token1,
-                    &uni_factory,
-                    &sushi_factory,
-                ).await {
-                    Ok(price_info) => {
-                        if price_info.profit_margin > MIN_PROFIT_MARGIN {
-                            println!("{} {} Arbitrage opportunity found! {}/{} Profit: {:.2}%", 
-                                "[ALERT]".bright_yellow(),
-                                time.bright_black(),
-                                price_info.symbol_a,
-                                price_info.symbol_b,
-                                price_info.profit_margin * 100.0
-                            );
-
-                            let message = format!(
-                                "🚨 <b>Arbitrage Opportunity!</b>\n\n\
-                                Pair: <code>{}/{}</code>\n\
-                                Uniswap Price: <code>{} {}/{}</code>\n\
-                                Sushiswap Price: <code>{} {}/{}</code>\n\
-                                Profit Margin: <b>{:.2}%</b>",
-                                price_info.symbol_a,
-                                price_info.symbol_b,
-                                price_info.price_uni,
-                                price_info.symbol_b,
-                                price_info.symbol_a,
-                                price_info.price_sushi,
-                                price_info.symbol_b,
-                                price_info.symbol_a,
-                                price_info.profit_margin * 100.0
-                            );
-                            
-                            if let Err(e) = send_telegram_alert(&bot, chat_id, message).await {
-                                println!("{} Failed to send Telegram alert: {}", "[ERROR]".bright_red(), e);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        println!("{} Error calculating prices: {}", "[ERROR]".bright_red(), e);
-                        tokio::time::sleep(RETRY_DELAY).await;
+    println!("{} {} Arbitrage opportunity {} found by {}! {}/{} Profit: {}",
+        "[ALERT]".bright_yellow(),
+        time.bright_black(),
+        id,
+        strategy,
+        price_info.symbol_a,
+        price_info.symbol_b,
+        format_percent(price_info.profit_margin)
+    );
+
+    let safety = [pair.base(), pair.quote()]
+        .iter()
+        .map(|t| match ctx.safety.get(&t.address) {
+            Some(report) => report.summary(t.symbol),
+            None => format!("{} n/a", t.symbol),
+        })
+        .collect::<Vec<_>>()
+        .join(" · ");
+
+    if !ctx.should_alert() {
+        println!("{} {} Standby replica, not alerting on {}/{}",
+            "[INFO]".bright_blue(),
+            time.bright_black(),
+            price_info.symbol_a,
+            price_info.symbol_b,
+        );
+        return;
+    }
+
+    let alert_key = format!(
+        "{}:{}/{}:{}>{}",
+        preset.chain,
+        opportunity.base.symbol,
+        opportunity.quote.symbol,
+        opportunity.buy.venue_label(),
+        opportunity.sell.venue_label()
+    );
+    // A restart can rescan the block it stopped at
+    if ctx.storage.has_opportunity(id).unwrap_or(false) {
+        println!("{} {} Opportunity {} already recorded, skipping",
+            "[INFO]".bright_blue(),
+            time.bright_black(),
+            id,
+        );
+        return;
+    }
+    if !ctx.shared.claim_alert(&alert_key, ctx.alert_cooldown).await {
+        println!("{} {} Already alerted on {} recently, skipping",
+            "[INFO]".bright_blue(),
+            time.bright_black(),
+            alert_key,
+        );
+        return;
+    }
+
+    let simulation = match &ctx.tenderly {
+        Some(tenderly) => match tenderly.simulate(&opportunity).await {
+            Ok(links) => Some(links),
+            Err(e) => {
+                println!("{} Tenderly simulation failed: {}", "[ERROR]".bright_red(), e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let btc = btc_wrapper_price(ctx, price_info).and_then(|(token, price)| {
+        ctx.btc
+            .get()
+            .map(|reference| format_btc_reference(&reference, token, price))
+    });
+
+    let correlated = correlated_spreads(ctx, pair);
+    let twap_spread = twap_spread(ctx, pair, price_info).await;
+    let triggers = trigger::triggers(&ctx.provider, pair, [price_info.a, price_info.b], from_block, block)
+        .await
+        .unwrap_or_else(|e| {
+            println!("{} Failed to read the swaps behind {}/{}: {}",
+                "[ERROR]".bright_red(),
+                price_info.symbol_a,
+                price_info.symbol_b,
+                e,
+            );
+            Vec::new()
+        });
+
+    let (summary_a, summary_b) = (meta_a.summary(), meta_b.summary());
+    let notes = AlertNotes {
+        id,
+        summary_a: &summary_a,
+        summary_b: &summary_b,
+        safety: &safety,
+        btc: btc.as_deref(),
+        correlated: correlated.as_deref(),
+        twap_spread,
+        triggers: &triggers,
+        regime: ctx.regime.current(),
+        simulation: simulation.as_ref(),
+        filter: filter_note.as_deref(),
+    };
+    let pair_name = format!("{}/{}", opportunity.base.symbol, opportunity.quote.symbol);
+    let net_profit_usd = opportunity
+        .waterfall()
+        .net
+        .and_then(|net| ctx.gas.usd_value(net, opportunity.quote));
+    let subscribers = ctx
+        .subscribers
+        .matching(preset.chain, &pair_name, price_info.profit_margin, net_profit_usd)
+        .into_iter()
+        .filter(|s| !ctx.chats.iter().any(|chat| chat.id == s.chat_id))
+        .map(|s| Chat { id: s.chat_id, lang: s.lang });
+    let alert = QueuedAlert {
+        key: alert_key,
+        messages: ctx
+            .chats
+            .iter()
+            .copied()
+            .chain(subscribers)
+            .map(|chat| {
+                let message = format_alert(chat.lang, price_info, &opportunity, &notes);
+                (chat.id, message)
+            })
+            .collect(),
+    };
+    ctx.notify(alert).await;
+
+    let record = OpportunityRecord {
+        id,
+        run_id: ctx.run_id,
+        timestamp: chrono::Utc::now(),
+        chain: preset.chain.to_string(),
+        pair: pair_name,
+        buy_venue: opportunity.buy.venue.name().to_string(),
+        sell_venue: opportunity.sell.venue.name().to_string(),
+        buy_price: opportunity.buy.price(),
+        sell_price: opportunity.sell.price(),
+        spread: price_info.profit_margin,
+        size: opportunity.amount_in,
+        profit: opportunity.profit(),
+        profit_usd: ctx.gas.usd_value(opportunity.profit(), opportunity.quote),
+    };
+    if let Err(e) = ctx.storage.record_opportunity(&record) {
+        println!("{} Failed to store opportunity {}: {}", "[ERROR]".bright_red(), id, e);
+    }
+    if ctx.webhook {
+        if let Err(e) = ctx.storage.queue_webhook(&storage::webhook::payload(&record)) {
+            println!("{} Failed to queue opportunity {} for webhook: {}", "[ERROR]".bright_red(), id, e);
+        }
+    }
+    ctx.shared.publish_opportunity(&record).await;
+    // Fails only without `/events` clients
+    let _ = ctx.events.send(api::Event::Opportunity(record.clone()));
+    if ctx.paper.is_some() {
+        tokio::spawn(paper::trade(Arc::clone(ctx), record, *pair, opportunity, block));
+    }
+}
+
+/// Pairs whose reserves moved, and the market they make up as of the
+/// snapshot's block.
+struct Batch {
+    snapshot: MarketSnapshot,
+    /// First block whose changes the batch carries; earlier ones were
+    /// handed over before.
+    from_block: u64,
+    read_at: Instant,
+    pairs: Vec<(MonitoredPair, PriceInfo)>,
+}
+
+/// The market `pairs` make up as of `block`, with gas and reference prices
+/// as they stand right after the read. Pairs held back from an earlier
+/// block haven't moved since, so their reserves are still current at `block`.
+fn capture_snapshot(
+    ctx: &ScanContext,
+    block: u64,
+    read_at: chrono::DateTime<chrono::Utc>,
+    pairs: &[(MonitoredPair, PriceInfo)],
+) -> MarketSnapshot {
+    MarketSnapshot {
+        block,
+        read_at,
+        pairs: pairs
+            .iter()
+            .map(|(pair, price_info)| PairQuote {
+                base: pair.base(),
+                quote: pair.quote(),
+                pools: [price_info.a, price_info.b],
+                gas_cost: ctx.gas.arb_cost_in(pair.quote()),
+                quote_usd: ctx.gas.usd_value(1.0, pair.quote()),
+            })
+            .collect(),
+        gas_price_gwei: ctx.gas.gas_price_gwei(),
+        flash_loan_fee: ctx.flash_loan_fee,
+        min_profit_usd: ctx.min_profit_usd,
+        threshold_scale: match ctx.regime.current() {
+            Some(reading) if ctx.adaptive_thresholds => reading.regime.threshold_scale(),
+            _ => 1.0,
+        },
+        btc: ctx.btc.get(),
+    }
+}
+
+/// A pair's pools on the two venues it's compared across, which tell it
+/// apart from the pair of the same tokens on other venues.
+type PairPools = [Address; 2];
+
+/// What ingestion last handed to evaluation, shared by both ways of
+/// ingesting: each pool's reserves then, and the pairs that moved while
+/// evaluation was behind. While evaluation is a full queue behind, changes
+/// are merged into one pending batch, newest price per pair, so a burst
+/// costs at most one entry per pair.
+#[derive(Default)]
+struct Handover {
+    last_seen: HashMap<Address, (f64, f64)>,
+    pending: HashMap<PairPools, (MonitoredPair, PriceInfo)>,
+    /// Block the pending changes date from.
+    pending_from: Option<u64>,
+    last_head: Option<u64>,
+}
+
+impl Handover {
+    /// Records the prices of every scanned pair as of `head`, then hands
+    /// the pairs whose reserves moved to evaluation.
+    async fn send(
+        &mut self,
+        ctx: &ScanContext,
+        evaluations: &mpsc::Sender<Batch>,
+        prices: impl IntoIterator<Item = (MonitoredPair, PriceInfo)>,
+        head: u64,
+        read_at: Instant,
+    ) -> Result<()> {
+        let now = chrono::Utc::now();
+        let from_block = self.last_head.map_or(head, |last| last + 1);
+        self.last_head = Some(head);
+        let streaming = ctx.events.receiver_count() > 0;
+        let mut streamed = Vec::new();
+        for (pair, price_info) in prices {
+            if streaming {
+                streamed.push(api::PairPrices {
+                    pair: price_info.pair(),
+                    venues: [price_info.a, price_info.b]
+                        .iter()
+                        .map(|pool| api::VenuePrice {
+                            venue: pool.venue_label(),
+                            pool: format!("{:?}", pool.pool),
+                            price: pool.price(),
+                        })
+                        .collect(),
+                });
+            }
+            for pool in [price_info.a, price_info.b] {
+                ctx.history.record(pool.pool, head, now, pool.price());
+                ctx.regime.record(pool.pool, now, pool.price());
+            }
+            ctx.latest.write().unwrap().insert(price_info.key(), (price_info.clone(), now));
+            // No short-circuit: both pools' reserves must be recorded
+            let moved_a = self.last_seen.insert(price_info.pool_a, price_info.a.reserves())
+                != Some(price_info.a.reserves());
+            let moved_b = self.last_seen.insert(price_info.pool_b, price_info.b.reserves())
+                != Some(price_info.b.reserves());
+            if moved_a | moved_b {
+                self.pending_from.get_or_insert(from_block);
+                self.pending.insert([pair.pool_a, pair.pool_b], (pair, price_info));
+            }
+        }
+        if streaming {
+            let _ = ctx.events.send(api::Event::Prices(api::PriceUpdate {
+                block: head,
+                timestamp: now.timestamp(),
+                pairs: streamed,
+            }));
+        }
+
+        if !self.pending.is_empty() {
+            // The only sender, so spare capacity can't be taken before the send
+            if evaluations.capacity() > 0 {
+                let pairs = self.pending.drain().map(|(_, entry)| entry).collect::<Vec<_>>();
+                let batch = Batch {
+                    snapshot: capture_snapshot(ctx, head, now, &pairs),
+                    from_block: self.pending_from.take().unwrap_or(head),
+                    read_at,
+                    pairs,
+                };
+                evaluations.send(batch).await.context("Evaluation stage stopped")?;
+            } else {
+                println!("{} {} Evaluation behind, {} pairs pending as of block {}",
+                    "[INFO]".bright_blue(),
+                    fmt::clock().bright_black(),
+                    self.pending.len(),
+                    head,
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adds the pairs new pools completed since the last block to `pairs`,
+/// unless another shard owns them or they're already scanned, reading their
+/// fees first. Returns how many were added, at the end of `pairs`.
+async fn admit_new_pairs(pairs: &mut Vec<MonitoredPair>, ctx: &ScanContext) -> usize {
+    let scanned = pairs.len();
+    let new_pairs = std::mem::take(&mut *ctx.new_pairs.lock().unwrap());
+    for pair in new_pairs {
+        if !ctx.shard.owns(pair.pool_a)
+            || pairs.iter().any(|p| p.pool_a == pair.pool_a && p.pool_b == pair.pool_b)
+        {
+            continue;
+        }
+        let venues = &preset::active().venues;
+        let legs = [(pair.pool_a, venues[pair.venues[0]].venue), (pair.pool_b, venues[pair.venues[1]].venue)];
+        enrich::fees::refresh(&ctx.provider, &legs, &ctx.fees).await;
+        ctx.pools.insert(pair);
+        pairs.push(pair);
+    }
+    pairs.len() - scanned
+}
+
+/// Ingestion stage: one batched reserve read per new block, handing every
+/// pair whose reserves moved to evaluation.
+async fn monitor_blocks(
+    pairs: &mut Vec<MonitoredPair>,
+    ctx: &ScanContext,
+    evaluations: &mpsc::Sender<Batch>,
+) -> Result<()> {
+    let multicall = scanner::multicall(&ctx.provider, preset::active()).await?;
+    let mut last_block = None;
+    let mut handover = Handover::default();
+
+    loop {
+        let head = ctx.provider.get_block_number().await?;
+        if last_block != Some(head) {
+            last_block = Some(head);
+            admit_new_pairs(pairs, ctx).await;
+            let read_at = Instant::now();
+            let prices = read_prices(ctx, multicall, pairs, head).await?;
+            handover.send(ctx, evaluations, pairs.iter().copied().zip(prices), head, read_at).await?;
+        }
+        tokio::time::sleep(BLOCK_POLL_INTERVAL).await;
+    }
+}
+
+/// Node-direct ingestion stage: follows the block notifications of a reth
+/// ExEx (see [`exex`]) and applies the pools' `Sync` logs to their reserves
+/// instead of polling the RPC node and reading every pool each block. The
+/// RPC node is still read to seed the reserves, for new pairs, after a
+/// reorg and for Trader Joe LB pools, which don't log their reserves.
+async fn monitor_node_events(
+    pairs: &mut Vec<MonitoredPair>,
+    ctx: &ScanContext,
+    evaluations: &mpsc::Sender<Batch>,
+    path: &std::path::Path,
+) -> Result<()> {
+    let venues = &preset::active().venues;
+    // Opened before the seed read, so no block after it is missed; the
+    // ones up to it are skipped
+    let mut feed = exex::Feed::open(path).await?;
+    let multicall = scanner::multicall(&ctx.provider, preset::active()).await?;
+    let mut head = ctx.provider.get_block_number().await?;
+    let read_at = Instant::now();
+    let mut reserves = scanner::read_reserves(&ctx.provider, multicall, venues, pairs, head).await?;
+    let mut handover = Handover::default();
+    handover
+        .send(ctx, evaluations, pairs.iter().copied().zip(to_prices(ctx, pairs, &reserves)), head, read_at)
+        .await?;
+    // A pool is in a pair per other venue with the pair
+    let index = |pairs: &[MonitoredPair]| {
+        let mut legs = HashMap::<Address, Vec<(usize, usize)>>::new();
+        for (i, pair) in pairs.iter().enumerate() {
+            legs.entry(pair.pool_a).or_default().push((i, 0));
+            legs.entry(pair.pool_b).or_default().push((i, 1));
+        }
+        legs
+    };
+    let mut legs = index(pairs);
+    // Whether reverted blocks' logs were applied, so every pool is re-read
+    let mut stale = false;
+
+    loop {
+        let notification = feed.next().await?;
+        let read_at = Instant::now();
+        stale |= notification.reverted.iter().any(|block| *block <= head);
+        let Some(tip) = notification.committed.last().map(|block| block.number) else {
+            continue;
+        };
+        if tip <= head && !stale {
+            continue;
+        }
+
+        if stale {
+            reserves = scanner::read_reserves(&ctx.provider, multicall, venues, pairs, tip).await?;
+            stale = false;
+        } else {
+            let mut unlogged = HashSet::new();
+            for log in notification.committed.iter().filter(|block| block.number > head).flat_map(|block| &block.logs) {
+                let Some(pool_legs) = legs.get(&log.address) else {
+                    continue;
+                };
+                let (i, leg) = pool_legs[0];
+                if !venues[pairs[i].venues[leg]].batchable() {
+                    unlogged.insert(log.address);
+                } else if let Some(synced) = log.synced_reserves() {
+                    for &(i, leg) in pool_legs {
+                        reserves[i][leg] = synced;
                     }
                 }
             }
-            Err(e) => {
-                println!("{} Error processing event: {}", "[ERROR]".bright_red(), e);
-                tokio::time::sleep(RETRY_DELAY).await;
+            let reads = unlogged.into_iter().map(|pool| {
+                let (i, leg) = legs[&pool][0];
+                let pair = pairs[i];
+                async move {
+                    let venue = &venues[pair.venues[leg]];
+                    let read = venue.read_reserves(&ctx.provider, pool, pair.token0.address, Some(tip)).await?;
+                    Ok::<_, anyhow::Error>((pool, read))
+                }
+            });
+            for (pool, read) in futures::future::try_join_all(reads).await? {
+                for &(i, leg) in &legs[&pool] {
+                    reserves[i][leg] = read;
+                }
             }
         }
+        head = tip;
+
+        let added = admit_new_pairs(pairs, ctx).await;
+        if added > 0 {
+            let new_pairs = &pairs[pairs.len() - added..];
+            reserves.extend(scanner::read_reserves(&ctx.provider, multicall, venues, new_pairs, head).await?);
+            legs = index(pairs);
+        }
+        handover
+            .send(ctx, evaluations, pairs.iter().copied().zip(to_prices(ctx, pairs, &reserves)), head, read_at)
+            .await?;
+    }
+}
+
+/// What the strategies found for each pair at each recent block. Ingestion
+/// hands over a pair once per block, but forgets what it handed over when
+/// it restarts after a read error, and would then re-send the same block's
+/// pairs. Those reuse the opportunities already found instead of being
+/// evaluated again; alerting still dedups them by id and cooldown.
+#[derive(Default)]
+struct EvalCache {
+    evaluated: HashMap<(PairPools, u64), Vec<(String, Opportunity)>>,
+    lookups: u64,
+    hits: u64,
+}
+
+impl EvalCache {
+    /// What was found for the pair with pools `pools` at `block`, if it was
+    /// evaluated there.
+    fn get(&mut self, pools: PairPools, block: u64) -> Option<&[(String, Opportunity)]> {
+        self.lookups += 1;
+        let found = self.evaluated.get(&(pools, block))?;
+        self.hits += 1;
+        Some(found)
+    }
+
+    fn insert(&mut self, pools: PairPools, block: u64, found: Vec<(String, Opportunity)>) {
+        self.evaluated.insert((pools, block), found);
+    }
+
+    fn prune(&mut self, head: u64) {
+        self.evaluated
+            .retain(|(_, block), _| block + EVAL_CACHE_BLOCKS > head);
+    }
+
+    fn hit_rate(&self) -> f64 {
+        if self.lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / self.lookups as f64
     }
-    Ok(())
 }
 
-async fn monitor_swaps(provider: Arc<Provider<Http>>, bot: Bot, chat_id: i64) -> Result<()> {
+/// Evaluation stage: archives each batch's snapshot if enabled, then runs
+/// every enabled strategy over the pairs not already evaluated at that
+/// block, reusing what was found for the rest, and alerts on it all
+/// concurrently.
+async fn evaluate_batches(ctx: Arc<ScanContext>, mut batches: mpsc::Receiver<Batch>) {
+    let mut cache = EvalCache::default();
+    let mut last_prune: Option<Instant> = None;
+    while let Some(batch) = batches.recv().await {
+        let started = Instant::now();
+        let Batch { mut snapshot, from_block, read_at, pairs } = batch;
+
+        if let Some(retention) = ctx.snapshot_retention {
+            let chain = preset::active().chain;
+            if let Err(e) = ctx.storage.archive_snapshot(chain, &snapshot) {
+                println!("{} Failed to archive market snapshot: {}", "[ERROR]".bright_red(), e);
+            }
+            if last_prune.is_none_or(|at| at.elapsed() >= SNAPSHOT_PRUNE_INTERVAL) {
+                last_prune = Some(Instant::now());
+                if let Err(e) = ctx.storage.prune_snapshots(chrono::Utc::now() - retention) {
+                    println!("{} Failed to prune market snapshots: {}", "[ERROR]".bright_red(), e);
+                }
+            }
+        }
+
+        cache.prune(snapshot.block);
+        let mut found = Vec::new();
+        snapshot.pairs.retain(|quote| match cache.get(quote.pools.map(|pool| pool.pool), snapshot.block) {
+            Some(cached) => {
+                found.extend(cached.iter().map(|(strategy, o)| (quote.pools.map(|pool| pool.pool), strategy.clone(), o.clone())));
+                false
+            }
+            None => true,
+        });
+        let evaluated = snapshot.pairs.len();
+
+        let quoted = snapshot
+            .pairs
+            .iter()
+            .map(|quote| quote.pools.map(|pool| pool.pool))
+            .collect::<HashSet<_>>();
+        // Both legs' pools make up the pair, either way round; a routed
+        // leg's belongs to another one, and the direct leg's pool leads
+        // back to a pair it's in
+        let pair_of = |opportunity: &Opportunity| {
+            let legs = [opportunity.buy.pool, opportunity.sell.pool];
+            [legs, [legs[1], legs[0]]]
+                .into_iter()
+                .find(|pools| quoted.contains(pools))
+                .or_else(|| {
+                    legs.iter()
+                        .filter_map(|pool| ctx.pools.get(pool))
+                        .find(|entry| {
+                            let tokens = entry.tokens().map(|token| token.address);
+                            tokens.contains(&opportunity.base.address) && tokens.contains(&opportunity.quote.address)
+                        })
+                        .map(|entry| [entry.pair.pool_a, entry.pair.pool_b])
+                })
+        };
+        let mut fresh = quoted.iter().map(|pools| (*pools, Vec::new())).collect::<HashMap<_, _>>();
+        for strategy in &ctx.strategies {
+            for opportunity in strategy.evaluate(&snapshot) {
+                if let Some(found) = pair_of(&opportunity).and_then(|pools| fresh.get_mut(&pools)) {
+                    found.push((strategy.name().to_string(), opportunity));
+                }
+            }
+        }
+        for (pools, opportunities) in fresh {
+            found.extend(opportunities.iter().map(|(strategy, o)| (pools, strategy.clone(), o.clone())));
+            cache.insert(pools, snapshot.block, opportunities);
+        }
+
+        let by_pair = pairs
+            .iter()
+            .map(|entry| ([entry.0.pool_a, entry.0.pool_b], entry))
+            .collect::<HashMap<_, _>>();
+        let (ctx, block) = (&ctx, snapshot.block);
+        futures::future::join_all(found.into_iter().filter_map(|(pools, strategy, opportunity)| {
+            let (pair, price_info) = by_pair.get(&pools)?;
+            Some(async move {
+                alert_opportunity(from_block, block, &strategy, pair, price_info, opportunity, ctx).await
+            })
+        }))
+        .await;
+        if let Some(lp_watcher) = &ctx.lp_watcher {
+            lp_watcher.check(&snapshot, ctx).await;
+        }
+        println!("{} {} Block {}: evaluated {} of {} pairs in {}ms ({}ms after the read), cache hit rate {}",
+            "[INFO]".bright_blue(),
+            fmt::clock().bright_black(),
+            snapshot.block,
+            evaluated,
+            pairs.len(),
+            started.elapsed().as_millis(),
+            read_at.elapsed().as_millis(),
+            format_percent(cache.hit_rate()),
+        );
+    }
+}
+
+async fn monitor_swaps(ctx: Arc<ScanContext>) -> Result<()> {
     println!("{}", "\n=== DEX Arbitrage Scanner ===".bright_green().bold());
     println!("{}", "Initializing contracts...".yellow());
 
-    let uni_factory = Contract::new(
-        UNISWAP_V2_FACTORY.parse::<Address>()?,
-        FACTORY_ABI.clone(),
-        Arc::clone(&provider),
+    let preset = preset::active();
+
+    if !ctx.token_lists.is_empty() {
+        println!("{}", "Syncing token lists...".yellow());
+        token_list::sync_all(&reqwest::Client::new(), &ctx.token_lists).await;
+        tokio::spawn(refresh_token_lists(Arc::clone(&ctx)));
+    }
+
+    println!("{}", "Fetching token pairs...".yellow());
+    let tokens = preset.scanned_tokens().collect::<Vec<_>>();
+    let mut pairs = scanner::find_pairs(&ctx.provider, &preset.venues, &tokens, |_| {
+        print!("{}", ".".bright_blue());
+    })
+    .await?;
+    println!("\n");
+
+    if ctx.shard.is_partial() {
+        let total = pairs.len();
+        pairs.retain(|p| ctx.shard.owns(p.pool_a));
+        println!("{} {}: scanning {} of {} pairs",
+            "[INFO]".bright_blue(),
+            format!("Shard {}", ctx.shard).bright_yellow(),
+            pairs.len(),
+            total,
+        );
+    }
+    for pair in &pairs {
+        ctx.pools.insert(*pair);
+    }
+
+    let index = Arc::clone(&ctx);
+    enrich::spawn_refresh(
+        ctx.provider.clone(),
+        move || {
+            index
+                .pools
+                .entries()
+                .into_iter()
+                .map(|(pool, entry)| (pool, [entry.pair.token0.address, entry.pair.token1.address]))
+                .collect()
+        },
+        ctx.metadata.clone(),
+        METADATA_REFRESH_INTERVAL,
     );
 
-    let sushi_factory = Contract::new(
-        SUSHISWAP_FACTORY.parse::<Address>()?,
-        FACTORY_ABI.clone(),
-        Arc::clone(&provider),
+    let venue_pools = pairs
+        .iter()
+        .flat_map(|p| [(p.pool_a, preset.venues[p.venues[0]].venue), (p.pool_b, preset.venues[p.venues[1]].venue)])
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    enrich::fees::spawn_refresh(
+        ctx.provider.clone(),
+        venue_pools,
+        ctx.fees.clone(),
+        FEE_REFRESH_INTERVAL,
     );
 
-    let mut pairs = Vec::new();
-    println!("{}", "Fetching token pairs...".yellow());
-    
-    for token0 in TOKENS.iter() {
-        for token1 in TOKENS.iter() {
-            if token0.address >= token1.address { continue; }
-            
-            let uni_pair = uni_factory
-                .method::<_, Address>("getPair", (token0.address, token1.address))?
-                .call()
-                .await
-                .context("Failed to get pair address")?;
-
-            if uni_pair != Address::zero() {
-                let pair_contract = Contract::new(
-                    uni_pair,
-                    PAIR_ABI.clone(),
-                    Arc::clone(&provider),
+    if let Some(lp_watcher) = &ctx.lp_watcher {
+        for position in lp_watcher.positions() {
+            if !ctx.pools.contains(&position.pool) {
+                println!("{} LP position pool {:?} isn't among the scanned pools, it won't be watched",
+                    "[ERROR]".bright_red(),
+                    position.pool,
                 );
-                pairs.push((pair_contract, token0.symbol, token1.symbol));
-                print!("{}", ".".bright_blue());
             }
         }
+        lp_watcher.spawn_refresh(ctx.provider.clone(), LP_SHARE_REFRESH_INTERVAL);
     }
-    println!("\n");
+
+    let token_pools = preset
+        .scanned_tokens()
+        .map(|t| {
+            let mut pools = pairs
+                .iter()
+                .filter(|p| p.token0.address == t.address || p.token1.address == t.address)
+                .flat_map(|p| [p.pool_a, p.pool_b])
+                .collect::<Vec<_>>();
+            pools.sort();
+            pools.dedup();
+            (t.address, pools)
+        })
+        .collect();
+    // Looked up separately from the monitored pairs so gas can still be
+    // priced when a strategy doesn't scan WETH
+    let weth_pools = scanner::weth_pools(&ctx.provider, &preset.venues[0], &tokens).await?;
+    utils::gas::spawn_refresh(
+        ctx.provider.clone(),
+        weth_pools,
+        ctx.gas.clone(),
+        GAS_REFRESH_INTERVAL,
+    );
+
+    if preset.scanned_tokens().any(btc::is_btc_wrapper) {
+        btc::spawn_refresh(ctx.provider.clone(), ctx.btc.clone(), BTC_REFRESH_INTERVAL);
+        tokio::spawn(watch_btc_peg(Arc::clone(&ctx)));
+    }
+    if let Some(min_usd) = ctx.whale_min_usd {
+        tokio::spawn(watcher::whale::watch(Arc::clone(&ctx), min_usd));
+    }
+    if !ctx.gas_wallets.is_empty() {
+        tokio::spawn(watcher::balance::watch(Arc::clone(&ctx)));
+    }
+    tokio::spawn(sample_regime(Arc::clone(&ctx)));
+
+    enrich::safety::spawn_refresh(
+        ctx.provider.clone(),
+        token_pools,
+        ctx.etherscan_api_key.clone(),
+        ctx.safety.clone(),
+        SAFETY_REFRESH_INTERVAL,
+    );
 
     println!("{} {} {}", 
         "Monitoring".bright_green(),
@@ -301,91 +1797,477 @@ async fn monitor_swaps(provider: Arc<Provider<Http>>, bot: Bot, chat_id: i64) ->
     );
     println!("{}", "Press Ctrl+C to stop\n".bright_black());
 
-    let mut tasks = Vec::new();
-    for (pair, symbol0, symbol1) in pairs {
-        let provider = Arc::clone(&provider);
-        let uni_factory = uni_factory.clone();
-        let sushi_factory = sushi_factory.clone();
-        let bot = bot.clone();
-        
-        let task = tokio::spawn(async move {
-            loop {
-                if let Err(e) = monitor_pair(
-                    pair.clone(),
-                    symbol0,
-                    symbol1,
-                    Arc::clone(&provider),
-                    uni_factory.clone(),
-                    sushi_factory.clone(),
-                    bot.clone(),
-                    chat_id,
-                ).await {
-                    println!("{} Error monitoring {}/{}: {}", 
-                        "[ERROR]".bright_red(),
-                        symbol0,
-                        symbol1,
-                        e
-                    );
-                    tokio::time::sleep(RETRY_DELAY).await;
-                }
-            }
-        });
-        tasks.push(task);
+    let (evaluations, batches) = mpsc::channel(EVALUATION_QUEUE);
+    tokio::spawn(evaluate_batches(Arc::clone(&ctx), batches));
+
+    loop {
+        let scanned = match &ctx.node_events {
+            Some(path) => monitor_node_events(&mut pairs, &ctx, &evaluations, path).await,
+            None => monitor_blocks(&mut pairs, &ctx, &evaluations).await,
+        };
+        if let Err(e) = scanned {
+            println!("{} Error scanning blocks: {}", "[ERROR]".bright_red(), e);
+            let message = format!(
+                "⚠️ <b>Scan error</b>\n\n\
+                <code>{}</code>\n\n\
+                Reconnecting in {}s.",
+                teloxide::utils::html::escape(&format!("{:#}", e)),
+                RETRY_DELAY.as_secs()
+            );
+            watcher::sync::warn(&ctx, &ctx.ops_chats, "scan-error", message).await;
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
     }
+}
 
-    futures::future::join_all(tasks).await;
-    Ok(())
+/// Tells the ops chats the scanner is alive every `interval`: how long it's
+/// been up and how fresh its prices are.
+async fn heartbeat(ctx: Arc<ScanContext>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    // The first tick is immediate and the startup message covers it
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        let now = chrono::Utc::now();
+        let uptime = now - ctx.started_at;
+        let (priced, newest) = {
+            let latest = ctx.latest.read().unwrap();
+            let pairs = latest.values().map(|(price_info, _)| price_info.pair()).collect::<HashSet<_>>();
+            (pairs.len(), latest.values().map(|(_, read_at)| *read_at).max())
+        };
+        let message = format!(
+            "💓 <b>Scanner alive</b>\n\n\
+            Uptime: {}h {}m\n\
+            Pairs priced: {}\n\
+            Last price: {}\n\
+            Run: <code>{}</code>",
+            uptime.num_hours(),
+            uptime.num_minutes() % 60,
+            priced,
+            match newest {
+                Some(read_at) => format!("{}s ago", (now - read_at).num_seconds()),
+                None => "none yet".to_string(),
+            },
+            ctx.run_id
+        );
+        ctx.broadcast_ops(&message).await;
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
     env_logger::init();
+    let cli = Cli::parse();
 
     println!("{}", "\n=== DEX Arbitrage Scanner ===".bright_green().bold());
     println!("{}", "\nLoading configuration...".yellow());
-    let rpc_url = env::var("RPC_URL").context("RPC_URL not set")?;
-    let chat_id = env::var("TELEGRAM_CHAT_ID")
-        .context("TELEGRAM_CHAT_ID not set")?
-        .parse::<i64>()
-        .context("Invalid TELEGRAM_CHAT_ID")?;
+    let preset = match &cli.strategy {
+        Some(name) => Preset::strategy(name)?,
+        None => Preset::by_name(&cli.preset)?,
+    };
+    let preset = match config::load(cli.config.as_deref())? {
+        Some(file) => {
+            file.apply_env();
+            file.apply(preset)?
+        }
+        None => preset,
+    };
+    preset::init(preset);
+    if let Some(value) = env::var("QUOTE_PRIORITY").ok().filter(|s| !s.is_empty()) {
+        token::set_quote_priority(token::parse_quote_priority(&value).context("Invalid QUOTE_PRIORITY")?);
+    }
+    match cli.command {
+        Some(Command::CheckConfig) => return check::run(preset).await,
+        Some(Command::SoakTest(args)) => return soak::run(args).await,
+        Some(Command::Research(args)) => return research::run(args).await,
+        _ => {}
+    }
+    let rpc_url = secrets::require("RPC_URL").await?;
+    let default_lang = match env::var("ALERT_LANGUAGE").ok().filter(|s| !s.is_empty()) {
+        Some(lang) => lang.parse::<Lang>().context("Invalid ALERT_LANGUAGE")?,
+        None => Lang::default(),
+    };
+    let chats = parse_chats(
+        &env::var("TELEGRAM_CHAT_ID").context("TELEGRAM_CHAT_ID not set")?,
+        default_lang,
+    )
+    .context("Invalid TELEGRAM_CHAT_ID")?;
+    let ops_chats = match env::var("TELEGRAM_OPS_CHAT_ID").ok().filter(|s| !s.is_empty()) {
+        Some(value) => parse_chats(&value, default_lang).context("Invalid TELEGRAM_OPS_CHAT_ID")?,
+        None => chats.clone(),
+    };
+    let min_tvl_usd = match env::var("MIN_POOL_TVL_USD").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<f64>().context("Invalid MIN_POOL_TVL_USD")?,
+        None => preset.min_tvl_usd,
+    };
+    let flash_loan_fee = match env::var("FLASH_LOAN_FEE").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<f64>().context("Invalid FLASH_LOAN_FEE")?,
+        None => 0.0,
+    };
+    let min_profit_usd = match env::var("MIN_PROFIT_USD").ok().filter(|s| !s.is_empty()) {
+        Some(value) => Some(value.parse::<f64>().context("Invalid MIN_PROFIT_USD")?),
+        None => None,
+    };
+    let strategies = env::var("STRATEGIES").ok().filter(|s| !s.is_empty());
+    let registry = strategy::Registry::with_plugins(&env::var("STRATEGY_PLUGINS").unwrap_or_default())
+        .context("Invalid STRATEGY_PLUGINS")?;
+    let strategies = registry
+        .build(strategies.as_deref().unwrap_or("cross-venue"))
+        .context("Invalid STRATEGIES")?;
+    let alert_filter = AlertFilter::from_env()?;
+    let snapshot_retention = match env::var("SNAPSHOT_RETENTION_DAYS").ok().filter(|s| !s.is_empty()) {
+        Some(value) => Some(chrono::Duration::days(
+            value.parse::<i64>().context("Invalid SNAPSHOT_RETENTION_DAYS")?,
+        )),
+        None => None,
+    };
+    let whale_min_usd = match env::var("WHALE_MIN_USD").ok().filter(|s| !s.is_empty()) {
+        Some(value) => Some(value.parse::<f64>().context("Invalid WHALE_MIN_USD")?),
+        None => None,
+    };
+    let gas_wallets = env::var("GAS_WALLETS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<Address>())
+        .collect::<Result<Vec<_>, _>>()
+        .context("Invalid GAS_WALLETS")?;
+    let gas_wallet_floor = match env::var("GAS_WALLET_FLOOR").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<f64>().context("Invalid GAS_WALLET_FLOOR")?,
+        None => DEFAULT_GAS_WALLET_FLOOR,
+    };
+    let btc_peg_threshold = match env::var("BTC_PEG_THRESHOLD").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<f64>().context("Invalid BTC_PEG_THRESHOLD")?,
+        None => DEFAULT_BTC_PEG_THRESHOLD,
+    };
+    let max_clock_skew = match env::var("MAX_CLOCK_SKEW_MS").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<i64>().context("Invalid MAX_CLOCK_SKEW_MS")?,
+        None => DEFAULT_MAX_CLOCK_SKEW_MS,
+    };
+    let max_block_lag = match env::var("MAX_BLOCK_LAG").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<u64>().context("Invalid MAX_BLOCK_LAG")?,
+        None => DEFAULT_MAX_BLOCK_LAG,
+    };
+    let token_lists = match env::var("TOKEN_LISTS").ok().filter(|s| !s.is_empty()) {
+        Some(value) => token_list::parse_sources(&value).context("Invalid TOKEN_LISTS")?,
+        None => Vec::new(),
+    };
+    let node_events = env::var("NODE_EVENTS_FILE").ok().filter(|s| !s.is_empty()).map(PathBuf::from);
+    let ntp_server = env::var("NTP_SERVER")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_NTP_SERVER.to_string());
+    let locale = fmt::Locale::from_env()?;
+    fmt::init(locale.clone());
+    if let Some(Command::NotifyTest) = cli.command {
+        let bot = init_telegram().await?;
+        let mut failed = 0;
+        for (id, result) in send_test_alerts(&bot, &chats).await {
+            match result {
+                Ok(()) => println!("{} Test alert sent to {}", "[INFO]".bright_blue(), id),
+                Err(e) => {
+                    failed += 1;
+                    println!("{} Test alert to {} failed: {}", "[ERROR]".bright_red(), id, e);
+                }
+            }
+        }
+        anyhow::ensure!(failed == 0, "{} of {} chats unreachable", failed, chats.len());
+        return Ok(());
+    }
+    let etherscan_api_key = secrets::load("ETHERSCAN_API_KEY").await?;
+    let tenderly = match secrets::load("TENDERLY_ACCESS_KEY").await? {
+        Some(access_key) => Some(Tenderly::new(
+            env::var("TENDERLY_ACCOUNT").context("TENDERLY_ACCOUNT not set")?,
+            env::var("TENDERLY_PROJECT").context("TENDERLY_PROJECT not set")?,
+            access_key,
+            env::var("TENDERLY_FROM")
+                .context("TENDERLY_FROM not set")?
+                .parse::<Address>()
+                .context("Invalid TENDERLY_FROM")?,
+        )),
+        None => None,
+    };
+    let database_path = env::var("DATABASE_PATH").unwrap_or_else(|_| "scanner.db".to_string());
+    let storage = Storage::open(&database_path)?;
+    let paper = PaperTrader::from_env(flash_loan_fee)?;
+    if let Some(paper) = &paper {
+        paper.restore(&storage)?;
+    }
+    let parquet_export_dir = env::var("PARQUET_EXPORT_DIR").ok().filter(|d| !d.is_empty());
+    let webhook = match secrets::load("OPPORTUNITY_WEBHOOK_URL").await? {
+        Some(url) => Some(WebhookSink {
+            url,
+            max_attempts: match env::var("WEBHOOK_MAX_ATTEMPTS").ok().filter(|s| !s.is_empty()) {
+                Some(value) => value.parse::<u32>().context("Invalid WEBHOOK_MAX_ATTEMPTS")?,
+                None => DEFAULT_WEBHOOK_MAX_ATTEMPTS,
+            },
+            dead_letter: env::var("WEBHOOK_DEAD_LETTER_PATH")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "webhook-dead-letter.jsonl".to_string())
+                .into(),
+        }),
+        None => None,
+    };
+    let api = match env::var("API_ADDR").ok().filter(|s| !s.is_empty()) {
+        Some(addr) => Some(ApiServer {
+            addr: addr.parse().context("Invalid API_ADDR")?,
+            keys: api::parse_keys(&secrets::require("API_KEYS").await?)?,
+            tls: api::tls_from_env()?,
+        }),
+        None => None,
+    };
+    let alert_cooldown = Duration::from_secs(
+        match env::var("ALERT_COOLDOWN_SECS").ok().filter(|s| !s.is_empty()) {
+            Some(value) => value.parse::<u64>().context("Invalid ALERT_COOLDOWN_SECS")?,
+            None => preset.alert_cooldown_secs,
+        },
+    );
+    let redis_url = secrets::load("REDIS_URL").await?;
+    let redis_prefix = env::var("REDIS_KEY_PREFIX").unwrap_or_else(|_| "arb-scanner".to_string());
+    let shared = match &redis_url {
+        Some(url) => {
+            println!("{}", "Connecting to Redis...".yellow());
+            SharedState::redis(url, &redis_prefix).await?
+        }
+        None => SharedState::local(),
+    };
+    let new_pool_alerts = env::var("NEW_POOL_ALERTS")
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false);
+    let ops_heartbeat_mins = match env::var("OPS_HEARTBEAT_MINS").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<u64>().context("Invalid OPS_HEARTBEAT_MINS")?,
+        None => DEFAULT_OPS_HEARTBEAT_MINS,
+    };
+    let new_pool_min_age = match env::var("NEW_POOL_MIN_AGE_SECS").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<i64>().context("Invalid NEW_POOL_MIN_AGE_SECS")?,
+        None => DEFAULT_NEW_POOL_MIN_AGE_SECS,
+    };
+    let leader_election = env::var("LEADER_ELECTION")
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false);
+    let roles = match env::var("TELEGRAM_ROLES").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<Roles>().context("Invalid TELEGRAM_ROLES")?,
+        None => Roles::default(),
+    };
+    let multi_tenant = env::var("MULTI_TENANT")
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false);
+    // Subscriptions outlive multi-tenant mode being off, but aren't served
+    let subscribers = if multi_tenant {
+        Subscribers::new(storage.subscriptions()?)
+    } else {
+        Subscribers::default()
+    };
+    let adaptive_thresholds = env::var("REGIME_ADAPTIVE_THRESHOLDS")
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false);
+    let shard = match env::var("SHARD").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<Shard>().context("Invalid SHARD")?,
+        None => Shard::default(),
+    };
+    if shard.is_partial() && redis_url.is_none() {
+        anyhow::bail!("SHARD requires REDIS_URL to funnel alerts to one notifier");
+    }
+    let leader = if leader_election {
+        let url = redis_url.as_deref().context("LEADER_ELECTION requires REDIS_URL")?;
+        let lease_secs = env::var("LEADER_LEASE_SECS")
+            .unwrap_or_else(|_| "15".to_string())
+            .parse::<u64>()
+            .context("Invalid LEADER_LEASE_SECS")?;
+        // The lease is renewed every third of it, so zero would spin
+        anyhow::ensure!(lease_secs > 0, "LEADER_LEASE_SECS must be at least 1");
+        let lease = Duration::from_secs(lease_secs);
+        Leader::elect(url, &redis_prefix, lease).await?
+    } else if shard.index == 0 {
+        Leader::always()
+    } else {
+        Leader::never()
+    };
+    let watched_wallets = env::var("WATCHED_WALLETS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<Address>())
+        .collect::<Result<Vec<_>, _>>()
+        .context("Invalid WATCHED_WALLETS")?;
+    let lp_positions = watcher::lp::parse_positions(&env::var("LP_POSITIONS").unwrap_or_default())
+        .context("Invalid LP_POSITIONS")?;
+    let lp_watcher = (!lp_positions.is_empty()).then(|| LpWatcher::new(lp_positions));
+    let run_id = Uuid::new_v4();
     
     println!("{}", "Connecting to Ethereum network...".yellow());
-    let provider = Provider::<Http>::try_from(rpc_url)
+    let provider = dex_scanner::connect(&rpc_url)
+        .await
         .context("Failed to connect to Ethereum network")?;
-    let provider = Arc::new(provider);
     
     println!("{}", "Initializing Telegram bot...".yellow());
     let bot = init_telegram().await?;
 
     // Prepare initialization message
-    let token_list = TOKENS.iter()
+    let token_list = preset.scanned_tokens()
         .map(|t| t.symbol)
         .collect::<Vec<_>>()
         .join(", ");
     
     // Log to console
     println!("\n{}", "Initialization Details:".bright_blue().bold());
-    println!("{} {}", "DEXes:".bright_yellow(), "Uniswap V2, Sushiswap".bright_white());
+    println!("{} {}", "Run:".bright_yellow(), run_id.to_string().bright_white());
+    println!("{} {}", "Preset:".bright_yellow(), preset.name.bright_white());
+    println!("{} {}", "DEXes:".bright_yellow(), preset.venue_names().bright_white());
     println!("{} {}", "Tokens:".bright_yellow(), token_list.bright_white());
-    println!("{} {}%\n", "Min Profit:".bright_yellow(), MIN_PROFIT_MARGIN * 100.0);
+    let min_profit = match min_profit_usd {
+        Some(usd) => format!("{} after gas", format_usd(usd)),
+        None => format_percent(preset.min_profit_margin),
+    };
+    println!("{} {}", "Min Profit:".bright_yellow(), min_profit);
+    for m in preset.pair_margins {
+        println!("{} {}/{} {}", "Pair Margin:".bright_yellow(), m.base, m.quote, format_percent(m.margin));
+    }
+    println!("{} {}", "Timezone:".bright_yellow(), locale.timezone_name());
+    println!("{} {}", "Min Pool TVL:".bright_yellow(), format_usd(min_tvl_usd));
+    if paper.is_some() {
+        println!("{} {}", "Paper trading:".bright_yellow(), env::var("PAPER_TRADING").unwrap_or_default());
+    }
+    println!();
     
     // Send to Telegram
     let startup_msg = format!(
         "🤖 <b>DEX Arbitrage Scanner Started</b>\n\n\
-        Monitoring:\n\
-        • Uniswap V2\n\
-        • Sushiswap\n\n\
+        Monitoring ({}):\n\
+        {}\n\n\
         Token Pairs:\n\
         <code>{}</code>\n\n\
-        Minimum Profit: <b>{}%</b>",
+        Minimum Profit: <b>{}</b>\n\
+        Run: <code>{}</code>",
+        preset.name,
+        preset
+            .venues
+            .iter()
+            .map(|v| format!("• {}", v.label()))
+            .collect::<Vec<_>>()
+            .join("\n"),
         token_list,
-        MIN_PROFIT_MARGIN * 100.0
+        min_profit,
+        run_id
     );
     
-    send_telegram_alert(&bot, chat_id, startup_msg).await?;
+    if leader.is_leader() {
+        for chat in &ops_chats {
+            send_telegram_alert(&bot, chat.id, startup_msg.clone()).await?;
+        }
+    } else if !leader_election {
+        println!("{}", "Queuing alerts for shard 0 to send".bright_yellow());
+    } else {
+        println!("{}", "Standing by: another replica holds the leader lease".bright_yellow());
+    }
+
+    let (alerts, alert_queue) = mpsc::channel(ALERT_QUEUE);
+    let ctx = Arc::new(ScanContext {
+        run_id,
+        provider,
+        node_events,
+        bot,
+        chats,
+        roles,
+        multi_tenant,
+        subscribers,
+        ops_chats,
+        started_at: chrono::Utc::now(),
+        pools: PoolIndex::default(),
+        metadata: MetadataCache::default(),
+        safety: SafetyCache::default(),
+        fees: FeeCache::default(),
+        gas: GasOracle::default(),
+        btc: BtcOracle::default(),
+        history: PriceHistory::default(),
+        regime: RegimeDetector::default(),
+        adaptive_thresholds,
+        btc_peg_threshold,
+        whale_min_usd,
+        gas_wallets,
+        gas_wallet_floor,
+        token_lists,
+        storage,
+        webhook: webhook.is_some(),
+        shared,
+        leader,
+        shard,
+        alert_cooldown,
+        tenderly,
+        etherscan_api_key,
+        min_tvl_usd,
+        flash_loan_fee,
+        min_profit_usd,
+        strategies,
+        alert_filter,
+        snapshot_retention,
+        lp_watcher,
+        paper,
+        acks: AckBoard::default(),
+        alerts,
+        new_pairs: Mutex::new(Vec::new()),
+        latest: RwLock::new(HashMap::new()),
+        events: api::events(),
+    });
+
+    tokio::spawn(deliver_alerts(Arc::clone(&ctx), alert_queue));
+    report::spawn(Arc::clone(&ctx), REPORT_INTERVAL);
+    if ops_heartbeat_mins > 0 {
+        println!("{} every {}m", "Heartbeat:".bright_yellow(), ops_heartbeat_mins);
+        tokio::spawn(heartbeat(Arc::clone(&ctx), Duration::from_secs(ops_heartbeat_mins * 60)));
+    }
+    if shard.is_partial() {
+        println!("{} {}", "Shard:".bright_yellow(), shard);
+        tokio::spawn(relay_queued_alerts(Arc::clone(&ctx)));
+    }
+    tokio::spawn(bot::run(Arc::clone(&ctx)));
+
+    if let Some(dir) = parquet_export_dir {
+        println!("{} {}", "Parquet export:".bright_yellow(), dir);
+        let ctx = Arc::clone(&ctx);
+        tokio::spawn(async move { storage::parquet::run(&ctx.storage, Path::new(&dir)).await });
+    }
+
+    if let Some(sink) = webhook {
+        println!("{} dead letters to {}", "Opportunity webhook:".bright_yellow(), sink.dead_letter.display());
+        let ctx = Arc::clone(&ctx);
+        tokio::spawn(async move { storage::webhook::run(&ctx.storage, sink).await });
+    }
+
+    if let Some(server) = api {
+        let scheme = if server.tls.is_some() { "https" } else { "http" };
+        println!("{} {}://{}/api", "REST API:".bright_yellow(), scheme, server.addr);
+        tokio::spawn(api::serve(Arc::clone(&ctx), server));
+    }
+
+    if !watched_wallets.is_empty() {
+        println!("{} {}", "Watching wallets:".bright_yellow(), watched_wallets.len());
+        let watcher = Arc::new(WalletWatcher::new(ctx.provider.clone(), watched_wallets));
+        watcher::wallet::spawn(watcher, Arc::clone(&ctx), WATCH_DIGEST_INTERVAL);
+    }
+
+    let sync = SyncMonitor {
+        ntp_server,
+        max_clock_skew: chrono::Duration::milliseconds(max_clock_skew),
+        max_block_lag,
+        etherscan_api_key: ctx.etherscan_api_key.clone(),
+    };
+    tokio::spawn(sync.run(Arc::clone(&ctx)));
+
+    if new_pool_alerts {
+        println!("{} on, scanned from {}s old with {} TVL",
+            "New pool alerts:".bright_yellow(),
+            new_pool_min_age,
+            format_usd(ctx.min_tvl_usd),
+        );
+        tokio::spawn(watcher::pools::watch(Arc::clone(&ctx), chrono::Duration::seconds(new_pool_min_age)));
+    }
 
-    monitor_swaps(provider, bot, chat_id).await?;
+    monitor_swaps(ctx).await?;
 
     Ok(())
 } 
\ No newline at end of file
@@ -0,0 +1,94 @@
+use alloy::primitives::Address;
+use once_cell::sync::Lazy;
+
+/// How a token carries its canonical asset onto the chain it's on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Issuance {
+    /// Minted on the chain by the asset's own issuer, e.g. Circle's USDC.
+    Native,
+    /// A claim on the asset held elsewhere: `via` is the bridge or
+    /// custodian that holds it, `from` the chain it's held on.
+    Bridged { via: &'static str, from: &'static str },
+}
+
+/// Which representation of an asset a token is. Two tokens with the same
+/// symbol on different chains, or different symbols on one, are only
+/// fungible when both are native; each bridge's wrapper carries that
+/// bridge's risk and redeems only through it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetFlavor {
+    /// The asset on its home chain, e.g. `"USDC"` for USDbC and USDC.e.
+    pub canonical: &'static str,
+    pub issuance: Issuance,
+}
+
+impl AssetFlavor {
+    pub fn is_bridged(&self) -> bool {
+        matches!(self.issuance, Issuance::Bridged { .. })
+    }
+}
+
+struct Entry {
+    chain_id: u64,
+    address: &'static str,
+    flavor: AssetFlavor,
+}
+
+const fn native(chain_id: u64, address: &'static str, canonical: &'static str) -> Entry {
+    Entry { chain_id, address, flavor: AssetFlavor { canonical, issuance: Issuance::Native } }
+}
+
+const fn bridged(
+    chain_id: u64,
+    address: &'static str,
+    canonical: &'static str,
+    via: &'static str,
+    from: &'static str,
+) -> Entry {
+    Entry { chain_id, address, flavor: AssetFlavor { canonical, issuance: Issuance::Bridged { via, from } } }
+}
+
+/// Known flavors of the presets' tokens. Wrapped native gas tokens and
+/// tokens only issued on one chain aren't listed, nor are tokens whose
+/// issuance isn't settled, e.g. a bridged stablecoin its issuer is
+/// migrating in place.
+const ENTRIES: &[Entry] = &[
+    native(1, "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", "USDC"),
+    native(1, "0xdAC17F958D2ee523a2206206994597C13D831ec7", "USDT"),
+    native(1, "0x6B175474E89094C44Da98b954EedeAC495271d0F", "DAI"),
+    bridged(1, "0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599", "BTC", "BitGo", "Bitcoin"),
+    native(8453, "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913", "USDC"),
+    bridged(8453, "0xd9aAEc86B65D86f6A7B5B1b0c42FFA531710b6CA", "USDC", "Base Bridge", "Ethereum"),
+    bridged(8453, "0x50c5725949A6F0c72E6C4a641F24049A917DB0Cb", "DAI", "Base Bridge", "Ethereum"),
+    bridged(8453, "0x2Ae3F1Ec7F1F5012CFEab0185bfc7aa3cf0DEc22", "cbETH", "Base Bridge", "Ethereum"),
+    bridged(8453, "0xc1CBa3fCea344f92D9239c08C0568f6F2F0ee452", "wstETH", "Base Bridge", "Ethereum"),
+    bridged(8453, "0xcbB7C0000aB88B473b1f5aFd9ef808440eed33Bf", "BTC", "Coinbase", "Bitcoin"),
+    native(324, "0x1d17CBcF0D6D143135aE902365D2E5e2A16538D4", "USDC"),
+    bridged(324, "0x3355df6D4c9C3035724Fd0e3914dE96A5a83aaf4", "USDC", "zkSync Bridge", "Ethereum"),
+    bridged(324, "0x493257fD37EDB34451f62EDf8D2a0C418852bA4C", "USDT", "zkSync Bridge", "Ethereum"),
+    bridged(324, "0xBBeB516fb02a01611cBBE0453Fe3c580D7281011", "WBTC", "zkSync Bridge", "Ethereum"),
+    bridged(59144, "0xA219439258ca9da29E9Cc4cE5596924745e12B93", "USDT", "Linea Bridge", "Ethereum"),
+    bridged(59144, "0x4AF15ec2A0BD43Db75dd04E62FAA3B8EF36b00d5", "DAI", "Linea Bridge", "Ethereum"),
+    bridged(59144, "0x3aAB2285ddcDdaD8edf438C1bAB47e1a9D05a9b4", "WBTC", "Linea Bridge", "Ethereum"),
+    native(43114, "0xB97EF9Ef8734C71904D8002F8b6Bc66Dd9c48a6E", "USDC"),
+    native(43114, "0x9702230A8Ea53601f5cD2dc00fDBc13d4dF4A8c7", "USDT"),
+    bridged(43114, "0x49D5c2BdFfac6CE2BFdB6640F4F80f226bc10bAB", "WETH", "Avalanche Bridge", "Ethereum"),
+    bridged(43114, "0x152b9d0FdC40C096757F570A51E494bd4b943E50", "BTC", "Avalanche Bridge", "Bitcoin"),
+    bridged(146, "0x29219dd400f2Bf60E5a23d13Be72B486D4038894", "USDC", "Sonic Gateway", "Ethereum"),
+    bridged(146, "0x50c42dEAcD8Fc9773493ED674b675bE577f2634b", "WETH", "Sonic Gateway", "Ethereum"),
+];
+
+static REGISTRY: Lazy<Vec<(u64, Address, AssetFlavor)>> = Lazy::new(|| {
+    ENTRIES
+        .iter()
+        .map(|entry| (entry.chain_id, entry.address.parse().unwrap(), entry.flavor))
+        .collect()
+});
+
+/// The flavor of the token at `address` on chain `chain_id`, if known.
+pub fn flavor(chain_id: u64, address: Address) -> Option<AssetFlavor> {
+    REGISTRY
+        .iter()
+        .find(|(chain, token, _)| *chain == chain_id && *token == address)
+        .map(|(_, _, flavor)| *flavor)
+}
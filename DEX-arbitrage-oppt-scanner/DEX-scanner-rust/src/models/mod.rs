@@ -0,0 +1,4 @@
+pub mod asset;
+pub mod opportunity;
+pub mod snapshot;
+pub mod token;
@@ -0,0 +1,261 @@
+use alloy::primitives::{keccak256, Address};
+use std::fmt;
+use uuid::Uuid;
+
+use crate::i18n::{self, Lang, Localize};
+use crate::models::token::TokenInfo;
+use crate::utils::price::{self, V2_FEE};
+use crate::utils::format::{format_amount, format_percent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Venue {
+    UniswapV2,
+    Sushiswap,
+    UniswapV3,
+    Aerodrome,
+    PancakeSwap,
+    SyncSwap,
+    TraderJoe,
+    TraderJoeLB,
+    SpookySwap,
+    Equalizer,
+}
+
+impl Venue {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Venue::UniswapV2 => "Uniswap V2",
+            Venue::Sushiswap => "Sushiswap",
+            Venue::UniswapV3 => "Uniswap V3",
+            Venue::Aerodrome => "Aerodrome",
+            Venue::PancakeSwap => "PancakeSwap",
+            Venue::SyncSwap => "SyncSwap",
+            Venue::TraderJoe => "Trader Joe",
+            Venue::TraderJoeLB => "Trader Joe LB",
+            Venue::SpookySwap => "SpookySwap",
+            Venue::Equalizer => "Equalizer",
+        }
+    }
+
+    /// Swap fee assumed until the pool's own fee has been read.
+    pub fn default_fee(&self) -> f64 {
+        match self {
+            Venue::UniswapV2 | Venue::Sushiswap | Venue::TraderJoe => V2_FEE,
+            // SpookySwap V2 pairs hard-code 0.2%
+            Venue::SpookySwap => 0.002,
+            // Most common V3 tier; real pools carry their own
+            Venue::UniswapV3 => 0.003,
+            // Default for volatile pools; the factory can override it per pool
+            Venue::Aerodrome | Venue::Equalizer => 0.003,
+            // PancakeSwap V2 pairs hard-code 0.25%
+            Venue::PancakeSwap => 0.0025,
+            // Classic pools' usual volatile fee; SyncSwap's fee manager can
+            // set others per pool
+            Venue::SyncSwap => 0.003,
+            // Base fee of a 20bp bin step pair until the pair's own is read
+            Venue::TraderJoeLB => 0.002,
+        }
+    }
+}
+
+impl fmt::Display for Venue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Reserves of one pool in whole-token units, oriented as base/quote.
+#[derive(Debug, Clone, Copy)]
+pub struct PoolState {
+    pub venue: Venue,
+    pub pool: Address,
+    pub reserve_base: f64,
+    pub reserve_quote: f64,
+    /// Swap fee as a fraction of the input, e.g. 0.003.
+    pub fee: f64,
+}
+
+impl PoolState {
+    /// `(reserve_base, reserve_quote)`, for telling whether the pool traded.
+    pub fn reserves(&self) -> (f64, f64) {
+        (self.reserve_base, self.reserve_quote)
+    }
+
+    /// The venue's name, with its fee tier for Uniswap V3, e.g.
+    /// `Uniswap V3 0.05%`, which tells two tiers of one pair apart.
+    pub fn venue_label(&self) -> String {
+        match self.venue {
+            Venue::UniswapV3 => format!("{} {}", self.venue.name(), format_percent(self.fee)),
+            _ => self.venue.name().to_string(),
+        }
+    }
+
+    /// Quote per base, e.g. USDC per WETH.
+    pub fn price(&self) -> f64 {
+        if self.reserve_base == 0.0 {
+            return 0.0;
+        }
+        self.reserve_quote / self.reserve_base
+    }
+
+    /// The pool the other way round, quote as base.
+    pub fn flipped(self) -> PoolState {
+        PoolState {
+            reserve_base: self.reserve_quote,
+            reserve_quote: self.reserve_base,
+            ..self
+        }
+    }
+}
+
+/// Where the spread goes at the chosen size, in quote units. The lines add
+/// up: `gross - dex_fees - price_impact - gas - flash_loan_fee = net`.
+#[derive(Debug, Clone, Copy)]
+pub struct Waterfall {
+    pub gross: f64,
+    pub dex_fees: f64,
+    pub price_impact: f64,
+    pub gas: Option<f64>,
+    pub flash_loan_fee: f64,
+    pub net: Option<f64>,
+}
+
+/// An actionable arbitrage: buy `base` with `quote` where it's cheap, sell it
+/// back where it's expensive. Amounts are for the profit-maximising size.
+#[derive(Debug, Clone)]
+pub struct Opportunity {
+    pub base: &'static TokenInfo,
+    pub quote: &'static TokenInfo,
+    pub buy: PoolState,
+    pub sell: PoolState,
+    /// Quote spent on the buy leg.
+    pub amount_in: f64,
+    /// Base received on the buy leg and sold on the sell leg.
+    pub base_amount: f64,
+    /// Quote received from the sell leg.
+    pub amount_out: f64,
+    /// Gas for the arbitrage transaction in quote units, if known.
+    pub gas_cost: Option<f64>,
+    /// Fee on borrowing `amount_in` through a flash loan, in quote units.
+    pub flash_loan_fee: f64,
+    /// Smallest quote input whose profit covers gas. `None` when gas is
+    /// unknown or no size covers it.
+    pub break_even: Option<f64>,
+    /// Token the buy leg routes through on its venue instead of swapping in
+    /// a pool of the pair, e.g. USDC for DAI → USDC → WETH.
+    pub buy_via: Option<&'static TokenInfo>,
+    /// Token the sell leg routes through, likewise.
+    pub sell_via: Option<&'static TokenInfo>,
+}
+
+impl Opportunity {
+    /// Orients the two pools into buy/sell legs and sizes the trade. Returns
+    /// `None` when swap fees eat the whole spread. `flash_loan_rate` is the
+    /// fee on the borrowed quote, e.g. 0.0005, or 0 when trading own capital.
+    pub fn evaluate(
+        base: &'static TokenInfo,
+        quote: &'static TokenInfo,
+        a: PoolState,
+        b: PoolState,
+        gas_cost: Option<f64>,
+        flash_loan_rate: f64,
+    ) -> Option<Opportunity> {
+        let (buy, sell) = if a.price() <= b.price() { (a, b) } else { (b, a) };
+
+        let amount_in = price::optimal_arb_input(
+            buy.reserve_quote,
+            buy.reserve_base,
+            sell.reserve_base,
+            sell.reserve_quote,
+            buy.fee,
+            sell.fee,
+        )?;
+        let base_amount = price::get_amount_out(amount_in, buy.reserve_quote, buy.reserve_base, buy.fee);
+        let amount_out = price::get_amount_out(base_amount, sell.reserve_base, sell.reserve_quote, sell.fee);
+
+        let profit_at = |input: f64| {
+            let bought = price::get_amount_out(input, buy.reserve_quote, buy.reserve_base, buy.fee);
+            price::get_amount_out(bought, sell.reserve_base, sell.reserve_quote, sell.fee)
+                - input
+                - input * flash_loan_rate
+        };
+        let break_even = gas_cost.and_then(|cost| price::break_even_input(profit_at, cost, amount_in));
+
+        Some(Opportunity {
+            base,
+            quote,
+            buy,
+            sell,
+            amount_in,
+            base_amount,
+            amount_out,
+            gas_cost,
+            flash_loan_fee: amount_in * flash_loan_rate,
+            break_even,
+            buy_via: None,
+            sell_via: None,
+        })
+    }
+
+    /// Identifies the opportunity across alerts, logs, storage and
+    /// webhooks. It's derived from the chain, block, pair and direction, so
+    /// finding the same opportunity again after a restart gives the same ID.
+    pub fn id(&self, chain: &str, block: u64) -> Uuid {
+        let key = format!(
+            "{}:{}:{:?}/{:?}:{:?}>{:?}",
+            chain, block, self.base.address, self.quote.address, self.buy.pool, self.sell.pool
+        );
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&keccak256(key)[..16]);
+        Uuid::new_v8(bytes)
+    }
+
+    /// Profit after swap fees and price impact, before gas and financing.
+    pub fn profit(&self) -> f64 {
+        self.amount_out - self.amount_in
+    }
+
+    /// Profit after gas and the flash-loan fee, if the gas cost is known.
+    pub fn net_profit(&self) -> Option<f64> {
+        self.gas_cost.map(|gas| self.profit() - gas - self.flash_loan_fee)
+    }
+
+    /// Splits the gap between the two prices at this size into what each
+    /// cost takes from it.
+    pub fn waterfall(&self) -> Waterfall {
+        // Trading `amount_in` at the two spot prices with no fees or impact
+        let gross = self.amount_in * (self.sell.price() / self.buy.price() - 1.0);
+        let dex_fees = self.amount_in * self.buy.fee + self.base_amount * self.sell.fee * self.sell.price();
+        Waterfall {
+            gross,
+            dex_fees,
+            // Whatever the fees don't explain is slippage along the curves
+            price_impact: gross - dex_fees - self.profit(),
+            gas: self.gas_cost,
+            flash_loan_fee: self.flash_loan_fee,
+            net: self.net_profit(),
+        }
+    }
+
+    /// e.g. `Buy WETH on Sushiswap at 2,410.00 USDC, sell on Uniswap V2 at 2,442.00 USDC`,
+    /// or `... sell on Uniswap V2 via DAI ...` for a routed leg. Uniswap V3
+    /// legs name their fee tier, e.g. `Uniswap V3 0.05%`.
+    pub fn direction(&self, lang: Lang) -> String {
+        let text = lang.catalog();
+        let venue = |pool: &PoolState, via: Option<&TokenInfo>| match via {
+            Some(token) => i18n::fill(text.via, &[("venue", &pool.venue_label()), ("token", token.symbol)]),
+            None => pool.venue_label(),
+        };
+        i18n::fill(
+            text.direction,
+            &[
+                ("base", self.base.symbol),
+                ("quote", self.quote.symbol),
+                ("buy_venue", &venue(&self.buy, self.buy_via)),
+                ("buy_price", &format_amount(self.buy.price())),
+                ("sell_venue", &venue(&self.sell, self.sell_via)),
+                ("sell_price", &format_amount(self.sell.price())),
+            ],
+        )
+    }
+}
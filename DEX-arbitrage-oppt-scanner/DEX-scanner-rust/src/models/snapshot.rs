@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use crate::enrich::btc::BtcReference;
+use crate::models::opportunity::PoolState;
+use crate::models::token::TokenInfo;
+
+/// One pair's pools as read at the snapshot's block.
+#[derive(Debug, Clone, Copy)]
+pub struct PairQuote {
+    pub base: &'static TokenInfo,
+    pub quote: &'static TokenInfo,
+    /// The pair's pools on the two venues it's compared across.
+    pub pools: [PoolState; 2],
+    /// Gas for an arbitrage on the pair in quote units, if known.
+    pub gas_cost: Option<f64>,
+    /// USD value of one quote token, if known.
+    pub quote_usd: Option<f64>,
+}
+
+/// The market as of one block, which every enabled strategy evaluates.
+/// Every pool's reserves are read at `block`; gas and reference prices come
+/// from their background refreshes and are taken as they stood right after
+/// that read, so they're the closest the scanner has to the same moment.
+#[derive(Debug, Clone)]
+pub struct MarketSnapshot {
+    pub block: u64,
+    pub read_at: DateTime<Utc>,
+    pub pairs: Vec<PairQuote>,
+    /// Gas price in gwei, if known.
+    pub gas_price_gwei: Option<f64>,
+    /// Flash-loan fee rate on the buy leg's input, 0 for own capital.
+    pub flash_loan_fee: f64,
+    /// Minimum profit in USD, after gas when it's known, that strategies
+    /// take in place of their margin. `None` to use margins.
+    pub min_profit_usd: Option<f64>,
+    /// Factor strategies scale their thresholds by, e.g. 1.5 while
+    /// thresholds adapt to a volatile market; 1 otherwise.
+    pub threshold_scale: f64,
+    pub btc: Option<BtcReference>,
+}
+
+impl MarketSnapshot {
+    /// The snapshot as archived, with tokens and pools by address.
+    pub fn to_json(&self) -> Value {
+        let pairs = self
+            .pairs
+            .iter()
+            .map(|pair| {
+                let pools = pair
+                    .pools
+                    .iter()
+                    .map(|pool| {
+                        json!({
+                            "venue": pool.venue.name(),
+                            "pool": format!("{:?}", pool.pool),
+                            "reserve_base": pool.reserve_base,
+                            "reserve_quote": pool.reserve_quote,
+                            "fee": pool.fee,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                json!({
+                    "base": format!("{:?}", pair.base.address),
+                    "quote": format!("{:?}", pair.quote.address),
+                    "pair": format!("{}/{}", pair.base.symbol, pair.quote.symbol),
+                    "pools": pools,
+                    "gas_cost": pair.gas_cost,
+                    "quote_usd": pair.quote_usd,
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "block": self.block,
+            "read_at": self.read_at.timestamp_millis(),
+            "gas_price_gwei": self.gas_price_gwei,
+            "flash_loan_fee": self.flash_loan_fee,
+            "min_profit_usd": self.min_profit_usd,
+            "threshold_scale": self.threshold_scale,
+            "btc_usd": self.btc.as_ref().map(|btc| btc.price_usd),
+            "pairs": pairs,
+        })
+    }
+}
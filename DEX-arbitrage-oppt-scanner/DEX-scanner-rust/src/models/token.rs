@@ -0,0 +1,367 @@
+use alloy::primitives::Address;
+use anyhow::{ensure, Result};
+use once_cell::sync::{Lazy, OnceCell};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::models::asset::{self, AssetFlavor};
+use crate::token_list::plain_symbol;
+
+#[derive(Debug, Clone)]
+pub struct TokenInfo {
+    pub address: Address,
+    pub symbol: &'static str,
+    pub decimals: u8,
+}
+
+impl TokenInfo {
+    pub fn is_usd_stable(&self) -> bool {
+        matches!(self.symbol, "USDC" | "USDbC" | "USDC.e" | "USDT" | "DAI" | "scUSD")
+    }
+
+    /// The asset the token tracks, if it's one of several that do, e.g.
+    /// `"BTC"` for WBTC and cbBTC. Pairs on such tokens tend to misprice
+    /// together.
+    pub fn tracks(&self) -> Option<&'static str> {
+        if self.is_usd_stable() {
+            return Some("USD");
+        }
+        match self.symbol {
+            "WETH" | "WETH.e" | "cbETH" | "wstETH" | "rETH" | "weETH" => Some("ETH"),
+            "WBTC" | "cbBTC" | "BTC.b" => Some("BTC"),
+            _ => None,
+        }
+    }
+
+    /// Which representation of its asset the token is on the active
+    /// preset's chain, if known.
+    pub fn flavor(&self) -> Option<AssetFlavor> {
+        asset::flavor(crate::preset::active().chain_id, self.address)
+    }
+}
+
+pub static ETHEREUM_TOKENS: Lazy<Vec<TokenInfo>> = Lazy::new(|| {
+    vec![
+        TokenInfo {
+            address: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".parse().unwrap(),
+            symbol: "WETH",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0x6B175474E89094C44Da98b954EedeAC495271d0F".parse().unwrap(),
+            symbol: "DAI",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".parse().unwrap(),
+            symbol: "USDC",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "0xdAC17F958D2ee523a2206206994597C13D831ec7".parse().unwrap(),
+            symbol: "USDT",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599".parse().unwrap(),
+            symbol: "WBTC",
+            decimals: 8,
+        },
+    ]
+});
+
+pub static BASE_TOKENS: Lazy<Vec<TokenInfo>> = Lazy::new(|| {
+    vec![
+        TokenInfo {
+            address: "0x4200000000000000000000000000000000000006".parse().unwrap(),
+            symbol: "WETH",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913".parse().unwrap(),
+            symbol: "USDC",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "0xd9aAEc86B65D86f6A7B5B1b0c42FFA531710b6CA".parse().unwrap(),
+            symbol: "USDbC",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "0x50c5725949A6F0c72E6C4a641F24049A917DB0Cb".parse().unwrap(),
+            symbol: "DAI",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0x2Ae3F1Ec7F1F5012CFEab0185bfc7aa3cf0DEc22".parse().unwrap(),
+            symbol: "cbETH",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0xcbB7C0000aB88B473b1f5aFd9ef808440eed33Bf".parse().unwrap(),
+            symbol: "cbBTC",
+            decimals: 8,
+        },
+        TokenInfo {
+            address: "0xc1CBa3fCea344f92D9239c08C0568f6F2F0ee452".parse().unwrap(),
+            symbol: "wstETH",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0x940181a94A35A4569E4529A3CDfB74e38FD98631".parse().unwrap(),
+            symbol: "AERO",
+            decimals: 18,
+        },
+    ]
+});
+
+pub static ZKSYNC_TOKENS: Lazy<Vec<TokenInfo>> = Lazy::new(|| {
+    vec![
+        TokenInfo {
+            address: "0x5AEa5775959fBC2557Cc8789bC1bf90A239D9a91".parse().unwrap(),
+            symbol: "WETH",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0x1d17CBcF0D6D143135aE902365D2E5e2A16538D4".parse().unwrap(),
+            symbol: "USDC",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "0x3355df6D4c9C3035724Fd0e3914dE96A5a83aaf4".parse().unwrap(),
+            symbol: "USDC.e",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "0x493257fD37EDB34451f62EDf8D2a0C418852bA4C".parse().unwrap(),
+            symbol: "USDT",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "0xBBeB516fb02a01611cBBE0453Fe3c580D7281011".parse().unwrap(),
+            symbol: "WBTC",
+            decimals: 8,
+        },
+        TokenInfo {
+            address: "0x5A7d6b2F92C77FAD6CCaBd7EE0624E64907Eaf3E".parse().unwrap(),
+            symbol: "ZK",
+            decimals: 18,
+        },
+    ]
+});
+
+pub static LINEA_TOKENS: Lazy<Vec<TokenInfo>> = Lazy::new(|| {
+    vec![
+        TokenInfo {
+            address: "0xe5D7C2a44FfDDf6b295A15c148167daaAf5Cf34f".parse().unwrap(),
+            symbol: "WETH",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0x176211869cA2b568f2A7D4EE941E073a821EE1ff".parse().unwrap(),
+            symbol: "USDC",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "0xA219439258ca9da29E9Cc4cE5596924745e12B93".parse().unwrap(),
+            symbol: "USDT",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "0x4AF15ec2A0BD43Db75dd04E62FAA3B8EF36b00d5".parse().unwrap(),
+            symbol: "DAI",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0x3aAB2285ddcDdaD8edf438C1bAB47e1a9D05a9b4".parse().unwrap(),
+            symbol: "WBTC",
+            decimals: 8,
+        },
+    ]
+});
+
+pub static AVALANCHE_TOKENS: Lazy<Vec<TokenInfo>> = Lazy::new(|| {
+    vec![
+        TokenInfo {
+            address: "0xB31f66AA3C1e785363F0875A1B74E27b85FD66c7".parse().unwrap(),
+            symbol: "WAVAX",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0xB97EF9Ef8734C71904D8002F8b6Bc66Dd9c48a6E".parse().unwrap(),
+            symbol: "USDC",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "0x9702230A8Ea53601f5cD2dc00fDBc13d4dF4A8c7".parse().unwrap(),
+            symbol: "USDT",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "0x49D5c2BdFfac6CE2BFdB6640F4F80f226bc10bAB".parse().unwrap(),
+            symbol: "WETH.e",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0x152b9d0FdC40C096757F570A51E494bd4b943E50".parse().unwrap(),
+            symbol: "BTC.b",
+            decimals: 8,
+        },
+        TokenInfo {
+            address: "0x6e84a6216eA6dACC71eE8E6b0a5B7322EEbC0fDd".parse().unwrap(),
+            symbol: "JOE",
+            decimals: 18,
+        },
+    ]
+});
+
+pub static SONIC_TOKENS: Lazy<Vec<TokenInfo>> = Lazy::new(|| {
+    vec![
+        TokenInfo {
+            address: "0x039e2fB66102314Ce7b64Ce5Ce3E5183bc94aD38".parse().unwrap(),
+            symbol: "wS",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0x29219dd400f2Bf60E5a23d13Be72B486D4038894".parse().unwrap(),
+            symbol: "USDC.e",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "0x50c42dEAcD8Fc9773493ED674b675bE577f2634b".parse().unwrap(),
+            symbol: "WETH",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0xd3DCe716f3eF535C5Ff8d041c1A41C3bd89b97aE".parse().unwrap(),
+            symbol: "scUSD",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "0xddF26B42C1d903De8962d3F79a74a501420d5F19".parse().unwrap(),
+            symbol: "EQUAL",
+            decimals: 18,
+        },
+        TokenInfo {
+            address: "0x7A0C53F7eb34C5BC8B01691723669adA9D6CB384".parse().unwrap(),
+            symbol: "BOO",
+            decimals: 18,
+        },
+    ]
+});
+
+/// Tokens added from token lists per chain ID, see [`register`]. Each
+/// registration replaces the chain's slice with a longer one and leaks it,
+/// so tokens handed out before stay valid; lists change rarely.
+static LISTED: Lazy<RwLock<HashMap<u64, &'static [TokenInfo]>>> = Lazy::new(Default::default);
+
+/// Tokens added from token lists on chain `chain_id`.
+pub fn listed(chain_id: u64) -> &'static [TokenInfo] {
+    LISTED.read().unwrap().get(&chain_id).copied().unwrap_or_default()
+}
+
+/// Adds `candidates` to chain `chain_id`'s tokens, next to `known` (its
+/// preset's), and returns the ones added. A token already known by address
+/// is skipped, and so is one whose symbol is taken, since tokens are looked
+/// up and pairs keyed by symbol.
+pub fn register(chain_id: u64, known: &[TokenInfo], candidates: Vec<TokenInfo>) -> Vec<&'static TokenInfo> {
+    let mut listed = LISTED.write().unwrap();
+    let current = listed.get(&chain_id).copied().unwrap_or_default();
+    let mut merged = Vec::new();
+    for candidate in candidates {
+        let taken = |t: &TokenInfo| t.address == candidate.address || t.symbol.eq_ignore_ascii_case(candidate.symbol);
+        if !known.iter().chain(current).chain(&merged).any(taken) {
+            merged.push(candidate);
+        }
+    }
+    if merged.is_empty() {
+        return Vec::new();
+    }
+    let added = merged.len();
+    let tokens: &'static [TokenInfo] = Vec::leak(current.iter().map(TokenInfo::clone).chain(merged).collect());
+    listed.insert(chain_id, tokens);
+    tokens[tokens.len() - added..].iter().collect()
+}
+
+/// Tokens of the active preset, then those added from token lists.
+pub fn tokens() -> impl Iterator<Item = &'static TokenInfo> {
+    let preset = crate::preset::active();
+    preset.tokens.iter().chain(listed(preset.chain_id))
+}
+
+pub fn get_token_info(address: &Address) -> Option<&'static TokenInfo> {
+    tokens().find(|t| &t.address == address)
+}
+
+/// Looks up a token of the active preset by symbol, ignoring case.
+pub fn find_by_symbol(symbol: &str) -> Option<&'static TokenInfo> {
+    tokens().find(|t| t.symbol.eq_ignore_ascii_case(symbol))
+}
+
+/// Symbols preferred as a pair's quote, most preferred first, replacing the
+/// default order. See [`set_quote_priority`].
+static QUOTE_PRIORITY: OnceCell<Vec<String>> = OnceCell::new();
+
+/// Sets the symbols preferred as a pair's quote, most preferred first,
+/// ignoring case. Tokens not listed are never preferred. Only the first
+/// call has an effect.
+pub fn set_quote_priority(symbols: Vec<String>) {
+    let _ = QUOTE_PRIORITY.set(symbols);
+}
+
+/// Parses `QUOTE_PRIORITY`: comma-separated symbols, most preferred as
+/// quote first, e.g. `USDC,USDT,WETH`.
+pub fn parse_quote_priority(value: &str) -> Result<Vec<String>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            ensure!(plain_symbol(s), "Invalid token symbol {:?}", s);
+            Ok(s.to_string())
+        })
+        .collect()
+}
+
+/// Where `token` ranks as a pair's quote, lowest first, or `None` if it's
+/// never preferred. By default USD stables come first, then the active
+/// preset's native token, then other ETH trackers, then BTC trackers.
+fn quote_rank(token: &TokenInfo) -> Option<usize> {
+    if let Some(symbols) = QUOTE_PRIORITY.get() {
+        return symbols.iter().position(|s| s.eq_ignore_ascii_case(token.symbol));
+    }
+    if token.is_usd_stable() {
+        return Some(0);
+    }
+    if token.symbol == crate::preset::active().native {
+        return Some(1);
+    }
+    match token.tracks() {
+        Some("ETH") => Some(2),
+        Some("BTC") => Some(3),
+        _ => None,
+    }
+}
+
+/// `a` and `b` as `(base, quote)`, the way their pair is priced and shown
+/// everywhere, e.g. WETH in USDC rather than USDC in WETH. The quote is the
+/// one ranked higher by the quote priority; with neither ranked higher, the
+/// lower address is the base, as in the pools.
+pub fn orient(a: &'static TokenInfo, b: &'static TokenInfo) -> (&'static TokenInfo, &'static TokenInfo) {
+    let rank = |token| quote_rank(token).unwrap_or(usize::MAX);
+    match rank(a).cmp(&rank(b)) {
+        Ordering::Less => (b, a),
+        Ordering::Greater => (a, b),
+        Ordering::Equal if a.address < b.address => (a, b),
+        Ordering::Equal => (b, a),
+    }
+}
+
+/// The wrapped native token of the active preset, which gas is paid in.
+pub fn weth() -> &'static TokenInfo {
+    let native = crate::preset::active().native;
+    tokens()
+        .find(|t| t.symbol == native)
+        .expect("native token missing from token list")
+}
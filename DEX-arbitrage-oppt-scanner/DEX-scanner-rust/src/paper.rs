@@ -0,0 +1,296 @@
+use alloy::providers::Provider;
+use anyhow::{ensure, Context, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+
+use crate::models::opportunity::{Opportunity, PoolState};
+use crate::models::token::find_by_symbol;
+use crate::preset;
+use crate::storage::{OpportunityRecord, PaperPnl, PaperTrade, Storage};
+use crate::utils::format::{clock, format_amount, format_percent, format_time, format_usd};
+use crate::utils::price;
+use crate::{MonitoredPair, ScanContext, BLOCK_POLL_INTERVAL};
+
+const DEFAULT_SLIPPAGE: f64 = 0.001;
+
+/// A simulated fill of an opportunity, in quote units.
+#[derive(Debug, Clone, Copy)]
+pub struct Fill {
+    pub size: f64,
+    pub proceeds: f64,
+    pub gas: f64,
+    pub flash_loan_fee: f64,
+    pub pnl: f64,
+    /// Virtual balance of the quote token after the fill.
+    pub balance: f64,
+    /// Whether the spread had closed by the fill, so the trade reverted
+    /// and only paid gas.
+    pub reverted: bool,
+}
+
+/// Paper-trading engine: "executes" every qualified opportunity against a
+/// virtual portfolio, so a strategy's profitability can be judged before
+/// real capital is at stake. Fills are re-quoted against the pools with
+/// extra slippage on each leg, and pay gas and any flash-loan fee.
+pub struct PaperTrader {
+    /// Adverse slippage on each leg's output, e.g. 0.001 for 0.1%.
+    slippage: f64,
+    /// Starting balances by token symbol.
+    starting: Vec<(&'static str, f64)>,
+    balances: Mutex<HashMap<&'static str, f64>>,
+    /// Flash-loan fee rate; when set, trades larger than the balance
+    /// borrow the whole size.
+    flash_loan_rate: f64,
+    /// Blocks from detection to inclusion; fills are priced at the pools'
+    /// state that many blocks after the one the opportunity was found in.
+    pub latency_blocks: u64,
+}
+
+impl PaperTrader {
+    /// Reads `PAPER_TRADING`, comma-separated `SYMBOL:amount` starting
+    /// balances, `PAPER_SLIPPAGE` and `PAPER_LATENCY_BLOCKS`. `None` when
+    /// paper trading is off.
+    pub fn from_env(flash_loan_rate: f64) -> Result<Option<Self>> {
+        let Some(value) = env::var("PAPER_TRADING").ok().filter(|s| !s.is_empty()) else {
+            return Ok(None);
+        };
+        let starting = value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|entry| {
+                let (symbol, amount) = entry
+                    .split_once(':')
+                    .with_context(|| format!("Expected SYMBOL:amount, got {:?}", entry))?;
+                let token = find_by_symbol(symbol.trim())
+                    .with_context(|| format!("Unknown token {}", symbol.trim()))?;
+                let amount = amount
+                    .trim()
+                    .parse::<f64>()
+                    .with_context(|| format!("Invalid amount {:?}", amount))?;
+                ensure!(amount >= 0.0, "Negative balance for {}", token.symbol);
+                Ok((token.symbol, amount))
+            })
+            .collect::<Result<Vec<_>>>()
+            .context("Invalid PAPER_TRADING")?;
+        let slippage = match env::var("PAPER_SLIPPAGE").ok().filter(|s| !s.is_empty()) {
+            Some(value) => value.parse::<f64>().context("Invalid PAPER_SLIPPAGE")?,
+            None => DEFAULT_SLIPPAGE,
+        };
+        ensure!((0.0..1.0).contains(&slippage), "PAPER_SLIPPAGE must be between 0 and 1");
+        let latency_blocks = match env::var("PAPER_LATENCY_BLOCKS").ok().filter(|s| !s.is_empty()) {
+            Some(value) => value.parse::<u64>().context("Invalid PAPER_LATENCY_BLOCKS")?,
+            None => 0,
+        };
+        Ok(Some(PaperTrader {
+            slippage,
+            balances: Mutex::new(starting.iter().copied().collect()),
+            starting,
+            flash_loan_rate,
+            latency_blocks,
+        }))
+    }
+
+    /// Brings balances forward by every paper trade recorded so far, so the
+    /// portfolio carries over restarts.
+    pub fn restore(&self, storage: &Storage) -> Result<()> {
+        let mut balances = self.balances.lock().unwrap();
+        for pnl in storage.paper_pnl(DateTime::<Utc>::UNIX_EPOCH)? {
+            if let Some(token) = find_by_symbol(&pnl.quote) {
+                *balances.entry(token.symbol).or_default() += pnl.pnl;
+            }
+        }
+        Ok(())
+    }
+
+    /// Simulates `opportunity` at its size, capped at the quote balance
+    /// unless flash loans are on, against the `buy` and `sell` pools as they
+    /// stand at inclusion, and books the result. A fill that would lose
+    /// money before gas reverts instead, as an arbitrage contract's profit
+    /// check would. `None` when gas can't be priced or there's nothing to
+    /// trade with.
+    pub fn execute(&self, opportunity: &Opportunity, buy: &PoolState, sell: &PoolState) -> Option<Fill> {
+        let gas = opportunity.gas_cost?;
+        let quote = opportunity.quote.symbol;
+        let mut balances = self.balances.lock().unwrap();
+        let balance = balances.get(quote).copied().unwrap_or_default();
+        let (size, flash_loan_fee) = if self.flash_loan_rate > 0.0 && balance < opportunity.amount_in {
+            (opportunity.amount_in, opportunity.amount_in * self.flash_loan_rate)
+        } else {
+            (opportunity.amount_in.min(balance), 0.0)
+        };
+        if size <= 0.0 {
+            return None;
+        }
+
+        let bought = price::get_amount_out(size, buy.reserve_quote, buy.reserve_base, buy.fee) * (1.0 - self.slippage);
+        let proceeds =
+            price::get_amount_out(bought, sell.reserve_base, sell.reserve_quote, sell.fee) * (1.0 - self.slippage);
+        let reverted = proceeds < size + flash_loan_fee;
+        let (proceeds, flash_loan_fee) = if reverted { (size, 0.0) } else { (proceeds, flash_loan_fee) };
+        let pnl = proceeds - size - gas - flash_loan_fee;
+        let balance = balance + pnl;
+        balances.insert(quote, balance);
+        Some(Fill { size, proceeds, gas, flash_loan_fee, pnl, balance, reverted })
+    }
+
+    /// Balances now against the start, and results per token since
+    /// `since`, for `/paper`.
+    pub fn summary(&self, storage: &Storage, since: DateTime<Utc>) -> Result<String> {
+        let balances = self.balances.lock().unwrap().clone();
+        let holdings = self
+            .starting
+            .iter()
+            .map(|(symbol, start)| {
+                let now = balances.get(symbol).copied().unwrap_or_default();
+                format!(
+                    "• <code>{} {}</code> (started {}, {})",
+                    format_amount(now),
+                    symbol,
+                    format_amount(*start),
+                    format_percent(if *start > 0.0 { now / start - 1.0 } else { 0.0 })
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let results = storage.paper_pnl(since)?;
+        let lines = if results.is_empty() {
+            "No trades yet.".to_string()
+        } else {
+            let total = results.iter().map(|r| r.pnl_usd).sum::<f64>();
+            let trades = results.iter().map(|r| r.trades).sum::<u64>();
+            let wins = results.iter().map(|r| r.wins).sum::<u64>();
+            format!(
+                "{}\nTotal: <b>{}</b>, win rate {}",
+                results.iter().map(format_pnl).collect::<Vec<_>>().join("\n"),
+                format_usd(total),
+                format_percent(wins as f64 / trades.max(1) as f64)
+            )
+        };
+        Ok(format!(
+            "📝 <b>Paper Portfolio</b>\n\n{}\n\n<b>Since {}</b>\n{}\n\nSlippage per leg: {}, latency: {} blocks",
+            holdings,
+            format_time(since),
+            lines,
+            format_percent(self.slippage),
+            self.latency_blocks
+        ))
+    }
+}
+
+/// Paper-trades `opportunity`, found at block `detected`, once its fill
+/// block is in, and stores the trade.
+pub async fn trade(
+    ctx: Arc<ScanContext>,
+    record: OpportunityRecord,
+    pair: MonitoredPair,
+    opportunity: Opportunity,
+    detected: u64,
+) {
+    let Some(paper) = &ctx.paper else {
+        return;
+    };
+    let filled = detected + paper.latency_blocks;
+    let (buy, sell) = if paper.latency_blocks == 0 {
+        (opportunity.buy, opportunity.sell)
+    } else {
+        match pools_at(&ctx, &pair, &opportunity, filled).await {
+            Ok(pools) => pools,
+            Err(e) => {
+                println!("{} Failed to read pools at block {} for paper trade {}: {}",
+                    "[ERROR]".bright_red(),
+                    filled,
+                    record.id,
+                    e,
+                );
+                return;
+            }
+        }
+    };
+    let Some(fill) = paper.execute(&opportunity, &buy, &sell) else {
+        return;
+    };
+
+    let quote = opportunity.quote.symbol;
+    println!("{} {} {} {} {} at block {}: {} for {} {}, PnL {} {} (balance {} {})",
+        "[PAPER]".bright_magenta(),
+        clock().bright_black(),
+        record.pair,
+        record.id,
+        if fill.reverted { "reverted" } else { "filled" },
+        filled,
+        format_amount(fill.size),
+        format_amount(fill.proceeds),
+        quote,
+        format_amount(fill.pnl),
+        quote,
+        format_amount(fill.balance),
+        quote,
+    );
+    let trade = PaperTrade {
+        opportunity_id: record.id,
+        run_id: record.run_id,
+        timestamp: record.timestamp,
+        pair: record.pair.clone(),
+        quote: quote.to_string(),
+        detected_block: detected,
+        filled_block: filled,
+        size: fill.size,
+        proceeds: fill.proceeds,
+        gas: fill.gas,
+        flash_loan_fee: fill.flash_loan_fee,
+        pnl: fill.pnl,
+        pnl_usd: ctx.gas.usd_value(fill.pnl, opportunity.quote),
+        balance: fill.balance,
+        reverted: fill.reverted,
+    };
+    if let Err(e) = ctx.storage.record_paper_trade(&trade) {
+        println!("{} Failed to store paper trade for {}: {}", "[ERROR]".bright_red(), record.id, e);
+    }
+}
+
+/// Both legs' pools as of `block`, once the node has it. A leg routed
+/// through another token isn't one pool of the pair, so can't be re-read.
+async fn pools_at(
+    ctx: &ScanContext,
+    pair: &MonitoredPair,
+    opportunity: &Opportunity,
+    block: u64,
+) -> Result<(PoolState, PoolState)> {
+    ensure!(
+        opportunity.buy_via.is_none() && opportunity.sell_via.is_none(),
+        "routed legs can't be re-read at a later block"
+    );
+    while ctx.provider.get_block_number().await? < block {
+        tokio::time::sleep(BLOCK_POLL_INTERVAL).await;
+    }
+    let pool_at = |leg: PoolState| async move {
+        let venue = preset::active()
+            .venues
+            .iter()
+            .find(|venue| venue.venue == leg.venue)
+            .with_context(|| format!("{} isn't a venue of the preset", leg.venue))?;
+        let reserves = venue
+            .read_reserves(&ctx.provider, leg.pool, pair.token0.address, Some(block))
+            .await?;
+        Ok::<_, anyhow::Error>(crate::to_pool_state(reserves, leg.pool, venue, pair, &ctx.fees))
+    };
+    tokio::try_join!(pool_at(opportunity.buy), pool_at(opportunity.sell))
+}
+
+/// e.g. `• USDC: +120.50 over 14 trades (10 won, 2 reverted), $120`
+pub fn format_pnl(pnl: &PaperPnl) -> String {
+    format!(
+        "• {}: <b>{}{}</b> over {} trades ({} won, {} reverted), {}",
+        pnl.quote,
+        if pnl.pnl >= 0.0 { "+" } else { "" },
+        format_amount(pnl.pnl),
+        pnl.trades,
+        pnl.wins,
+        pnl.reverted,
+        format_usd(pnl.pnl_usd)
+    )
+}
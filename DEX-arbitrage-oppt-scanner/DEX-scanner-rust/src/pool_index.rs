@@ -0,0 +1,52 @@
+//! Every scanned pool by address, shared by the tasks that come across a
+//! pool and need its pair: demultiplexed logs, alert lookups, watchers.
+
+use alloy::primitives::Address;
+use dashmap::DashMap;
+
+use crate::models::opportunity::Venue;
+use crate::models::token::TokenInfo;
+use crate::preset;
+use crate::MonitoredPair;
+
+/// One scanned pool: a pair it prices and the venue it's on. A pool is in
+/// as many pairs as there are other venues to compare it with; any of them
+/// stands for its tokens.
+#[derive(Debug, Clone, Copy)]
+pub struct PoolEntry {
+    pub pair: MonitoredPair,
+    pub venue: Venue,
+}
+
+impl PoolEntry {
+    /// The pair's tokens, base first.
+    pub fn tokens(&self) -> [&'static TokenInfo; 2] {
+        [self.pair.base(), self.pair.quote()]
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PoolIndex(DashMap<Address, PoolEntry>);
+
+impl PoolIndex {
+    /// Indexes both of `pair`'s pools.
+    pub fn insert(&self, pair: MonitoredPair) {
+        let venues = &preset::active().venues;
+        for (pool, venue) in [pair.pool_a, pair.pool_b].into_iter().zip(pair.venues) {
+            self.0.insert(pool, PoolEntry { pair, venue: venues[venue].venue });
+        }
+    }
+
+    pub fn get(&self, pool: &Address) -> Option<PoolEntry> {
+        self.0.get(pool).map(|entry| *entry)
+    }
+
+    pub fn contains(&self, pool: &Address) -> bool {
+        self.0.contains_key(pool)
+    }
+
+    /// Every pool and its entry, in no particular order.
+    pub fn entries(&self) -> Vec<(Address, PoolEntry)> {
+        self.0.iter().map(|entry| (*entry.key(), *entry.value())).collect()
+    }
+}
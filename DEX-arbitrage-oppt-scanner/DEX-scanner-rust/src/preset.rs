@@ -0,0 +1,605 @@
+use alloy::primitives::{Address, U256};
+use alloy::providers::bindings::IMulticall3::Call3;
+use alloy::rpc::types::BlockId;
+use alloy::sol;
+use alloy::sol_types::SolCall;
+use anyhow::{bail, ensure, Result};
+use once_cell::sync::{Lazy, OnceCell};
+
+use crate::models::opportunity::Venue;
+use crate::models::token::{
+    self, TokenInfo, AVALANCHE_TOKENS, BASE_TOKENS, ETHEREUM_TOKENS, LINEA_TOKENS, SONIC_TOKENS,
+    ZKSYNC_TOKENS,
+};
+use crate::utils::gas::{GasModel, ARB_GAS_UNITS};
+use crate::univ3;
+use crate::utils::format::format_percent;
+use crate::utils::liquidity_book;
+use crate::{IUniswapV2Factory, IUniswapV2Pair, RpcProvider};
+
+static ACTIVE: OnceCell<&'static Preset> = OnceCell::new();
+
+sol!(
+    #[sol(rpc)]
+    IAerodromeFactory,
+    "src/abis/IAerodromeFactory.json"
+);
+
+sol!(
+    #[sol(rpc)]
+    IEqualizerFactory,
+    "src/abis/IEqualizerFactory.json"
+);
+
+sol!(
+    #[sol(rpc)]
+    ISyncSwapFactory,
+    "src/abis/ISyncSwapFactory.json"
+);
+
+sol!(
+    #[sol(rpc)]
+    ISyncSwapPool,
+    "src/abis/ISyncSwapPool.json"
+);
+
+/// A venue's pool factory and, if it speaks the Uniswap V2 router ABI, its
+/// router.
+#[derive(Debug, Clone)]
+pub struct VenueConfig {
+    pub venue: Venue,
+    pub factory: Address,
+    pub router: Option<Address>,
+    /// Uniswap V3 fee tier scanned, in hundredths of a basis point, e.g.
+    /// 500 for 0.05%. The deepest of [`univ3::FEE_TIERS`] if `None`.
+    pub fee_tier: Option<u32>,
+}
+
+impl VenueConfig {
+    /// The venue's name, with its fee tier if it has one, e.g. `Uniswap V3
+    /// 0.05%`.
+    pub fn label(&self) -> String {
+        match self.fee_tier {
+            Some(fee_tier) => format!("{} {}", self.venue, format_percent(fee_tier as f64 / 1_000_000.0)),
+            None => self.venue.name().to_string(),
+        }
+    }
+
+    /// Address of the `token0`/`token1` pool, or zero if there is none. Only
+    /// volatile (x*y=k) Aerodrome and Equalizer pools are looked up, as
+    /// stable pools use a different curve; likewise `factory` must be SyncSwap's classic pool
+    /// factory. Trader Joe LB has a pair per bin step, of which the one
+    /// holding the most `token1` is used, and Uniswap V3 a pool per fee
+    /// tier, see [`univ3::find_pool`].
+    pub async fn get_pool(
+        &self,
+        provider: &RpcProvider,
+        token0: Address,
+        token1: Address,
+    ) -> Result<Address> {
+        let pool = match self.venue {
+            Venue::Aerodrome => {
+                IAerodromeFactory::new(self.factory, provider)
+                    .getPool(token0, token1, false)
+                    .call()
+                    .await?
+            }
+            Venue::Equalizer => {
+                IEqualizerFactory::new(self.factory, provider)
+                    .getPair(token0, token1, false)
+                    .call()
+                    .await?
+            }
+            Venue::SyncSwap => {
+                ISyncSwapFactory::new(self.factory, provider)
+                    .getPool(token0, token1)
+                    .call()
+                    .await?
+            }
+            Venue::TraderJoeLB => {
+                liquidity_book::find_pair(provider, self.factory, token0, token1).await?
+            }
+            Venue::UniswapV3 => univ3::find_pool(provider, self.factory, token0, token1, self.fee_tier).await?,
+            _ => {
+                IUniswapV2Factory::new(self.factory, provider)
+                    .getPair(token0, token1)
+                    .call()
+                    .await?
+            }
+        };
+        Ok(pool)
+    }
+
+    /// The `getReserves` call for one of this venue's pools, for a
+    /// Multicall3 batch, to be read with [`reserves_of`]. SyncSwap pools
+    /// return just the two reserves, V2 pairs a timestamp as well, under
+    /// the same selector. Trader Joe LB pairs and Uniswap V3 pools take
+    /// more than one call and are read with [`VenueConfig::read_reserves`]
+    /// instead.
+    pub fn reserves_call(&self, pool: Address) -> Result<Call3> {
+        if !self.batchable() {
+            bail!("{} pools can't be read in a single call", self.venue.name());
+        }
+        Ok(Call3 {
+            target: pool,
+            allowFailure: false,
+            callData: IUniswapV2Pair::getReservesCall {}.abi_encode().into(),
+        })
+    }
+
+    /// Whether pools of this venue can go in a multicall batch.
+    pub fn batchable(&self) -> bool {
+        !matches!(self.venue, Venue::TraderJoeLB | Venue::UniswapV3)
+    }
+
+    /// `(reserve0, reserve1)` of `pool`, whose first token is `token0`, at
+    /// `block` or the latest one. For Trader Joe LB these are the active
+    /// bin's virtual reserves, and for Uniswap V3 those of the liquidity
+    /// at the current price.
+    pub async fn read_reserves(
+        &self,
+        provider: &RpcProvider,
+        pool: Address,
+        token0: Address,
+        block: Option<u64>,
+    ) -> Result<(U256, U256)> {
+        match self.venue {
+            Venue::TraderJoeLB => return liquidity_book::read_reserves(provider, pool, token0, block).await,
+            Venue::UniswapV3 => return univ3::read_reserves(provider, pool, block).await,
+            _ => {}
+        }
+        let block = block.map_or(BlockId::latest(), BlockId::number);
+        match self.venue {
+            Venue::SyncSwap => {
+                let reserves = ISyncSwapPool::new(pool, provider).getReserves().block(block).call().await?;
+                Ok((reserves._reserve0, reserves._reserve1))
+            }
+            _ => {
+                let reserves = IUniswapV2Pair::new(pool, provider).getReserves().block(block).call().await?;
+                Ok((U256::from(reserves.reserve0), U256::from(reserves.reserve1)))
+            }
+        }
+    }
+}
+
+/// `(reserve0, reserve1)` from the return data of a
+/// [`VenueConfig::reserves_call`], the first two words for every venue.
+pub fn reserves_of(data: &[u8]) -> Result<(U256, U256)> {
+    ensure!(data.len() >= 64, "unexpected getReserves output 0x{}", alloy::hex::encode(data));
+    Ok((U256::from_be_slice(&data[..32]), U256::from_be_slice(&data[32..64])))
+}
+
+/// Minimum profit margin for one pair, in place of the preset's.
+#[derive(Debug, Clone, Copy)]
+pub struct PairMargin {
+    pub base: &'static str,
+    pub quote: &'static str,
+    pub margin: f64,
+}
+
+/// Ready-made configuration for scanning one chain: the tokens, the
+/// venues compared, and thresholds suited to the chain's gas costs.
+/// Strategy profiles are presets narrowed to a few tokens with their own
+/// thresholds.
+#[derive(Clone)]
+pub struct Preset {
+    pub name: &'static str,
+    /// Chain name recorded with opportunities.
+    pub chain: &'static str,
+    pub chain_id: u64,
+    /// GeckoTerminal network slug for pool metadata.
+    pub gecko_network: &'static str,
+    /// DefiLlama chain slug, for pricing pools GeckoTerminal doesn't list.
+    pub llama_chain: &'static str,
+    /// Every known token on the chain.
+    pub tokens: &'static [TokenInfo],
+    /// Symbol of the wrapped native token, which gas is priced in.
+    pub native: &'static str,
+    /// Symbols to scan, all of `tokens` if `None`.
+    pub symbols: Option<&'static [&'static str]>,
+    /// Venues compared, each against every other, so at least two. Each
+    /// Uniswap V3 fee tier scanned is a venue of its own.
+    pub venues: Vec<VenueConfig>,
+    /// Multicall3 address, when the chain doesn't have it at the usual one.
+    pub multicall: Option<Address>,
+    pub gas: GasModel,
+    pub min_profit_margin: f64,
+    /// Pairs with their own minimum profit margin, in either order.
+    pub pair_margins: &'static [PairMargin],
+    pub min_tvl_usd: f64,
+    pub alert_cooldown_secs: u64,
+}
+
+/// Names of the chain presets `Preset::by_name` knows.
+pub const CHAINS: &[&str] = &["ethereum", "base", "zksync", "linea", "avalanche", "sonic"];
+
+/// Names of the strategy profiles `Preset::strategy` knows.
+pub const STRATEGIES: &[&str] = &["eth-majors", "stable-depeg", "lst-basis", "uni-v2-v3", "uni-v3-tiers"];
+
+impl Preset {
+    pub fn by_name(name: &str) -> Result<&'static Preset> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "ethereum" | "mainnet" => Ok(&ETHEREUM),
+            "base" => Ok(&BASE),
+            "zksync" | "zksync-era" => Ok(&ZKSYNC),
+            "linea" => Ok(&LINEA),
+            "avalanche" | "avax" => Ok(&AVALANCHE),
+            "sonic" => Ok(&SONIC),
+            other => bail!(
+                "Unknown preset {:?} (expected ethereum, base, zksync, linea, avalanche or sonic)",
+                other
+            ),
+        }
+    }
+
+    pub fn strategy(name: &str) -> Result<&'static Preset> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "eth-majors" => Ok(&ETH_MAJORS),
+            "stable-depeg" => Ok(&STABLE_DEPEG),
+            "lst-basis" => Ok(&LST_BASIS),
+            "uni-v2-v3" => Ok(&UNI_V2_V3),
+            "uni-v3-tiers" => Ok(&UNI_V3_TIERS),
+            "solana-memes" => bail!("solana-memes runs on the Solana scanner (solana-dex-scanner)"),
+            other => bail!(
+                "Unknown strategy {:?} (expected eth-majors, stable-depeg, lst-basis, uni-v2-v3 or uni-v3-tiers)",
+                other
+            ),
+        }
+    }
+
+    /// The tokens scanned: `symbols` of `tokens`, or all of them and the
+    /// chain's tokens from token lists.
+    pub fn scanned_tokens(&self) -> impl Iterator<Item = &'static TokenInfo> {
+        let symbols = self.symbols;
+        let listed = if symbols.is_none() { token::listed(self.chain_id) } else { &[] };
+        self.tokens
+            .iter()
+            .filter(move |t| match symbols {
+                Some(symbols) => symbols.contains(&t.symbol),
+                None => true,
+            })
+            .chain(listed)
+    }
+
+    /// The pair's own minimum profit margin, if it has one.
+    pub fn pair_margin(&self, base: &TokenInfo, quote: &TokenInfo) -> Option<f64> {
+        self.pair_margins
+            .iter()
+            .find(|m| {
+                (m.base == base.symbol && m.quote == quote.symbol) || (m.base == quote.symbol && m.quote == base.symbol)
+            })
+            .map(|m| m.margin)
+    }
+
+    pub fn router(&self, venue: Venue) -> Option<Address> {
+        self.venues
+            .iter()
+            .find(|v| v.venue == venue)
+            .and_then(|v| v.router)
+    }
+
+    pub fn venue_names(&self) -> String {
+        self.venues.iter().map(VenueConfig::label).collect::<Vec<_>>().join(", ")
+    }
+}
+
+fn address(hex: &str) -> Address {
+    hex.parse().expect("invalid preset address")
+}
+
+const ETHEREUM_UNISWAP_V3_FACTORY: &str = "0x1F98431c8aD98523631AE4a59f267346ea31F984";
+const BASE_UNISWAP_V3_FACTORY: &str = "0x33128a8fC17869897dcE68Ed026d694621f6FDfD";
+
+/// A venue per Uniswap V3 fee tier in [`univ3::FEE_TIERS`], as V3 has a
+/// pool per tier and they drift apart.
+fn uniswap_v3_tiers(factory: &'static str) -> impl Iterator<Item = VenueConfig> {
+    univ3::FEE_TIERS.into_iter().map(move |fee_tier| VenueConfig {
+        venue: Venue::UniswapV3,
+        factory: address(factory),
+        router: None,
+        fee_tier: Some(fee_tier),
+    })
+}
+
+fn ethereum() -> Preset {
+    Preset {
+        name: "ethereum",
+        chain: "ethereum",
+        chain_id: 1,
+        gecko_network: "eth",
+        llama_chain: "ethereum",
+        tokens: ETHEREUM_TOKENS.as_slice(),
+        native: "WETH",
+        symbols: None,
+        venues: vec![
+            VenueConfig {
+                venue: Venue::UniswapV2,
+                factory: address("0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f"),
+                router: Some(address("0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D")),
+                fee_tier: None,
+            },
+            VenueConfig {
+                venue: Venue::Sushiswap,
+                factory: address("0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac"),
+                router: Some(address("0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F")),
+                fee_tier: None,
+            },
+        ]
+        .into_iter()
+        .chain(uniswap_v3_tiers(ETHEREUM_UNISWAP_V3_FACTORY))
+        .collect(),
+        multicall: None,
+        gas: GasModel::Execution { units: ARB_GAS_UNITS },
+        min_profit_margin: 0.01,
+        pair_margins: &[],
+        min_tvl_usd: 0.0,
+        alert_cooldown_secs: 60,
+    }
+}
+
+// Gas on Base is cents, so thinner spreads are worth alerting on, but long
+// tail pools there are often too shallow to trade
+fn base() -> Preset {
+    Preset {
+        name: "base",
+        chain: "base",
+        chain_id: 8453,
+        gecko_network: "base",
+        llama_chain: "base",
+        tokens: BASE_TOKENS.as_slice(),
+        native: "WETH",
+        symbols: None,
+        venues: vec![
+            VenueConfig {
+                venue: Venue::UniswapV2,
+                factory: address("0x8909Dc15e40173Ff4699343b6eB8132c65e18eC6"),
+                router: Some(address("0x4752ba5DBc23f44D87826276BF6Fd6b1C372aD24")),
+                fee_tier: None,
+            },
+            VenueConfig {
+                venue: Venue::Aerodrome,
+                factory: address("0x420DD381b31aEf6683db6B902084cB0FFECe40Da"),
+                // Aerodrome's router takes routes rather than token paths
+                router: None,
+                fee_tier: None,
+            },
+        ]
+        .into_iter()
+        .chain(uniswap_v3_tiers(BASE_UNISWAP_V3_FACTORY))
+        .collect(),
+        multicall: None,
+        gas: GasModel::Execution { units: ARB_GAS_UNITS },
+        min_profit_margin: 0.005,
+        pair_margins: &[],
+        min_tvl_usd: 50_000.0,
+        alert_cooldown_secs: 60,
+    }
+}
+
+// SyncSwap holds most zkSync liquidity, PancakeSwap's V2 deployment is the
+// V2-compatible counterpart. Gas is cheap but most of an arb's cost is the
+// pubdata its state diffs publish to L1.
+fn zksync() -> Preset {
+    Preset {
+        name: "zksync",
+        chain: "zksync",
+        chain_id: 324,
+        gecko_network: "zksync",
+        llama_chain: "era",
+        tokens: ZKSYNC_TOKENS.as_slice(),
+        native: "WETH",
+        symbols: None,
+        venues: vec![
+            VenueConfig {
+                venue: Venue::PancakeSwap,
+                factory: address("0xd03D8D566183F0086d8D09A84E1e30b58Dd5619d"),
+                router: Some(address("0x5aEaF2883FBf30f3D62471154eDa3C0c1b05942d")),
+                fee_tier: None,
+            },
+            VenueConfig {
+                venue: Venue::SyncSwap,
+                // Classic (x*y=k) pool factory
+                factory: address("0xf2DAd89f2788a8CD54625C60b55cD3d2D0ACa7Cb"),
+                // SyncSwap's router takes swap paths of pool steps
+                router: None,
+                fee_tier: None,
+            },
+        ],
+        // zkSync's own Multicall3 deployment, not the usual CREATE2 address
+        multicall: Some(address("0xF9cda624FBC7e059355ce98a31693d299FACd963")),
+        // zkSync meters execution in its own gas units, so these are rough
+        gas: GasModel::ZkSync {
+            units: 800_000,
+            pubdata_bytes: 400,
+        },
+        min_profit_margin: 0.005,
+        pair_margins: &[],
+        min_tvl_usd: 25_000.0,
+        alert_cooldown_secs: 60,
+    }
+}
+
+fn linea() -> Preset {
+    Preset {
+        name: "linea",
+        chain: "linea",
+        chain_id: 59144,
+        gecko_network: "linea",
+        llama_chain: "linea",
+        tokens: LINEA_TOKENS.as_slice(),
+        native: "WETH",
+        symbols: None,
+        venues: vec![
+            VenueConfig {
+                venue: Venue::PancakeSwap,
+                factory: address("0x02a84c1b3BBD7401a5f7fa98a384EBC70bB5749E"),
+                router: Some(address("0x8cFe327CEc66d1C090Dd72bd0FF11d690C33a2Eb")),
+                fee_tier: None,
+            },
+            VenueConfig {
+                venue: Venue::SyncSwap,
+                // Classic (x*y=k) pool factory
+                factory: address("0x37BAc764494c8db4e54BDE72f6965beA9fa0AC2d"),
+                router: None,
+                fee_tier: None,
+            },
+        ],
+        multicall: Some(address("0xcA11bde05977b3631167028862bE2a173976CA11")),
+        gas: GasModel::Execution { units: ARB_GAS_UNITS },
+        min_profit_margin: 0.005,
+        pair_margins: &[],
+        min_tvl_usd: 25_000.0,
+        alert_cooldown_secs: 60,
+    }
+}
+
+// Trader Joe's V1 AMM against its Liquidity Book, where most Avalanche
+// volume has moved. LB pools are priced from their active bin alone.
+fn avalanche() -> Preset {
+    Preset {
+        name: "avalanche",
+        chain: "avalanche",
+        chain_id: 43114,
+        gecko_network: "avax",
+        llama_chain: "avax",
+        tokens: AVALANCHE_TOKENS.as_slice(),
+        native: "WAVAX",
+        symbols: None,
+        venues: vec![
+            VenueConfig {
+                venue: Venue::TraderJoe,
+                factory: address("0x9Ad6C38BE94206cA50bb0d90783181662f0Cfa10"),
+                router: Some(address("0x60aE616a2155Ee3d9A68541Ba4544862310933d4")),
+                fee_tier: None,
+            },
+            VenueConfig {
+                venue: Venue::TraderJoeLB,
+                // LB v2.1 factory
+                factory: address("0x8e42f2F4101563bF679975178e880FD87d3eFd4e"),
+                // The LB router takes bin steps and versions alongside paths
+                router: None,
+                fee_tier: None,
+            },
+        ],
+        multicall: None,
+        gas: GasModel::Execution { units: ARB_GAS_UNITS },
+        min_profit_margin: 0.005,
+        pair_margins: &[],
+        min_tvl_usd: 25_000.0,
+        alert_cooldown_secs: 60,
+    }
+}
+
+// Sonic, Fantom's successor chain. Fewer searchers compete there, so
+// spreads stay open longer, but pools are shallow.
+fn sonic() -> Preset {
+    Preset {
+        name: "sonic",
+        chain: "sonic",
+        chain_id: 146,
+        gecko_network: "sonic",
+        llama_chain: "sonic",
+        tokens: SONIC_TOKENS.as_slice(),
+        native: "wS",
+        symbols: None,
+        venues: vec![
+            VenueConfig {
+                venue: Venue::SpookySwap,
+                factory: address("0xEE4bC42157cf65291Ba2FE839AE127e3Cc76f741"),
+                router: Some(address("0xF5F7231073b3B41c04BA655e1a7438b1a7b29c27")),
+                fee_tier: None,
+            },
+            VenueConfig {
+                venue: Venue::Equalizer,
+                factory: address("0xDDD9845Ba0D8f38d3045f804f67A1a8B9A528FcC"),
+                // Equalizer's router takes Solidly-style routes
+                router: None,
+                fee_tier: None,
+            },
+        ],
+        multicall: Some(address("0xcA11bde05977b3631167028862bE2a173976CA11")),
+        gas: GasModel::Execution { units: ARB_GAS_UNITS },
+        min_profit_margin: 0.005,
+        pair_margins: &[],
+        min_tvl_usd: 10_000.0,
+        alert_cooldown_secs: 60,
+    }
+}
+
+static ETHEREUM: Lazy<Preset> = Lazy::new(ethereum);
+static BASE: Lazy<Preset> = Lazy::new(base);
+static ZKSYNC: Lazy<Preset> = Lazy::new(zksync);
+static LINEA: Lazy<Preset> = Lazy::new(linea);
+static AVALANCHE: Lazy<Preset> = Lazy::new(avalanche);
+static SONIC: Lazy<Preset> = Lazy::new(sonic);
+
+// Deep majors only: spreads are thin, so alert on less but insist on depth
+static ETH_MAJORS: Lazy<Preset> = Lazy::new(|| Preset {
+    name: "eth-majors",
+    symbols: Some(&["WETH", "WBTC", "USDC", "USDT", "DAI"]),
+    min_profit_margin: 0.005,
+    min_tvl_usd: 1_000_000.0,
+    alert_cooldown_secs: 300,
+    ..ethereum()
+});
+
+// Stablecoin pairs should trade at par, so a small gap is already a signal.
+// Depegs last for minutes to hours, hence the long cooldown.
+static STABLE_DEPEG: Lazy<Preset> = Lazy::new(|| Preset {
+    name: "stable-depeg",
+    symbols: Some(&["USDC", "USDT", "DAI"]),
+    min_profit_margin: 0.003,
+    min_tvl_usd: 250_000.0,
+    alert_cooldown_secs: 900,
+    ..ethereum()
+});
+
+// Liquid staking tokens against WETH on Base, where Aerodrome holds the
+// deepest LST liquidity and gas doesn't eat the basis
+static LST_BASIS: Lazy<Preset> = Lazy::new(|| Preset {
+    name: "lst-basis",
+    symbols: Some(&["WETH", "cbETH", "wstETH"]),
+    min_profit_margin: 0.003,
+    min_tvl_usd: 100_000.0,
+    alert_cooldown_secs: 600,
+    ..base()
+});
+
+// Majors on Uniswap V2 against each Uniswap V3 fee tier: V3 reprices
+// first, and V2 pairs lag behind it
+static UNI_V2_V3: Lazy<Preset> = Lazy::new(|| {
+    let ethereum = ethereum();
+    Preset {
+        name: "uni-v2-v3",
+        symbols: Some(&["WETH", "WBTC", "USDC", "USDT", "DAI"]),
+        venues: std::iter::once(ethereum.venues[0].clone())
+            .chain(uniswap_v3_tiers(ETHEREUM_UNISWAP_V3_FACTORY))
+            .collect(),
+        min_profit_margin: 0.005,
+        min_tvl_usd: 1_000_000.0,
+        alert_cooldown_secs: 300,
+        ..ethereum
+    }
+});
+
+// Majors across the Uniswap V3 fee tiers of the same pair, each tier
+// against the others. Arbitrageurs keep these close, so only a thin spread
+// is expected.
+static UNI_V3_TIERS: Lazy<Preset> = Lazy::new(|| Preset {
+    name: "uni-v3-tiers",
+    symbols: Some(&["WETH", "WBTC", "USDC", "USDT", "DAI"]),
+    venues: uniswap_v3_tiers(ETHEREUM_UNISWAP_V3_FACTORY).collect(),
+    min_profit_margin: 0.003,
+    min_tvl_usd: 1_000_000.0,
+    alert_cooldown_secs: 300,
+    ..ethereum()
+});
+
+pub fn init(preset: &'static Preset) {
+    let _ = ACTIVE.set(preset);
+}
+
+/// The preset selected at startup, Ethereum mainnet if none was.
+pub fn active() -> &'static Preset {
+    ACTIVE.get_or_init(|| &ETHEREUM)
+}
@@ -0,0 +1,84 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use colored::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::heatmap;
+use crate::paper;
+use crate::storage::Storage;
+use crate::utils::format::{format_percent, format_time, format_usd};
+use crate::ScanContext;
+
+fn build_report(storage: &Storage, from: DateTime<Utc>, to: DateTime<Utc>, paper: bool) -> Result<Option<String>> {
+    let stats = storage.profit_by_venue_pair(from)?;
+    if stats.is_empty() {
+        return Ok(None);
+    }
+
+    let total_count: u64 = stats.iter().map(|s| s.count).sum();
+    let total_profit: f64 = stats.iter().map(|s| s.profit_usd).sum();
+    let lines = stats
+        .iter()
+        .map(|s| {
+            format!(
+                "• <code>{}</code>: <b>{}</b> over {} opps (max spread {})",
+                s.venues,
+                format_usd(s.profit_usd),
+                s.count,
+                format_percent(s.max_spread)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut report = format!(
+        "📊 <b>Daily Report</b>\n\
+        <i>{} – {}</i>\n\n\
+        Opportunities: <b>{}</b>\n\
+        Theoretical Profit: <b>{}</b> (before gas)\n\n\
+        <b>Profit by Venue Pair</b>\n{}",
+        format_time(from),
+        format_time(to),
+        total_count,
+        format_usd(total_profit),
+        lines
+    );
+    if paper {
+        let pnl = storage.paper_pnl(from)?;
+        if !pnl.is_empty() {
+            report.push_str("\n\n<b>Paper Trading</b>\n");
+            report.push_str(&pnl.iter().map(paper::format_pnl).collect::<Vec<_>>().join("\n"));
+        }
+    }
+    Ok(Some(report))
+}
+
+/// Sends a report covering the previous `interval` every `interval`, followed
+/// by a heatmap of average spread by pair and hour.
+pub fn spawn(ctx: Arc<ScanContext>, interval: Duration) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let window = ChronoDuration::from_std(interval).unwrap_or(ChronoDuration::days(1));
+            let to = Utc::now();
+            let from = to - window;
+            match build_report(&ctx.storage, from, to, ctx.paper.is_some()) {
+                Ok(Some(message)) => ctx.broadcast(&message).await,
+                Ok(None) => continue,
+                Err(e) => println!("{} Failed to build report: {}", "[ERROR]".bright_red(), e),
+            }
+
+            let png = ctx
+                .storage
+                .opportunities_between(from, to)
+                .and_then(|records| heatmap::render(&records));
+            match png {
+                Ok(Some(png)) => ctx.broadcast_photo(png, "Average spread by pair and hour").await,
+                Ok(None) => {}
+                Err(e) => println!("{} Failed to render spread heatmap: {}", "[ERROR]".bright_red(), e),
+            }
+        }
+    });
+}
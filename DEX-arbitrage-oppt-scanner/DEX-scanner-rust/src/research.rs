@@ -0,0 +1,311 @@
+use alloy::primitives::Address;
+use alloy::providers::Provider;
+use anyhow::{bail, ensure, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::Args;
+use colored::*;
+use futures::{StreamExt, TryStreamExt};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use crate::enrich::fees::{self, FeeCache};
+use crate::models::opportunity::Opportunity;
+use crate::models::token::{find_by_symbol, TokenInfo};
+use crate::preset::{self, VenueConfig};
+use crate::scanner::{self, MonitoredPair};
+use crate::utils::format::{format_amount, format_percent};
+use crate::utils::{price, secrets};
+use crate::RpcProvider;
+
+// Samples read at once; each is one multicall and one header
+const CONCURRENT_READS: usize = 8;
+const MAX_SAMPLES: u64 = 10_000;
+
+/// Settings for `research`.
+#[derive(Args)]
+pub struct ResearchArgs {
+    /// Pair to study as BASE/QUOTE symbols of the preset, e.g. WETH/USDC
+    #[arg(long)]
+    pair: String,
+
+    /// Start of the range: a date (UTC midnight) or an RFC 3339 time
+    #[arg(long)]
+    from: String,
+
+    /// End of the range, as --from (default: now)
+    #[arg(long)]
+    to: Option<String>,
+
+    /// Seconds between samples
+    #[arg(long, default_value_t = 3600)]
+    interval: u64,
+
+    /// Report path without extension; the series is written to <output>.csv
+    /// and the summary to <output>.md (default: research-<BASE>-<QUOTE>)
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+/// Both venues' prices of the pair at one block, in quote per base.
+struct Sample {
+    block: u64,
+    at: DateTime<Utc>,
+    price_a: f64,
+    price_b: f64,
+    spread: f64,
+    /// Profit at the best size before gas, in the pools' second token, if
+    /// the spread covers swap fees.
+    profit: Option<f64>,
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(date) = value.parse::<NaiveDate>() {
+        return Ok(date.and_time(Default::default()).and_utc());
+    }
+    Ok(DateTime::parse_from_rfc3339(value)
+        .with_context(|| format!("Expected a date or RFC 3339 time, got {:?}", value))?
+        .with_timezone(&Utc))
+}
+
+fn find_token(symbol: &str) -> Result<&'static TokenInfo> {
+    find_by_symbol(symbol).with_context(|| format!("{} isn't a token of the {} preset", symbol, preset::active().name))
+}
+
+async fn block_time(provider: &RpcProvider, block: u64) -> Result<DateTime<Utc>> {
+    let block = provider
+        .get_block_by_number(block.into())
+        .await?
+        .with_context(|| format!("Block {} not found", block))?;
+    DateTime::from_timestamp(block.header.timestamp as i64, 0).context("Invalid block timestamp")
+}
+
+/// Last block mined at or before `at`, by binary search over `0..=head`.
+async fn block_at(provider: &RpcProvider, at: DateTime<Utc>, head: u64) -> Result<u64> {
+    let (mut low, mut high) = (0, head);
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if block_time(provider, mid).await? <= at {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
+
+/// Reads the pair's historical reserves on both of the preset's venues
+/// every `--interval` over a date range from an archive node, and writes
+/// the spread series and its summary, for judging a pair before scanning
+/// it live.
+pub async fn run(args: ResearchArgs) -> Result<()> {
+    ensure!(args.interval > 0, "--interval must be positive");
+    let preset = preset::active();
+    let (base, quote) = args
+        .pair
+        .split_once('/')
+        .with_context(|| format!("Expected --pair BASE/QUOTE, got {:?}", args.pair))?;
+    let (base, quote) = (find_token(base.trim())?, find_token(quote.trim())?);
+    ensure!(base.address != quote.address, "--pair needs two different tokens");
+    let from = parse_time(&args.from)?;
+    let to = match &args.to {
+        Some(to) => parse_time(to)?,
+        None => Utc::now(),
+    };
+    ensure!(from < to, "--from must be before --to");
+    let samples = (to - from).num_seconds() as u64 / args.interval + 1;
+    ensure!(
+        samples <= MAX_SAMPLES,
+        "{} samples is over the limit of {}, use a longer --interval",
+        samples,
+        MAX_SAMPLES
+    );
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("research-{}-{}", base.symbol, quote.symbol)));
+
+    println!("{}", "\n=== Spread Research ===".bright_green().bold());
+    println!("{} {}/{} on {}, {} to {}, every {}s\n",
+        "Pair:".bright_yellow(),
+        base.symbol,
+        quote.symbol,
+        preset.venue_names(),
+        from,
+        to,
+        args.interval,
+    );
+
+    let provider = dex_scanner::connect(&secrets::require("RPC_URL").await?).await?;
+    let venues = &preset.venues;
+    let pairs = scanner::find_pairs(&provider, venues, &[base, quote], |_| {}).await?;
+    // The first two venues with a pool, in the preset's order
+    let Some(&pair) = pairs.first() else {
+        bail!("{}/{} doesn't have a pool on two of {}", base.symbol, quote.symbol, preset.venue_names());
+    };
+    // Pools are read in the pair's own orientation; prices are flipped to BASE/QUOTE
+    let flipped = pair.base().address != base.address;
+    let fee_cache = FeeCache::default();
+    let [venue_a, venue_b] = pair.venues.map(|venue| &venues[venue]);
+    fees::refresh(&provider, &[(pair.pool_a, venue_a.venue), (pair.pool_b, venue_b.venue)], &fee_cache).await;
+    let multicall = scanner::multicall(&provider, preset).await?;
+
+    let head = provider.get_block_number().await?;
+    let (first, last) = tokio::try_join!(block_at(&provider, from, head), block_at(&provider, to, head))?;
+    ensure!(first < last, "No blocks between --from and --to");
+    let span = (block_time(&provider, last).await? - block_time(&provider, first).await?).num_seconds();
+    let blocks_per_sample = ((last - first) as f64 * args.interval as f64 / span.max(1) as f64).max(1.0);
+    let blocks = (0..samples)
+        .map(|i| first + (i as f64 * blocks_per_sample).round() as u64)
+        .take_while(|block| *block <= last)
+        .collect::<Vec<_>>();
+    println!("{} Reading {} samples between blocks {} and {}",
+        "[INFO]".bright_blue(),
+        blocks.len(),
+        first,
+        last,
+    );
+
+    let series = futures::stream::iter(blocks)
+        .map(|block| read_sample(&provider, multicall, &pair, block, flipped, &fee_cache))
+        .buffered(CONCURRENT_READS)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let csv = output.with_extension("csv");
+    std::fs::write(&csv, to_csv(&series))
+        .with_context(|| format!("Failed to write {}", csv.display()))?;
+    let min_margin = preset.pair_margin(base, quote).unwrap_or(preset.min_profit_margin);
+    let summary = summarize(&series, base, quote, [venue_a, venue_b], pair.quote(), min_margin);
+    let report = output.with_extension("md");
+    std::fs::write(&report, &summary)
+        .with_context(|| format!("Failed to write {}", report.display()))?;
+
+    println!("\n{}", summary);
+    println!("{} Wrote {} and {}", "[INFO]".bright_blue(), csv.display(), report.display());
+    Ok(())
+}
+
+async fn read_sample(
+    provider: &RpcProvider,
+    multicall: Address,
+    pair: &MonitoredPair,
+    block: u64,
+    flipped: bool,
+    fees: &FeeCache,
+) -> Result<Sample> {
+    let venues = &preset::active().venues;
+    let (reserves, at) = tokio::try_join!(
+        scanner::read_reserves(provider, multicall, venues, std::slice::from_ref(pair), block),
+        block_time(provider, block)
+    )
+    .with_context(|| format!("Failed to read block {}; historical reserves need an archive node", block))?;
+    let [reserves_a, reserves_b] = reserves[0];
+    let a = crate::to_pool_state(reserves_a, pair.pool_a, &venues[pair.venues[0]], pair, fees);
+    let b = crate::to_pool_state(reserves_b, pair.pool_b, &venues[pair.venues[1]], pair, fees);
+    let spread = price::calculate_profit_margin(a.price(), b.price());
+    let profit = Opportunity::evaluate(pair.base(), pair.quote(), a, b, None, 0.0)
+        .map(|opportunity| opportunity.profit());
+    let oriented = |price: f64| if flipped && price > 0.0 { 1.0 / price } else { price };
+    Ok(Sample {
+        block,
+        at,
+        price_a: oriented(a.price()),
+        price_b: oriented(b.price()),
+        spread,
+        profit,
+    })
+}
+
+fn to_csv(series: &[Sample]) -> String {
+    let mut csv = String::from("timestamp,block,price_a,price_b,spread,profit\n");
+    for sample in series {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{}",
+            sample.at.to_rfc3339(),
+            sample.block,
+            sample.price_a,
+            sample.price_b,
+            sample.spread,
+            sample.profit.map(|p| p.to_string()).unwrap_or_default()
+        );
+    }
+    csv
+}
+
+/// The `q` quantile of `values`, which must be sorted, 0 when empty.
+fn percentile(values: &[f64], q: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values[((values.len() - 1) as f64 * q).round() as usize]
+}
+
+/// Markdown summary of the spread series between `venues`: its
+/// distribution, how often and for how long it cleared the preset's margin,
+/// and the profit on offer in `profit_token`.
+fn summarize(
+    series: &[Sample],
+    base: &TokenInfo,
+    quote: &TokenInfo,
+    venues: [&VenueConfig; 2],
+    profit_token: &TokenInfo,
+    min_margin: f64,
+) -> String {
+    let preset = preset::active();
+    let mut spreads = series.iter().map(|s| s.spread).collect::<Vec<_>>();
+    spreads.sort_by(f64::total_cmp);
+    let mean = spreads.iter().sum::<f64>() / spreads.len().max(1) as f64;
+    let above = series.iter().filter(|s| s.spread > min_margin).count();
+    let longest = series
+        .iter()
+        .fold((0, 0), |(longest, run), s| {
+            let run = if s.spread > min_margin { run + 1 } else { 0 };
+            (longest.max(run), run)
+        })
+        .0;
+    let profitable = series.iter().filter_map(|s| s.profit).filter(|p| *p > 0.0).collect::<Vec<_>>();
+    let (first, last) = match (series.first(), series.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return format!("# {}/{} spread research\n\nNo samples.\n", base.symbol, quote.symbol),
+    };
+
+    let mut report = format!(
+        "# {}/{} spread research\n\n\
+        {} vs {} on {}, {} to {} (blocks {} to {}), {} samples.\n\n\
+        | | |\n|---|---|\n",
+        base.symbol,
+        quote.symbol,
+        venues[0].label(),
+        venues[1].label(),
+        preset.name,
+        first.at.to_rfc3339(),
+        last.at.to_rfc3339(),
+        first.block,
+        last.block,
+        series.len(),
+    );
+    let rows = [
+        ("Mean spread", format_percent(mean)),
+        ("Median spread", format_percent(percentile(&spreads, 0.5))),
+        ("95th percentile", format_percent(percentile(&spreads, 0.95))),
+        ("Max spread", format_percent(percentile(&spreads, 1.0))),
+        (
+            "Above min margin",
+            format!("{} of samples ({})", format_percent(above as f64 / series.len() as f64), format_percent(min_margin)),
+        ),
+        ("Longest run above it", format!("{} samples", longest)),
+        ("Profitable before gas", format!("{} samples", profitable.len())),
+        (
+            "Mean profit when profitable",
+            format!(
+                "{} {}",
+                format_amount(profitable.iter().sum::<f64>() / profitable.len().max(1) as f64),
+                profit_token.symbol
+            ),
+        ),
+    ];
+    for (name, value) in rows {
+        let _ = writeln!(report, "| {} | {} |", name, value);
+    }
+    report
+}
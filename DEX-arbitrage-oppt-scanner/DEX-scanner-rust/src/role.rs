@@ -0,0 +1,86 @@
+use anyhow::{bail, Context, Error};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// What a Telegram user may do through the bot, each role allowing what the
+/// ones before it do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Query the scanner: prices, stats, configuration.
+    Viewer,
+    /// Act on alerts and operations: ack, test alerts.
+    Operator,
+    /// Change how the scanner runs.
+    Admin,
+}
+
+impl FromStr for Role {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "operator" => Ok(Role::Operator),
+            "admin" => Ok(Role::Admin),
+            other => bail!("Unknown role {:?} (expected viewer, operator or admin)", other),
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Role::Viewer => "viewer",
+            Role::Operator => "operator",
+            Role::Admin => "admin",
+        })
+    }
+}
+
+/// Roles per Telegram user ID, parsed from `TELEGRAM_ROLES`: comma-separated
+/// `<user id>:<role>`, e.g. `1234:admin,5678:operator`. Users not listed are
+/// viewers; with no roles at all, everyone in a configured chat is an admin,
+/// as before roles existed.
+#[derive(Debug, Clone, Default)]
+pub struct Roles(HashMap<u64, Role>);
+
+impl Roles {
+    pub fn of(&self, user: u64) -> Role {
+        if self.0.is_empty() {
+            return Role::Admin;
+        }
+        self.0.get(&user).copied().unwrap_or(Role::Viewer)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Users with `role`, for `/config`.
+    pub fn count(&self, role: Role) -> usize {
+        self.0.values().filter(|r| **r == role).count()
+    }
+}
+
+impl FromStr for Roles {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (user, role) = entry
+                    .split_once(':')
+                    .with_context(|| format!("expected <user id>:<role>, got {:?}", entry))?;
+                let user = user
+                    .trim()
+                    .parse::<u64>()
+                    .with_context(|| format!("Invalid user id {:?}", user))?;
+                Ok((user, role.parse()?))
+            })
+            .collect::<Result<_, Error>>()
+            .map(Roles)
+    }
+}
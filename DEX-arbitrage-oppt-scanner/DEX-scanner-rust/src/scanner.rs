@@ -0,0 +1,550 @@
+//! The detection engine on its own, for embedding in other programs: finds
+//! the pairs any two venues share, reads their pools every block and runs
+//! strategies over the resulting market snapshot. Alerting, storage and
+//! the rest of the binary's pipeline are left to the embedder.
+//!
+//! ```no_run
+//! use dex_scanner::strategy::CrossVenue;
+//! use dex_scanner::Scanner;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! Scanner::builder()
+//!     .rpc_url("https://mainnet.base.org")
+//!     .chain("base")
+//!     .strategy(CrossVenue::all())
+//!     .on_opportunity(|opportunity, snapshot| {
+//!         println!("block {}: {}/{} +{:.4}", snapshot.block, opportunity.base.symbol,
+//!             opportunity.quote.symbol, opportunity.profit());
+//!     })
+//!     .build()?
+//!     .run()
+//!     .await
+//! # }
+//! ```
+
+use alloy::network::AnyNetwork;
+use alloy::primitives::{Address, U256};
+use alloy::providers::bindings::IMulticall3;
+use alloy::providers::{Provider, ProviderBuilder, MULTICALL3_ADDRESS};
+use alloy::rpc::types::TransactionRequest;
+use alloy::serde::WithOtherFields;
+use alloy::sol_types::SolCall;
+use anyhow::{bail, ensure, Context, Result};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::enrich::fees::{self, FeeCache};
+use crate::models::opportunity::{Opportunity, PoolState, Venue};
+use crate::models::snapshot::{MarketSnapshot, PairQuote};
+use crate::models::token::{self, weth, TokenInfo};
+use crate::preset::{self, Preset, VenueConfig};
+use crate::strategy::{CrossVenue, Strategy};
+use crate::utils::gas::{self, GasOracle};
+use crate::utils::price;
+use crate::RpcProvider;
+
+// getReserves calls per multicall round trip
+const MULTICALL_BATCH: usize = 200;
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const GAS_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+const FEE_REFRESH_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// A token pair that exists on two venues, with its pool address on each.
+/// A pair on more than two venues is monitored once per two of them.
+/// `token0` sorts first by address, as it does in the pools; prices are
+/// shown the other way round when [`token::orient`] says so.
+#[derive(Debug, Clone, Copy)]
+pub struct MonitoredPair {
+    pub token0: &'static TokenInfo,
+    pub token1: &'static TokenInfo,
+    pub pool_a: Address,
+    pub pool_b: Address,
+    /// Indices of the venues of `pool_a` and `pool_b` in the venues the
+    /// pair was found on.
+    pub venues: [usize; 2],
+}
+
+impl MonitoredPair {
+    /// The token the pair is priced per.
+    pub fn base(&self) -> &'static TokenInfo {
+        token::orient(self.token0, self.token1).0
+    }
+
+    /// The token the pair is priced in.
+    pub fn quote(&self) -> &'static TokenInfo {
+        token::orient(self.token0, self.token1).1
+    }
+
+    /// Whether the base is `token1`, so reserves in pool order are the
+    /// other way round.
+    pub fn flipped(&self) -> bool {
+        self.base().address != self.token0.address
+    }
+
+    /// `pool`, read in pool order, as base/quote.
+    pub fn orient(&self, pool: PoolState) -> PoolState {
+        if self.flipped() {
+            pool.flipped()
+        } else {
+            pool
+        }
+    }
+}
+
+/// Every pair of `tokens` with a pool on two of `venues`, once per two
+/// venues that have it, calling `on_found` on each as it's found.
+pub async fn find_pairs(
+    provider: &RpcProvider,
+    venues: &[VenueConfig],
+    tokens: &[&'static TokenInfo],
+    mut on_found: impl FnMut(&MonitoredPair),
+) -> Result<Vec<MonitoredPair>> {
+    let mut pairs = Vec::new();
+    for &token0 in tokens {
+        for &token1 in tokens {
+            if token0.address >= token1.address {
+                continue;
+            }
+            for pair in find_pair(provider, venues, token0, token1).await? {
+                on_found(&pair);
+                pairs.push(pair);
+            }
+        }
+    }
+    Ok(pairs)
+}
+
+/// Every pair of `token` with one of `others` that has a pool on two of
+/// `venues`, e.g. for a token added while scanning.
+pub async fn pairs_with(
+    provider: &RpcProvider,
+    venues: &[VenueConfig],
+    token: &'static TokenInfo,
+    others: &[&'static TokenInfo],
+) -> Result<Vec<MonitoredPair>> {
+    let mut pairs = Vec::new();
+    for &other in others.iter().filter(|t| t.address != token.address) {
+        // Pairs hold the lower address first
+        let (token0, token1) = if token.address < other.address { (token, other) } else { (other, token) };
+        pairs.extend(find_pair(provider, venues, token0, token1).await?);
+    }
+    Ok(pairs)
+}
+
+/// The `token0`/`token1` pair on every two of `venues` with a pool for it.
+async fn find_pair(
+    provider: &RpcProvider,
+    venues: &[VenueConfig],
+    token0: &'static TokenInfo,
+    token1: &'static TokenInfo,
+) -> Result<Vec<MonitoredPair>> {
+    let mut pools = Vec::with_capacity(venues.len());
+    for venue in venues {
+        let pool = venue
+            .get_pool(provider, token0.address, token1.address)
+            .await
+            .context("Failed to get pair address")?;
+        pools.push(pool);
+    }
+    let mut pairs = Vec::new();
+    for a in 0..pools.len() {
+        for b in a + 1..pools.len() {
+            // Two V3 venues without a fee tier both find the deepest pool
+            if !pools[a].is_zero() && !pools[b].is_zero() && pools[a] != pools[b] {
+                pairs.push(MonitoredPair { token0, token1, pool_a: pools[a], pool_b: pools[b], venues: [a, b] });
+            }
+        }
+    }
+    Ok(pairs)
+}
+
+/// The WETH pool on `venue` of each of `tokens` that has one, for pricing
+/// gas in them.
+pub async fn weth_pools(
+    provider: &RpcProvider,
+    venue: &VenueConfig,
+    tokens: &[&'static TokenInfo],
+) -> Result<Vec<(&'static TokenInfo, Address)>> {
+    let weth = weth();
+    let mut pools = Vec::new();
+    for &token in tokens.iter().filter(|t| t.address != weth.address) {
+        let pool = venue
+            .get_pool(provider, weth.address, token.address)
+            .await
+            .context("Failed to get pair address")?;
+        if !pool.is_zero() {
+            pools.push((token, pool));
+        }
+    }
+    Ok(pools)
+}
+
+/// The chain's Multicall3, at the preset's address or the usual one,
+/// checked to be deployed.
+pub async fn multicall(provider: &RpcProvider, preset: &Preset) -> Result<Address> {
+    let address = preset.multicall.unwrap_or(MULTICALL3_ADDRESS);
+    let code = provider.get_code_at(address).await?;
+    ensure!(!code.is_empty(), "Multicall3 not available on this chain");
+    Ok(address)
+}
+
+/// Raw `(reserve0, reserve1)` of both pools of every pair at `block`, in
+/// venue order, batching up to `MULTICALL_BATCH` `getReserves` calls per
+/// round trip through the Multicall3 at `multicall`. Pools of venues that
+/// take several calls to read are read concurrently alongside. A pool in
+/// several pairs is read once.
+pub async fn read_reserves(
+    provider: &RpcProvider,
+    multicall: Address,
+    venues: &[VenueConfig],
+    pairs: &[MonitoredPair],
+    block: u64,
+) -> Result<Vec<[(U256, U256); 2]>> {
+    let mut pools = Vec::new();
+    let mut read_at = HashMap::new();
+    let mut legs = Vec::with_capacity(pairs.len());
+    for pair in pairs {
+        legs.push([(pair.venues[0], pair.pool_a), (pair.venues[1], pair.pool_b)].map(|(venue, pool)| {
+            *read_at.entry(pool).or_insert_with(|| {
+                pools.push((&venues[venue], pool, pair));
+                pools.len() - 1
+            })
+        }));
+    }
+
+    let batched = async {
+        let calls = pools.iter().filter(|(venue, ..)| venue.batchable()).collect::<Vec<_>>();
+        let mut reserves = Vec::with_capacity(calls.len());
+        for chunk in calls.chunks(MULTICALL_BATCH) {
+            let calls = chunk
+                .iter()
+                .map(|(venue, pool, _)| venue.reserves_call(*pool))
+                .collect::<Result<Vec<_>>>()?;
+            let request = TransactionRequest::default()
+                .to(multicall)
+                .input(IMulticall3::aggregate3Call { calls }.abi_encode().into());
+            let output = provider.call(WithOtherFields::new(request)).block(block.into()).await?;
+            for result in IMulticall3::aggregate3Call::abi_decode_returns(&output)? {
+                reserves.push(preset::reserves_of(&result.returnData)?);
+            }
+        }
+        Ok::<_, anyhow::Error>(reserves)
+    };
+    let unbatched = futures::future::try_join_all(
+        pools
+            .iter()
+            .filter(|(venue, ..)| !venue.batchable())
+            .map(|(venue, pool, pair)| {
+                venue.read_reserves(provider, *pool, pair.token0.address, Some(block))
+            }),
+    );
+    let (batched, unbatched) = tokio::try_join!(batched, unbatched)?;
+
+    let (mut batched, mut unbatched) = (batched.into_iter(), unbatched.into_iter());
+    let reserves = pools
+        .iter()
+        .map(|(venue, ..)| {
+            if venue.batchable() { batched.next() } else { unbatched.next() }
+                .context("Missing reserves in batch result")
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(legs.iter().map(|legs| legs.map(|leg| reserves[leg])).collect())
+}
+
+type Callback = Box<dyn Fn(&Opportunity, &MarketSnapshot) + Send + Sync>;
+
+/// Configures a [`Scanner`]. Only the RPC URL or a provider is required;
+/// the rest defaults to the Ethereum preset's venues and the `cross-venue`
+/// strategy.
+#[derive(Default)]
+pub struct ScannerBuilder {
+    rpc_url: Option<String>,
+    provider: Option<RpcProvider>,
+    chain: Option<String>,
+    venues: Vec<VenueConfig>,
+    strategies: Vec<Box<dyn Strategy>>,
+    on_opportunity: Option<Callback>,
+    flash_loan_fee: f64,
+    min_profit_usd: Option<f64>,
+    poll_interval: Option<Duration>,
+}
+
+impl ScannerBuilder {
+    /// HTTP RPC endpoint of the chain's node.
+    pub fn rpc_url(mut self, url: impl Into<String>) -> Self {
+        self.rpc_url = Some(url.into());
+        self
+    }
+
+    /// Provider to read the chain through, in place of an HTTP
+    /// [`ScannerBuilder::rpc_url`], e.g. one over WebSocket or IPC from
+    /// [`crate::connect`].
+    pub fn provider(mut self, provider: impl Provider<AnyNetwork> + 'static) -> Self {
+        self.provider = Some(provider.erased());
+        self
+    }
+
+    /// Preset or strategy profile to scan, by the name the binary takes,
+    /// e.g. `base` or `stable-depeg`. A process scans one chain, so every
+    /// scanner in it must use the same one.
+    pub fn chain(mut self, name: impl Into<String>) -> Self {
+        self.chain = Some(name.into());
+        self
+    }
+
+    /// A venue to compare, replacing the preset's venues. Add at least
+    /// two, each compared against every other, or none to keep the
+    /// preset's.
+    pub fn venue(mut self, venue: VenueConfig) -> Self {
+        self.venues.push(venue);
+        self
+    }
+
+    /// A strategy to run on every snapshot, built-in (see
+    /// [`crate::strategy`]) or your own.
+    pub fn strategy(mut self, strategy: impl Strategy + 'static) -> Self {
+        self.strategies.push(Box::new(strategy));
+        self
+    }
+
+    /// Flash-loan fee rate charged on each trade's input, 0 by default.
+    pub fn flash_loan_fee(mut self, rate: f64) -> Self {
+        self.flash_loan_fee = rate;
+        self
+    }
+
+    /// Minimum USD profit, after gas when it's known, for an opportunity
+    /// to count, in place of the strategies' margins. Margins by default.
+    pub fn min_profit_usd(mut self, usd: f64) -> Self {
+        self.min_profit_usd = Some(usd);
+        self
+    }
+
+    /// How often to check for a new block, 2s by default.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = Some(interval);
+        self
+    }
+
+    /// Called with every opportunity a strategy finds and the snapshot it
+    /// was found in. It runs on the scan loop, so it should hand slow work
+    /// off rather than do it inline.
+    pub fn on_opportunity(
+        mut self,
+        callback: impl Fn(&Opportunity, &MarketSnapshot) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_opportunity = Some(Box::new(callback));
+        self
+    }
+
+    pub fn build(self) -> Result<Scanner> {
+        let provider = match (self.provider, self.rpc_url) {
+            (Some(provider), _) => provider,
+            (None, Some(rpc_url)) => ProviderBuilder::new()
+                .disable_recommended_fillers()
+                .network::<AnyNetwork>()
+                .connect_http(rpc_url.parse().context("Invalid RPC URL")?)
+                .erased(),
+            (None, None) => bail!("Scanner needs an RPC URL or a provider"),
+        };
+        let preset = match &self.chain {
+            Some(name) => Preset::by_name(name).or_else(|_| Preset::strategy(name))?,
+            None => Preset::by_name("ethereum")?,
+        };
+        preset::init(preset);
+        if !std::ptr::eq(preset::active(), preset) {
+            bail!("This process already scans {}, it can't scan {} too", preset::active().name, preset.name);
+        }
+        let venues = if self.venues.is_empty() { preset.venues.clone() } else { self.venues };
+        check_venues(&venues)?;
+        let mut strategies = self.strategies;
+        if strategies.is_empty() {
+            strategies.push(Box::new(CrossVenue::all()));
+        }
+        Ok(Scanner {
+            provider,
+            preset,
+            venues,
+            strategies,
+            on_opportunity: self.on_opportunity,
+            flash_loan_fee: self.flash_loan_fee,
+            min_profit_usd: self.min_profit_usd,
+            poll_interval: self.poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL),
+        })
+    }
+}
+
+/// At least two venues, no two the same: venues differ if they are
+/// different DEXes or, for Uniswap V3, different fee tiers of it.
+fn check_venues(venues: &[VenueConfig]) -> Result<()> {
+    ensure!(venues.len() >= 2, "Scanner compares at least two venues, got {}", venues.len());
+    for (i, a) in venues.iter().enumerate() {
+        ensure!(
+            venues[i + 1..].iter().all(|b| (a.venue, a.fee_tier) != (b.venue, b.fee_tier)),
+            "Scanner has {} twice",
+            a.label()
+        );
+    }
+    Ok(())
+}
+
+/// Pairs a scanner found and the pools it refreshes for them.
+struct Market {
+    pairs: Vec<MonitoredPair>,
+    multicall: Address,
+    venue_pools: Vec<(Address, Venue)>,
+    weth_pools: Vec<(&'static TokenInfo, Address)>,
+}
+
+/// Scans one chain's pairs across its venues, block by block, and hands
+/// what its strategies find to a callback.
+pub struct Scanner {
+    provider: RpcProvider,
+    preset: &'static Preset,
+    venues: Vec<VenueConfig>,
+    strategies: Vec<Box<dyn Strategy>>,
+    on_opportunity: Option<Callback>,
+    flash_loan_fee: f64,
+    min_profit_usd: Option<f64>,
+    poll_interval: Duration,
+}
+
+impl Scanner {
+    pub fn builder() -> ScannerBuilder {
+        ScannerBuilder::default()
+    }
+
+    /// The pairs two or more venues have, and what reading them needs.
+    async fn discover(&self) -> Result<Market> {
+        let tokens = self.preset.scanned_tokens().collect::<Vec<_>>();
+        let pairs = find_pairs(&self.provider, &self.venues, &tokens, |_| {}).await?;
+        let multicall = multicall(&self.provider, self.preset).await?;
+        let venue_pools = pairs
+            .iter()
+            .flat_map(|p| [(p.pool_a, self.venues[p.venues[0]].venue), (p.pool_b, self.venues[p.venues[1]].venue)])
+            .collect();
+        let weth_pools = weth_pools(&self.provider, &self.venues[0], &tokens).await?;
+        Ok(Market { pairs, multicall, venue_pools, weth_pools })
+    }
+
+    /// The market as of the latest block, once. Pairs are discovered and
+    /// fees and gas read on every call, so for more than a few snapshots
+    /// [`Scanner::run`] is far cheaper.
+    pub async fn snapshot(&self) -> Result<MarketSnapshot> {
+        let market = self.discover().await?;
+        let (fees, gas) = (FeeCache::default(), GasOracle::default());
+        tokio::join!(
+            fees::refresh(&self.provider, &market.venue_pools, &fees),
+            gas::refresh(&self.provider, &market.weth_pools, &gas),
+        );
+        let head = self.provider.get_block_number().await?;
+        let reserves = read_reserves(&self.provider, market.multicall, &self.venues, &market.pairs, head).await?;
+        Ok(self.capture(&market.pairs, reserves, head, &fees, &gas))
+    }
+
+    /// Every opportunity the scanner's strategies find in `snapshot`.
+    pub fn evaluate(&self, snapshot: &MarketSnapshot) -> Vec<Opportunity> {
+        self.strategies
+            .iter()
+            .flat_map(|strategy| strategy.evaluate(snapshot))
+            .collect()
+    }
+
+    /// Finds the pairs two or more venues have, then scans every new block until
+    /// a read fails, which is returned. Calling it again starts over.
+    pub async fn run(&self) -> Result<()> {
+        let market = self.discover().await?;
+        let (fees, gas) = (FeeCache::default(), GasOracle::default());
+        let refreshes = [
+            fees::spawn_refresh(self.provider.clone(), market.venue_pools, fees.clone(), FEE_REFRESH_INTERVAL),
+            gas::spawn_refresh(self.provider.clone(), market.weth_pools, gas.clone(), GAS_REFRESH_INTERVAL),
+        ];
+
+        let result = self.scan(&market.pairs, market.multicall, &fees, &gas).await;
+        for refresh in refreshes {
+            refresh.abort();
+        }
+        result
+    }
+
+    async fn scan(
+        &self,
+        pairs: &[MonitoredPair],
+        multicall: Address,
+        fees: &FeeCache,
+        gas: &GasOracle,
+    ) -> Result<()> {
+        let mut last_block = None;
+        loop {
+            let head = self.provider.get_block_number().await?;
+            if last_block != Some(head) {
+                last_block = Some(head);
+                let reserves = read_reserves(&self.provider, multicall, &self.venues, pairs, head).await?;
+                let snapshot = self.capture(pairs, reserves, head, fees, gas);
+                for opportunity in self.evaluate(&snapshot) {
+                    if let Some(callback) = &self.on_opportunity {
+                        callback(&opportunity, &snapshot);
+                    }
+                }
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    fn capture(
+        &self,
+        pairs: &[MonitoredPair],
+        reserves: Vec<[(U256, U256); 2]>,
+        block: u64,
+        fees: &FeeCache,
+        gas: &GasOracle,
+    ) -> MarketSnapshot {
+        let pool_state = |venue: &VenueConfig, pool: Address, pair: &MonitoredPair, reserves: (U256, U256)| {
+            pair.orient(PoolState {
+                venue: venue.venue,
+                pool,
+                reserve_base: price::to_units(reserves.0, pair.token0.decimals),
+                reserve_quote: price::to_units(reserves.1, pair.token1.decimals),
+                fee: fees.fee(&pool, venue.venue),
+            })
+        };
+        MarketSnapshot {
+            block,
+            read_at: chrono::Utc::now(),
+            pairs: pairs
+                .iter()
+                .zip(reserves)
+                .map(|(pair, [a, b])| PairQuote {
+                    base: pair.base(),
+                    quote: pair.quote(),
+                    pools: [
+                        pool_state(&self.venues[pair.venues[0]], pair.pool_a, pair, a),
+                        pool_state(&self.venues[pair.venues[1]], pair.pool_b, pair, b),
+                    ],
+                    gas_cost: gas.arb_cost_in(pair.quote()),
+                    quote_usd: gas.usd_value(1.0, pair.quote()),
+                })
+                .collect(),
+            gas_price_gwei: gas.gas_price_gwei(),
+            flash_loan_fee: self.flash_loan_fee,
+            min_profit_usd: self.min_profit_usd,
+            threshold_scale: 1.0,
+            btc: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_compare_distinct_venues() {
+        for name in preset::CHAINS {
+            check_venues(&Preset::by_name(name).unwrap().venues).unwrap();
+        }
+        for name in preset::STRATEGIES {
+            check_venues(&Preset::strategy(name).unwrap().venues).unwrap();
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use alloy::primitives::Address;
+use anyhow::{bail, Context, Error};
+use std::fmt;
+use std::str::FromStr;
+
+/// The slice of the pair universe this worker scans, parsed from
+/// `<index>/<count>`, e.g. `2/4`. Pairs are assigned by their first venue's
+/// pool address, so every worker agrees on the split without coordinating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    pub index: u64,
+    pub count: u64,
+}
+
+impl Default for Shard {
+    fn default() -> Self {
+        Shard { index: 0, count: 1 }
+    }
+}
+
+impl Shard {
+    /// Whether there are other workers scanning the rest of the pairs.
+    pub fn is_partial(&self) -> bool {
+        self.count > 1
+    }
+
+    /// Whether the pair with first-venue pool `pool` belongs to this shard.
+    pub fn owns(&self, pool: Address) -> bool {
+        // Pair addresses are CREATE2 hashes, so their low bytes are already
+        // uniformly spread
+        let low = u64::from_be_bytes(pool.as_slice()[12..].try_into().unwrap());
+        low % self.count == self.index
+    }
+}
+
+impl FromStr for Shard {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, count) = s
+            .trim()
+            .split_once('/')
+            .context("expected <index>/<count>, e.g. 0/4")?;
+        let index = index.trim().parse::<u64>().context("Invalid shard index")?;
+        let count = count.trim().parse::<u64>().context("Invalid shard count")?;
+        if count == 0 || index >= count {
+            bail!("shard index must be below the shard count, got {}/{}", index, count);
+        }
+        Ok(Shard { index, count })
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
@@ -0,0 +1,255 @@
+use alloy::primitives::Address;
+use anyhow::{ensure, Context, Result};
+use clap::Args;
+use colored::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::time::MissedTickBehavior;
+
+use crate::models::opportunity::{Opportunity, PoolState};
+use crate::preset;
+use crate::utils::price;
+
+// Generator ticks per second; each tick emits its share of the target rate
+const TICKS_PER_SEC: u64 = 100;
+const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+// Share of the target rate evaluation has to keep up with to pass
+const MIN_THROUGHPUT: f64 = 0.95;
+// Base reserve of every synthetic pool, in whole tokens
+const POOL_DEPTH: f64 = 1_000.0;
+
+/// Settings for `soak-test`.
+#[derive(Args)]
+pub struct SoakArgs {
+    /// Synthetic swap events (pool updates) per second
+    #[arg(long, default_value_t = 1000)]
+    rate: u64,
+
+    /// Synthetic pairs the events are spread over
+    #[arg(long, default_value_t = 500)]
+    pairs: usize,
+
+    /// How long to generate events for, in seconds
+    #[arg(long, default_value_t = 60)]
+    duration: u64,
+
+    /// Capacity of the queues between stages
+    #[arg(long, default_value_t = 1024)]
+    queue: usize,
+
+    /// Simulated Telegram round trip per alert, in milliseconds
+    #[arg(long, default_value_t = 50)]
+    notify_latency_ms: u64,
+
+    /// Widest price gap between the two venues, e.g. 0.01 for 1%
+    #[arg(long, default_value_t = 0.01)]
+    max_spread: f64,
+}
+
+/// One synthetic swap: a pair's pools right after it.
+struct Update {
+    a: PoolState,
+    b: PoolState,
+    created: Instant,
+}
+
+#[derive(Default)]
+struct Metrics {
+    generated: AtomicU64,
+    /// Updates the generator couldn't queue because evaluation was behind.
+    dropped_updates: AtomicU64,
+    evaluated: AtomicU64,
+    alerts: AtomicU64,
+    /// Alerts the evaluator couldn't queue because notification was behind.
+    dropped_alerts: AtomicU64,
+    sent: AtomicU64,
+    /// Microseconds from generation to evaluation since the last report.
+    latencies: Mutex<Vec<u64>>,
+}
+
+/// Pushes synthetic swap events through evaluation and a simulated notifier
+/// at `args.rate`, reporting throughput, queue depth, latency and drops
+/// every few seconds. Fails if evaluation didn't keep up or updates were
+/// dropped, so it can gate a rollout to a busier chain.
+pub async fn run(args: SoakArgs) -> Result<()> {
+    ensure!(
+        args.rate > 0 && args.pairs > 0 && args.queue > 0,
+        "--rate, --pairs and --queue must be positive"
+    );
+    let preset = preset::active();
+    let mut tokens = preset.scanned_tokens().collect::<Vec<_>>();
+    tokens.sort_by_key(|t| t.address);
+    let (base, quote) = match tokens[..] {
+        [base, quote, ..] => (base, quote),
+        _ => anyhow::bail!("preset {} scans fewer than two tokens", preset.name),
+    };
+    let (venue_a, venue_b) = (&preset.venues[0], &preset.venues[1]);
+
+    println!("{}", "\n=== Soak Test ===".bright_green().bold());
+    println!("{} {} updates/s over {} pairs for {}s, queues of {}, {}ms per alert\n",
+        "Target:".bright_yellow(),
+        args.rate,
+        args.pairs,
+        args.duration,
+        args.queue,
+        args.notify_latency_ms,
+    );
+
+    let metrics = Arc::new(Metrics::default());
+    let (update_tx, mut update_rx) = mpsc::channel::<Update>(args.queue);
+    let (alert_tx, mut alert_rx) = mpsc::channel::<Opportunity>(args.queue);
+
+    let evaluator = {
+        let metrics = Arc::clone(&metrics);
+        tokio::spawn(async move {
+            while let Some(update) = update_rx.recv().await {
+                let margin = price::calculate_profit_margin(update.a.price(), update.b.price());
+                if margin > preset.min_profit_margin {
+                    if let Some(opportunity) = Opportunity::evaluate(base, quote, update.a, update.b, None, 0.0) {
+                        metrics.alerts.fetch_add(1, Ordering::Relaxed);
+                        if let Err(TrySendError::Full(_)) = alert_tx.try_send(opportunity) {
+                            metrics.dropped_alerts.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                }
+                metrics.evaluated.fetch_add(1, Ordering::Relaxed);
+                metrics
+                    .latencies
+                    .lock()
+                    .unwrap()
+                    .push(update.created.elapsed().as_micros() as u64);
+            }
+        })
+    };
+
+    let notifier = {
+        let metrics = Arc::clone(&metrics);
+        let latency = Duration::from_millis(args.notify_latency_ms);
+        tokio::spawn(async move {
+            while alert_rx.recv().await.is_some() {
+                tokio::time::sleep(latency).await;
+                metrics.sent.fetch_add(1, Ordering::Relaxed);
+            }
+        })
+    };
+
+    let reporter = {
+        let metrics = Arc::clone(&metrics);
+        let update_tx = update_tx.clone();
+        tokio::spawn(async move {
+            let mut last = (0, 0);
+            loop {
+                tokio::time::sleep(REPORT_INTERVAL).await;
+                let generated = metrics.generated.load(Ordering::Relaxed);
+                let evaluated = metrics.evaluated.load(Ordering::Relaxed);
+                let mut latencies = std::mem::take(&mut *metrics.latencies.lock().unwrap());
+                let secs = REPORT_INTERVAL.as_secs_f64();
+                println!("{} {:.0} gen/s, {:.0} eval/s, queue {}/{}, latency p50 {} p99 {}, dropped {} updates {} alerts, {} alerts sent",
+                    "[SOAK]".bright_blue(),
+                    (generated - last.0) as f64 / secs,
+                    (evaluated - last.1) as f64 / secs,
+                    update_tx.max_capacity() - update_tx.capacity(),
+                    update_tx.max_capacity(),
+                    format_micros(percentile(&mut latencies, 0.5)),
+                    format_micros(percentile(&mut latencies, 0.99)),
+                    metrics.dropped_updates.load(Ordering::Relaxed),
+                    metrics.dropped_alerts.load(Ordering::Relaxed),
+                    metrics.sent.load(Ordering::Relaxed),
+                );
+                last = (generated, evaluated);
+            }
+        })
+    };
+
+    // Each pair drifts around its own mid, and every event reprices one of
+    // its pools within max_spread of it
+    let mut rng = StdRng::from_entropy();
+    let mut pairs = (0..args.pairs)
+        .map(|_| {
+            let mid = rng.gen_range(0.5..2.0);
+            let pool = |venue: &preset::VenueConfig| PoolState {
+                venue: venue.venue,
+                pool: Address::ZERO,
+                reserve_base: POOL_DEPTH,
+                reserve_quote: POOL_DEPTH * mid,
+                fee: venue.venue.default_fee(),
+            };
+            (mid, pool(venue_a), pool(venue_b))
+        })
+        .collect::<Vec<_>>();
+
+    // Events due are worked out from wall time, so a generator that can't
+    // reach the target rate shows up as a shortfall instead of a longer run
+    let started = Instant::now();
+    let duration = Duration::from_secs(args.duration);
+    let mut ticker = tokio::time::interval(Duration::from_secs(1) / TICKS_PER_SEC as u32);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    while started.elapsed() < duration {
+        ticker.tick().await;
+        let due = (args.rate as f64 * started.elapsed().min(duration).as_secs_f64()) as u64;
+        while metrics.generated.load(Ordering::Relaxed) < due {
+            let (mid, a, b) = &mut pairs[rng.gen_range(0..args.pairs)];
+            *mid *= 1.0 + rng.gen_range(-0.001..0.001);
+            let pool = if rng.gen_bool(0.5) { &mut *a } else { &mut *b };
+            pool.reserve_quote = pool.reserve_base * *mid * (1.0 + rng.gen_range(-0.5..0.5) * args.max_spread);
+
+            let update = Update { a: *a, b: *b, created: Instant::now() };
+            if let Err(TrySendError::Full(_)) = update_tx.try_send(update) {
+                metrics.dropped_updates.fetch_add(1, Ordering::Relaxed);
+            }
+            metrics.generated.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    let generating = started.elapsed();
+
+    drop(update_tx);
+    reporter.abort();
+    evaluator.await.context("Evaluator panicked")?;
+    // Alerts still queued would only measure the simulated latency
+    notifier.abort();
+
+    let generated = metrics.generated.load(Ordering::Relaxed);
+    let evaluated = metrics.evaluated.load(Ordering::Relaxed);
+    let dropped = metrics.dropped_updates.load(Ordering::Relaxed);
+    let throughput = evaluated as f64 / started.elapsed().as_secs_f64();
+    println!("\n{}", "Soak Test Summary:".bright_blue().bold());
+    println!("{} {} in {:.1}s ({:.0}/s)", "Generated:".bright_yellow(), generated, generating.as_secs_f64(), generated as f64 / generating.as_secs_f64());
+    println!("{} {} ({:.0}/s)", "Evaluated:".bright_yellow(), evaluated, throughput);
+    println!("{} {} updates, {} alerts", "Dropped:".bright_yellow(), dropped, metrics.dropped_alerts.load(Ordering::Relaxed));
+    println!("{} {} raised, {} sent", "Alerts:".bright_yellow(), metrics.alerts.load(Ordering::Relaxed), metrics.sent.load(Ordering::Relaxed));
+
+    ensure!(
+        generated as f64 >= args.rate as f64 * generating.as_secs_f64() * MIN_THROUGHPUT,
+        "generator only reached {:.0} updates/s; lower --rate",
+        generated as f64 / generating.as_secs_f64()
+    );
+    ensure!(dropped == 0, "{} of {} updates dropped: evaluation can't keep up", dropped, generated);
+    ensure!(
+        throughput >= args.rate as f64 * MIN_THROUGHPUT,
+        "sustained {:.0} updates/s, below the {} target",
+        throughput,
+        args.rate
+    );
+    println!("{}", "Pipeline sustained the target rate".bright_green());
+    Ok(())
+}
+
+/// The `q` quantile of `values`, 0 when empty.
+fn percentile(values: &mut [u64], q: f64) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.sort_unstable();
+    values[((values.len() - 1) as f64 * q).round() as usize]
+}
+
+fn format_micros(micros: u64) -> String {
+    if micros < 1_000 {
+        format!("{}µs", micros)
+    } else {
+        format!("{:.1}ms", micros as f64 / 1_000.0)
+    }
+}
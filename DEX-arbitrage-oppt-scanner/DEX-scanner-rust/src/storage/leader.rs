@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use colored::*;
+use redis::aio::ConnectionManager;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+// Extends the lease only if we still hold it, so a replica that lost the
+// lock during a stall can't steal it back from the new leader
+const RENEW_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("PEXPIRE", KEYS[1], ARGV[2])
+else
+    return 0
+end
+"#;
+
+/// Whether this replica is the one that sends alerts. Without election every
+/// instance is its own leader.
+pub struct Leader {
+    is_leader: AtomicBool,
+}
+
+impl Leader {
+    pub fn always() -> Arc<Self> {
+        Arc::new(Leader {
+            is_leader: AtomicBool::new(true),
+        })
+    }
+
+    /// A shard worker that isn't the notifier: it queues its alerts and
+    /// leaves the bot and reports to shard 0.
+    pub fn never() -> Arc<Self> {
+        Arc::new(Leader {
+            is_leader: AtomicBool::new(false),
+        })
+    }
+
+    /// Competes for a Redis lease at `<prefix>:leader`, renewing it every
+    /// third of `lease` while held. A standby keeps retrying, so it takes
+    /// over within one lease period of the leader dying.
+    pub async fn elect(url: &str, prefix: &str, lease: Duration) -> Result<Arc<Self>> {
+        let client = redis::Client::open(url).context("Invalid REDIS_URL")?;
+        let mut conn = ConnectionManager::new(client)
+            .await
+            .context("Failed to connect to Redis")?;
+
+        let leader = Arc::new(Leader {
+            is_leader: AtomicBool::new(false),
+        });
+        let key = format!("{}:leader", prefix);
+        let id = instance_id();
+
+        // First attempt inline so the caller knows its role before starting
+        leader.step(&mut conn, &key, &id, lease).await;
+
+        let runner = Arc::clone(&leader);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(lease / 3).await;
+                runner.step(&mut conn, &key, &id, lease).await;
+            }
+        });
+
+        Ok(leader)
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.is_leader.load(Ordering::Relaxed)
+    }
+
+    async fn step(&self, conn: &mut ConnectionManager, key: &str, id: &str, lease: Duration) {
+        let lease_ms = lease.as_millis() as u64;
+        let was_leader = self.is_leader();
+
+        let held = if was_leader {
+            redis::Script::new(RENEW_SCRIPT)
+                .key(key)
+                .arg(id)
+                .arg(lease_ms)
+                .invoke_async::<i64>(conn)
+                .await
+                .map(|renewed| renewed == 1)
+        } else {
+            redis::cmd("SET")
+                .arg(key)
+                .arg(id)
+                .arg("NX")
+                .arg("PX")
+                .arg(lease_ms)
+                .query_async::<Option<String>>(conn)
+                .await
+                .map(|reply| reply.is_some())
+        };
+
+        let held = match held {
+            Ok(held) => held,
+            Err(e) => {
+                // Can't prove we hold the lease, so stand down rather than risk two leaders
+                println!("{} Leader election error: {}", "[ERROR]".bright_red(), e);
+                false
+            }
+        };
+
+        if held != was_leader {
+            self.is_leader.store(held, Ordering::Relaxed);
+            if held {
+                println!("{} Elected leader ({}), sending alerts", "[INFO]".bright_blue(), id);
+            } else {
+                println!("{} Lost leadership, standing by", "[INFO]".bright_blue());
+            }
+        }
+    }
+}
+
+fn instance_id() -> String {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "scanner".to_string());
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    format!("{}-{}-{}", host, std::process::id(), nanos)
+}
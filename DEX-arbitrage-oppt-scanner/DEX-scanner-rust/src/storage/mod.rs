@@ -0,0 +1,626 @@
+pub mod leader;
+pub mod parquet;
+pub mod shared;
+pub mod webhook;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+pub use scanner_core::models::{OpportunityRecord, PairStats, VenuePairStats};
+
+use crate::models::snapshot::MarketSnapshot;
+use crate::subscription::Subscription;
+
+const RECORD_COLUMNS: &str =
+    "timestamp, chain, pair, buy_venue, sell_venue, buy_price, sell_price, spread, size, profit, profit_usd, \
+     opportunity_id, run_id";
+
+/// Aggregate over one pair's opportunities.
+#[derive(Debug, Clone)]
+pub struct PairRanking {
+    pub chain: String,
+    pub pair: String,
+    pub count: u64,
+    pub profit_usd: f64,
+    pub max_spread: f64,
+}
+
+/// Something done to a running instance through the bot: a command, an ack
+/// or a setting it changed, and by whom.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    /// Telegram user ID, if the update carried one.
+    pub user_id: Option<u64>,
+    /// `@username`, or the user's full name.
+    pub user: String,
+    pub chat_id: i64,
+    /// E.g. `command`, `denied`, `ack` or `subscription`.
+    pub action: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// A simulated fill of an opportunity in paper-trading mode, in quote units.
+#[derive(Debug, Clone)]
+pub struct PaperTrade {
+    pub opportunity_id: Uuid,
+    pub run_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub pair: String,
+    /// Symbol of the token the trade starts and ends in.
+    pub quote: String,
+    /// Block the opportunity was found in.
+    pub detected_block: u64,
+    /// Block whose state the trade was filled at, after the simulated
+    /// latency.
+    pub filled_block: u64,
+    pub size: f64,
+    pub proceeds: f64,
+    pub gas: f64,
+    pub flash_loan_fee: f64,
+    /// Proceeds less size, gas and the flash-loan fee.
+    pub pnl: f64,
+    pub pnl_usd: Option<f64>,
+    /// Virtual balance of `quote` after the trade.
+    pub balance: f64,
+    /// Whether the spread had closed by the fill block, so only gas was paid.
+    pub reverted: bool,
+}
+
+/// Paper-trading results in one token.
+#[derive(Debug, Clone)]
+pub struct PaperPnl {
+    pub quote: String,
+    pub trades: u64,
+    /// Trades that made money after gas.
+    pub wins: u64,
+    pub reverted: u64,
+    pub pnl: f64,
+    pub pnl_usd: f64,
+}
+
+/// An opportunity waiting to be delivered to the webhook.
+#[derive(Debug, Clone)]
+pub struct OutboxEntry {
+    pub id: i64,
+    /// JSON body to POST.
+    pub payload: String,
+    /// Failed deliveries so far.
+    pub attempts: u32,
+}
+
+fn read_record(row: &Row) -> rusqlite::Result<OpportunityRecord> {
+    Ok(OpportunityRecord {
+        timestamp: DateTime::from_timestamp(row.get(0)?, 0).unwrap_or_default(),
+        chain: row.get(1)?,
+        pair: row.get(2)?,
+        buy_venue: row.get(3)?,
+        sell_venue: row.get(4)?,
+        buy_price: row.get(5)?,
+        sell_price: row.get(6)?,
+        spread: row.get(7)?,
+        size: row.get(8)?,
+        profit: row.get(9)?,
+        profit_usd: row.get(10)?,
+        id: read_uuid(row, 11)?,
+        run_id: read_uuid(row, 12)?,
+    })
+}
+
+fn read_uuid(row: &Row, index: usize) -> rusqlite::Result<Uuid> {
+    Ok(row
+        .get::<_, Option<String>>(index)?
+        .and_then(|id| id.parse().ok())
+        .unwrap_or_default())
+}
+
+/// SQLite-backed opportunity history.
+pub struct Storage {
+    conn: Mutex<Connection>,
+}
+
+impl Storage {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path).with_context(|| format!("Failed to open database {}", path))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS opportunities (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                chain TEXT NOT NULL,
+                pair TEXT NOT NULL,
+                buy_venue TEXT NOT NULL,
+                sell_venue TEXT NOT NULL,
+                buy_price REAL NOT NULL,
+                sell_price REAL NOT NULL,
+                spread REAL NOT NULL,
+                size REAL NOT NULL,
+                profit REAL NOT NULL,
+                profit_usd REAL,
+                opportunity_id TEXT,
+                run_id TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_opportunities_timestamp ON opportunities(timestamp);
+            CREATE INDEX IF NOT EXISTS idx_opportunities_pair ON opportunities(pair, timestamp);
+            CREATE TABLE IF NOT EXISTS webhook_outbox (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                payload TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE TABLE IF NOT EXISTS market_snapshots (
+                chain TEXT NOT NULL,
+                block INTEGER NOT NULL,
+                timestamp INTEGER NOT NULL,
+                snapshot TEXT NOT NULL,
+                PRIMARY KEY (chain, block)
+            );
+            CREATE INDEX IF NOT EXISTS idx_market_snapshots_timestamp ON market_snapshots(timestamp);
+            CREATE TABLE IF NOT EXISTS paper_trades (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                opportunity_id TEXT NOT NULL,
+                run_id TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                pair TEXT NOT NULL,
+                quote TEXT NOT NULL,
+                size REAL NOT NULL,
+                proceeds REAL NOT NULL,
+                gas REAL NOT NULL,
+                flash_loan_fee REAL NOT NULL,
+                pnl REAL NOT NULL,
+                pnl_usd REAL,
+                balance REAL NOT NULL,
+                detected_block INTEGER,
+                filled_block INTEGER,
+                reverted INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE INDEX IF NOT EXISTS idx_paper_trades_timestamp ON paper_trades(timestamp);
+            CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                user_id INTEGER,
+                user TEXT NOT NULL,
+                chat_id INTEGER NOT NULL,
+                action TEXT NOT NULL,
+                old_value TEXT,
+                new_value TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_audit_log_timestamp ON audit_log(timestamp);
+            CREATE TABLE IF NOT EXISTS subscriptions (
+                chat_id INTEGER PRIMARY KEY,
+                lang TEXT NOT NULL,
+                pairs TEXT NOT NULL,
+                chains TEXT NOT NULL,
+                min_margin REAL,
+                min_profit_usd REAL
+            );",
+        )?;
+        // Databases created before opportunities had IDs
+        if conn.prepare("SELECT opportunity_id FROM opportunities LIMIT 0").is_err() {
+            conn.execute_batch(
+                "ALTER TABLE opportunities ADD COLUMN opportunity_id TEXT;
+                ALTER TABLE opportunities ADD COLUMN run_id TEXT;",
+            )?;
+        }
+        // Databases created before paper trades modelled latency
+        if conn.prepare("SELECT reverted FROM paper_trades LIMIT 0").is_err() {
+            conn.execute_batch(
+                "ALTER TABLE paper_trades ADD COLUMN detected_block INTEGER;
+                ALTER TABLE paper_trades ADD COLUMN filled_block INTEGER;
+                ALTER TABLE paper_trades ADD COLUMN reverted INTEGER NOT NULL DEFAULT 0;",
+            )?;
+        }
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_opportunities_id ON opportunities(opportunity_id)",
+            [],
+        )?;
+
+        Ok(Storage {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Records `record`, unless one with its ID already is.
+    pub fn record_opportunity(&self, record: &OpportunityRecord) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            &format!(
+                "INSERT OR IGNORE INTO opportunities ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                RECORD_COLUMNS
+            ),
+            params![
+                record.timestamp.timestamp(),
+                record.chain,
+                record.pair,
+                record.buy_venue,
+                record.sell_venue,
+                record.buy_price,
+                record.sell_price,
+                record.spread,
+                record.size,
+                record.profit,
+                record.profit_usd,
+                record.id.to_string(),
+                record.run_id.to_string(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Whether an opportunity with ID `id` was recorded, by this run or an
+    /// earlier one.
+    pub fn has_opportunity(&self, id: Uuid) -> Result<bool> {
+        Ok(self.conn.lock().unwrap().query_row(
+            "SELECT EXISTS (SELECT 1 FROM opportunities WHERE opportunity_id = ?1)",
+            params![id.to_string()],
+            |row| row.get(0),
+        )?)
+    }
+
+    pub fn record_paper_trade(&self, trade: &PaperTrade) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO paper_trades
+                (opportunity_id, run_id, timestamp, pair, quote, size, proceeds, gas, flash_loan_fee, pnl, pnl_usd, balance,
+                 detected_block, filled_block, reverted)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            params![
+                trade.opportunity_id.to_string(),
+                trade.run_id.to_string(),
+                trade.timestamp.timestamp(),
+                trade.pair,
+                trade.quote,
+                trade.size,
+                trade.proceeds,
+                trade.gas,
+                trade.flash_loan_fee,
+                trade.pnl,
+                trade.pnl_usd,
+                trade.balance,
+                trade.detected_block,
+                trade.filled_block,
+                trade.reverted,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_audit(&self, entry: &AuditEntry) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO audit_log (timestamp, user_id, user, chat_id, action, old_value, new_value)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                entry.timestamp.timestamp(),
+                entry.user_id.map(|id| id as i64),
+                entry.user,
+                entry.chat_id,
+                entry.action,
+                entry.old_value,
+                entry.new_value,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The `limit` latest audit entries, newest first.
+    pub fn audit_log(&self, limit: usize) -> Result<Vec<AuditEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT timestamp, user_id, user, chat_id, action, old_value, new_value
+             FROM audit_log
+             ORDER BY id DESC
+             LIMIT ?1",
+        )?;
+
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            Ok(AuditEntry {
+                timestamp: DateTime::from_timestamp(row.get(0)?, 0).unwrap_or_default(),
+                user_id: row.get::<_, Option<i64>>(1)?.map(|id| id as u64),
+                user: row.get(2)?,
+                chat_id: row.get(3)?,
+                action: row.get(4)?,
+                old_value: row.get(5)?,
+                new_value: row.get(6)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Adds `subscription`, or replaces its chat's.
+    pub fn save_subscription(&self, subscription: &Subscription) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO subscriptions (chat_id, lang, pairs, chains, min_margin, min_profit_usd)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                subscription.chat_id,
+                subscription.lang.code(),
+                subscription.pairs.join(","),
+                subscription.chains.join(","),
+                subscription.min_margin,
+                subscription.min_profit_usd,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_subscription(&self, chat_id: i64) -> Result<()> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute("DELETE FROM subscriptions WHERE chat_id = ?1", params![chat_id])?;
+        Ok(())
+    }
+
+    pub fn subscriptions(&self) -> Result<Vec<Subscription>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT chat_id, lang, pairs, chains, min_margin, min_profit_usd FROM subscriptions")?;
+        let list = |value: String| value.split(',').filter(|s| !s.is_empty()).map(str::to_string).collect();
+        let rows = stmt.query_map([], |row| {
+            Ok(Subscription {
+                chat_id: row.get(0)?,
+                lang: row.get::<_, String>(1)?.parse().unwrap_or_default(),
+                pairs: list(row.get(2)?),
+                chains: list(row.get(3)?),
+                min_margin: row.get(4)?,
+                min_profit_usd: row.get(5)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Paper-trading results per token since `since`, most trades first.
+    pub fn paper_pnl(&self, since: DateTime<Utc>) -> Result<Vec<PaperPnl>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT quote, COUNT(*), SUM(pnl > 0), SUM(reverted), SUM(pnl), COALESCE(SUM(pnl_usd), 0.0)
+             FROM paper_trades
+             WHERE timestamp >= ?1
+             GROUP BY quote
+             ORDER BY 2 DESC",
+        )?;
+
+        let rows = stmt.query_map(params![since.timestamp()], |row| {
+            Ok(PaperPnl {
+                quote: row.get(0)?,
+                trades: row.get(1)?,
+                wins: row.get(2)?,
+                reverted: row.get(3)?,
+                pnl: row.get(4)?,
+                pnl_usd: row.get(5)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Theoretical USD profit per venue combination since `since`, most
+    /// profitable first.
+    pub fn profit_by_venue_pair(&self, since: DateTime<Utc>) -> Result<Vec<VenuePairStats>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT
+                CASE WHEN buy_venue < sell_venue
+                    THEN buy_venue || ' ↔ ' || sell_venue
+                    ELSE sell_venue || ' ↔ ' || buy_venue
+                END AS venues,
+                COUNT(*),
+                COALESCE(SUM(profit_usd), 0.0),
+                MAX(spread)
+             FROM opportunities
+             WHERE timestamp >= ?1
+             GROUP BY venues
+             ORDER BY 3 DESC",
+        )?;
+
+        let rows = stmt.query_map(params![since.timestamp()], |row| {
+            Ok(VenuePairStats {
+                venues: row.get(0)?,
+                count: row.get(1)?,
+                profit_usd: row.get(2)?,
+                max_spread: row.get(3)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// The `limit` pairs with the most theoretical USD profit since `since`,
+    /// ties broken by opportunity count. Pairs are per chain: the same
+    /// symbols on two chains can be different assets, e.g. native USDC and
+    /// a bridge's.
+    pub fn top_pairs(&self, since: DateTime<Utc>, limit: usize) -> Result<Vec<PairRanking>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT chain, pair, COUNT(*), COALESCE(SUM(profit_usd), 0.0), MAX(spread)
+             FROM opportunities
+             WHERE timestamp >= ?1
+             GROUP BY chain, pair
+             ORDER BY 4 DESC, 3 DESC
+             LIMIT ?2",
+        )?;
+
+        let rows = stmt.query_map(params![since.timestamp(), limit as i64], |row| {
+            Ok(PairRanking {
+                chain: row.get(0)?,
+                pair: row.get(1)?,
+                count: row.get(2)?,
+                profit_usd: row.get(3)?,
+                max_spread: row.get(4)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// All opportunities recorded in `[from, to)`, oldest first.
+    pub fn opportunities_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<OpportunityRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM opportunities
+             WHERE timestamp >= ?1 AND timestamp < ?2
+             ORDER BY timestamp",
+            RECORD_COLUMNS
+        ))?;
+
+        let rows = stmt.query_map(params![from.timestamp(), to.timestamp()], read_record)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Up to `limit` opportunities recorded in `[from, to)` on `pair`
+    /// (`BASE/QUOTE`, ignoring case) if given, newest first, skipping the
+    /// first `offset`.
+    pub fn opportunities_page(
+        &self,
+        pair: Option<&str>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<OpportunityRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM opportunities
+             WHERE timestamp >= ?1 AND timestamp < ?2 AND (?3 IS NULL OR LOWER(pair) = LOWER(?3))
+             ORDER BY timestamp DESC, rowid DESC
+             LIMIT ?4 OFFSET ?5",
+            RECORD_COLUMNS
+        ))?;
+
+        let rows = stmt.query_map(
+            params![from.timestamp(), to.timestamp(), pair, limit as i64, offset as i64],
+            read_record,
+        )?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Statistics over opportunities on `base`/`quote`, in either order and
+    /// ignoring case, since `since`.
+    pub fn pair_stats(&self, base: &str, quote: &str, since: DateTime<Utc>) -> Result<PairStats> {
+        let conn = self.conn.lock().unwrap();
+        let filter = "WHERE timestamp >= ?1 AND LOWER(pair) IN (LOWER(?2), LOWER(?3))";
+        let forward = format!("{}/{}", base, quote);
+        let reverse = format!("{}/{}", quote, base);
+        let args = params![since.timestamp(), forward, reverse];
+
+        let (count, min_spread, avg_spread, max_spread, profit_usd) = conn.query_row(
+            &format!(
+                "SELECT COUNT(*), MIN(spread), AVG(spread), MAX(spread), COALESCE(SUM(profit_usd), 0.0)
+                 FROM opportunities {}",
+                filter
+            ),
+            args,
+            |row| {
+                Ok((
+                    row.get::<_, u64>(0)?,
+                    row.get::<_, Option<f64>>(1)?,
+                    row.get::<_, Option<f64>>(2)?,
+                    row.get::<_, Option<f64>>(3)?,
+                    row.get::<_, f64>(4)?,
+                ))
+            },
+        )?;
+
+        let widest = conn
+            .prepare(&format!(
+                "SELECT {} FROM opportunities {} ORDER BY spread DESC LIMIT 1",
+                RECORD_COLUMNS, filter
+            ))?
+            .query_map(args, read_record)?
+            .next()
+            .transpose()?;
+
+        Ok(PairStats {
+            count,
+            min_spread: min_spread.unwrap_or_default(),
+            avg_spread: avg_spread.unwrap_or_default(),
+            max_spread: max_spread.unwrap_or_default(),
+            profit_usd,
+            widest,
+        })
+    }
+
+    /// Queues `payload` for the webhook.
+    pub fn queue_webhook(&self, payload: &str) -> Result<()> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute("INSERT INTO webhook_outbox (payload) VALUES (?1)", params![payload])?;
+        Ok(())
+    }
+
+    /// Up to `limit` queued webhook payloads, oldest first.
+    pub fn queued_webhooks(&self, limit: usize) -> Result<Vec<OutboxEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, payload, attempts FROM webhook_outbox ORDER BY id LIMIT ?1",
+        )?;
+
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            Ok(OutboxEntry {
+                id: row.get(0)?,
+                payload: row.get(1)?,
+                attempts: row.get(2)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Payloads still queued for the webhook.
+    pub fn webhook_backlog(&self) -> Result<u64> {
+        Ok(self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM webhook_outbox", [], |row| row.get(0))?)
+    }
+
+    /// Records a failed delivery of entry `id`.
+    pub fn fail_webhook(&self, id: i64) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            "UPDATE webhook_outbox SET attempts = attempts + 1 WHERE id = ?1",
+            params![id],
+        )?;
+        Ok(())
+    }
+
+    /// Archives `snapshot` as `chain`'s market at its block, replacing one
+    /// already archived for the block.
+    pub fn archive_snapshot(&self, chain: &str, snapshot: &MarketSnapshot) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO market_snapshots (chain, block, timestamp, snapshot)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                chain,
+                snapshot.block as i64,
+                snapshot.read_at.timestamp(),
+                snapshot.to_json().to_string(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Drops snapshots read before `before`, returning how many.
+    pub fn prune_snapshots(&self, before: DateTime<Utc>) -> Result<usize> {
+        Ok(self.conn.lock().unwrap().execute(
+            "DELETE FROM market_snapshots WHERE timestamp < ?1",
+            params![before.timestamp()],
+        )?)
+    }
+
+    /// Removes entry `id`, once delivered or dead-lettered.
+    pub fn remove_webhook(&self, id: i64) -> Result<()> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute("DELETE FROM webhook_outbox WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+}
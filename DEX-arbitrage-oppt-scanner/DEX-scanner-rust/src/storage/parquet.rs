@@ -0,0 +1,142 @@
+use anyhow::{Context, Result};
+use arrow::array::{ArrayRef, Float64Array, StringArray, TimestampSecondArray};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use chrono::{Days, NaiveDate, Utc};
+use colored::*;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{OpportunityRecord, Storage};
+
+const FILE_NAME: &str = "opportunities.parquet";
+
+fn schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("timestamp", DataType::Timestamp(TimeUnit::Second, Some("UTC".into())), false),
+        Field::new("chain", DataType::Utf8, false),
+        Field::new("pair", DataType::Utf8, false),
+        Field::new("buy_venue", DataType::Utf8, false),
+        Field::new("sell_venue", DataType::Utf8, false),
+        Field::new("buy_price", DataType::Float64, false),
+        Field::new("sell_price", DataType::Float64, false),
+        Field::new("spread", DataType::Float64, false),
+        Field::new("size", DataType::Float64, false),
+        Field::new("profit", DataType::Float64, false),
+        Field::new("profit_usd", DataType::Float64, true),
+        Field::new("id", DataType::Utf8, false),
+        Field::new("run_id", DataType::Utf8, false),
+    ]))
+}
+
+fn to_batch(records: &[&OpportunityRecord]) -> Result<RecordBatch> {
+    let strings = |f: fn(&OpportunityRecord) -> &str| -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(records.iter().map(|r| f(r))))
+    };
+    let floats = |f: fn(&OpportunityRecord) -> f64| -> ArrayRef {
+        Arc::new(Float64Array::from_iter_values(records.iter().map(|r| f(r))))
+    };
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            TimestampSecondArray::from_iter_values(records.iter().map(|r| r.timestamp.timestamp()))
+                .with_timezone("UTC"),
+        ),
+        strings(|r| &r.chain),
+        strings(|r| &r.pair),
+        strings(|r| &r.buy_venue),
+        strings(|r| &r.sell_venue),
+        floats(|r| r.buy_price),
+        floats(|r| r.sell_price),
+        floats(|r| r.spread),
+        floats(|r| r.size),
+        floats(|r| r.profit),
+        Arc::new(records.iter().map(|r| r.profit_usd).collect::<Float64Array>()),
+        Arc::new(StringArray::from_iter_values(records.iter().map(|r| r.id.to_string()))),
+        Arc::new(StringArray::from_iter_values(records.iter().map(|r| r.run_id.to_string()))),
+    ];
+
+    Ok(RecordBatch::try_new(schema(), columns)?)
+}
+
+/// Hive-style partition directory, e.g. `<root>/date=2024-03-01/chain=ethereum`.
+fn partition_dir(root: &Path, date: NaiveDate, chain: &str) -> PathBuf {
+    root.join(format!("date={}", date.format("%Y-%m-%d")))
+        .join(format!("chain={}", chain))
+}
+
+fn write_file(path: &Path, batch: &RecordBatch) -> Result<()> {
+    // Write to a temporary name first so readers never see a half-written file
+    let tmp = path.with_extension("parquet.tmp");
+    let file = File::create(&tmp).with_context(|| format!("Failed to create {}", tmp.display()))?;
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(props))?;
+    writer.write(batch)?;
+    writer.close()?;
+
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Writes every opportunity recorded on `date` (UTC) to one Parquet file per
+/// chain under `root`. Returns the files written.
+pub fn export_day(storage: &Storage, root: &Path, date: NaiveDate) -> Result<Vec<PathBuf>> {
+    let from = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let records = storage.opportunities_between(from, from + Days::new(1))?;
+
+    let mut by_chain: BTreeMap<&str, Vec<&OpportunityRecord>> = BTreeMap::new();
+    for record in &records {
+        by_chain.entry(&record.chain).or_default().push(record);
+    }
+
+    let mut written = Vec::with_capacity(by_chain.len());
+    for (chain, records) in by_chain {
+        let dir = partition_dir(root, date, chain);
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let path = dir.join(FILE_NAME);
+        write_file(&path, &to_batch(&records)?)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// Exports each completed UTC day to `root` shortly after midnight, starting
+/// with yesterday if it hasn't been exported yet. Runs until the process exits.
+pub async fn run(storage: &Storage, root: &Path) {
+    loop {
+        let today = Utc::now().date_naive();
+        let yesterday = today - Days::new(1);
+
+        let already_exported = fs::read_dir(root.join(format!("date={}", yesterday.format("%Y-%m-%d"))))
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(false);
+        if !already_exported {
+            match export_day(storage, root, yesterday) {
+                Ok(files) => println!(
+                    "{} Exported {} to {} Parquet file(s) in {}",
+                    "[INFO]".bright_blue(),
+                    yesterday,
+                    files.len(),
+                    root.display()
+                ),
+                Err(e) => println!("{} Parquet export failed: {}", "[ERROR]".bright_red(), e),
+            }
+        }
+
+        let next_midnight = (today + Days::new(1)).and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let wait = (next_midnight - Utc::now()).to_std().unwrap_or_default();
+        // Small margin so opportunities stamped right at midnight have landed
+        tokio::time::sleep(wait + Duration::from_secs(60)).await;
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::Result;
+use chrono::Utc;
+use colored::*;
+use serde_json::json;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::{OpportunityRecord, OutboxEntry, Storage};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// Entries delivered per round; a longer backlog drains over several rounds
+const BATCH: usize = 100;
+const MAX_BACKOFF_SECS: u64 = 600;
+
+/// Where opportunities are POSTed, and what happens to ones that keep failing.
+pub struct WebhookSink {
+    pub url: String,
+    /// Failed deliveries before an opportunity is dead-lettered.
+    pub max_attempts: u32,
+    /// JSON Lines file dead-lettered opportunities are appended to.
+    pub dead_letter: PathBuf,
+}
+
+/// The JSON body POSTed for `record`.
+pub fn payload(record: &OpportunityRecord) -> String {
+    json!({
+        "id": record.id.to_string(),
+        "run_id": record.run_id.to_string(),
+        "timestamp": record.timestamp.timestamp(),
+        "chain": record.chain,
+        "pair": record.pair,
+        "buy_venue": record.buy_venue,
+        "sell_venue": record.sell_venue,
+        "buy_price": record.buy_price,
+        "sell_price": record.sell_price,
+        "spread": record.spread,
+        "size": record.size,
+        "profit": record.profit,
+        "profit_usd": record.profit_usd,
+    })
+    .to_string()
+}
+
+/// Pause after the `failures`th failed delivery in a row: 5s, doubling up
+/// to 10 minutes.
+fn backoff(failures: u32) -> Duration {
+    Duration::from_secs((5u64 << failures.saturating_sub(1).min(7)).min(MAX_BACKOFF_SECS))
+}
+
+fn dead_letter(sink: &WebhookSink, entry: &OutboxEntry, error: &str) -> Result<()> {
+    let line = json!({
+        "failed_at": Utc::now().timestamp(),
+        "attempts": entry.attempts + 1,
+        "error": error,
+        "payload": serde_json::from_str::<serde_json::Value>(&entry.payload)?,
+    });
+    let mut file = OpenOptions::new().create(true).append(true).open(&sink.dead_letter)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+async fn deliver(client: &reqwest::Client, sink: &WebhookSink, entry: &OutboxEntry) -> Result<()> {
+    client
+        .post(&sink.url)
+        .header("content-type", "application/json")
+        .body(entry.payload.clone())
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Delivers opportunities queued in `storage` to the webhook, oldest first.
+/// The queue lives in the database, so opportunities recorded while the
+/// endpoint is down, or the scanner is restarting, are replayed in order
+/// once it's back. While it's failing, the oldest entry is retried with
+/// backoff, and after `max_attempts` failures it's moved to the dead-letter
+/// file so the rest can go. Runs until the process exits.
+pub async fn run(storage: &Storage, sink: WebhookSink) {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    let mut failures = 0;
+    loop {
+        let entries = match storage.queued_webhooks(BATCH) {
+            Ok(entries) => entries,
+            Err(e) => {
+                println!("{} Failed to read webhook queue: {}", "[ERROR]".bright_red(), e);
+                Vec::new()
+            }
+        };
+
+        for entry in entries {
+            let error = match deliver(&client, &sink, &entry).await {
+                Ok(()) => {
+                    if failures > 0 {
+                        failures = 0;
+                        println!("{} Webhook recovered, replaying {} queued opportunities",
+                            "[INFO]".bright_blue(),
+                            storage.webhook_backlog().unwrap_or_default(),
+                        );
+                    }
+                    if let Err(e) = storage.remove_webhook(entry.id) {
+                        println!("{} Failed to dequeue webhook entry: {}", "[ERROR]".bright_red(), e);
+                    }
+                    continue;
+                }
+                Err(e) => e,
+            };
+
+            failures += 1;
+            if failures == 1 {
+                println!("{} Webhook delivery failed, queuing opportunities until it recovers: {}",
+                    "[ERROR]".bright_red(),
+                    error,
+                );
+            }
+            let result = if entry.attempts + 1 >= sink.max_attempts {
+                println!("{} Webhook delivery failed {} times, dead-lettering to {}: {}",
+                    "[ERROR]".bright_red(),
+                    entry.attempts + 1,
+                    sink.dead_letter.display(),
+                    error,
+                );
+                dead_letter(&sink, &entry, &error.to_string())
+                    .and_then(|()| storage.remove_webhook(entry.id))
+            } else {
+                storage.fail_webhook(entry.id)
+            };
+            if let Err(e) = result {
+                println!("{} Failed to update webhook queue: {}", "[ERROR]".bright_red(), e);
+            }
+            // Later entries would fail the same way, and must not overtake this one
+            break;
+        }
+
+        let pause = if failures > 0 { backoff(failures) } else { POLL_INTERVAL };
+        tokio::time::sleep(pause).await;
+    }
+}
@@ -0,0 +1,78 @@
+use super::{screen, Strategy};
+use crate::models::opportunity::Opportunity;
+use crate::models::snapshot::MarketSnapshot;
+use crate::models::token::{weth, TokenInfo};
+use crate::preset;
+
+// Stablecoins should trade at par and LSTs at their redemption rate, so
+// thinner gaps are already worth a look
+const STABLE_DEPEG_MARGIN: f64 = 0.003;
+const LST_BASIS_MARGIN: f64 = 0.003;
+
+fn is_lst(token: &TokenInfo) -> bool {
+    matches!(token.symbol, "cbETH" | "wstETH" | "rETH" | "weETH")
+}
+
+/// Buys a pair's base on the venue where it's cheaper and sells it on the
+/// other. Variants narrow the pairs considered and set their own threshold,
+/// which a pair's own margin in the preset and then the snapshot's
+/// `min_profit_usd` replace when set; either is scaled by the snapshot's
+/// `threshold_scale`.
+pub struct CrossVenue {
+    name: &'static str,
+    /// Minimum gap between the venues, the preset's if `None`.
+    min_margin: Option<f64>,
+    pairs: fn(&TokenInfo, &TokenInfo) -> bool,
+}
+
+impl CrossVenue {
+    /// Every pair, at the preset's minimum profit margin.
+    pub fn all() -> Self {
+        CrossVenue {
+            name: "cross-venue",
+            min_margin: None,
+            pairs: |_, _| true,
+        }
+    }
+
+    /// Pairs of two USD stablecoins.
+    pub fn stable_depeg() -> Self {
+        CrossVenue {
+            name: "stable-depeg",
+            min_margin: Some(STABLE_DEPEG_MARGIN),
+            pairs: |base, quote| base.is_usd_stable() && quote.is_usd_stable(),
+        }
+    }
+
+    /// Liquid staking tokens against the wrapped native token.
+    pub fn lst_basis() -> Self {
+        CrossVenue {
+            name: "lst-basis",
+            min_margin: Some(LST_BASIS_MARGIN),
+            pairs: |base, quote| {
+                let native = weth().address;
+                (is_lst(base) && quote.address == native) || (is_lst(quote) && base.address == native)
+            },
+        }
+    }
+}
+
+impl Strategy for CrossVenue {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn evaluate(&self, snapshot: &MarketSnapshot) -> Vec<Opportunity> {
+        let preset = preset::active();
+        let min_margin = self.min_margin.unwrap_or(preset.min_profit_margin);
+        snapshot
+            .pairs
+            .iter()
+            .filter(|pair| (self.pairs)(pair.base, pair.quote))
+            .filter_map(|pair| {
+                let min_margin = preset.pair_margin(pair.base, pair.quote).unwrap_or(min_margin);
+                screen(pair, snapshot, min_margin * snapshot.threshold_scale)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,150 @@
+mod cross_venue;
+mod triangular;
+pub mod wasm;
+
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::path::Path;
+
+use crate::models::opportunity::Opportunity;
+use crate::models::snapshot::{MarketSnapshot, PairQuote};
+use crate::utils::format as fmt;
+use crate::utils::price;
+
+pub use cross_venue::CrossVenue;
+pub use triangular::Triangular;
+
+/// A way of finding opportunities in a market snapshot. Strategies only
+/// detect: TVL filtering, alerting and recording are shared by all of them.
+pub trait Strategy: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn evaluate(&self, snapshot: &MarketSnapshot) -> Vec<Opportunity>;
+}
+
+/// Lets strategies built by name from a [`Registry`] be handed on as one.
+impl Strategy for Box<dyn Strategy> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn evaluate(&self, snapshot: &MarketSnapshot) -> Vec<Opportunity> {
+        (**self).evaluate(snapshot)
+    }
+}
+
+/// Sizes an arbitrage across `pair`'s pools, or `None` with a log line if
+/// the spread doesn't cover swap fees.
+fn size(pair: &PairQuote, snapshot: &MarketSnapshot) -> Option<Opportunity> {
+    let [a, b] = pair.pools;
+    let opportunity = Opportunity::evaluate(
+        pair.base,
+        pair.quote,
+        a,
+        b,
+        pair.gas_cost,
+        snapshot.flash_loan_fee,
+    );
+    if opportunity.is_none() {
+        println!("{} {} Spread on {}/{} doesn't cover swap fees at block {}",
+            "[INFO]".bright_blue(),
+            fmt::clock().bright_black(),
+            pair.base.symbol,
+            pair.quote.symbol,
+            snapshot.block,
+        );
+    }
+    opportunity
+}
+
+/// Sizes an arbitrage across `pair`'s pools if it clears the snapshot's
+/// `min_profit_usd`, or `min_margin` when that's unset or the quote has no
+/// USD price. `min_margin` comes already scaled.
+fn screen(pair: &PairQuote, snapshot: &MarketSnapshot, min_margin: f64) -> Option<Opportunity> {
+    let [a, b] = pair.pools;
+    let margin = price::calculate_profit_margin(a.price(), b.price());
+    match (snapshot.min_profit_usd, pair.quote_usd) {
+        // A spread inside both pools' fees can't be sized at all
+        (Some(min_usd), Some(quote_usd)) if margin > a.fee + b.fee => size(pair, snapshot)
+            .filter(|opportunity| {
+                let profit = opportunity.net_profit().unwrap_or(opportunity.profit());
+                profit * quote_usd >= min_usd * snapshot.threshold_scale
+            }),
+        (Some(_), Some(_)) => None,
+        // Without a USD price for the quote, fall back to the margin
+        _ if margin > min_margin => size(pair, snapshot),
+        _ => None,
+    }
+}
+
+type Factory = Box<dyn Fn() -> Box<dyn Strategy>>;
+
+/// Strategies that can be enabled by name, e.g. through `STRATEGIES`.
+pub struct Registry {
+    factories: Vec<(String, Factory)>,
+}
+
+impl Default for Registry {
+    /// The built-in strategies.
+    fn default() -> Self {
+        let mut registry = Registry { factories: Vec::new() };
+        registry.register("cross-venue", || Box::new(CrossVenue::all()));
+        registry.register("stable-depeg", || Box::new(CrossVenue::stable_depeg()));
+        registry.register("lst-basis", || Box::new(CrossVenue::lst_basis()));
+        registry.register("triangular", || Box::new(Triangular));
+        registry
+    }
+}
+
+impl Registry {
+    /// Makes a strategy available as `name`, replacing any registered under
+    /// the same name.
+    pub fn register(&mut self, name: &str, factory: impl Fn() -> Box<dyn Strategy> + 'static) {
+        self.factories.retain(|(existing, _)| existing != name);
+        self.factories.push((name.to_string(), Box::new(factory)));
+    }
+
+    /// The built-in strategies plus the WebAssembly plugins in `paths`,
+    /// comma-separated.
+    pub fn with_plugins(paths: &str) -> Result<Self> {
+        let mut registry = Registry::default();
+        for path in paths.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            registry
+                .load_plugin(Path::new(path))
+                .with_context(|| format!("Failed to load plugin {}", path))?;
+        }
+        Ok(registry)
+    }
+
+    /// Compiles the WebAssembly strategy at `path` and registers it under
+    /// its file name, e.g. `my-strategy` for `plugins/my-strategy.wasm`.
+    pub fn load_plugin(&mut self, path: &Path) -> Result<()> {
+        let plugin = wasm::Plugin::load(path)?;
+        let name = plugin.name().to_string();
+        self.register(&name, move || Box::new(plugin.clone()));
+        Ok(())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.factories.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// The strategies named in `list`, comma-separated.
+    pub fn build(&self, list: &str) -> Result<Vec<Box<dyn Strategy>>> {
+        let mut strategies = Vec::new();
+        for name in list.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            match self.factories.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+                Some((_, factory)) => strategies.push(factory()),
+                None => bail!(
+                    "Unknown strategy {:?} (expected one of {})",
+                    name,
+                    self.names().join(", ")
+                ),
+            }
+        }
+        if strategies.is_empty() {
+            bail!("No strategies enabled");
+        }
+        Ok(strategies)
+    }
+}
@@ -0,0 +1,127 @@
+use alloy::primitives::Address;
+use std::collections::{HashMap, HashSet};
+
+use super::{screen, Strategy};
+use crate::models::opportunity::{Opportunity, PoolState};
+use crate::models::snapshot::{MarketSnapshot, PairQuote};
+use crate::models::token::TokenInfo;
+use crate::preset;
+
+// A routed arbitrage makes three swaps where a cross-venue one makes two
+const ROUTE_GAS_FACTOR: f64 = 1.5;
+
+/// The route from base to quote through a third token as one pool: `first`
+/// is the base/via pool and `second` the via/quote one. Two chained
+/// constant-product pools swap exactly like one with these virtual reserves
+/// and the first hop's fee, but only in the direction they're chained, as
+/// the second hop's fee is folded into the reserves. `sell` is whether base
+/// goes in (base → via → quote) rather than quote (quote → via → base).
+fn route(first: PoolState, second: PoolState, sell: bool) -> PoolState {
+    let (base, via_a) = first.reserves();
+    let (via_b, quote) = second.reserves();
+    if sell {
+        let keep = 1.0 - second.fee;
+        let depth = via_b + keep * via_a;
+        PoolState {
+            reserve_base: base * via_b / depth,
+            reserve_quote: keep * via_a * quote / depth,
+            ..first
+        }
+    } else {
+        let keep = 1.0 - first.fee;
+        let depth = via_a + keep * via_b;
+        PoolState {
+            reserve_base: keep * via_b * base / depth,
+            reserve_quote: quote * via_a / depth,
+            ..second
+        }
+    }
+}
+
+/// Trades a pair's pool against the route through another token on the
+/// same venue, e.g. WETH/DAI against WETH/USDC then USDC/DAI on Uniswap V2,
+/// a spread the cross-venue strategies can't see. Takes the most profitable
+/// route per pool at the preset's margin, or the snapshot's `min_profit_usd`
+/// when set, both scaled by its `threshold_scale`.
+pub struct Triangular;
+
+impl Strategy for Triangular {
+    fn name(&self) -> &str {
+        "triangular"
+    }
+
+    fn evaluate(&self, snapshot: &MarketSnapshot) -> Vec<Opportunity> {
+        let min_margin = preset::active().min_profit_margin * snapshot.threshold_scale;
+        let tokens = snapshot
+            .pairs
+            .iter()
+            .flat_map(|pair| [pair.base, pair.quote])
+            .map(|token| (token.address, token))
+            .collect::<HashMap<Address, &'static TokenInfo>>();
+
+        // Pairs share pools when a preset has more than two venues, so each
+        // pool is traded once, with the pair it was first quoted in
+        let mut direct = Vec::new();
+        let mut seen = HashSet::new();
+        for pair in &snapshot.pairs {
+            for pool in pair.pools {
+                if seen.insert(pool.pool) {
+                    direct.push((pair, pool));
+                }
+            }
+        }
+
+        // Every pool both ways round, by (venue, base, quote). Uniswap V3
+        // has a pool per fee tier; the deepest is routed through
+        let mut pools = HashMap::<_, PoolState>::new();
+        for (pair, pool) in &direct {
+            for (key, pool) in [
+                ((pool.venue, pair.base.address, pair.quote.address), *pool),
+                ((pool.venue, pair.quote.address, pair.base.address), pool.flipped()),
+            ] {
+                let kept = pools.entry(key).or_insert(pool);
+                if pool.reserve_base * pool.reserve_quote > kept.reserve_base * kept.reserve_quote {
+                    *kept = pool;
+                }
+            }
+        }
+
+        let mut found = Vec::new();
+        for (pair, direct) in direct {
+            let best = tokens
+                .values()
+                .filter_map(|via| {
+                    let first = *pools.get(&(direct.venue, pair.base.address, via.address))?;
+                    let second = *pools.get(&(direct.venue, via.address, pair.quote.address))?;
+                    if [first, second].iter().any(|pool| pool.reserve_base <= 0.0 || pool.reserve_quote <= 0.0) {
+                        return None;
+                    }
+                    // Fee-free, to tell which way the route is traded
+                    let sell = first.price() * second.price() > direct.price();
+                    let routed = route(first, second, sell);
+                    // Fees can turn the spread around
+                    if (routed.price() > direct.price()) != sell {
+                        return None;
+                    }
+                    let quote = PairQuote {
+                        pools: [direct, routed],
+                        gas_cost: pair.gas_cost.map(|gas| gas * ROUTE_GAS_FACTOR),
+                        ..*pair
+                    };
+                    let mut opportunity = screen(&quote, snapshot, min_margin)?;
+                    if sell {
+                        opportunity.sell_via = Some(*via);
+                    } else {
+                        opportunity.buy_via = Some(*via);
+                    }
+                    Some(opportunity)
+                })
+                .max_by(|a, b| {
+                    let profit = |o: &Opportunity| o.net_profit().unwrap_or(o.profit());
+                    profit(a).total_cmp(&profit(b))
+                });
+            found.extend(best);
+        }
+        found
+    }
+}
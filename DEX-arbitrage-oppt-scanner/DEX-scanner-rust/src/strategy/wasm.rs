@@ -0,0 +1,143 @@
+//! Strategies compiled to WebAssembly and loaded at startup, so custom
+//! detection can ship without forking the scanner.
+//!
+//! A plugin is a core wasm module with no imports that exports:
+//!
+//! - `memory`
+//! - `abi_version() -> i32`, returning [`ABI_VERSION`]
+//! - `alloc(len: i32) -> i32`, a buffer of `len` bytes for the host to
+//!   write the snapshot into
+//! - `evaluate(ptr: i32, len: i32) -> i64`, which reads the snapshot as
+//!   JSON (see [`MarketSnapshot::to_json`]) at `ptr` and returns where its
+//!   answer is, packed as `ptr << 32 | len`
+//!
+//! The answer is a JSON array of the pairs worth trading, by their index in
+//! the snapshot's `pairs`, e.g. `[{"pair": 0}]`. The host sizes each one
+//! the way the built-in strategies do and drops any whose spread doesn't
+//! cover fees.
+
+use anyhow::{ensure, Context, Result};
+use colored::*;
+use serde::Deserialize;
+use std::path::Path;
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use super::{size, Strategy};
+use crate::models::opportunity::Opportunity;
+use crate::models::snapshot::MarketSnapshot;
+
+/// Host API version plugins are built against.
+pub const ABI_VERSION: i32 = 1;
+// Bounds one evaluation to roughly a tenth of a second, so a plugin stuck
+// in a loop fails instead of stalling the scan
+const FUEL_PER_EVALUATION: u64 = 100_000_000;
+const MAX_MEMORY_BYTES: usize = 64 << 20;
+
+#[derive(Deserialize)]
+struct Pick {
+    pair: usize,
+}
+
+/// A compiled plugin. Every evaluation runs in a fresh instance, so
+/// plugins can't carry state from one block to the next.
+#[derive(Clone)]
+pub struct Plugin {
+    name: String,
+    engine: Engine,
+    module: Module,
+}
+
+impl Plugin {
+    /// Compiles the module at `path` and checks it implements the host API.
+    pub fn load(path: &Path) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .with_context(|| format!("Invalid plugin path {}", path.display()))?
+            .to_string();
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::from_file(&engine, path)
+            .with_context(|| format!("Failed to compile plugin {}", path.display()))?;
+        let plugin = Plugin { name, engine, module };
+
+        let (mut store, instance) = plugin.instantiate()?;
+        let version = instance
+            .get_typed_func::<(), i32>(&mut store, "abi_version")?
+            .call(&mut store, ())?;
+        ensure!(
+            version == ABI_VERSION,
+            "plugin {} targets ABI version {}, the scanner provides {}",
+            plugin.name,
+            version,
+            ABI_VERSION
+        );
+        Ok(plugin)
+    }
+
+    fn instantiate(&self) -> Result<(Store<StoreLimits>, Instance)> {
+        let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY_BYTES).build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(FUEL_PER_EVALUATION)?;
+        let instance = Instance::new(&mut store, &self.module, &[])?;
+        Ok((store, instance))
+    }
+
+    /// Indices of the snapshot pairs the plugin picks.
+    fn picks(&self, snapshot: &MarketSnapshot) -> Result<Vec<usize>> {
+        let input = snapshot.to_json().to_string();
+        let (mut store, instance) = self.instantiate()?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .context("plugin doesn't export its memory")?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let evaluate = instance.get_typed_func::<(i32, i32), i64>(&mut store, "evaluate")?;
+
+        let len = i32::try_from(input.len()).context("snapshot too large for the plugin")?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory.write(&mut store, ptr as u32 as usize, input.as_bytes())?;
+        let answer = evaluate.call(&mut store, (ptr, len))? as u64;
+
+        let mut output = vec![0; (answer & u32::MAX as u64) as usize];
+        memory.read(&store, (answer >> 32) as usize, &mut output)?;
+        let picks = serde_json::from_slice::<Vec<Pick>>(&output).context("Invalid plugin answer")?;
+        picks
+            .into_iter()
+            .map(|pick| {
+                ensure!(
+                    pick.pair < snapshot.pairs.len(),
+                    "plugin picked pair {} of {}",
+                    pick.pair,
+                    snapshot.pairs.len()
+                );
+                Ok(pick.pair)
+            })
+            .collect()
+    }
+}
+
+impl Strategy for Plugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn evaluate(&self, snapshot: &MarketSnapshot) -> Vec<Opportunity> {
+        match self.picks(snapshot) {
+            Ok(picks) => picks
+                .into_iter()
+                .filter_map(|index| size(&snapshot.pairs[index], snapshot))
+                .collect(),
+            Err(e) => {
+                println!("{} Plugin {} failed at block {}: {:#}",
+                    "[ERROR]".bright_red(),
+                    self.name,
+                    snapshot.block,
+                    e,
+                );
+                Vec::new()
+            }
+        }
+    }
+}
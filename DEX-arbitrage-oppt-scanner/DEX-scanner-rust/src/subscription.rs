@@ -0,0 +1,163 @@
+//! Multi-tenant mode: Telegram chats that subscribe themselves with
+//! /subscribe, on top of the configured alert chats, each with its own
+//! filters on the opportunities it's sent.
+
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::i18n::Lang;
+use crate::utils::format::{format_percent, format_usd};
+
+/// A chat subscribed to opportunity alerts. Empty lists match everything.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscription {
+    pub chat_id: i64,
+    pub lang: Lang,
+    /// `BASE/QUOTE` symbols, upper case.
+    pub pairs: Vec<String>,
+    /// Chain names as presets give them, e.g. `base`.
+    pub chains: Vec<String>,
+    /// Minimum spread, e.g. 0.005 for 0.5%.
+    pub min_margin: Option<f64>,
+    /// Minimum USD profit after gas.
+    pub min_profit_usd: Option<f64>,
+}
+
+impl Subscription {
+    pub fn new(chat_id: i64, lang: Lang) -> Self {
+        Subscription { chat_id, lang, pairs: Vec::new(), chains: Vec::new(), min_margin: None, min_profit_usd: None }
+    }
+
+    /// Whether an opportunity on `pair` (`BASE/QUOTE`) of `chain` passes
+    /// the filters. A USD minimum isn't met by a profit that can't be
+    /// valued.
+    pub fn matches(&self, chain: &str, pair: &str, spread: f64, profit_usd: Option<f64>) -> bool {
+        (self.chains.is_empty() || self.chains.iter().any(|c| c.eq_ignore_ascii_case(chain)))
+            && (self.pairs.is_empty() || self.pairs.iter().any(|p| p.eq_ignore_ascii_case(pair)))
+            && self.min_margin.is_none_or(|min| spread >= min)
+            && self.min_profit_usd.is_none_or(|min| profit_usd.is_some_and(|usd| usd >= min))
+    }
+
+    /// Applies `/filters` arguments: space-separated `key=value` settings,
+    /// `pairs=WETH/USDC,WBTC/USDC`, `chains=base`, `margin=0.5%` and
+    /// `profit=50` (USD), or `clear` to drop them all. A setting with an
+    /// empty value, e.g. `pairs=`, drops that one.
+    pub fn apply_filters(&mut self, args: &str) -> Result<()> {
+        let mut updated = self.clone();
+        for arg in args.split_whitespace() {
+            if arg.eq_ignore_ascii_case("clear") {
+                updated = Subscription::new(self.chat_id, self.lang);
+                continue;
+            }
+            let (key, value) = arg
+                .split_once('=')
+                .ok_or_else(|| anyhow!("expected key=value, got {:?}", arg))?;
+            let list = || {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            };
+            match key.to_ascii_lowercase().as_str() {
+                "pairs" => {
+                    let pairs = list();
+                    if let Some(pair) = pairs.iter().find(|p| !p.contains('/')) {
+                        bail!("pairs are BASE/QUOTE, got {:?}", pair);
+                    }
+                    updated.pairs = pairs.into_iter().map(|p| p.to_ascii_uppercase()).collect();
+                }
+                "chains" => updated.chains = list().into_iter().map(|c| c.to_ascii_lowercase()).collect(),
+                "margin" => updated.min_margin = parse_optional(value, parse_percent)?,
+                "profit" => {
+                    updated.min_profit_usd = parse_optional(value, |v| {
+                        v.trim_start_matches('$')
+                            .parse::<f64>()
+                            .map_err(|_| anyhow!("profit is USD, got {:?}", v))
+                    })?
+                }
+                other => bail!("unknown filter {:?} (pairs, chains, margin or profit)", other),
+            }
+        }
+        *self = updated;
+        Ok(())
+    }
+
+    /// Language and filters on one line, for the audit log.
+    pub fn summary(&self) -> String {
+        format!("lang {}; {}", self.lang.code(), self.describe().replace('\n', "; "))
+    }
+
+    /// The filters, one per line.
+    pub fn describe(&self) -> String {
+        let list = |items: &[String]| if items.is_empty() { "any".to_string() } else { items.join(", ") };
+        let min_profit = match self.min_profit_usd {
+            Some(usd) => format!("{} after gas", format_usd(usd)),
+            None => "any".to_string(),
+        };
+        [
+            format!("Pairs: {}", list(&self.pairs)),
+            format!("Chains: {}", list(&self.chains)),
+            format!("Min Margin: {}", self.min_margin.map_or("any".to_string(), format_percent)),
+            format!("Min Profit: {}", min_profit),
+        ]
+        .join("\n")
+    }
+}
+
+fn parse_optional(value: &str, parse: impl Fn(&str) -> Result<f64>) -> Result<Option<f64>> {
+    match value.trim() {
+        "" => Ok(None),
+        value => parse(value).map(Some),
+    }
+}
+
+/// `0.5%` or `0.005` as 0.005.
+fn parse_percent(value: &str) -> Result<f64> {
+    let invalid = || anyhow!("margin is a percentage, e.g. 0.5%, got {:?}", value);
+    match value.strip_suffix('%') {
+        Some(percent) => percent.parse::<f64>().map(|p| p / 100.0).map_err(|_| invalid()),
+        None => value.parse::<f64>().map_err(|_| invalid()),
+    }
+}
+
+/// Every subscription by chat, as loaded from storage at startup and kept
+/// in step with it by the bot.
+#[derive(Debug, Default)]
+pub struct Subscribers(RwLock<HashMap<i64, Subscription>>);
+
+impl Subscribers {
+    pub fn new(subscriptions: Vec<Subscription>) -> Self {
+        Subscribers(RwLock::new(subscriptions.into_iter().map(|s| (s.chat_id, s)).collect()))
+    }
+
+    pub fn get(&self, chat_id: i64) -> Option<Subscription> {
+        self.0.read().unwrap().get(&chat_id).cloned()
+    }
+
+    pub fn insert(&self, subscription: Subscription) {
+        self.0.write().unwrap().insert(subscription.chat_id, subscription);
+    }
+
+    pub fn remove(&self, chat_id: i64) -> Option<Subscription> {
+        self.0.write().unwrap().remove(&chat_id)
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.read().unwrap().len()
+    }
+
+    /// Subscriptions whose filters pass an opportunity, see
+    /// [`Subscription::matches`].
+    pub fn matching(&self, chain: &str, pair: &str, spread: f64, profit_usd: Option<f64>) -> Vec<Subscription> {
+        self.0
+            .read()
+            .unwrap()
+            .values()
+            .filter(|s| s.matches(chain, pair, spread, profit_usd))
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,200 @@
+//! Token lists in the tokenlists.org format, e.g. the Uniswap default list
+//! at <https://tokens.uniswap.org>, as a source of tokens to scan besides
+//! the preset's. Only the active chain's entries are taken.
+
+use alloy::primitives::Address;
+use anyhow::{bail, Context, Error, Result};
+use colored::*;
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::token::{self, TokenInfo};
+use crate::preset;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Versions a list is pinned to: a major version, e.g. `12`, which takes
+/// additions but not the removals a major bump signals, a minor one, e.g.
+/// `12.3`, or an exact one, e.g. `12.3.1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pin {
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+}
+
+impl Pin {
+    pub fn allows(&self, version: Version) -> bool {
+        version.major == self.major
+            && self.minor.is_none_or(|minor| version.minor == minor)
+            && self.patch.is_none_or(|patch| version.patch == patch)
+    }
+}
+
+impl FromStr for Pin {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .trim()
+            .split('.')
+            .map(|part| part.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("Invalid version {:?}", s))?;
+        match parts[..] {
+            [major] => Ok(Pin { major, minor: None, patch: None }),
+            [major, minor] => Ok(Pin { major, minor: Some(minor), patch: None }),
+            [major, minor, patch] => Ok(Pin { major, minor: Some(minor), patch: Some(patch) }),
+            _ => bail!("Invalid version {:?} (expected MAJOR[.MINOR[.PATCH]])", s),
+        }
+    }
+}
+
+impl fmt::Display for Pin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.major)?;
+        if let Some(minor) = self.minor {
+            write!(f, ".{}", minor)?;
+        }
+        if let Some(patch) = self.patch {
+            write!(f, ".{}", patch)?;
+        }
+        Ok(())
+    }
+}
+
+/// A list subscribed to: its URL, optionally suffixed with `@<version>` to
+/// pin it, e.g. `https://tokens.uniswap.org@12`.
+#[derive(Debug, Clone)]
+pub struct ListSource {
+    pub url: String,
+    pub pin: Option<Pin>,
+}
+
+impl FromStr for ListSource {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        // An `@` can also belong to the URL, so only a version after the
+        // last one is a pin
+        let (url, pin) = match s.rsplit_once('@') {
+            Some((url, version)) if version.starts_with(|c: char| c.is_ascii_digit()) => {
+                (url, Some(version.parse()?))
+            }
+            _ => (s, None),
+        };
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            bail!("Token list URL {:?} isn't http(s)", url);
+        }
+        Ok(ListSource { url: url.to_string(), pin })
+    }
+}
+
+impl fmt::Display for ListSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.pin {
+            Some(pin) => write!(f, "{}@{}", self.url, pin),
+            None => f.write_str(&self.url),
+        }
+    }
+}
+
+/// Parses `TOKEN_LISTS`: comma-separated [`ListSource`]s.
+pub fn parse_sources(value: &str) -> Result<Vec<ListSource>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenList {
+    name: String,
+    version: Version,
+    tokens: Vec<ListedToken>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListedToken {
+    chain_id: u64,
+    address: Address,
+    symbol: String,
+    decimals: u8,
+}
+
+/// Symbols go into alerts and `BASE/QUOTE` keys as they are, so only plain
+/// ones are taken.
+pub fn plain_symbol(symbol: &str) -> bool {
+    !symbol.is_empty() && symbol.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+'))
+}
+
+/// Fetches `source` and adds its tokens on the active preset's chain that
+/// aren't known yet, see [`token::register`]. Returns the list's name and
+/// version and the tokens added.
+pub async fn sync(client: &reqwest::Client, source: &ListSource) -> Result<(String, Version, Vec<&'static TokenInfo>)> {
+    let list = client
+        .get(&source.url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<TokenList>()
+        .await
+        .with_context(|| format!("Invalid token list at {}", source.url))?;
+    if let Some(pin) = source.pin {
+        if !pin.allows(list.version) {
+            bail!("{} is at v{}, outside the pinned {}", list.name, list.version, pin);
+        }
+    }
+
+    let preset = preset::active();
+    // Checked before leaking the symbols, so refreshes don't leak again
+    let candidates = list
+        .tokens
+        .into_iter()
+        .filter(|t| t.chain_id == preset.chain_id && plain_symbol(&t.symbol))
+        .filter(|t| token::get_token_info(&t.address).is_none() && token::find_by_symbol(&t.symbol).is_none())
+        .map(|t| TokenInfo { address: t.address, symbol: String::leak(t.symbol), decimals: t.decimals })
+        .collect();
+    let added = token::register(preset.chain_id, preset.tokens, candidates);
+    Ok((list.name, list.version, added))
+}
+
+/// Syncs every list in `sources`, reporting each, and returns the tokens
+/// added. A list that can't be fetched or is outside its pin is skipped
+/// until the next sync.
+pub async fn sync_all(client: &reqwest::Client, sources: &[ListSource]) -> Vec<&'static TokenInfo> {
+    let mut added = Vec::new();
+    for source in sources {
+        match sync(client, source).await {
+            Ok((name, version, tokens)) => {
+                if !tokens.is_empty() {
+                    println!("{} Token list {} v{}: added {}",
+                        "[INFO]".bright_blue(),
+                        name,
+                        version,
+                        tokens.iter().map(|t| t.symbol).collect::<Vec<_>>().join(", "),
+                    );
+                }
+                added.extend(tokens);
+            }
+            Err(e) => println!("{} Failed to sync token list {}: {}", "[ERROR]".bright_red(), source, e),
+        }
+    }
+    added
+}
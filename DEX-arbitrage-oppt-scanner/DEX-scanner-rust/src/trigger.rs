@@ -0,0 +1,90 @@
+//! What moved a pair's pools before an alert: the net of the swaps through
+//! each over the blocks the alerting batch covers, from their `Swap` logs,
+//! and how far that pushed the pool's price.
+
+use alloy::providers::Provider;
+use alloy::rpc::types::Filter;
+use anyhow::Result;
+
+use crate::decode::v2::{amounts, SOLIDLY_SWAP_TOPIC, SWAP_TOPIC};
+use crate::models::opportunity::{PoolState, Venue};
+use crate::models::token::TokenInfo;
+use crate::utils::price;
+use crate::{MonitoredPair, RpcProvider};
+
+// Most blocks searched for swaps, when evaluation fell far behind
+const MAX_BLOCKS: u64 = 100;
+
+/// Net trade through one pool: `amount` of `sold` went in on balance,
+/// moving the pool's price of the base token by `price_move`.
+#[derive(Debug, Clone, Copy)]
+pub struct Trigger {
+    pub venue: Venue,
+    pub sold: &'static TokenInfo,
+    pub amount: f64,
+    /// Relative change, e.g. -0.014 for 1.4% down.
+    pub price_move: f64,
+}
+
+/// Net swaps through each of `pair`'s `pools` in blocks `from` to `to`,
+/// with the pools as they stand at `to`. Pools nobody swapped through are
+/// left out, as are Trader Joe LB ones, whose swaps are logged per bin.
+pub async fn triggers(
+    provider: &RpcProvider,
+    pair: &MonitoredPair,
+    pools: [PoolState; 2],
+    from: u64,
+    to: u64,
+) -> Result<Vec<Trigger>> {
+    let pools = pools
+        .into_iter()
+        .filter(|pool| !matches!(pool.venue, Venue::TraderJoeLB | Venue::UniswapV3))
+        .collect::<Vec<_>>();
+    if pools.is_empty() {
+        return Ok(Vec::new());
+    }
+    let filter = Filter::new()
+        .address(pools.iter().map(|pool| pool.pool).collect::<Vec<_>>())
+        .event_signature(vec![*SWAP_TOPIC, *SOLIDLY_SWAP_TOPIC])
+        .from_block(from.max(to.saturating_sub(MAX_BLOCKS - 1)))
+        .to_block(to);
+    let logs = provider.get_logs(&filter).await?;
+
+    let (base, quote) = (pair.base(), pair.quote());
+    Ok(pools
+        .iter()
+        .filter_map(|pool| {
+            let swaps = logs
+                .iter()
+                .filter(|log| log.address() == pool.pool)
+                .filter_map(|log| amounts(&log.data().data))
+                .collect::<Vec<_>>();
+            if swaps.is_empty() {
+                return None;
+            }
+            // Swap amounts are in pool order, reserves base/quote
+            let (mut in0, mut in1) = (0.0, 0.0);
+            let (decimals0, decimals1) = (pair.token0.decimals, pair.token1.decimals);
+            for [amount0_in, amount1_in, amount0_out, amount1_out] in swaps {
+                in0 += price::to_units(amount0_in, decimals0) - price::to_units(amount0_out, decimals0);
+                in1 += price::to_units(amount1_in, decimals1) - price::to_units(amount1_out, decimals1);
+            }
+            let (base_in, quote_in) = if pair.flipped() { (in1, in0) } else { (in0, in1) };
+            let before = PoolState {
+                reserve_base: pool.reserve_base - base_in,
+                reserve_quote: pool.reserve_quote - quote_in,
+                ..*pool
+            };
+            if before.price() <= 0.0 {
+                return None;
+            }
+            let (sold, amount) = if base_in > 0.0 { (base, base_in) } else { (quote, quote_in) };
+            (amount > 0.0).then_some(Trigger {
+                venue: pool.venue,
+                sold,
+                amount,
+                price_move: pool.price() / before.price() - 1.0,
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,493 @@
+//! Uniswap V3 concentrated-liquidity math: ticks and prices, liquidity and
+//! token amounts, and fee growth. The `U256` functions are ports of the
+//! core and periphery libraries (`TickMath`, `SqrtPriceMath`,
+//! `LiquidityAmounts`, `Tick`, `Position`) and round the same way, so they
+//! agree with the chain to the wei; the `f64` ones are for display and
+//! estimates, in whole-token units like the rest of the scanner.
+//!
+//! Also the venue side: pools looked up by fee tier through the factory's
+//! `getPool` and read from `slot0` and `liquidity` as the constant-product
+//! reserves the rest of the scanner prices, and range positions read from
+//! the position manager for LP mode.
+
+use alloy::primitives::aliases::U24;
+use alloy::primitives::ruint::UintTryFrom;
+use alloy::primitives::{address, Address, U256, U512};
+use alloy::rpc::types::BlockId;
+use alloy::sol;
+use anyhow::{ensure, Result};
+
+use crate::utils::price::to_f64;
+use crate::RpcProvider;
+
+/// Lowest tick a V3 pool supports, where the price is about 2^-128.
+pub const MIN_TICK: i32 = -887_272;
+/// Highest tick a V3 pool supports, where the price is about 2^128.
+pub const MAX_TICK: i32 = 887_272;
+/// Square root price at [`MIN_TICK`], as a Q64.96.
+pub const MIN_SQRT_RATIO: U256 = U256::from_limbs([4_295_128_739, 0, 0, 0]);
+/// Square root price at [`MAX_TICK`], as a Q64.96.
+pub const MAX_SQRT_RATIO: U256 = U256::from_limbs([
+    0x5d95_1d52_6398_8d26,
+    0xefd1_fc6a_5064_8849,
+    0x0000_0000_fffd_8963,
+    0,
+]);
+
+// Ratio at each power-of-two tick, 2^128 / sqrt(1.0001^(2^i)), rounded up
+const TICK_RATIOS: [u128; 20] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+    0x48a170391f7dc42444e8fa2,
+];
+
+/// Fee tiers pools are looked up at, in hundredths of a basis point: 0.05%,
+/// 0.3% and 1%.
+pub const FEE_TIERS: [u32; 3] = [500, 3_000, 10_000];
+// Pools keep their fee in hundredths of a basis point
+const FEE_DENOMINATOR: f64 = 1_000_000.0;
+
+sol!(
+    #[sol(rpc)]
+    IUniswapV3Factory,
+    "src/abis/IUniswapV3Factory.json"
+);
+
+sol!(
+    #[sol(rpc)]
+    IUniswapV3Pool,
+    "src/abis/IUniswapV3Pool.json"
+);
+
+sol!(
+    #[sol(rpc)]
+    INonfungiblePositionManager,
+    "src/abis/INonfungiblePositionManager.json"
+);
+
+/// Uniswap's `NonfungiblePositionManager` on Ethereum, the chain V3 pools
+/// are scanned on. Range positions are its NFTs.
+pub const POSITION_MANAGER: Address = address!("0xC36442b4a4522E871399CD717aBDD847Ab11FE88");
+
+fn q96() -> U256 {
+    U256::ONE << 96
+}
+
+fn q128() -> U512 {
+    U512::ONE << 128
+}
+
+/// `a * b` as a 512-bit integer, which can't overflow.
+fn full_mul(a: U256, b: U256) -> U512 {
+    a.widening_mul(b)
+}
+
+/// `a * b / denominator` without overflowing the product, rounded down.
+/// Fails if the result doesn't fit in 256 bits or `denominator` is zero.
+pub fn mul_div(a: U256, b: U256, denominator: U256) -> Result<U256> {
+    ensure!(!denominator.is_zero(), "division by zero");
+    let quotient = full_mul(a, b) / U512::from(denominator);
+    U256::uint_try_from(quotient).map_err(|_| anyhow::anyhow!("mul_div overflows 256 bits"))
+}
+
+/// [`mul_div`], rounded up.
+pub fn mul_div_rounding_up(a: U256, b: U256, denominator: U256) -> Result<U256> {
+    let result = mul_div(a, b, denominator)?;
+    if (full_mul(a, b) % U512::from(denominator)).is_zero() {
+        Ok(result)
+    } else {
+        ensure!(result < U256::MAX, "mul_div overflows 256 bits");
+        Ok(result + U256::ONE)
+    }
+}
+
+/// `sqrt(1.0001^tick)` as a Q64.96, i.e. `TickMath.getSqrtRatioAtTick`.
+pub fn sqrt_ratio_at_tick(tick: i32) -> Result<U256> {
+    ensure!((MIN_TICK..=MAX_TICK).contains(&tick), "tick {} out of range", tick);
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio: U256 = U256::ONE << 128;
+    for (bit, factor) in TICK_RATIOS.iter().enumerate() {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = (ratio * U256::from(*factor)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+    // Q128.128 to Q64.96, rounded up so the tick of the result is `tick`
+    let round_up = !(ratio & U256::from(u32::MAX)).is_zero();
+    Ok((ratio >> 32) + U256::from(round_up as u8))
+}
+
+/// Greatest tick whose square root price is at most `sqrt_price_x96`, i.e.
+/// `TickMath.getTickAtSqrtRatio`.
+pub fn tick_at_sqrt_ratio(sqrt_price_x96: U256) -> Result<i32> {
+    ensure!(
+        sqrt_price_x96 >= MIN_SQRT_RATIO && sqrt_price_x96 < MAX_SQRT_RATIO,
+        "sqrt price {} out of range",
+        sqrt_price_x96
+    );
+    // The float estimate lands within a tick or two; the exact ratios settle it
+    let price = (to_f64(sqrt_price_x96) / to_f64(q96())).powi(2);
+    let mut tick = (price.ln() / 1.0001f64.ln()).floor() as i32;
+    tick = tick.clamp(MIN_TICK, MAX_TICK - 1);
+    while tick > MIN_TICK && sqrt_ratio_at_tick(tick)? > sqrt_price_x96 {
+        tick -= 1;
+    }
+    while tick < MAX_TICK && sqrt_ratio_at_tick(tick + 1)? <= sqrt_price_x96 {
+        tick += 1;
+    }
+    Ok(tick)
+}
+
+/// Price of token0 in token1 at `tick`, in whole-token units.
+pub fn tick_to_price(tick: i32, decimals0: u8, decimals1: u8) -> f64 {
+    1.0001f64.powi(tick) * 10f64.powi(decimals0 as i32 - decimals1 as i32)
+}
+
+/// Greatest tick whose price of token0 in token1, in whole-token units, is
+/// at most `price`.
+pub fn price_to_tick(price: f64, decimals0: u8, decimals1: u8) -> i32 {
+    let raw = price * 10f64.powi(decimals1 as i32 - decimals0 as i32);
+    ((raw.ln() / 1.0001f64.ln()).floor() as i32).clamp(MIN_TICK, MAX_TICK)
+}
+
+/// Price of token0 in token1 for a pool's `sqrtPriceX96`, in whole-token
+/// units.
+pub fn sqrt_price_to_price(sqrt_price_x96: U256, decimals0: u8, decimals1: u8) -> f64 {
+    (to_f64(sqrt_price_x96) / to_f64(q96())).powi(2) * 10f64.powi(decimals0 as i32 - decimals1 as i32)
+}
+
+/// `tick` rounded down to a multiple of `tick_spacing`, the nearest tick a
+/// position can be bounded by.
+pub fn usable_tick(tick: i32, tick_spacing: i32) -> i32 {
+    tick.div_euclid(tick_spacing) * tick_spacing
+}
+
+fn ordered(sqrt_a: U256, sqrt_b: U256) -> (U256, U256) {
+    if sqrt_a > sqrt_b { (sqrt_b, sqrt_a) } else { (sqrt_a, sqrt_b) }
+}
+
+/// Token0 for `liquidity` between two square root prices, rounded down,
+/// i.e. `SqrtPriceMath.getAmount0Delta`.
+pub fn amount0_delta(sqrt_a: U256, sqrt_b: U256, liquidity: u128) -> Result<U256> {
+    let (lower, upper) = ordered(sqrt_a, sqrt_b);
+    ensure!(!lower.is_zero(), "zero sqrt price");
+    let numerator = U256::from(liquidity) << 96;
+    Ok(mul_div(numerator, upper - lower, upper)? / lower)
+}
+
+/// Token1 for `liquidity` between two square root prices, rounded down,
+/// i.e. `SqrtPriceMath.getAmount1Delta`.
+pub fn amount1_delta(sqrt_a: U256, sqrt_b: U256, liquidity: u128) -> Result<U256> {
+    let (lower, upper) = ordered(sqrt_a, sqrt_b);
+    mul_div(U256::from(liquidity), upper - lower, q96())
+}
+
+/// Token0 and token1 held by `liquidity` in the range between `sqrt_a`
+/// and `sqrt_b` when the pool is at `sqrt_price`, i.e.
+/// `LiquidityAmounts.getAmountsForLiquidity`. Below the range it's all
+/// token0, above it all token1.
+pub fn amounts_for_liquidity(
+    sqrt_price: U256,
+    sqrt_a: U256,
+    sqrt_b: U256,
+    liquidity: u128,
+) -> Result<(U256, U256)> {
+    let (lower, upper) = ordered(sqrt_a, sqrt_b);
+    if sqrt_price <= lower {
+        Ok((amount0_delta(lower, upper, liquidity)?, U256::ZERO))
+    } else if sqrt_price < upper {
+        Ok((
+            amount0_delta(sqrt_price, upper, liquidity)?,
+            amount1_delta(lower, sqrt_price, liquidity)?,
+        ))
+    } else {
+        Ok((U256::ZERO, amount1_delta(lower, upper, liquidity)?))
+    }
+}
+
+/// Most liquidity `amount0` and `amount1` can mint in the range between
+/// `sqrt_a` and `sqrt_b` with the pool at `sqrt_price`, i.e.
+/// `LiquidityAmounts.getLiquidityForAmounts`.
+pub fn liquidity_for_amounts(
+    sqrt_price: U256,
+    sqrt_a: U256,
+    sqrt_b: U256,
+    amount0: U256,
+    amount1: U256,
+) -> Result<u128> {
+    let (lower, upper) = ordered(sqrt_a, sqrt_b);
+    let from0 = |from: U256| -> Result<U256> {
+        let intermediate = mul_div(from, upper, q96())?;
+        mul_div(amount0, intermediate, upper - from)
+    };
+    let from1 = |to: U256| mul_div(amount1, q96(), to - lower);
+    let liquidity = if sqrt_price <= lower {
+        from0(lower)?
+    } else if sqrt_price < upper {
+        from0(sqrt_price)?.min(from1(sqrt_price)?)
+    } else {
+        from1(upper)?
+    };
+    ensure!(liquidity <= U256::from(u128::MAX), "liquidity overflows 128 bits");
+    Ok(liquidity.to())
+}
+
+/// Fee growth per unit of liquidity a tick has recorded on its far side
+/// from the current price, per token, as Q128.128s (`feeGrowthOutside`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FeeGrowthOutside {
+    pub token0: U256,
+    pub token1: U256,
+}
+
+/// Fee growth per unit of liquidity inside the range between `tick_lower`
+/// and `tick_upper`, per token, i.e. `Tick.getFeeGrowthInside`. The
+/// accumulators are meant to overflow, so differences wrap.
+pub fn fee_growth_inside(
+    tick_lower: i32,
+    tick_upper: i32,
+    tick_current: i32,
+    lower: FeeGrowthOutside,
+    upper: FeeGrowthOutside,
+    global: (U256, U256),
+) -> (U256, U256) {
+    let below = |outside: U256, global: U256| {
+        if tick_current >= tick_lower { outside } else { global.overflowing_sub(outside).0 }
+    };
+    let above = |outside: U256, global: U256| {
+        if tick_current < tick_upper { outside } else { global.overflowing_sub(outside).0 }
+    };
+    let inside = |global: U256, lower: U256, upper: U256| {
+        global
+            .overflowing_sub(below(lower, global))
+            .0
+            .overflowing_sub(above(upper, global))
+            .0
+    };
+    (
+        inside(global.0, lower.token0, upper.token0),
+        inside(global.1, lower.token1, upper.token1),
+    )
+}
+
+/// Fees a position of `liquidity` earned while the fee growth inside its
+/// range went from `inside_last` to `inside_now`, in raw token units, as
+/// `Position.update` credits them.
+pub fn fees_earned(liquidity: u128, inside_last: U256, inside_now: U256) -> U256 {
+    let growth = inside_now.overflowing_sub(inside_last).0;
+    // Fits: a Q128.128 growth times 128-bit liquidity over 2^128
+    U256::uint_try_from(full_mul(growth, U256::from(liquidity)) / q128()).unwrap_or(U256::MAX)
+}
+
+/// Constant-product reserves, in raw units, that trade like `liquidity` at
+/// `sqrt_price_x96`: `L / sqrtP` of token0 and `L * sqrtP` of token1. They
+/// hold until a swap crosses into the next initialized tick, past which
+/// the pool can be much deeper or shallower.
+pub fn virtual_reserves(sqrt_price_x96: U256, liquidity: u128) -> Result<(U256, U256)> {
+    let liquidity = U256::from(liquidity);
+    Ok((mul_div(liquidity, q96(), sqrt_price_x96)?, mul_div(liquidity, sqrt_price_x96, q96())?))
+}
+
+/// The `token0`/`token1` pool at `fee_tier`, or, without one, the one with
+/// the most liquidity at its current price across [`FEE_TIERS`]. Zero if
+/// there is none.
+pub async fn find_pool(
+    provider: &RpcProvider,
+    factory: Address,
+    token0: Address,
+    token1: Address,
+    fee_tier: Option<u32>,
+) -> Result<Address> {
+    let factory = IUniswapV3Factory::new(factory, provider);
+    if let Some(fee_tier) = fee_tier {
+        return Ok(factory.getPool(token0, token1, U24::from(fee_tier)).call().await?);
+    }
+
+    let mut best = (Address::ZERO, 0);
+    for fee_tier in FEE_TIERS {
+        let pool = factory.getPool(token0, token1, U24::from(fee_tier)).call().await?;
+        if pool.is_zero() {
+            continue;
+        }
+        let liquidity = IUniswapV3Pool::new(pool, provider).liquidity().call().await?;
+        if liquidity > best.1 {
+            best = (pool, liquidity);
+        }
+    }
+    Ok(best.0)
+}
+
+/// Virtual reserves of `pool` (see [`virtual_reserves`]) as `(token0,
+/// token1)`, at `block` or the latest one. Zero for a pool that hasn't
+/// been initialized.
+pub async fn read_reserves(provider: &RpcProvider, pool: Address, block: Option<u64>) -> Result<(U256, U256)> {
+    let contract = IUniswapV3Pool::new(pool, provider);
+    let block = block.map_or(BlockId::latest(), BlockId::number);
+    let slot0 = contract.slot0().block(block);
+    let liquidity = contract.liquidity().block(block);
+    let (slot0, liquidity) = tokio::try_join!(slot0.call(), liquidity.call())?;
+    let sqrt_price_x96 = U256::from(slot0.sqrtPriceX96);
+    if sqrt_price_x96.is_zero() {
+        return Ok((U256::ZERO, U256::ZERO));
+    }
+    virtual_reserves(sqrt_price_x96, liquidity)
+}
+
+/// `pool`'s swap fee, fixed by its tier.
+pub async fn fee(provider: &RpcProvider, pool: Address) -> Result<f64> {
+    let fee: U24 = IUniswapV3Pool::new(pool, provider).fee().call().await?;
+    Ok(fee.to::<u32>() as f64 / FEE_DENOMINATOR)
+}
+
+/// A range position of the [`POSITION_MANAGER`], as it stood when read.
+#[derive(Debug, Clone, Copy)]
+pub struct RangePosition {
+    pub pool: Address,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    /// Fees earned but not collected yet, in raw `(token0, token1)` units.
+    pub fees: (U256, U256),
+}
+
+impl RangePosition {
+    /// Token0 and token1 the position holds with its pool at `sqrt_price`.
+    pub fn amounts(&self, sqrt_price: U256) -> Result<(U256, U256)> {
+        amounts_for_liquidity(
+            sqrt_price,
+            sqrt_ratio_at_tick(self.tick_lower)?,
+            sqrt_ratio_at_tick(self.tick_upper)?,
+            self.liquidity,
+        )
+    }
+}
+
+/// Position NFT `token_id` and the fees it has earned so far, the way
+/// `Position.update` would credit them on a burn.
+pub async fn read_position(provider: &RpcProvider, pool: Address, token_id: U256) -> Result<RangePosition> {
+    let position = INonfungiblePositionManager::new(POSITION_MANAGER, provider).positions(token_id).call().await?;
+    let (tick_lower, tick_upper) = (position.tickLower.as_i32(), position.tickUpper.as_i32());
+    let contract = IUniswapV3Pool::new(pool, provider);
+    let (token0, token1, fee) = (contract.token0(), contract.token1(), contract.fee());
+    let (token0, token1, fee) = tokio::try_join!(token0.call(), token1.call(), fee.call())?;
+    ensure!(
+        (token0, token1, fee) == (position.token0, position.token1, position.fee),
+        "position {} isn't in pool {:?}",
+        token_id,
+        pool
+    );
+    let slot0 = contract.slot0();
+    let (global0, global1) = (contract.feeGrowthGlobal0X128(), contract.feeGrowthGlobal1X128());
+    let (lower, upper) = (contract.ticks(position.tickLower), contract.ticks(position.tickUpper));
+    let (slot0, global0, global1, lower, upper) =
+        tokio::try_join!(slot0.call(), global0.call(), global1.call(), lower.call(), upper.call())?;
+    let outside = |tick: &IUniswapV3Pool::ticksReturn| FeeGrowthOutside {
+        token0: tick.feeGrowthOutside0X128,
+        token1: tick.feeGrowthOutside1X128,
+    };
+    let inside = fee_growth_inside(
+        tick_lower,
+        tick_upper,
+        slot0.tick.as_i32(),
+        outside(&lower),
+        outside(&upper),
+        (global0, global1),
+    );
+    let owed = |last: U256, now: U256, owed: u128| {
+        fees_earned(position.liquidity, last, now).saturating_add(U256::from(owed))
+    };
+    Ok(RangePosition {
+        pool,
+        tick_lower,
+        tick_upper,
+        liquidity: position.liquidity,
+        fees: (
+            owed(position.feeGrowthInside0LastX128, inside.0, position.tokensOwed0),
+            owed(position.feeGrowthInside1LastX128, inside.1, position.tokensOwed1),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Expected values are from the v3-core `TickMath` and `SqrtPriceMath` specs
+
+    fn uint(value: &str) -> U256 {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn sqrt_ratio_at_ticks() {
+        assert_eq!(sqrt_ratio_at_tick(0).unwrap(), q96());
+        assert_eq!(sqrt_ratio_at_tick(MIN_TICK).unwrap(), MIN_SQRT_RATIO);
+        assert_eq!(sqrt_ratio_at_tick(MIN_TICK + 1).unwrap(), uint("4295343490"));
+        assert_eq!(
+            sqrt_ratio_at_tick(MAX_TICK - 1).unwrap(),
+            uint("1461373636630004318706518188784493106690254656249")
+        );
+        assert_eq!(sqrt_ratio_at_tick(MAX_TICK).unwrap(), MAX_SQRT_RATIO);
+        assert_eq!(MAX_SQRT_RATIO, uint("1461446703485210103287273052203988822378723970342"));
+        assert!(sqrt_ratio_at_tick(MIN_TICK - 1).is_err());
+        assert!(sqrt_ratio_at_tick(MAX_TICK + 1).is_err());
+    }
+
+    #[test]
+    fn ticks_at_sqrt_ratios() {
+        assert_eq!(tick_at_sqrt_ratio(q96()).unwrap(), 0);
+        assert_eq!(tick_at_sqrt_ratio(MIN_SQRT_RATIO).unwrap(), MIN_TICK);
+        assert_eq!(tick_at_sqrt_ratio(MIN_SQRT_RATIO + U256::ONE).unwrap(), MIN_TICK);
+        assert_eq!(tick_at_sqrt_ratio(MAX_SQRT_RATIO - U256::ONE).unwrap(), MAX_TICK - 1);
+        assert!(tick_at_sqrt_ratio(MIN_SQRT_RATIO - U256::ONE).is_err());
+        assert!(tick_at_sqrt_ratio(MAX_SQRT_RATIO).is_err());
+    }
+
+    #[test]
+    fn rounds_deltas_down() {
+        // Prices 1 and 1.21, one token of liquidity
+        let (lower, upper) = (q96(), uint("87150978765690771352898345369"));
+        let liquidity = 1_000_000_000_000_000_000;
+        assert_eq!(amount0_delta(lower, upper, liquidity).unwrap(), uint("90909090909090909"));
+        assert_eq!(amount1_delta(upper, lower, liquidity).unwrap(), uint("99999999999999999"));
+        // The reference rounds up what the pool is paid, one wei more
+        let numerator = U256::from(liquidity) << 96;
+        let up = mul_div_rounding_up(numerator, upper - lower, upper).unwrap();
+        assert_eq!(mul_div_rounding_up(up, U256::ONE, lower).unwrap(), uint("90909090909090910"));
+        assert_eq!(
+            mul_div_rounding_up(U256::from(liquidity), upper - lower, q96()).unwrap(),
+            uint("100000000000000000")
+        );
+    }
+
+    #[test]
+    fn wraps_fee_growth() {
+        // The lower tick's accumulator overflowed past the global one
+        let lower = FeeGrowthOutside { token0: U256::MAX - U256::from(3), token1: U256::ZERO };
+        let upper = FeeGrowthOutside { token0: U256::from(3), token1: U256::ZERO };
+        let (inside, _) = fee_growth_inside(-2, 2, 0, lower, upper, (U256::from(15), U256::ZERO));
+        assert_eq!(inside, U256::from(16));
+        // Growth of 2 per unit across the wrap, on 3 units of liquidity
+        let q128 = U256::ONE << 128;
+        assert_eq!(fees_earned(3, U256::MAX - (q128 - U256::ONE), q128), U256::from(6));
+    }
+}
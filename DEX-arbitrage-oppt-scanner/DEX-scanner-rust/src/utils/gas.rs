@@ -0,0 +1,159 @@
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
+use anyhow::{Context, Result};
+use colored::*;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::models::token::{tokens, weth, TokenInfo};
+use crate::preset;
+use crate::utils::price::to_units;
+use crate::RpcProvider;
+
+/// Gas used by a two-swap arbitrage through a V2 router/contract.
+pub const ARB_GAS_UNITS: u64 = 250_000;
+// L1 gas per calldata byte, which pre-V2 zkSync fee params price pubdata at
+const L1_GAS_PER_PUBDATA_BYTE: u64 = 17;
+
+/// How a chain charges for an arbitrage transaction.
+#[derive(Debug, Clone, Copy)]
+pub enum GasModel {
+    /// `units` of gas at the node's gas price. Also right for Linea, which
+    /// prices L1 data into the gas price rather than billing it separately.
+    Execution { units: u64 },
+    /// zkSync Era: `units` of execution gas plus `pubdata_bytes` of state
+    /// diffs published to L1, at the pubdata price from `zks_getFeeParams`.
+    ZkSync { units: u64, pubdata_bytes: u64 },
+}
+
+#[derive(Debug, Default)]
+struct GasState {
+    gas_price: Option<U256>,
+    /// Wei per byte of L1 data, on chains that bill it separately.
+    l1_data_price: Option<U256>,
+    /// Units of each token worth one ETH.
+    eth_price: HashMap<Address, f64>,
+}
+
+/// Latest gas price and ETH conversion rates, refreshed in the background.
+#[derive(Debug, Clone, Default)]
+pub struct GasOracle(Arc<RwLock<GasState>>);
+
+impl GasOracle {
+    /// Cost of one arbitrage transaction denominated in `token`, if known.
+    pub fn arb_cost_in(&self, token: &TokenInfo) -> Option<f64> {
+        let state = self.0.read().unwrap();
+        let fee_wei = match preset::active().gas {
+            GasModel::Execution { units } => state.gas_price? * U256::from(units),
+            GasModel::ZkSync { units, pubdata_bytes } => {
+                state.gas_price? * U256::from(units) + state.l1_data_price? * U256::from(pubdata_bytes)
+            }
+        };
+        let gas_eth = to_units(fee_wei, 18);
+        let eth_price = if token.address == weth().address {
+            1.0
+        } else {
+            *state.eth_price.get(&token.address)?
+        };
+        Some(gas_eth * eth_price)
+    }
+
+    /// Latest gas price in gwei, if known.
+    pub fn gas_price_gwei(&self) -> Option<f64> {
+        Some(to_units(self.0.read().unwrap().gas_price?, 9))
+    }
+
+    /// USD value of `amount` of `token`, treating USD stablecoins as $1 and
+    /// pricing everything else through ETH.
+    pub fn usd_value(&self, amount: f64, token: &TokenInfo) -> Option<f64> {
+        if token.is_usd_stable() {
+            return Some(amount);
+        }
+
+        let state = self.0.read().unwrap();
+        let token_per_eth = if token.address == weth().address {
+            1.0
+        } else {
+            *state.eth_price.get(&token.address)?
+        };
+        let usd_per_eth = tokens()
+            .filter(|t| t.is_usd_stable())
+            .find_map(|t| state.eth_price.get(&t.address))?;
+        Some(amount / token_per_eth * usd_per_eth)
+    }
+}
+
+/// Wei per pubdata byte from zkSync's `zks_getFeeParams`.
+async fn zksync_pubdata_price(provider: &RpcProvider) -> Result<U256> {
+    let params: Value = provider.raw_request("zks_getFeeParams".into(), ()).await?;
+    let number = |value: &Value| {
+        value
+            .as_u64()
+            .map(U256::from)
+            .or_else(|| value.as_str().and_then(|s| s.parse::<U256>().ok()))
+    };
+    if let Some(price) = number(&params["V2"]["l1_pubdata_price"]) {
+        return Ok(price);
+    }
+    let l1_gas_price = number(&params["V1"]["l1_gas_price"])
+        .context("zks_getFeeParams returned neither V2 nor V1 fee params")?;
+    Ok(l1_gas_price * U256::from(L1_GAS_PER_PUBDATA_BYTE))
+}
+
+/// Reads the gas price (and, on zkSync, the pubdata price) and, from each
+/// `(token, WETH/token pool)` entry, the ETH price in that token into
+/// `oracle`. The pools are on the preset's first venue.
+pub async fn refresh(
+    provider: &RpcProvider,
+    weth_pools: &[(&'static TokenInfo, Address)],
+    oracle: &GasOracle,
+) {
+    let weth = weth();
+    match provider.get_gas_price().await {
+        Ok(gas_price) => oracle.0.write().unwrap().gas_price = Some(U256::from(gas_price)),
+        Err(e) => println!("{} Failed to fetch gas price: {}", "[ERROR]".bright_red(), e),
+    }
+    if let GasModel::ZkSync { .. } = preset::active().gas {
+        match zksync_pubdata_price(provider).await {
+            Ok(price) => oracle.0.write().unwrap().l1_data_price = Some(price),
+            Err(e) => println!("{} Failed to fetch pubdata price: {}", "[ERROR]".bright_red(), e),
+        }
+    }
+
+    let venue = &preset::active().venues[0];
+    for (token, pool) in weth_pools {
+        let token0 = weth.address.min(token.address);
+        let Ok(reserves) = venue.read_reserves(provider, *pool, token0, None).await else {
+            continue;
+        };
+
+        // Reserves are ordered by token address
+        let (reserve_weth, reserve_token) = if weth.address < token.address {
+            (reserves.0, reserves.1)
+        } else {
+            (reserves.1, reserves.0)
+        };
+        let reserve_weth = to_units(reserve_weth, weth.decimals);
+        if reserve_weth > 0.0 {
+            let price = to_units(reserve_token, token.decimals) / reserve_weth;
+            oracle.0.write().unwrap().eth_price.insert(token.address, price);
+        }
+    }
+}
+
+/// Runs [`refresh`] every `interval`.
+pub fn spawn_refresh(
+    provider: RpcProvider,
+    weth_pools: Vec<(&'static TokenInfo, Address)>,
+    oracle: GasOracle,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            refresh(&provider, &weth_pools, &oracle).await;
+            tokio::time::sleep(interval).await;
+        }
+    })
+}
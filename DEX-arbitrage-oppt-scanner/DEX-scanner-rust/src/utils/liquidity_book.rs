@@ -0,0 +1,123 @@
+use alloy::primitives::{aliases::U24, Address, U256};
+use alloy::rpc::types::BlockId;
+use alloy::sol;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::RpcProvider;
+
+// Bin id whose price is exactly 1
+const REAL_ID_SHIFT: i64 = 1 << 23;
+const BASIS_POINTS: f64 = 10_000.0;
+// baseFactor * binStep is the base fee in units of 1e-8
+const BASE_FEE_UNIT: f64 = 1e-8;
+
+sol!(
+    #[sol(rpc)]
+    ILBFactory,
+    "src/abis/ILBFactory.json"
+);
+
+sol!(
+    #[sol(rpc)]
+    ILBPair,
+    "src/abis/ILBPair.json"
+);
+
+// Token X and bin step never change, so they're read once per pair
+static SHAPES: Lazy<Mutex<HashMap<Address, Shape>>> = Lazy::new(Default::default);
+
+#[derive(Debug, Clone, Copy)]
+struct Shape {
+    token_x: Address,
+    bin_step: u16,
+}
+
+async fn shape(provider: &RpcProvider, pool: Address) -> Result<Shape> {
+    if let Some(shape) = SHAPES.lock().unwrap().get(&pool) {
+        return Ok(*shape);
+    }
+    let contract = ILBPair::new(pool, provider);
+    let shape = Shape {
+        token_x: contract.getTokenX().call().await?,
+        bin_step: contract.getBinStep().call().await?,
+    };
+    SHAPES.lock().unwrap().insert(pool, shape);
+    Ok(shape)
+}
+
+/// Price of bin `id`, in raw token Y per raw token X.
+fn bin_price(id: u32, bin_step: u16) -> f64 {
+    (1.0 + bin_step as f64 / BASIS_POINTS).powi((id as i64 - REAL_ID_SHIFT) as i32)
+}
+
+/// Constant-product reserves, in raw units, that quote the active bin's
+/// `price` and take about the bin's whole liquidity to move one bin. LB
+/// trades at a fixed price inside a bin, so this assumes the neighbouring
+/// bins hold as much as the active one, which is as close as a single bin
+/// read gets.
+fn virtual_reserves(bin_x: f64, bin_y: f64, price: f64, bin_step: u16) -> (f64, f64) {
+    let step = bin_step as f64 / BASIS_POINTS;
+    // Moving an x*y=k price up by `step` takes about y * step / 2 of Y
+    let liquidity_y = bin_y + bin_x * price;
+    let virtual_y = 2.0 * liquidity_y / step;
+    (virtual_y / price, virtual_y)
+}
+
+/// Virtual reserves of `pool` (see [`virtual_reserves`]) ordered as
+/// `(token0, token1)`, at `block` or the latest one.
+pub async fn read_reserves(
+    provider: &RpcProvider,
+    pool: Address,
+    token0: Address,
+    block: Option<u64>,
+) -> Result<(U256, U256)> {
+    let shape = shape(provider, pool).await?;
+    let contract = ILBPair::new(pool, provider);
+    let block = block.map_or(BlockId::latest(), BlockId::number);
+
+    let id: U24 = contract.getActiveId().block(block).call().await?;
+    let bin = contract.getBin(id).block(block).call().await?;
+    let (bin_x, bin_y) = (bin.binReserveX, bin.binReserveY);
+
+    let price = bin_price(id.to(), shape.bin_step);
+    let (x, y) = virtual_reserves(bin_x as f64, bin_y as f64, price, shape.bin_step);
+    let (x, y) = (U256::from(x as u128), U256::from(y as u128));
+    Ok(if shape.token_x == token0 { (x, y) } else { (y, x) })
+}
+
+/// The `token0`/`token1` pair holding the most `token1` across every bin
+/// step, or zero if there is none. Pairs the factory flags as ignored for
+/// routing are skipped.
+pub async fn find_pair(
+    provider: &RpcProvider,
+    factory: Address,
+    token0: Address,
+    token1: Address,
+) -> Result<Address> {
+    let pairs = ILBFactory::new(factory, provider).getAllLBPairs(token0, token1).call().await?;
+
+    let mut best = (Address::ZERO, 0);
+    for pair in pairs {
+        if pair.ignoredForRouting {
+            continue;
+        }
+        let shape = shape(provider, pair.LBPair).await?;
+        let reserves = ILBPair::new(pair.LBPair, provider).getReserves().call().await?;
+        let held = if shape.token_x == token1 { reserves.reserveX } else { reserves.reserveY };
+        if held > best.1 {
+            best = (pair.LBPair, held);
+        }
+    }
+    Ok(best.0)
+}
+
+/// The pair's base fee. The variable fee LB adds on top during volatility
+/// isn't included, so this is the floor of what a swap pays.
+pub async fn base_fee(provider: &RpcProvider, pool: Address) -> Result<f64> {
+    let shape = shape(provider, pool).await?;
+    let params = ILBPair::new(pool, provider).getStaticFeeParameters().call().await?;
+    Ok(params.baseFactor as f64 * shape.bin_step as f64 * BASE_FEE_UNIT)
+}
@@ -0,0 +1,6 @@
+pub mod gas;
+pub mod liquidity_book;
+pub mod price;
+pub mod secrets;
+
+pub use scanner_core::format;
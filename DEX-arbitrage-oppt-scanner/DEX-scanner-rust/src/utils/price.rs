@@ -0,0 +1,23 @@
+use alloy::primitives::U256;
+
+pub use scanner_core::price::{break_even_input, calculate_profit_margin, get_amount_out, optimal_arb_input};
+
+/// Swap fee charged by Uniswap V2 and its forks.
+pub const V2_FEE: f64 = 0.003;
+
+/// Lossy conversion keeping the top 128 bits, for prices and estimates
+/// from fixed-point values such as Q64.96 square root prices.
+pub fn to_f64(value: U256) -> f64 {
+    let shift = 256usize.saturating_sub(value.leading_zeros()).saturating_sub(128);
+    (value >> shift).to::<u128>() as f64 * 2f64.powi(shift as i32)
+}
+
+/// Converts a raw on-chain amount into whole-token units.
+pub fn to_units(amount: U256, decimals: u8) -> f64 {
+    f64::from(amount) / 10f64.powi(decimals as i32)
+}
+
+/// Inverse of [`to_units`], rounding down to the token's smallest unit.
+pub fn from_units(amount: f64, decimals: u8) -> U256 {
+    U256::from((amount.max(0.0) * 10f64.powi(decimals as i32)).floor() as u128)
+}
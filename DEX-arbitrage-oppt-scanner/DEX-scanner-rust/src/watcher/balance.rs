@@ -0,0 +1,97 @@
+use alloy::primitives::Address;
+use alloy::providers::Provider;
+use colored::*;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::preset;
+use crate::storage::shared::QueuedAlert;
+use crate::utils::format::{self as fmt, format_amount};
+use crate::utils::price;
+use crate::i18n::Localize;
+use crate::{i18n, ScanContext};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The chain's native token, which gas is paid in: the preset's wrapped
+/// one without its prefix, e.g. ETH for WETH.
+pub fn native_symbol() -> &'static str {
+    preset::active().native.trim_start_matches(['W', 'w'])
+}
+
+/// Alerts when a wallet in `GAS_WALLETS` holds less native token than
+/// `GAS_WALLET_FLOOR`, since an executor whose gas wallet runs dry stops
+/// without a word. Each wallet is alerted on once per dip below the floor,
+/// then again only after it has been topped back up. Runs forever.
+pub async fn watch(ctx: Arc<ScanContext>) {
+    let mut low = HashSet::new();
+    loop {
+        for &wallet in &ctx.gas_wallets {
+            let balance = match ctx.provider.get_balance(wallet).await {
+                Ok(balance) => price::to_units(balance, 18),
+                Err(e) => {
+                    println!("{} Failed to read balance of {:?}: {}", "[ERROR]".bright_red(), wallet, e);
+                    continue;
+                }
+            };
+            if balance >= ctx.gas_wallet_floor {
+                if low.remove(&wallet) {
+                    println!("{} Gas wallet {:?} topped up to {} {}",
+                        "[INFO]".bright_blue(),
+                        wallet,
+                        format_amount(balance),
+                        native_symbol(),
+                    );
+                }
+                continue;
+            }
+            if low.insert(wallet) {
+                alert(&ctx, wallet, balance).await;
+            }
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn alert(ctx: &ScanContext, wallet: Address, balance: f64) {
+    let symbol = native_symbol();
+    println!("{} {} Gas wallet {:?} down to {} {}, below the {} {} floor",
+        "[ALERT]".bright_yellow(),
+        fmt::clock().bright_black(),
+        wallet,
+        format_amount(balance),
+        symbol,
+        format_amount(ctx.gas_wallet_floor),
+        symbol,
+    );
+    // Every shard reads the same wallets, so only the leader alerts
+    if !ctx.leader.is_leader() {
+        return;
+    }
+    let messages = ctx
+        .chats
+        .iter()
+        .map(|chat| {
+            let text = chat.lang.catalog();
+            let line = i18n::fill(
+                text.low_balance_below,
+                &[
+                    ("balance", &format_amount(balance)),
+                    ("floor", &format_amount(ctx.gas_wallet_floor)),
+                    ("symbol", symbol),
+                ],
+            );
+            let message = format!(
+                "⛽ <b>{}</b>\n\n<code>{:?}</code>\n{}\n\n<i>{}</i>",
+                text.low_balance,
+                wallet,
+                line,
+                fmt::format_time(chrono::Utc::now())
+            );
+            (chat.id, message)
+        })
+        .collect();
+    let key = format!("{}:low-balance:{:?}", preset::active().chain, wallet);
+    ctx.notify(QueuedAlert { key, messages }).await;
+}
@@ -0,0 +1,362 @@
+use alloy::primitives::{Address, U256};
+use alloy::sol;
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::i18n::{self, Lang, Localize};
+use crate::models::opportunity::{Opportunity, PoolState};
+use crate::models::snapshot::MarketSnapshot;
+use crate::models::token::TokenInfo;
+use crate::storage::shared::QueuedAlert;
+use crate::univ3::{self, RangePosition};
+use crate::utils::format::{clock, format_amount, format_percent, format_time};
+use crate::utils::price;
+use crate::{RpcProvider, ScanContext};
+
+sol!(
+    #[sol(rpc)]
+    IERC20,
+    "src/abis/IERC20.json"
+);
+
+/// Who holds an LP position: an address holding V2 LP tokens of the pool,
+/// or a Uniswap V3 range position, by its position manager NFT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Holder {
+    Owner(Address),
+    Range(U256),
+}
+
+impl fmt::Display for Holder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Holder::Owner(owner) => write!(f, "{:?}", owner),
+            Holder::Range(token_id) => write!(f, "#{}", token_id),
+        }
+    }
+}
+
+/// An LP position in `pool`, and optionally the pool price (quote per
+/// base) it was entered at, for impermanent loss.
+#[derive(Debug, Clone, Copy)]
+pub struct LpPosition {
+    pub pool: Address,
+    pub holder: Holder,
+    pub entry_price: Option<f64>,
+}
+
+impl FromStr for LpPosition {
+    type Err = anyhow::Error;
+
+    /// `<pool>:<owner>` for V2 LP tokens or `<pool>#<token id>` for a V3
+    /// range position, optionally followed by `@<entry price>`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (position, entry_price) = match s.split_once('@') {
+            Some((position, entry)) => (
+                position,
+                Some(entry.trim().parse::<f64>().with_context(|| format!("Invalid entry price {:?}", entry))?),
+            ),
+            None => (s, None),
+        };
+        let (pool, holder) = if let Some((pool, owner)) = position.split_once(':') {
+            let owner = owner.trim().parse().with_context(|| format!("Invalid owner {:?}", owner))?;
+            (pool, Holder::Owner(owner))
+        } else if let Some((pool, token_id)) = position.split_once('#') {
+            let token_id = token_id
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid position token id {:?}", token_id))?;
+            (pool, Holder::Range(token_id))
+        } else {
+            bail!("Expected <pool>:<owner> or <pool>#<token id>, got {:?}", s);
+        };
+        Ok(LpPosition {
+            pool: pool.trim().parse().with_context(|| format!("Invalid pool {:?}", pool))?,
+            holder,
+            entry_price,
+        })
+    }
+}
+
+/// Parses `LP_POSITIONS`: comma-separated [`LpPosition`]s.
+pub fn parse_positions(value: &str) -> Result<Vec<LpPosition>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Impermanent loss of a constant-product position entered at
+/// `entry_price` now that the pool trades at `price`, against holding the
+/// tokens, e.g. -0.0057 for a 0.57% loss.
+fn impermanent_loss(entry_price: f64, price: f64) -> f64 {
+    let ratio = price / entry_price;
+    2.0 * ratio.sqrt() / (1.0 + ratio) - 1.0
+}
+
+/// What a position holds, as last read.
+#[derive(Debug, Clone, Copy)]
+enum Holding {
+    /// Share of the pool's LP token supply.
+    Share(f64),
+    Range(RangePosition),
+}
+
+/// Watches configured LP positions for a price gap to the other venue wide
+/// enough that arbitrage against the position's own pool pays, which the
+/// LP can take themselves instead of leaving it to searchers.
+#[derive(Clone)]
+pub struct LpWatcher {
+    positions: Arc<Vec<LpPosition>>,
+    holdings: Arc<RwLock<HashMap<(Address, Holder), Holding>>>,
+}
+
+impl LpWatcher {
+    pub fn new(positions: Vec<LpPosition>) -> Self {
+        LpWatcher {
+            positions: Arc::new(positions),
+            holdings: Arc::default(),
+        }
+    }
+
+    pub fn positions(&self) -> &[LpPosition] {
+        &self.positions
+    }
+
+    fn holding(&self, position: &LpPosition) -> Option<Holding> {
+        self.holdings.read().unwrap().get(&(position.pool, position.holder)).copied()
+    }
+
+    /// Rereads what every position holds every `interval`.
+    pub fn spawn_refresh(&self, provider: RpcProvider, interval: Duration) {
+        let watcher = self.clone();
+        tokio::spawn(async move {
+            loop {
+                for position in watcher.positions.iter() {
+                    let holding = match position.holder {
+                        Holder::Owner(owner) => read_share(&provider, position.pool, owner).await.map(Holding::Share),
+                        Holder::Range(token_id) => {
+                            univ3::read_position(&provider, position.pool, token_id).await.map(Holding::Range)
+                        }
+                    };
+                    match holding {
+                        Ok(holding) => {
+                            watcher
+                                .holdings
+                                .write()
+                                .unwrap()
+                                .insert((position.pool, position.holder), holding);
+                        }
+                        Err(e) => println!("{} Failed to read LP position {} in {:?}: {}",
+                            "[ERROR]".bright_red(),
+                            position.holder,
+                            position.pool,
+                            e,
+                        ),
+                    }
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+
+    /// Alerts on every position whose pool is in `snapshot` and can be
+    /// arbitraged against the other venue at a profit after gas.
+    pub async fn check(&self, snapshot: &MarketSnapshot, ctx: &ScanContext) {
+        for position in self.positions.iter() {
+            let Some(pair) = snapshot
+                .pairs
+                .iter()
+                .find(|pair| pair.pools.iter().any(|pool| pool.pool == position.pool))
+            else {
+                continue;
+            };
+            let [a, b] = pair.pools;
+            let Some(opportunity) =
+                Opportunity::evaluate(pair.base, pair.quote, a, b, pair.gas_cost, snapshot.flash_loan_fee)
+            else {
+                continue;
+            };
+            if opportunity.net_profit().is_none_or(|net| net <= 0.0) {
+                continue;
+            }
+            if !ctx.should_alert() {
+                continue;
+            }
+            let key = format!("lp:{:?}:{}", position.pool, position.holder);
+            if !ctx.shared.claim_alert(&key, ctx.alert_cooldown).await {
+                continue;
+            }
+
+            println!("{} {} LP position in {:?} can be rebalanced against {}: {}/{} {}",
+                "[LP]".bright_magenta(),
+                clock().bright_black(),
+                position.pool,
+                if a.pool == position.pool { b.venue_label() } else { a.venue_label() },
+                pair.base.symbol,
+                pair.quote.symbol,
+                format_percent(price::calculate_profit_margin(a.price(), b.price())),
+            );
+            let alert = QueuedAlert {
+                key,
+                messages: ctx
+                    .chats
+                    .iter()
+                    .map(|chat| (chat.id, self.format_alert(chat.lang, position, &opportunity)))
+                    .collect(),
+            };
+            ctx.notify(alert).await;
+        }
+    }
+
+    fn format_alert(&self, lang: Lang, position: &LpPosition, opportunity: &Opportunity) -> String {
+        let text = lang.catalog();
+        let (own, other): (&PoolState, &PoolState) = if opportunity.buy.pool == position.pool {
+            (&opportunity.buy, &opportunity.sell)
+        } else {
+            (&opportunity.sell, &opportunity.buy)
+        };
+        let (base, quote) = (opportunity.base, opportunity.quote);
+        let amount = |value: f64, token: &TokenInfo| format!("{} {}", format_amount(value), token.symbol);
+        let mut lines = Vec::new();
+        match self.holding(position) {
+            // Both sides of a constant-product pool are worth the same
+            Some(Holding::Share(share)) => lines.push(format!(
+                "{}: <code>{}</code>",
+                text.lp_holding,
+                i18n::fill(
+                    text.lp_share,
+                    &[("share", &format_percent(share)), ("value", &amount(2.0 * own.reserve_quote * share, quote))],
+                )
+            )),
+            Some(Holding::Range(range)) => {
+                if let Some((base_amount, quote_amount)) = range_amounts(&range, own.price(), base, quote) {
+                    let (lower, upper) = range_prices(&range, base, quote);
+                    let mut holding = i18n::fill(
+                        text.lp_range,
+                        &[
+                            ("amounts", &format!("{} + {}", amount(base_amount, base), amount(quote_amount, quote))),
+                            ("lower", &format_amount(lower)),
+                            ("upper", &format_amount(upper)),
+                            ("value", &amount(base_amount * own.price() + quote_amount, quote)),
+                        ],
+                    );
+                    if !(lower..upper).contains(&own.price()) {
+                        holding = format!("{} ({})", holding, text.lp_out_of_range);
+                    }
+                    lines.push(format!("{}: <code>{}</code>", text.lp_holding, holding));
+                }
+                let (fees_base, fees_quote) = oriented(range.fees, base, quote);
+                lines.push(format!(
+                    "{}: <code>{} + {}</code>",
+                    text.lp_fees,
+                    amount(price::to_units(fees_base, base.decimals), base),
+                    amount(price::to_units(fees_quote, quote.decimals), quote)
+                ));
+            }
+            None => lines.push(format!("{}: <code>n/a</code>", text.lp_holding)),
+        }
+        if let Some(entry) = position.entry_price {
+            let loss = match self.holding(position) {
+                Some(Holding::Range(range)) => range_loss(&range, entry, own.price(), base, quote),
+                _ => Some(impermanent_loss(entry, own.price())),
+            };
+            if let Some(loss) = loss {
+                let label = i18n::fill(text.lp_loss, &[("price", &format_amount(entry))]);
+                lines.push(format!("{}: <code>{}</code>", label, format_percent(loss)));
+            }
+        }
+        let nets = i18n::fill(
+            text.lp_nets,
+            &[
+                ("profit", &format!("<b>{}</b>", amount(opportunity.net_profit().unwrap_or_default(), quote))),
+                ("size", &format!("<code>{}</code>", amount(opportunity.amount_in, quote))),
+            ],
+        );
+        format!(
+            "🛠 <b>{}</b>\n\n\
+            {}: <code>{}/{} {}</code>\n\
+            {}: <code>{}</code>\n\
+            {}\n\n\
+            {}: <code>{} {}/{}</code>\n\
+            {}: <code>{} {}/{}</code>\n\n\
+            ➡️ <b>{}</b>\n\
+            {}\n\n\
+            <i>{}</i>",
+            text.lp_rebalance,
+            text.lp_position,
+            base.symbol,
+            quote.symbol,
+            own.venue_label(),
+            text.lp_owner,
+            position.holder,
+            lines.join("\n"),
+            text.lp_your_pool,
+            format_amount(own.price()),
+            quote.symbol,
+            base.symbol,
+            other.venue_label(),
+            format_amount(other.price()),
+            quote.symbol,
+            base.symbol,
+            opportunity.direction(lang),
+            nets,
+            format_time(chrono::Utc::now())
+        )
+    }
+}
+
+/// `(token0, token1)` amounts as `(base, quote)`.
+fn oriented<T>((amount0, amount1): (T, T), base: &TokenInfo, quote: &TokenInfo) -> (T, T) {
+    if base.address < quote.address { (amount0, amount1) } else { (amount1, amount0) }
+}
+
+/// Square root price, as a Q64.96, of `price` in quote per base, to the
+/// tick: close enough for showing amounts.
+fn sqrt_price_at(price: f64, base: &TokenInfo, quote: &TokenInfo) -> Option<U256> {
+    let (token0, token1) = oriented((base, quote), base, quote);
+    let price0 = if token0.address == base.address { price } else { 1.0 / price };
+    univ3::sqrt_ratio_at_tick(univ3::price_to_tick(price0, token0.decimals, token1.decimals)).ok()
+}
+
+/// Base and quote `range` holds, in whole tokens, with its pool at `price`.
+fn range_amounts(range: &RangePosition, price: f64, base: &TokenInfo, quote: &TokenInfo) -> Option<(f64, f64)> {
+    let (base_amount, quote_amount) = oriented(range.amounts(sqrt_price_at(price, base, quote)?).ok()?, base, quote);
+    Some((price::to_units(base_amount, base.decimals), price::to_units(quote_amount, quote.decimals)))
+}
+
+/// The prices, in quote per base, at `range`'s ends, lowest first.
+fn range_prices(range: &RangePosition, base: &TokenInfo, quote: &TokenInfo) -> (f64, f64) {
+    let (token0, token1) = oriented((base, quote), base, quote);
+    let [lower, upper] =
+        [range.tick_lower, range.tick_upper].map(|tick| univ3::tick_to_price(tick, token0.decimals, token1.decimals));
+    if token0.address == base.address { (lower, upper) } else { (1.0 / upper, 1.0 / lower) }
+}
+
+/// Impermanent loss of `range` entered at `entry_price` now that its pool
+/// trades at `price`: what it holds against what it held at entry, both
+/// valued at `price`.
+fn range_loss(range: &RangePosition, entry_price: f64, price: f64, base: &TokenInfo, quote: &TokenInfo) -> Option<f64> {
+    let value = |(base_amount, quote_amount): (f64, f64)| base_amount * price + quote_amount;
+    let held = value(range_amounts(range, entry_price, base, quote)?);
+    let holds = value(range_amounts(range, price, base, quote)?);
+    (held > 0.0).then(|| holds / held - 1.0)
+}
+
+/// `owner`'s share of `pool`'s LP token supply.
+async fn read_share(provider: &RpcProvider, pool: Address, owner: Address) -> Result<f64> {
+    let lp = IERC20::new(pool, provider);
+    let supply = lp.totalSupply().call().await?;
+    let balance = lp.balanceOf(owner).call().await?;
+    if supply.is_zero() {
+        return Ok(0.0);
+    }
+    Ok(f64::from(balance) / f64::from(supply))
+}
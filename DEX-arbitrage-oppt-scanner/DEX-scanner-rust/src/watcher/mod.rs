@@ -0,0 +1,6 @@
+pub mod balance;
+pub mod lp;
+pub mod pools;
+pub mod sync;
+pub mod wallet;
+pub mod whale;
@@ -0,0 +1,318 @@
+use alloy::primitives::{keccak256, Address, B256, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::{Filter, Log};
+use anyhow::Result;
+use chrono::{DateTime, TimeDelta, Utc};
+use colored::*;
+use once_cell::sync::Lazy;
+use std::sync::Arc;
+
+use crate::enrich::fees;
+use crate::models::opportunity::Venue;
+use crate::models::token::{self, get_token_info, TokenInfo};
+use crate::preset;
+use crate::utils::format::{clock, format_amount, format_percent, format_usd};
+use crate::utils::price;
+use crate::{scanner, MonitoredPair, ScanContext, BLOCK_POLL_INTERVAL, RETRY_DELAY};
+
+// Most blocks read for creations at once, e.g. catching up after an outage
+const MAX_LOG_RANGE: u64 = 1_000;
+
+/// `PairCreated(token0, token1, pair, index)` of Uniswap V2 and its forks.
+static PAIR_CREATED: Lazy<B256> =
+    Lazy::new(|| keccak256("PairCreated(address,address,address,uint256)"));
+/// `PoolCreated(token0, token1, stable, pool, index)`, `stable` indexed.
+static AERODROME_POOL_CREATED: Lazy<B256> =
+    Lazy::new(|| keccak256("PoolCreated(address,address,bool,address,uint256)"));
+/// `PairCreated(token0, token1, stable, pair, index)`, `stable` not indexed.
+static EQUALIZER_PAIR_CREATED: Lazy<B256> =
+    Lazy::new(|| keccak256("PairCreated(address,address,bool,address,uint256)"));
+/// `PoolCreated(token0, token1, pool)` of SyncSwap's classic pool factory.
+static SYNCSWAP_POOL_CREATED: Lazy<B256> =
+    Lazy::new(|| keccak256("PoolCreated(address,address,address)"));
+/// `PoolCreated(token0, token1, fee, tickSpacing, pool)`, `fee` indexed.
+static V3_POOL_CREATED: Lazy<B256> =
+    Lazy::new(|| keccak256("PoolCreated(address,address,uint24,int24,address)"));
+/// `LBPairCreated(tokenX, tokenY, binStep, pair, pid)`, `binStep` indexed.
+static LB_PAIR_CREATED: Lazy<B256> =
+    Lazy::new(|| keccak256("LBPairCreated(address,address,uint256,address,uint256)"));
+
+/// A pool a venue's factory just created.
+#[derive(Debug, Clone, Copy)]
+pub struct NewPool {
+    pub venue: Venue,
+    pub pool: Address,
+    pub token0: &'static TokenInfo,
+    pub token1: &'static TokenInfo,
+    pub block: u64,
+    /// Trader Joe LB pair's bin step in basis points.
+    pub bin_step: Option<u64>,
+}
+
+fn creation_topic(venue: Venue) -> B256 {
+    match venue {
+        Venue::UniswapV2 | Venue::Sushiswap | Venue::PancakeSwap | Venue::TraderJoe | Venue::SpookySwap => {
+            *PAIR_CREATED
+        }
+        Venue::Aerodrome => *AERODROME_POOL_CREATED,
+        Venue::Equalizer => *EQUALIZER_PAIR_CREATED,
+        Venue::SyncSwap => *SYNCSWAP_POOL_CREATED,
+        Venue::TraderJoeLB => *LB_PAIR_CREATED,
+        Venue::UniswapV3 => *V3_POOL_CREATED,
+    }
+}
+
+/// The pool `log` announces, if it's a creation by `venue`'s factory of a
+/// pool the scanner would price. Stable Aerodrome and Equalizer pools are
+/// left out, as their curve isn't constant-product.
+fn decode(venue: Venue, log: &Log) -> Option<NewPool> {
+    let topics = log.topics();
+    if topics.first() != Some(&creation_topic(venue)) || topics.len() < 3 {
+        return None;
+    }
+    let address = |word: &[u8]| Address::from_slice(&word[12..32]);
+    let word = |i: usize| log.data().data.get(i * 32..(i + 1) * 32);
+    let token = |topic: &B256| get_token_info(&Address::from_word(*topic));
+    let (pool, bin_step) = match venue {
+        Venue::Aerodrome => {
+            let stable = topics.get(3)?;
+            if !stable.is_zero() {
+                return None;
+            }
+            (address(word(0)?), None)
+        }
+        Venue::Equalizer => {
+            if word(0)?.iter().any(|byte| *byte != 0) {
+                return None;
+            }
+            (address(word(1)?), None)
+        }
+        Venue::UniswapV3 => (address(word(1)?), None),
+        Venue::TraderJoeLB => {
+            let bin_step = U256::from_be_bytes(topics.get(3)?.0);
+            (address(word(0)?), Some(bin_step.saturating_to()))
+        }
+        _ => (address(word(0)?), None),
+    };
+    Some(NewPool {
+        venue,
+        pool,
+        token0: token(&topics[1])?,
+        token1: token(&topics[2])?,
+        block: log.block_number?,
+        bin_step,
+    })
+}
+
+/// A new pool that completes a pair with one or more other venues, held
+/// back from evaluation until it has aged and filled up.
+struct Candidate {
+    pool: NewPool,
+    /// One per other venue with a pool for its tokens.
+    pairs: Vec<MonitoredPair>,
+    created_at: DateTime<Utc>,
+}
+
+/// Follows the preset's factories for new pools between two scanned
+/// tokens and alerts on each with its initial liquidity and fee, since a
+/// fresh pool is often mispriced against the established ones.
+///
+/// A new pool that gives its tokens a pool on both venues is also queued
+/// for scanning, but only once it's `min_age` old and holds the minimum
+/// pool TVL: in its first minutes a pool trades at whatever price its
+/// creator seeded, and the wild spreads that shows against established
+/// venues are mostly traps for snipers.
+pub async fn watch(ctx: Arc<ScanContext>, min_age: TimeDelta) {
+    let mut next = None;
+    let mut pending = Vec::new();
+    loop {
+        match poll(&ctx, next, &mut pending).await {
+            Ok(block) => next = Some(block),
+            Err(e) => {
+                println!("{} Failed to read pool creations: {}", "[ERROR]".bright_red(), e);
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+        }
+        admit(&ctx, &mut pending, min_age).await;
+        tokio::time::sleep(BLOCK_POLL_INTERVAL).await;
+    }
+}
+
+/// Alerts on the pools created from block `from` to the head, or from the
+/// head on the first call, queues those that complete a pair in `pending`,
+/// and returns the block to read from next.
+async fn poll(ctx: &ScanContext, from: Option<u64>, pending: &mut Vec<Candidate>) -> Result<u64> {
+    let head = ctx.provider.get_block_number().await?;
+    let from = from.unwrap_or(head).max(head.saturating_sub(MAX_LOG_RANGE - 1));
+    if from > head {
+        return Ok(from);
+    }
+    let venues = &preset::active().venues;
+    // Uniswap V3 fee tiers share a factory
+    let mut factories = venues.iter().map(|venue| venue.factory).collect::<Vec<_>>();
+    factories.sort();
+    factories.dedup();
+    let mut topics = venues.iter().map(|venue| creation_topic(venue.venue)).collect::<Vec<_>>();
+    topics.sort();
+    topics.dedup();
+    let filter = Filter::new()
+        .address(factories)
+        .event_signature(topics)
+        .from_block(from)
+        .to_block(head);
+    let scanned = preset::active().scanned_tokens().collect::<Vec<_>>();
+    for log in ctx.provider.get_logs(&filter).await? {
+        let Some(venue) = venues.iter().find(|venue| venue.factory == log.address()) else {
+            continue;
+        };
+        let Some(pool) = decode(venue.venue, &log) else {
+            continue;
+        };
+        if !scanned.iter().any(|t| t.address == pool.token0.address)
+            || !scanned.iter().any(|t| t.address == pool.token1.address)
+        {
+            continue;
+        }
+        let (base, quote) = token::orient(pool.token0, pool.token1);
+        println!("{} {} New {} pool {:?} for {}/{} at block {}",
+            "[POOL]".bright_cyan(),
+            clock().bright_black(),
+            pool.venue,
+            pool.pool,
+            base.symbol,
+            quote.symbol,
+            pool.block,
+        );
+        let message = format_new_pool(ctx, &pool).await;
+        super::sync::warn(ctx, &ctx.chats, &format!("new-pool:{:?}", pool.pool), message).await;
+
+        match candidate(ctx, pool).await {
+            Ok(Some(candidate)) => pending.push(candidate),
+            Ok(None) => {}
+            Err(e) => println!("{} Failed to pair new pool {:?}: {}", "[ERROR]".bright_red(), pool.pool, e),
+        }
+    }
+    Ok(head + 1)
+}
+
+/// The pairs `pool` completes, if another venue has a pool for its tokens
+/// and `pool` is the one its venue would be scanned on.
+async fn candidate(ctx: &ScanContext, pool: NewPool) -> Result<Option<Candidate>> {
+    let pairs = scanner::pairs_with(&ctx.provider, &preset::active().venues, pool.token0, &[pool.token1])
+        .await?
+        .into_iter()
+        .filter(|pair| pair.pool_a == pool.pool || pair.pool_b == pool.pool)
+        .collect::<Vec<_>>();
+    if pairs.is_empty() {
+        return Ok(None);
+    }
+    let created_at = ctx
+        .provider
+        .get_block_by_number(pool.block.into())
+        .await?
+        .and_then(|block| DateTime::from_timestamp(block.header.timestamp as i64, 0))
+        .unwrap_or_else(Utc::now);
+    Ok(Some(Candidate {
+        pool,
+        pairs,
+        created_at,
+    }))
+}
+
+/// Hands every candidate at least `min_age` old whose pool now holds the
+/// minimum TVL to the scanner. Ones that don't yet are checked again next
+/// time.
+async fn admit(ctx: &ScanContext, pending: &mut Vec<Candidate>, min_age: TimeDelta) {
+    let now = Utc::now();
+    let mut waiting = Vec::with_capacity(pending.len());
+    for candidate in pending.drain(..) {
+        if now - candidate.created_at < min_age {
+            waiting.push(candidate);
+            continue;
+        }
+        match liquidity(ctx, &candidate.pool).await {
+            Ok((_, _, Some(usd))) if usd >= ctx.min_tvl_usd => {
+                println!("{} {} {} pool {:?} for {}/{} is {}m old with {} TVL, now scanning it",
+                    "[POOL]".bright_cyan(),
+                    clock().bright_black(),
+                    candidate.pool.venue,
+                    candidate.pool.pool,
+                    candidate.pairs[0].base().symbol,
+                    candidate.pairs[0].quote().symbol,
+                    (now - candidate.created_at).num_minutes(),
+                    format_usd(usd),
+                );
+                ctx.new_pairs.lock().unwrap().extend(candidate.pairs);
+            }
+            Ok(_) => waiting.push(candidate),
+            Err(e) => {
+                println!("{} Failed to read new pool {:?}: {}", "[ERROR]".bright_red(), candidate.pool.pool, e);
+                waiting.push(candidate);
+            }
+        }
+    }
+    *pending = waiting;
+}
+
+/// `pool`'s reserves in whole tokens, and their USD value when either
+/// token can be priced.
+async fn liquidity(ctx: &ScanContext, pool: &NewPool) -> Result<(f64, f64, Option<f64>)> {
+    let venue = preset::active()
+        .venues
+        .iter()
+        .find(|venue| venue.venue == pool.venue)
+        .expect("pool from a preset venue");
+    let (reserve0, reserve1) = venue
+        .read_reserves(&ctx.provider, pool.pool, pool.token0.address, None)
+        .await?;
+    let (amount0, amount1) = (
+        price::to_units(reserve0, pool.token0.decimals),
+        price::to_units(reserve1, pool.token1.decimals),
+    );
+    // Both sides are worth the same at the pool's own price
+    let usd = ctx
+        .gas
+        .usd_value(amount1, pool.token1)
+        .or_else(|| ctx.gas.usd_value(amount0, pool.token0))
+        .map(|usd| usd * 2.0);
+    Ok((amount0, amount1, usd))
+}
+
+async fn format_new_pool(ctx: &ScanContext, pool: &NewPool) -> String {
+    let liquidity = match liquidity(ctx, pool).await {
+        Ok((amount0, amount1, usd)) => format!(
+            "{} {} + {} {}{}",
+            format_amount(amount0),
+            pool.token0.symbol,
+            format_amount(amount1),
+            pool.token1.symbol,
+            usd.map(|usd| format!(" (≈{})", format_usd(usd))).unwrap_or_default()
+        ),
+        Err(e) => format!("unknown ({})", e),
+    };
+    let fee = fees::read_fee(&ctx.provider, pool.pool, pool.venue)
+        .await
+        .unwrap_or_else(|| pool.venue.default_fee());
+    let fee = match pool.bin_step {
+        Some(bin_step) => format!("{} (bin step {}bp)", format_percent(fee), bin_step),
+        None => format_percent(fee),
+    };
+    let (base, quote) = token::orient(pool.token0, pool.token1);
+    format!(
+        "🆕 <b>New pool</b>\n\n\
+        Venue: {}\n\
+        Pair: <code>{}/{}</code>\n\
+        Pool: <code>{:?}</code>\n\
+        Initial liquidity: <code>{}</code>\n\
+        Fee: <code>{}</code>\n\
+        Block: <code>{}</code>\n\n\
+        Fresh pools often open mispriced against established ones.",
+        pool.venue,
+        base.symbol,
+        quote.symbol,
+        pool.pool,
+        liquidity,
+        fee,
+        pool.block
+    )
+}
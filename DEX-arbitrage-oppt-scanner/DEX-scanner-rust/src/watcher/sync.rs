@@ -0,0 +1,166 @@
+use anyhow::{ensure, Context, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use colored::*;
+use alloy::providers::Provider;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+use crate::enrich::safety::ETHERSCAN_API_URL;
+use crate::preset;
+use crate::storage::shared::QueuedAlert;
+use crate::utils::format::{clock, format_time};
+use crate::{Chat, ScanContext};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const NTP_TIMEOUT: Duration = Duration::from_secs(5);
+// Seconds from the NTP epoch, 1900, to the Unix one
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+/// Checks the local clock against an NTP server and the RPC node's head
+/// against the block explorer's, and warns when either is off. A lagging
+/// node serves stale reserves, which show up as false opportunities.
+pub struct SyncMonitor {
+    pub ntp_server: String,
+    pub max_clock_skew: TimeDelta,
+    pub max_block_lag: u64,
+    /// Needed for the explorer's head; without it only the clock is checked.
+    pub etherscan_api_key: Option<String>,
+}
+
+impl SyncMonitor {
+    pub async fn run(self, ctx: Arc<ScanContext>) {
+        let client = reqwest::Client::new();
+        if self.etherscan_api_key.is_none() {
+            println!("{} {} ETHERSCAN_API_KEY not set, not checking the RPC node's head against the explorer",
+                "[INFO]".bright_blue(),
+                clock().bright_black(),
+            );
+        }
+        loop {
+            match clock_skew(&self.ntp_server).await {
+                Ok(skew) if skew.abs() > self.max_clock_skew => {
+                    let skew = format!("{}ms", skew.num_milliseconds());
+                    println!("{} {} Local clock is off by {} from {}",
+                        "[SYNC]".bright_yellow(),
+                        clock().bright_black(),
+                        skew,
+                        self.ntp_server,
+                    );
+                    let message = format!(
+                        "⚠️ <b>Clock skew</b>\n\n\
+                        The scanner's clock is off by <code>{}</code> from <code>{}</code>. \
+                        Alert times, reports and TWAP windows are off by as much.",
+                        skew, self.ntp_server
+                    );
+                    warn(&ctx, &ctx.ops_chats, "clock-skew", message).await;
+                }
+                Ok(_) => {}
+                Err(e) => println!("{} Failed to check the clock against {}: {:#}",
+                    "[ERROR]".bright_red(),
+                    self.ntp_server,
+                    e,
+                ),
+            }
+
+            if let Some(api_key) = &self.etherscan_api_key {
+                let (local, explorer) = tokio::join!(ctx.provider.get_block_number(), explorer_head(&client, api_key));
+                match (local, explorer) {
+                    (Ok(local), Ok(explorer)) if explorer.saturating_sub(local) > self.max_block_lag => {
+                        let lag = explorer - local;
+                        println!("{} {} RPC node is {} blocks behind the explorer (block {} vs {})",
+                            "[SYNC]".bright_yellow(),
+                            clock().bright_black(),
+                            lag,
+                            local,
+                            explorer,
+                        );
+                        let message = format!(
+                            "⚠️ <b>RPC node lagging</b>\n\n\
+                            The node is <code>{}</code> blocks behind the explorer \
+                            (block <code>{}</code> vs <code>{}</code>). \
+                            Its reserves are stale, so opportunities may be false until it catches up.",
+                            lag, local, explorer
+                        );
+                        warn(&ctx, &ctx.ops_chats, "rpc-lag", message).await;
+                    }
+                    (Ok(_), Ok(_)) => {}
+                    (Err(e), _) => println!("{} Failed to read the RPC node's head: {}", "[ERROR]".bright_red(), e),
+                    (_, Err(e)) => println!("{} Failed to read the explorer's head: {:#}", "[ERROR]".bright_red(), e),
+                }
+            }
+
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    }
+}
+
+/// Sends `message` to `chats`, at most once per cooldown per `kind`.
+pub(crate) async fn warn(ctx: &ScanContext, chats: &[Chat], kind: &str, message: String) {
+    if !ctx.should_alert() {
+        return;
+    }
+    let key = format!("{}:{}", preset::active().chain, kind);
+    if !ctx.shared.claim_alert(&key, ctx.alert_cooldown).await {
+        return;
+    }
+    let message = format!("{}\n\n<i>{}</i>", message, format_time(Utc::now()));
+    let messages = chats.iter().map(|chat| (chat.id, message.clone())).collect();
+    ctx.notify(QueuedAlert { key, messages }).await;
+}
+
+/// How far the local clock is behind `server`'s (negative when ahead), by
+/// one SNTP exchange. `server` is a host, optionally with a port.
+async fn clock_skew(server: &str) -> Result<TimeDelta> {
+    let address = if server.contains(':') { server.to_string() } else { format!("{}:123", server) };
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(&address).await.with_context(|| format!("Failed to resolve {}", address))?;
+
+    // Version 4, client mode
+    let mut packet = [0u8; 48];
+    packet[0] = 0x23;
+    let sent = Utc::now();
+    socket.send(&packet).await?;
+    let len = tokio::time::timeout(NTP_TIMEOUT, socket.recv(&mut packet))
+        .await
+        .context("NTP server didn't answer")??;
+    let received = Utc::now();
+    ensure!(len >= 48, "Short NTP response ({} bytes)", len);
+
+    // The server's receive and transmit times, against ours
+    let server_received = ntp_time(&packet[32..40])?;
+    let server_sent = ntp_time(&packet[40..48])?;
+    Ok(((server_received - sent) + (server_sent - received)) / 2)
+}
+
+fn ntp_time(bytes: &[u8]) -> Result<DateTime<Utc>> {
+    let seconds = u32::from_be_bytes(bytes[..4].try_into()?) as i64 - NTP_UNIX_OFFSET;
+    let fraction = u32::from_be_bytes(bytes[4..8].try_into()?) as u64;
+    DateTime::from_timestamp(seconds, ((fraction * 1_000_000_000) >> 32) as u32)
+        .context("Invalid NTP timestamp")
+}
+
+#[derive(Debug, Deserialize)]
+struct ProxyResponse {
+    result: String,
+}
+
+/// Latest block on the active chain per its Etherscan-family explorer.
+async fn explorer_head(client: &reqwest::Client, api_key: &str) -> Result<u64> {
+    let chain_id = preset::active().chain_id.to_string();
+    let response = client
+        .get(ETHERSCAN_API_URL)
+        .query(&[
+            ("chainid", chain_id.as_str()),
+            ("module", "proxy"),
+            ("action", "eth_blockNumber"),
+            ("apikey", api_key),
+        ])
+        .send()
+        .await?
+        .json::<ProxyResponse>()
+        .await?;
+    let hex = response.result.trim_start_matches("0x");
+    u64::from_str_radix(hex, 16).with_context(|| format!("Unexpected explorer response {:?}", response.result))
+}
@@ -0,0 +1,128 @@
+use alloy::network::{ReceiptResponse, TransactionResponse};
+use alloy::primitives::Address;
+use alloy::providers::Provider;
+use anyhow::Result;
+use colored::*;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::decode::event::EventRegistry;
+use crate::decode::{self, PoolResolver};
+use crate::utils::format::clock;
+use crate::{RpcProvider, ScanContext};
+
+/// Tracks known arbitrageur wallets and tallies the routes they trade.
+pub struct WalletWatcher {
+    wallets: Vec<Address>,
+    events: EventRegistry,
+    resolver: PoolResolver,
+    // route description -> (arb count, wallets seen)
+    routes: Mutex<HashMap<String, (usize, Vec<Address>)>>,
+}
+
+impl WalletWatcher {
+    pub fn new(provider: RpcProvider, wallets: Vec<Address>) -> Self {
+        WalletWatcher {
+            wallets,
+            events: EventRegistry::default(),
+            resolver: PoolResolver::new(provider),
+            routes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Follows new blocks and records every successful arb sent by a watched wallet.
+    pub async fn run(&self, provider: &RpcProvider) -> Result<()> {
+        let mut blocks = provider.watch_blocks().await?.into_stream().flat_map(futures::stream::iter);
+
+        while let Some(hash) = blocks.next().await {
+            let block = match provider.get_block_by_hash(hash).full().await {
+                Ok(Some(block)) => block,
+                Ok(None) => continue,
+                Err(e) => {
+                    println!("{} Failed to fetch block {:?}: {}", "[ERROR]".bright_red(), hash, e);
+                    continue;
+                }
+            };
+
+            for tx in block.transactions.txns().filter(|tx| self.wallets.contains(&tx.from())) {
+                let receipt = match provider.get_transaction_receipt(tx.tx_hash()).await {
+                    Ok(Some(receipt)) if receipt.status() => receipt,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        println!("{} Failed to fetch receipt {:?}: {}", "[ERROR]".bright_red(), tx.tx_hash(), e);
+                        continue;
+                    }
+                };
+
+                let decoded = decode::decode_receipt(&self.events, &self.resolver, &receipt).await;
+                if !decoded.is_arb() {
+                    continue;
+                }
+
+                println!(
+                    "{} {} Watched wallet {:?} arbed: {} (tx {:?})",
+                    "[WATCH]".bright_magenta(),
+                    clock().bright_black(),
+                    decoded.from,
+                    decoded.describe(),
+                    decoded.hash
+                );
+
+                let mut routes = self.routes.lock().unwrap();
+                let entry = routes.entry(decoded.route()).or_insert((0, Vec::new()));
+                entry.0 += 1;
+                if !entry.1.contains(&decoded.from) {
+                    entry.1.push(decoded.from);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Takes the tallies collected since the last digest as a Telegram message.
+    fn take_digest(&self) -> Option<String> {
+        let routes = std::mem::take(&mut *self.routes.lock().unwrap());
+        if routes.is_empty() {
+            return None;
+        }
+
+        let mut routes = routes.into_iter().collect::<Vec<_>>();
+        routes.sort_by_key(|(_, (count, _))| std::cmp::Reverse(*count));
+
+        let lines = routes
+            .iter()
+            .map(|(route, (count, wallets))| {
+                format!("• <code>{}</code> ×{} ({} wallet(s))", route, count, wallets.len())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Some(format!("👀 <b>Watched Wallet Activity</b>\n\n{}", lines))
+    }
+}
+
+/// Runs the watcher and sends a digest of observed routes every `interval`.
+pub fn spawn(watcher: Arc<WalletWatcher>, ctx: Arc<ScanContext>, interval: Duration) {
+    let runner = Arc::clone(&watcher);
+    let provider = ctx.provider.clone();
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = runner.run(&provider).await {
+                println!("{} Wallet watcher error: {}", "[ERROR]".bright_red(), e);
+            }
+            tokio::time::sleep(crate::RETRY_DELAY).await;
+        }
+    });
+
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            if let Some(message) = watcher.take_digest() {
+                ctx.broadcast(&message).await;
+            }
+        }
+    });
+}
@@ -0,0 +1,174 @@
+use alloy::primitives::Address;
+use alloy::providers::Provider;
+use alloy::rpc::types::{Filter, Log};
+use anyhow::Result;
+use colored::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::decode::v2::{amounts, SOLIDLY_SWAP_TOPIC, SWAP_TOPIC};
+use crate::models::opportunity::Venue;
+use crate::models::token::TokenInfo;
+use crate::preset;
+use crate::storage::shared::QueuedAlert;
+use crate::utils::format::{self as fmt, format_amount, format_usd};
+use crate::utils::price;
+use crate::i18n::Localize;
+use crate::{i18n, ScanContext, BLOCK_POLL_INTERVAL, RETRY_DELAY};
+
+// Most blocks read for swaps at once, e.g. catching up after an outage
+const MAX_LOG_RANGE: u64 = 100;
+
+/// A scanned pool and its pair's tokens, in the pool's order.
+#[derive(Debug, Clone, Copy)]
+struct WatchedPool {
+    venue: Venue,
+    token0: &'static TokenInfo,
+    token1: &'static TokenInfo,
+}
+
+/// One swap worth at least the whale threshold.
+#[derive(Debug)]
+struct WhaleSwap {
+    venue: Venue,
+    token_in: &'static TokenInfo,
+    token_out: &'static TokenInfo,
+    amount_in: f64,
+    amount_out: f64,
+    usd: f64,
+    tx: String,
+}
+
+/// Every scanned pool, so pairs joining the scan are watched too. Trader
+/// Joe LB pools log swaps per bin and are left out.
+fn watched_pools(ctx: &ScanContext) -> HashMap<Address, WatchedPool> {
+    ctx.pools
+        .entries()
+        .into_iter()
+        .filter(|(_, entry)| !matches!(entry.venue, Venue::TraderJoeLB | Venue::UniswapV3))
+        .map(|(pool, entry)| {
+            let (token0, token1) = (entry.pair.token0, entry.pair.token1);
+            (pool, WatchedPool { venue: entry.venue, token0, token1 })
+        })
+        .collect()
+}
+
+/// The swap `log` records, if it's worth at least `min_usd` on either side.
+fn whale_swap(ctx: &ScanContext, log: &Log, pool: &WatchedPool, min_usd: f64) -> Option<WhaleSwap> {
+    let [amount0_in, amount1_in, amount0_out, amount1_out] = amounts(&log.data().data)?;
+    let (token_in, token_out, amount_in, amount_out) = if amount0_in.is_zero() {
+        (pool.token1, pool.token0, amount1_in, amount0_out)
+    } else {
+        (pool.token0, pool.token1, amount0_in, amount1_out)
+    };
+    let amount_in = price::to_units(amount_in, token_in.decimals);
+    let amount_out = price::to_units(amount_out, token_out.decimals);
+    let usd = [ctx.gas.usd_value(amount_in, token_in), ctx.gas.usd_value(amount_out, token_out)]
+        .into_iter()
+        .flatten()
+        .reduce(f64::max)?;
+    (usd >= min_usd).then(|| WhaleSwap {
+        venue: pool.venue,
+        token_in,
+        token_out,
+        amount_in,
+        amount_out,
+        usd,
+        tx: log.transaction_hash.map(|hash| format!("{:?}", hash)).unwrap_or_default(),
+    })
+}
+
+/// Alerts on every swap through a scanned pool worth at least `min_usd`,
+/// whether or not it opens a spread, since a big trade often knocks a pool
+/// out of line with the other venue. Runs forever.
+pub async fn watch(ctx: Arc<ScanContext>, min_usd: f64) {
+    let mut next = None;
+    loop {
+        match scan(&ctx, next, min_usd).await {
+            Ok(scanned_to) => next = Some(scanned_to),
+            Err(e) => {
+                println!("{} Failed to read whale swaps: {}", "[ERROR]".bright_red(), e);
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
+            }
+        }
+        tokio::time::sleep(BLOCK_POLL_INTERVAL).await;
+    }
+}
+
+/// Reads swaps from block `from` (the head if `None`) to the head and
+/// alerts on the whales. Returns the block to read from next.
+async fn scan(ctx: &ScanContext, from: Option<u64>, min_usd: f64) -> Result<u64> {
+    let head = ctx.provider.get_block_number().await?;
+    let from = from.unwrap_or(head).max(head.saturating_sub(MAX_LOG_RANGE - 1));
+    if from > head {
+        return Ok(from);
+    }
+    let pools = watched_pools(ctx);
+    if pools.is_empty() {
+        return Ok(head + 1);
+    }
+    let filter = Filter::new()
+        .address(pools.keys().copied().collect::<Vec<_>>())
+        .event_signature(vec![*SWAP_TOPIC, *SOLIDLY_SWAP_TOPIC])
+        .from_block(from)
+        .to_block(head);
+    for log in ctx.provider.get_logs(&filter).await? {
+        let Some(pool) = pools.get(&log.address()) else {
+            continue;
+        };
+        if let Some(swap) = whale_swap(ctx, &log, pool, min_usd) {
+            alert(ctx, &swap, log.log_index.unwrap_or_default()).await;
+        }
+    }
+    Ok(head + 1)
+}
+
+async fn alert(ctx: &ScanContext, swap: &WhaleSwap, log_index: u64) {
+    println!("{} {} Whale swap on {}: {} {} for {} {} ({}) in {}",
+        "[ALERT]".bright_yellow(),
+        fmt::clock().bright_black(),
+        swap.venue.name(),
+        format_amount(swap.amount_in),
+        swap.token_in.symbol,
+        format_amount(swap.amount_out),
+        swap.token_out.symbol,
+        format_usd(swap.usd),
+        swap.tx,
+    );
+    if !ctx.should_alert() {
+        return;
+    }
+    // Per swap, so a restart or another replica doesn't repeat it
+    let key = format!("{}:whale:{}:{}", preset::active().chain, swap.tx, log_index);
+    if !ctx.shared.claim_alert(&key, ctx.alert_cooldown).await {
+        return;
+    }
+    let messages = ctx
+        .chats
+        .iter()
+        .map(|chat| {
+            let text = chat.lang.catalog();
+            let line = i18n::fill(
+                text.whale_swapped,
+                &[
+                    ("amount_in", &format_amount(swap.amount_in)),
+                    ("token_in", swap.token_in.symbol),
+                    ("amount_out", &format_amount(swap.amount_out)),
+                    ("token_out", swap.token_out.symbol),
+                    ("venue", swap.venue.name()),
+                    ("usd", &format_usd(swap.usd)),
+                ],
+            );
+            let message = format!(
+                "🐋 <b>{}</b>\n\n{}\n<code>{}</code>\n\n<i>{}</i>",
+                text.whale_swap,
+                line,
+                swap.tx,
+                fmt::format_time(chrono::Utc::now())
+            );
+            (chat.id, message)
+        })
+        .collect();
+    ctx.notify(QueuedAlert { key, messages }).await;
+}
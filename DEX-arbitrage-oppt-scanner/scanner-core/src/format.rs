@@ -0,0 +1,151 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, Utc};
+use chrono_tz::Tz;
+use once_cell::sync::OnceCell;
+use std::env;
+
+static LOCALE: OnceCell<Locale> = OnceCell::new();
+
+/// How timestamps and numbers are rendered in alerts, reports and logs.
+#[derive(Debug, Clone)]
+pub struct Locale {
+    /// `None` uses the system's local timezone.
+    pub timezone: Option<Tz>,
+    /// strftime pattern for full timestamps.
+    pub date_format: String,
+    pub thousands_separator: String,
+    pub decimal_separator: String,
+    pub currency_symbol: String,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale {
+            timezone: None,
+            date_format: "%Y-%m-%d %H:%M:%S %Z".to_string(),
+            thousands_separator: ",".to_string(),
+            decimal_separator: ".".to_string(),
+            currency_symbol: "$".to_string(),
+        }
+    }
+}
+
+impl Locale {
+    /// Reads TIMEZONE, DATE_FORMAT, THOUSANDS_SEPARATOR, DECIMAL_SEPARATOR and
+    /// CURRENCY_SYMBOL, falling back to the defaults for unset variables.
+    pub fn from_env() -> Result<Self> {
+        let default = Locale::default();
+        let timezone = match env::var("TIMEZONE").ok().filter(|s| !s.is_empty()) {
+            Some(name) => Some(
+                name.parse::<Tz>()
+                    .map_err(|e| anyhow!("Invalid TIMEZONE {}: {}", name, e))?,
+            ),
+            None => None,
+        };
+
+        Ok(Locale {
+            timezone,
+            date_format: env::var("DATE_FORMAT").unwrap_or(default.date_format),
+            thousands_separator: env::var("THOUSANDS_SEPARATOR").unwrap_or(default.thousands_separator),
+            decimal_separator: env::var("DECIMAL_SEPARATOR").unwrap_or(default.decimal_separator),
+            currency_symbol: env::var("CURRENCY_SYMBOL").unwrap_or(default.currency_symbol),
+        })
+    }
+
+    pub fn timezone_name(&self) -> String {
+        match self.timezone {
+            Some(tz) => tz.name().to_string(),
+            None => "system local".to_string(),
+        }
+    }
+}
+
+/// Sets the process-wide locale. Only the first call has an effect.
+pub fn init(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+fn locale() -> &'static Locale {
+    LOCALE.get_or_init(Locale::default)
+}
+
+/// Applies the locale's separators to a plain `-1234.5` style number.
+fn localize(formatted: &str) -> String {
+    let locale = locale();
+    let (sign, digits) = match formatted.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", formatted),
+    };
+    let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+
+    let mut grouped = String::new();
+    for (i, c) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            grouped.push_str(&locale.thousands_separator);
+        }
+        grouped.push(c);
+    }
+
+    if frac_part.is_empty() {
+        format!("{}{}", sign, grouped)
+    } else {
+        format!("{}{}{}{}", sign, grouped, locale.decimal_separator, frac_part)
+    }
+}
+
+/// Formats a price or token amount with thousands separators and a number of
+/// decimals suited to its magnitude, e.g. `2,410.52` or `0.05213`.
+pub fn format_amount(value: f64) -> String {
+    let decimals = match value.abs() {
+        v if v >= 1_000.0 => 2,
+        v if v >= 1.0 => 4,
+        _ => 6,
+    };
+    localize(&format!("{:.*}", decimals, value))
+}
+
+/// Compact currency amount, e.g. `$1.23M` or `$310.5K`.
+pub fn format_usd(value: f64) -> String {
+    let symbol = &locale().currency_symbol;
+    if value >= 1_000_000_000.0 {
+        format!("{}{}B", symbol, localize(&format!("{:.2}", value / 1_000_000_000.0)))
+    } else if value >= 1_000_000.0 {
+        format!("{}{}M", symbol, localize(&format!("{:.2}", value / 1_000_000.0)))
+    } else if value >= 1_000.0 {
+        format!("{}{}K", symbol, localize(&format!("{:.1}", value / 1_000.0)))
+    } else {
+        format!("{}{}", symbol, localize(&format!("{:.0}", value)))
+    }
+}
+
+/// A ratio as a percentage with two decimals, e.g. `0.0123` -> `1.23%`.
+pub fn format_percent(ratio: f64) -> String {
+    format!("{}%", localize(&format!("{:.2}", ratio * 100.0)))
+}
+
+fn format_in_zone(time: DateTime<Utc>, pattern: &str) -> String {
+    match locale().timezone {
+        Some(tz) => time.with_timezone(&tz).format(pattern).to_string(),
+        None => time.with_timezone(&Local).format(pattern).to_string(),
+    }
+}
+
+/// Full timestamp in the configured timezone and date format.
+pub fn format_time(time: DateTime<Utc>) -> String {
+    format_in_zone(time, &locale().date_format)
+}
+
+/// Hour of the day, 0-23, in the configured timezone.
+pub fn local_hour(time: DateTime<Utc>) -> u32 {
+    format_in_zone(time, "%H").parse().unwrap_or_default()
+}
+
+/// Short name of the configured timezone for labels, e.g. `UTC` or `CET`.
+pub fn timezone_label() -> String {
+    format_in_zone(Utc::now(), "%Z")
+}
+
+/// Current wall-clock time for console log lines, e.g. `14:03:27`.
+pub fn clock() -> String {
+    format_in_zone(Utc::now(), "%H:%M:%S")
+}
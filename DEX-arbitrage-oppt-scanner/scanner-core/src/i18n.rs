@@ -0,0 +1,53 @@
+//! Languages alerts are written in. The strings themselves differ between
+//! scanners, so each keeps its own catalog per [`Lang`].
+
+use anyhow::{bail, Error};
+use std::str::FromStr;
+
+/// Languages alert text is available in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    En,
+    Es,
+    De,
+    Fr,
+    Pt,
+}
+
+impl FromStr for Lang {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "en" => Ok(Lang::En),
+            "es" => Ok(Lang::Es),
+            "de" => Ok(Lang::De),
+            "fr" => Ok(Lang::Fr),
+            "pt" => Ok(Lang::Pt),
+            other => bail!("Unsupported language {:?} (expected en, es, de, fr or pt)", other),
+        }
+    }
+}
+
+impl Lang {
+    /// The code [`Lang`] parses from, e.g. `es`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Es => "es",
+            Lang::De => "de",
+            Lang::Fr => "fr",
+            Lang::Pt => "pt",
+        }
+    }
+}
+
+/// Replaces each `{name}` in `template` with its value.
+pub fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
@@ -0,0 +1,10 @@
+//! What the EVM and Solana scanners share whatever the chain: stored
+//! opportunity records, spread and constant-product math, alert languages
+//! and the locale numbers and times are shown in. It depends on no chain
+//! SDK, so each scanner takes it as a path dependency and keeps its own
+//! dependency tree and lockfile.
+
+pub mod format;
+pub mod i18n;
+pub mod models;
+pub mod price;
@@ -0,0 +1,51 @@
+//! Records both scanners store, alert on and serve, whatever the chain.
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// One qualified opportunity as persisted for analytics.
+#[derive(Debug, Clone)]
+pub struct OpportunityRecord {
+    /// Derived from the chain, block or slot, pair and venues, so one found
+    /// again after a restart keeps its ID. Nil for rows recorded before
+    /// opportunities had IDs.
+    pub id: Uuid,
+    /// The scanner run that found it, nil for rows recorded before runs
+    /// had IDs.
+    pub run_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub chain: String,
+    pub pair: String,
+    pub buy_venue: String,
+    pub sell_venue: String,
+    pub buy_price: f64,
+    pub sell_price: f64,
+    pub spread: f64,
+    /// Quote spent at the profit-maximising size.
+    pub size: f64,
+    /// Theoretical profit in quote units, before gas.
+    pub profit: f64,
+    pub profit_usd: Option<f64>,
+}
+
+/// Aggregate over all opportunities between the same two venues, regardless
+/// of direction.
+#[derive(Debug, Clone)]
+pub struct VenuePairStats {
+    pub venues: String,
+    pub count: u64,
+    pub profit_usd: f64,
+    pub max_spread: f64,
+}
+
+/// Spread and profit statistics over one pair's opportunities.
+#[derive(Debug, Clone)]
+pub struct PairStats {
+    pub count: u64,
+    pub min_spread: f64,
+    pub avg_spread: f64,
+    pub max_spread: f64,
+    pub profit_usd: f64,
+    /// The opportunity with the widest spread, if there were any.
+    pub widest: Option<OpportunityRecord>,
+}
@@ -0,0 +1,70 @@
+//! Spread and constant-product math on whole-token amounts, the same on
+//! every chain.
+
+/// Relative gap between two prices of the same pair, e.g. `0.012` for
+/// 1.2%. Zero if either price isn't positive.
+pub fn calculate_profit_margin(price_a: f64, price_b: f64) -> f64 {
+    if price_a > price_b && price_b > 0.0 {
+        price_a / price_b - 1.0
+    } else if price_b > price_a && price_a > 0.0 {
+        price_b / price_a - 1.0
+    } else {
+        0.0
+    }
+}
+
+/// Constant-product output for `amount_in`, all amounts in whole-token units.
+pub fn get_amount_out(amount_in: f64, reserve_in: f64, reserve_out: f64, fee: f64) -> f64 {
+    let amount_in_with_fee = amount_in * (1.0 - fee);
+    if reserve_in + amount_in_with_fee <= 0.0 {
+        return 0.0;
+    }
+    amount_in_with_fee * reserve_out / (reserve_in + amount_in_with_fee)
+}
+
+/// Input that maximises `out - in` when routing X -> Y through pool 1 and
+/// Y -> X back through pool 2, each with its own fee. Returns `None` when no
+/// size is profitable.
+///
+/// The two hops collapse into a single virtual constant-product pool with
+/// reserves `(ea, eb)` and pool 1's fee, whose optimum has a closed form.
+pub fn optimal_arb_input(
+    reserve1_in: f64,
+    reserve1_out: f64,
+    reserve2_in: f64,
+    reserve2_out: f64,
+    fee1: f64,
+    fee2: f64,
+) -> Option<f64> {
+    let gamma1 = 1.0 - fee1;
+    let gamma2 = 1.0 - fee2;
+    let denominator = reserve2_in + gamma2 * reserve1_out;
+    if denominator <= 0.0 {
+        return None;
+    }
+
+    let ea = reserve1_in * reserve2_in / denominator;
+    let eb = gamma2 * reserve1_out * reserve2_out / denominator;
+    let optimal = ((ea * eb * gamma1).sqrt() - ea) / gamma1;
+
+    (optimal > 0.0).then_some(optimal)
+}
+
+/// Smallest input for which `profit_at(input) >= cost`, searching up to
+/// `max_input` (the profit-maximising size, beyond which profit only falls).
+pub fn break_even_input(profit_at: impl Fn(f64) -> f64, cost: f64, max_input: f64) -> Option<f64> {
+    if profit_at(max_input) < cost {
+        return None;
+    }
+
+    let (mut low, mut high) = (0.0, max_input);
+    for _ in 0..64 {
+        let mid = (low + high) / 2.0;
+        if profit_at(mid) >= cost {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    Some(high)
+}
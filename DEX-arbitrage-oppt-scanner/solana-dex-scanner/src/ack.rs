@@ -0,0 +1,98 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// A thread nobody has alerted on for this long is forgotten, so the next
+// alert on the opportunity starts fresh and can be acked again
+const THREAD_TTL: Duration = Duration::from_secs(3600);
+
+/// The alerts sent on one opportunity and who, if anyone, has claimed it.
+#[derive(Debug, Clone)]
+pub struct Thread {
+    pub id: u64,
+    /// Chat and message id of every alert sent, oldest first.
+    pub messages: Vec<(String, i32)>,
+    pub acked_by: Option<String>,
+    last_alert: Instant,
+}
+
+impl Thread {
+    /// The first alert sent to `chat`, which follow-ups reply to.
+    pub fn root(&self, chat: &str) -> Option<i32> {
+        self.messages
+            .iter()
+            .find(|(id, _)| id == chat)
+            .map(|(_, message)| *message)
+    }
+}
+
+pub enum AckOutcome {
+    Acked(Thread),
+    AlreadyAcked(String),
+    /// The thread went idle and was forgotten.
+    Unknown,
+}
+
+/// Alert threads keyed by alert key, so a teammate can claim an opportunity
+/// before anyone else acts on it. Only the leader sends alerts and polls for
+/// button taps, so this is per instance.
+#[derive(Default)]
+pub struct AckBoard {
+    threads: Mutex<(u64, HashMap<String, Thread>)>,
+}
+
+impl AckBoard {
+    /// The thread for `key`, starting a new one if there is none.
+    pub fn open(&self, key: &str) -> Thread {
+        let mut guard = self.threads.lock().unwrap();
+        let (next_id, threads) = &mut *guard;
+        threads.retain(|_, t| t.last_alert.elapsed() < THREAD_TTL);
+
+        let thread = threads.entry(key.to_string()).or_insert_with(|| {
+            *next_id += 1;
+            Thread {
+                id: *next_id,
+                messages: Vec::new(),
+                acked_by: None,
+                last_alert: Instant::now(),
+            }
+        });
+        thread.last_alert = Instant::now();
+        thread.clone()
+    }
+
+    /// Remembers an alert sent on `key`'s thread.
+    pub fn record(&self, key: &str, chat: &str, message: i32) {
+        if let Some(thread) = self.threads.lock().unwrap().1.get_mut(key) {
+            thread.messages.push((chat.to_string(), message));
+        }
+    }
+
+    /// Claims thread `id` for `by`, unless someone already has.
+    pub fn ack(&self, id: u64, by: &str) -> AckOutcome {
+        let mut guard = self.threads.lock().unwrap();
+        let Some(thread) = guard.1.values_mut().find(|t| t.id == id) else {
+            return AckOutcome::Unknown;
+        };
+        match &thread.acked_by {
+            Some(owner) => AckOutcome::AlreadyAcked(owner.clone()),
+            None => {
+                thread.acked_by = Some(by.to_string());
+                AckOutcome::Acked(thread.clone())
+            }
+        }
+    }
+}
+
+/// The Ack button for thread `id`, as a Bot API `reply_markup`.
+pub fn button(label: &str, id: u64) -> Value {
+    json!({
+        "inline_keyboard": [[{ "text": label, "callback_data": format!("ack:{}", id) }]]
+    })
+}
+
+/// The thread id in an Ack button's callback data.
+pub fn parse_callback(data: &str) -> Option<u64> {
+    data.strip_prefix("ack:")?.parse().ok()
+}
@@ -0,0 +1,516 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use teloxide::{
+    prelude::*,
+    types::{MessageId, ParseMode, Recipient},
+    utils::command::BotCommands,
+    utils::html,
+};
+
+use crate::ack::{self, AckBoard, AckOutcome};
+use crate::dex;
+use crate::i18n::{self, Localize};
+use crate::models::token::{self, TokenInfo};
+use crate::storage::Storage;
+use crate::utils::format::{format_amount, format_percent, format_time, format_usd};
+use crate::utils::price;
+use crate::utils::telegram::Notifier;
+
+// How often a standby checks whether it has become leader, and the leader
+// whether it still is
+const LEADER_POLL_INTERVAL: Duration = Duration::from_secs(5);
+// Pairs listed by /top
+const TOP_PAIRS: usize = 10;
+
+/// Latest price and liquidity of a pair on one venue.
+#[derive(Debug, Clone)]
+pub struct VenueQuote {
+    pub venue: &'static str,
+    pub price: f64,
+    /// Base token reserves of the pool.
+    pub liquidity: f64,
+}
+
+/// Latest prices and liquidity of a pair on each venue read.
+#[derive(Debug, Clone)]
+pub struct PairQuote {
+    pub venues: Vec<VenueQuote>,
+    pub read_at: DateTime<Utc>,
+}
+
+impl PairQuote {
+    /// The venues' average price, if any was read.
+    pub fn mean_price(&self) -> Option<f64> {
+        if self.venues.is_empty() {
+            return None;
+        }
+        Some(self.venues.iter().map(|venue| venue.price).sum::<f64>() / self.venues.len() as f64)
+    }
+}
+
+/// Latest quote per pair (`A/B`), written by the scan loop.
+pub type QuoteBoard = Arc<RwLock<HashMap<String, PairQuote>>>;
+
+/// State the command handlers read from.
+pub struct BotContext {
+    pub notifier: Arc<Notifier>,
+    pub storage: Arc<Storage>,
+    pub quotes: QuoteBoard,
+    pub client: Arc<RpcClient>,
+    pub acks: Arc<AckBoard>,
+    pub strategy: Option<&'static str>,
+    pub tokens: Vec<&'static TokenInfo>,
+    pub min_profit_threshold: f64,
+    /// Pairs with their own threshold, from the config file.
+    pub pair_thresholds: Vec<(&'static TokenInfo, &'static TokenInfo, f64)>,
+    pub alert_cooldown: Duration,
+    /// Whether alert claims and history are shared through Redis.
+    pub redis: bool,
+}
+
+#[derive(BotCommands, Clone)]
+#[command(rename_rule = "lowercase", description = "Scanner commands:")]
+pub enum Command {
+    #[command(description = "show this help")]
+    Help,
+    #[command(description = "send a sample alert to every configured chat")]
+    Test,
+    #[command(description = "24h spread stats, prices and liquidity for a pair, e.g. /stats SOL/USDC")]
+    Stats(String),
+    #[command(description = "live price on every venue and the best spread, e.g. /price SOL/USDC")]
+    Price(String),
+    #[command(description = "effective configuration of this instance")]
+    Config,
+    #[command(description = "most profitable pairs over a window (30m, 24h, 7d; default 24h)")]
+    Top(String),
+}
+
+/// Answers bot commands while this replica is the leader. Telegram allows a
+/// single `getUpdates` consumer per bot, so standbys don't poll.
+pub async fn run(ctx: Arc<BotContext>) {
+    let notifier = Arc::clone(&ctx.notifier);
+    let bot = Bot::new(notifier.bot_token());
+    if let Err(e) = bot.set_my_commands(Command::bot_commands()).await {
+        println!(
+            "{} Failed to register bot commands: {}",
+            "[ERROR]".bright_red(),
+            e
+        );
+    }
+
+    loop {
+        while !notifier.is_leader() {
+            tokio::time::sleep(LEADER_POLL_INTERVAL).await;
+        }
+
+        let handler = dptree::entry()
+            .branch(
+                Update::filter_message()
+                    .filter_command::<Command>()
+                    .endpoint(handle),
+            )
+            .branch(Update::filter_callback_query().endpoint(handle_ack));
+        let mut dispatcher = Dispatcher::builder(bot.clone(), handler)
+            .dependencies(dptree::deps![Arc::clone(&ctx)])
+            .build();
+
+        let token = dispatcher.shutdown_token();
+        let watch = Arc::clone(&notifier);
+        tokio::spawn(async move {
+            while watch.is_leader() {
+                tokio::time::sleep(LEADER_POLL_INTERVAL).await;
+            }
+            if let Ok(shutdown) = token.shutdown() {
+                shutdown.await;
+            }
+        });
+
+        dispatcher.dispatch().await;
+    }
+}
+
+/// Claims an alert for whoever tapped its Ack button, then takes the button
+/// off every copy and tells each chat who is on it.
+async fn handle_ack(bot: Bot, query: CallbackQuery, ctx: Arc<BotContext>) -> ResponseResult<()> {
+    let Some(id) = query.data.as_deref().and_then(ack::parse_callback) else {
+        return Ok(());
+    };
+    let Some(chat) = query.message.as_ref().and_then(|m| {
+        let chat_id = m.chat.id.0.to_string();
+        ctx.notifier.chats().iter().find(|c| c.id == chat_id)
+    }) else {
+        return Ok(());
+    };
+    let name = match &query.from.username {
+        Some(username) => format!("@{}", username),
+        None => query.from.full_name(),
+    };
+
+    match ctx.acks.ack(id, &name) {
+        AckOutcome::Acked(thread) => {
+            bot.answer_callback_query(query.id).await?;
+            for (chat, message) in &thread.messages {
+                let recipient = match chat.parse::<i64>() {
+                    Ok(id) => Recipient::Id(ChatId(id)),
+                    Err(_) => Recipient::ChannelUsername(chat.clone()),
+                };
+                if let Err(e) = bot
+                    .edit_message_reply_markup(recipient, MessageId(*message))
+                    .await
+                {
+                    println!(
+                        "{} Failed to remove Ack button: {}",
+                        "[ERROR]".bright_red(),
+                        e
+                    );
+                }
+            }
+            for chat in ctx.notifier.chats() {
+                let acked_by = i18n::fill(
+                    chat.lang.catalog().acked_by,
+                    &[("name", &html::escape(&name))],
+                );
+                ctx.notifier
+                    .send_threaded(
+                        chat,
+                        &format!("✅ <b>{}</b>", acked_by),
+                        thread.root(&chat.id),
+                        None,
+                    )
+                    .await;
+            }
+        }
+        AckOutcome::AlreadyAcked(owner) => {
+            bot.answer_callback_query(query.id)
+                .text(i18n::fill(chat.lang.catalog().acked_by, &[("name", &owner)]))
+                .show_alert(true)
+                .await?;
+        }
+        AckOutcome::Unknown => {
+            bot.answer_callback_query(query.id)
+                .text("This alert has expired")
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn handle(
+    bot: Bot,
+    msg: Message,
+    command: Command,
+    ctx: Arc<BotContext>,
+) -> ResponseResult<()> {
+    let notifier = &ctx.notifier;
+    // Only chats the scanner alerts may query it
+    let chat_id = msg.chat.id.0.to_string();
+    if !notifier.chats().iter().any(|c| c.id == chat_id) {
+        return Ok(());
+    }
+
+    match command {
+        Command::Help => {
+            bot.send_message(msg.chat.id, Command::descriptions().to_string())
+                .await?;
+        }
+        Command::Test => {
+            let summary = crate::send_test_alerts(notifier)
+                .await
+                .iter()
+                .map(|(id, result)| match result {
+                    Ok(()) => format!("✅ {}", id),
+                    Err(e) => format!("❌ {}: {}", id, e),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            bot.send_message(msg.chat.id, format!("Test alert sent:\n{}", summary))
+                .await?;
+        }
+        Command::Price(pair) => {
+            let reply = match pair.trim().split_once('/') {
+                Some((base, quote)) => live_price(&ctx, base.trim(), quote.trim())
+                    .await
+                    .unwrap_or_else(|e| {
+                        format!("Failed to read prices: {}", html::escape(&e.to_string()))
+                    }),
+                None => "Usage: /price BASE/QUOTE, e.g. /price SOL/USDC".to_string(),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        Command::Config => {
+            bot.send_message(msg.chat.id, config_summary(&ctx))
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        Command::Stats(pair) => {
+            let reply = match pair.trim().split_once('/') {
+                Some((base, quote)) => pair_stats(&ctx, base.trim(), quote.trim())
+                    .unwrap_or_else(|e| format!("Failed to read stats: {}", e)),
+                None => "Usage: /stats BASE/QUOTE, e.g. /stats SOL/USDC".to_string(),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        Command::Top(window) => {
+            let reply = match parse_window(&window) {
+                Some(window) => top_pairs(&ctx, window)
+                    .unwrap_or_else(|e| format!("Failed to read history: {}", e)),
+                None => "Usage: /top [WINDOW], e.g. /top 24h, /top 7d or /top 30m".to_string(),
+            };
+            bot.send_message(msg.chat.id, reply)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+fn find_token(symbol: &str) -> Result<&'static TokenInfo> {
+    token::find_by_symbol(symbol).ok_or_else(|| {
+        anyhow!(
+            "unknown token {} (known: {})",
+            symbol,
+            token::known()
+                .map(|t| t.symbol)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    })
+}
+
+/// Reads `base`/`quote` pools on every venue right now, whether or not the
+/// pair is scanned, and reports each price and the widest gap.
+async fn live_price(ctx: &BotContext, base: &str, quote: &str) -> Result<String> {
+    let base = find_token(base)?;
+    let quote = find_token(quote)?;
+    if base.address == quote.address {
+        return Ok("Pick two different tokens.".to_string());
+    }
+
+    let mut lines = vec![format!("💱 <b>{}/{}</b>\n", base.symbol, quote.symbol)];
+    let mut quotes = Vec::new();
+    for dex in dex::venues() {
+        let venue = dex.name();
+        let pool = match dex.locate(&ctx.client, base, quote).await {
+            Ok(Some(pool)) => dex::read_pool(&ctx.client, pool.as_ref()),
+            Ok(None) => continue,
+            Err(e) => Err(e),
+        };
+        match pool {
+            Ok(pool) => {
+                let price = price::calculate_price(&pool.reserves);
+                let fee = pool.fee as f64 / 1_000_000.0;
+                lines.push(format!(
+                    "{}: <code>{} {}</code> · fee {}",
+                    venue,
+                    format_amount(price),
+                    quote.symbol,
+                    format_percent(fee)
+                ));
+                if price > 0.0 {
+                    quotes.push((venue, price, fee));
+                }
+            }
+            Err(e) => lines.push(format!("{}: {}", venue, html::escape(&e.to_string()))),
+        }
+    }
+
+    let by_price = |a: &&(&str, f64, f64), b: &&(&str, f64, f64)| a.1.total_cmp(&b.1);
+    if let (true, Some(&buy), Some(&sell)) = (
+        quotes.len() >= 2,
+        quotes.iter().min_by(by_price),
+        quotes.iter().max_by(by_price),
+    ) {
+        let spread = price::calculate_profit_margin(buy.1, sell.1);
+        lines.push(format!(
+            "\nBest Spread: <b>{}</b> buy {} → sell {} ({} after pool fees)",
+            format_percent(spread),
+            buy.0,
+            sell.0,
+            format_percent(spread - buy.2 - sell.2)
+        ));
+    }
+    lines.push(format!("<i>Read at {}</i>", format_time(Utc::now())));
+
+    Ok(lines.join("\n"))
+}
+
+/// What this instance is actually running with, after env overrides.
+fn config_summary(ctx: &BotContext) -> String {
+    let mut pairs = ctx.quotes.read().unwrap().keys().cloned().collect::<Vec<_>>();
+    pairs.sort();
+
+    [
+        "⚙️ <b>Configuration</b>\n".to_string(),
+        format!("Strategy: <b>{}</b>", ctx.strategy.unwrap_or("default")),
+        "Chain: Solana".to_string(),
+        format!(
+            "Venues: {}",
+            dex::venues().iter().map(|venue| venue.name()).collect::<Vec<_>>().join(", ")
+        ),
+        format!(
+            "Tokens: {}",
+            ctx.tokens.iter().map(|t| t.symbol).collect::<Vec<_>>().join(", ")
+        ),
+        format!(
+            "Min Profit: <b>{}</b> ({})",
+            format_percent(ctx.min_profit_threshold),
+            if ctx.pair_thresholds.is_empty() { "every pair" } else { "other pairs" }
+        ),
+        format!(
+            "Pair Thresholds: {}",
+            if ctx.pair_thresholds.is_empty() {
+                "none".to_string()
+            } else {
+                ctx.pair_thresholds
+                    .iter()
+                    .map(|(base, quote, threshold)| {
+                        format!("{}/{} {}", base.symbol, quote.symbol, format_percent(*threshold))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        ),
+        format!("Alert Cooldown: {}s", ctx.alert_cooldown.as_secs()),
+        format!(
+            "Pairs priced ({}): {}",
+            pairs.len(),
+            if pairs.is_empty() {
+                "none yet".to_string()
+            } else {
+                pairs.join(", ")
+            }
+        ),
+        String::new(),
+        "Execution: off (alert-only)".to_string(),
+        format!("Shared State: {}", if ctx.redis { "Redis" } else { "local" }),
+        format!(
+            "Leader: {}",
+            if ctx.notifier.is_leader() { "yes" } else { "no" }
+        ),
+        format!("Alert Chats: {}", ctx.notifier.chats().len()),
+    ]
+    .join("\n")
+}
+
+/// Parses a window like `30m`, `24h` or `7d`. Empty means 24 hours.
+fn parse_window(text: &str) -> Option<ChronoDuration> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Some(ChronoDuration::hours(24));
+    }
+    let (amount, unit) = text.split_at(text.len() - 1);
+    let amount = amount.parse::<i64>().ok().filter(|&n| n > 0)?;
+    match unit.to_ascii_lowercase().as_str() {
+        "m" => ChronoDuration::try_minutes(amount),
+        "h" => ChronoDuration::try_hours(amount),
+        "d" => ChronoDuration::try_days(amount),
+        _ => None,
+    }
+}
+
+/// Pairs ranked by cumulative theoretical profit over `window`.
+fn top_pairs(ctx: &BotContext, window: ChronoDuration) -> Result<String> {
+    let since = Utc::now() - window;
+    let ranking = ctx.storage.top_pairs(since, TOP_PAIRS)?;
+    if ranking.is_empty() {
+        return Ok(format!("No opportunities since {}.", format_time(since)));
+    }
+
+    let mut lines = vec![format!("🏆 <b>Top pairs</b> since {}\n", format_time(since))];
+    for (rank, entry) in ranking.iter().enumerate() {
+        lines.push(format!(
+            "{}. <b>{}</b>: {} · {} opportunities · max {}",
+            rank + 1,
+            html::escape(&entry.pair),
+            format_usd(entry.profit_usd),
+            entry.count,
+            format_percent(entry.max_spread)
+        ));
+    }
+    lines.push("\n<i>Theoretical profit, stable-quoted pairs only</i>".to_string());
+
+    Ok(lines.join("\n"))
+}
+
+/// Last 24h of stored opportunities on a pair, plus its latest prices and
+/// pool liquidity.
+fn pair_stats(ctx: &BotContext, base: &str, quote: &str) -> Result<String> {
+    let since = Utc::now() - ChronoDuration::hours(24);
+    let stats = ctx.storage.pair_stats(base, quote, since)?;
+
+    let quotes = ctx.quotes.read().unwrap();
+    let latest = quotes.iter().find(|(key, _)| {
+        [format!("{}/{}", base, quote), format!("{}/{}", quote, base)]
+            .iter()
+            .any(|pair| key.eq_ignore_ascii_case(pair))
+    });
+
+    if stats.count == 0 && latest.is_none() {
+        let mut known = quotes.keys().cloned().collect::<Vec<_>>();
+        known.sort();
+        return Ok(format!(
+            "No data for {}/{} yet. Pairs with prices: {}",
+            html::escape(base),
+            html::escape(quote),
+            if known.is_empty() {
+                "none yet".to_string()
+            } else {
+                known.join(", ")
+            }
+        ));
+    }
+
+    let mut lines = vec![format!(
+        "📈 <b>{}/{}</b> · last 24h\n",
+        html::escape(base),
+        html::escape(quote)
+    )];
+    lines.push(format!("Opportunities: <b>{}</b>", stats.count));
+    if let Some(widest) = &stats.widest {
+        lines.push(format!(
+            "Spread: min {} · avg {} · max {}",
+            format_percent(stats.min_spread),
+            format_percent(stats.avg_spread),
+            format_percent(stats.max_spread)
+        ));
+        lines.push(format!(
+            "Biggest: <b>{}</b> buy {} → sell {} at {}",
+            format_percent(widest.spread),
+            widest.buy_venue,
+            widest.sell_venue,
+            format_time(widest.timestamp)
+        ));
+        lines.push(format!(
+            "Theoretical Profit: <b>{}</b> (stable-quoted pairs only)",
+            format_usd(stats.profit_usd)
+        ));
+    }
+
+    if let Some((key, latest)) = latest {
+        let (base, quote) = key.split_once('/').unwrap_or((key, ""));
+        lines.push(String::new());
+        for venue in &latest.venues {
+            lines.push(format!(
+                "{}: <code>{} {}/{}</code> · liquidity {} {}",
+                venue.venue,
+                format_amount(venue.price),
+                quote,
+                base,
+                format_amount(venue.liquidity),
+                base
+            ));
+        }
+        lines.push(format!("<i>Prices as of {}</i>", format_time(latest.read_at)));
+    }
+
+    Ok(lines.join("\n"))
+}
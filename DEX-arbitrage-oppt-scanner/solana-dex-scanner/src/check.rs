@@ -0,0 +1,207 @@
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::env;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::dex;
+use crate::i18n::Lang;
+use crate::models::token::TokenInfo;
+use crate::storage::shared::SharedState;
+use crate::utils::format::Locale;
+use crate::utils::{secrets, telegram};
+
+/// Counts failed checks so every problem is reported in one run instead of
+/// stopping at the first.
+#[derive(Default)]
+struct Report {
+    failures: usize,
+}
+
+impl Report {
+    fn check<T>(&mut self, name: &str, result: Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                println!("{} {}", "✓".bright_green(), name);
+                Some(value)
+            }
+            Err(e) => {
+                self.failures += 1;
+                println!("{} {}: {:#}", "✗".bright_red(), name, e);
+                None
+            }
+        }
+    }
+}
+
+/// Parses env var `name` if it is set and non-empty.
+fn parse_var<T>(name: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match env::var(name).ok().filter(|s| !s.is_empty()) {
+        Some(value) => value
+            .parse::<T>()
+            .map(Some)
+            .map_err(|e| anyhow!("{:?} is not valid: {}", value, e)),
+        None => Ok(None),
+    }
+}
+
+/// Validates every setting the scanner reads, the token mints and the DEX
+/// programs. Errors if anything is wrong, so it can gate a deploy.
+pub async fn run(tokens: &[&'static TokenInfo]) -> Result<()> {
+    let mut report = Report::default();
+    println!("{} Checking configuration\n", "[INFO]".bright_green());
+
+    let rpc_url = report.check("SOLANA_RPC_URL", secrets::require("SOLANA_RPC_URL").await);
+    let bot_token = report.check(
+        "TELEGRAM_BOT_TOKEN",
+        secrets::require("TELEGRAM_BOT_TOKEN").await,
+    );
+    let default_lang = report
+        .check("ALERT_LANGUAGE", parse_var::<Lang>("ALERT_LANGUAGE"))
+        .flatten()
+        .unwrap_or_default();
+    report.check(
+        "TELEGRAM_CHAT_ID",
+        env::var("TELEGRAM_CHAT_ID")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .context("TELEGRAM_CHAT_ID is required")
+            .and_then(|value| {
+                if telegram::parse_chats(&value, default_lang)?.is_empty() {
+                    bail!("no chat ids listed");
+                }
+                Ok(())
+            }),
+    );
+    report.check("MIN_PROFIT_THRESHOLD", parse_var::<f64>("MIN_PROFIT_THRESHOLD"));
+    report.check("ALERT_COOLDOWN_SECS", parse_var::<u64>("ALERT_COOLDOWN_SECS"));
+    report.check(
+        "PRIORITY_FEE_MICROLAMPORTS",
+        parse_var::<u64>("PRIORITY_FEE_MICROLAMPORTS"),
+    );
+    report.check("FLASH_LOAN_FEE", parse_var::<f64>("FLASH_LOAN_FEE"));
+    report.check("QUARANTINE_AFTER_FAILURES", parse_var::<u32>("QUARANTINE_AFTER_FAILURES"));
+    report.check("QUARANTINE_MINS", parse_var::<i64>("QUARANTINE_MINS"));
+    report.check("SOLANA_COMMITMENT", parse_var::<CommitmentConfig>("SOLANA_COMMITMENT"));
+    report.check(
+        "LEADER_LEASE_SECS",
+        parse_var::<u64>("LEADER_LEASE_SECS").and_then(|lease| {
+            ensure!(lease != Some(0), "must be at least 1");
+            Ok(lease)
+        }),
+    );
+    report.check(
+        "WATCHED_WALLETS",
+        env::var("WATCHED_WALLETS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .try_for_each(|s| {
+                s.parse::<Pubkey>()
+                    .map(|_| ())
+                    .map_err(|e| anyhow!("{:?} is not a pubkey: {}", s, e))
+            }),
+    );
+    report.check(
+        "FEE_PAYER_WALLETS",
+        env::var("FEE_PAYER_WALLETS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .try_for_each(|s| {
+                s.parse::<Pubkey>()
+                    .map(|_| ())
+                    .map_err(|e| anyhow!("{:?} is not a pubkey: {}", s, e))
+            }),
+    );
+    report.check("FEE_PAYER_FLOOR_SOL", parse_var::<f64>("FEE_PAYER_FLOOR_SOL"));
+    report.check(
+        "TIMEZONE, DATE_FORMAT, separators, CURRENCY_SYMBOL",
+        Locale::from_env(),
+    );
+
+    report.check("BIRDEYE_API_KEY", secrets::load("BIRDEYE_API_KEY").await);
+    let redis_url = report.check("REDIS_URL", secrets::load("REDIS_URL").await).flatten();
+    if let Some(url) = &redis_url {
+        let prefix = env::var("REDIS_KEY_PREFIX").unwrap_or_else(|_| "arb-scanner".to_string());
+        report.check("Redis connection", SharedState::redis(url, &prefix).await);
+    }
+    let leader_election = env::var("LEADER_ELECTION")
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false);
+    if leader_election && redis_url.is_none() {
+        report.check::<()>("LEADER_ELECTION", Err(anyhow!("requires REDIS_URL")));
+    }
+
+    if let Some(token) = bot_token {
+        report.check("Telegram bot", telegram::get_me(&token).await);
+    }
+
+    if let Some(url) = rpc_url {
+        let client = RpcClient::new(url);
+        let connected = report.check(
+            "RPC connection",
+            client
+                .get_version()
+                .map(|version| println!("  node runs solana-core {}", version.solana_core))
+                .context("SOLANA_RPC_URL is unreachable"),
+        );
+        if connected.is_some() {
+            check_accounts(&mut report, &client, tokens);
+        }
+    }
+
+    println!();
+    if report.failures > 0 {
+        bail!("{} configuration problem(s) found", report.failures);
+    }
+    println!("{} Configuration OK", "[INFO]".bright_green());
+    Ok(())
+}
+
+fn check_accounts(report: &mut Report, client: &RpcClient, tokens: &[&'static TokenInfo]) {
+    for token in tokens {
+        let result = client
+            .get_token_supply(&token.address)
+            .context("not a token mint on this cluster")
+            .and_then(|supply| {
+                if supply.decimals != token.decimals {
+                    bail!(
+                        "mint has {} decimals, token list says {}",
+                        supply.decimals,
+                        token.decimals
+                    );
+                }
+                Ok(())
+            });
+        report.check(&format!("{} {}", token.symbol, token.address), result);
+    }
+
+    let programs = dex::programs();
+    for (name, program_id) in [
+        ("Raydium", programs.raydium),
+        ("Raydium CP-Swap", programs.raydium_cp_swap),
+        ("Orca Whirlpool", programs.orca),
+        ("Saber", programs.saber),
+    ] {
+        let result = client
+            .get_account(&program_id)
+            .context("program account not found")
+            .and_then(|account| {
+                if !account.executable {
+                    bail!("account is not an executable program");
+                }
+                Ok(())
+            });
+        report.check(&format!("{} program {}", name, program_id), result);
+    }
+}
@@ -0,0 +1,160 @@
+//! The optional TOML config file: extra tokens, DEX program IDs, profit
+//! thresholds, the RPC endpoint and Telegram chats, for what's unwieldy to
+//! put in env vars. Env vars still take precedence over it. See
+//! `config.example.toml`.
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::env;
+use std::path::Path;
+
+use crate::dex::{self, Programs};
+use crate::models::token::{self, TokenInfo};
+
+/// Read when `--config` isn't given, if it exists.
+pub const DEFAULT_PATH: &str = "config.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub rpc: Rpc,
+    pub telegram: Telegram,
+    /// Tokens scanned on top of the built-in ones.
+    pub tokens: Vec<Token>,
+    pub programs: ProgramIds,
+    pub min_profit: MinProfit,
+    /// `QUOTE_PRIORITY`, as a list.
+    pub quote_priority: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rpc {
+    /// `SOLANA_RPC_URL`
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Telegram {
+    /// `TELEGRAM_CHAT_ID`
+    pub chat_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Token {
+    pub address: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// Program IDs replacing the mainnet ones, see [`Programs`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProgramIds {
+    pub raydium: Option<String>,
+    pub raydium_cp_swap: Option<String>,
+    pub orca: Option<String>,
+    pub orca_config: Option<String>,
+    pub saber: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MinProfit {
+    /// `MIN_PROFIT_THRESHOLD`
+    pub margin: Option<f64>,
+    /// Thresholds for single pairs, keyed `BASE/QUOTE`.
+    pub pairs: BTreeMap<String, f64>,
+}
+
+/// Reads the config file at `path`, or at [`DEFAULT_PATH`] if `path` is
+/// `None` and it exists. Empty if there's no file to read.
+pub fn load(path: Option<&Path>) -> Result<ConfigFile> {
+    let path = match path {
+        Some(path) => path,
+        None if Path::new(DEFAULT_PATH).exists() => Path::new(DEFAULT_PATH),
+        None => return Ok(ConfigFile::default()),
+    };
+    let contents = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    toml::from_str(&contents).with_context(|| format!("Invalid config file {}", path.display()))
+}
+
+fn pubkey(value: &str, what: &str) -> Result<Pubkey> {
+    value.parse().map_err(|e| anyhow!("Invalid {} {:?}: {}", what, value, e))
+}
+
+impl ConfigFile {
+    /// Sets the env vars the file has values for, unless they're set
+    /// already. Secrets count as set when their `_FILE` or `_VAULT`
+    /// variant is.
+    pub fn apply_env(&self) {
+        let settings = [
+            ("SOLANA_RPC_URL", self.rpc.url.clone()),
+            ("TELEGRAM_CHAT_ID", self.telegram.chat_id.clone()),
+            ("MIN_PROFIT_THRESHOLD", self.min_profit.margin.map(|m| m.to_string())),
+            ("QUOTE_PRIORITY", self.quote_priority.as_ref().map(|symbols| symbols.join(","))),
+        ];
+        for (name, value) in settings {
+            let set = [name.to_string(), format!("{}_FILE", name), format!("{}_VAULT", name)]
+                .iter()
+                .any(|name| env::var(name).is_ok_and(|v| !v.is_empty()));
+            if let (Some(value), false) = (value, set) {
+                env::set_var(name, value);
+            }
+        }
+    }
+
+    /// Registers the file's tokens and sets its program IDs.
+    pub fn apply(&self) -> Result<()> {
+        let mut tokens = Vec::new();
+        for t in &self.tokens {
+            let address = pubkey(&t.address, &format!("{} mint", t.symbol))?;
+            match token::known().find(|known| known.symbol.eq_ignore_ascii_case(&t.symbol)) {
+                Some(known) if known.address == address => continue,
+                Some(known) => bail!("Token {} is already {}", t.symbol, known.address),
+                None => tokens.push(TokenInfo {
+                    address,
+                    symbol: String::leak(t.symbol.clone()),
+                    decimals: t.decimals,
+                }),
+            }
+        }
+        token::register(tokens);
+
+        let defaults = Programs::default();
+        let program = |id: &Option<String>, default: Pubkey, name: &str| match id {
+            Some(id) => pubkey(id, &format!("{} program ID", name)),
+            None => Ok(default),
+        };
+        dex::init_programs(Programs {
+            raydium: program(&self.programs.raydium, defaults.raydium, "Raydium")?,
+            raydium_cp_swap: program(&self.programs.raydium_cp_swap, defaults.raydium_cp_swap, "Raydium CP-Swap")?,
+            orca: program(&self.programs.orca, defaults.orca, "Orca")?,
+            orca_config: program(&self.programs.orca_config, defaults.orca_config, "Orca whirlpools config")?,
+            saber: program(&self.programs.saber, defaults.saber, "Saber")?,
+        });
+        Ok(())
+    }
+
+    /// The pairs with their own threshold, by token. Call after
+    /// [`apply`](Self::apply), so configured tokens are known.
+    pub fn pair_thresholds(&self) -> Result<Vec<(&'static TokenInfo, &'static TokenInfo, f64)>> {
+        let mut thresholds = Vec::new();
+        for (pair, &margin) in &self.min_profit.pairs {
+            let Some((base, quote)) = pair.split_once('/') else {
+                bail!("Invalid pair {:?} in min_profit.pairs (expected BASE/QUOTE)", pair);
+            };
+            let find = |symbol: &str| {
+                token::find_by_symbol(symbol.trim())
+                    .with_context(|| format!("Unknown token {:?} in min_profit.pairs", symbol.trim()))
+            };
+            ensure!((0.0..1.0).contains(&margin), "Invalid min_profit threshold {} for {}", margin, pair);
+            thresholds.push((find(base)?, find(quote)?, margin));
+        }
+        Ok(thresholds)
+    }
+}
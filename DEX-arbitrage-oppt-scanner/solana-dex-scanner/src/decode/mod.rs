@@ -0,0 +1,163 @@
+pub mod orca;
+pub mod raydium;
+
+use solana_sdk::{bs58, pubkey::Pubkey};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, UiInstruction, UiLoadedAddresses,
+};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::dex;
+use crate::models::token::get_token_info;
+
+/// One swap through one pool, normalised to what went in and came out.
+/// Amounts come from the user token accounts' balance changes.
+#[derive(Debug, Clone)]
+pub struct SwapLeg {
+    pub venue: &'static str,
+    pub mint_in: Pubkey,
+    pub mint_out: Pubkey,
+    pub amount_in: f64,
+    pub amount_out: f64,
+}
+
+/// All swap legs of one transaction, in execution order.
+#[derive(Debug, Clone)]
+pub struct DecodedTx {
+    pub slot: u64,
+    pub legs: Vec<SwapLeg>,
+}
+
+/// User source/destination token accounts of a swap instruction, as indices
+/// into the transaction's account keys.
+pub struct SwapAccounts {
+    pub source: u8,
+    pub destination: u8,
+}
+
+impl DecodedTx {
+    /// Two or more swaps that start and end in the same mint.
+    pub fn is_arb(&self) -> bool {
+        self.legs.len() >= 2
+            && self.legs.first().map(|l| l.mint_in) == self.legs.last().map(|l| l.mint_out)
+    }
+
+    /// Amount-free route used for aggregation, e.g. `SOL→USDC on Raydium, USDC→SOL on Orca`
+    pub fn route(&self) -> String {
+        self.legs
+            .iter()
+            .map(|l| format!("{}→{} on {}", mint_label(&l.mint_in), mint_label(&l.mint_out), l.venue))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Route with amounts, e.g. `1.5 SOL→210.3 USDC on Raydium, ...`
+    pub fn describe(&self) -> String {
+        self.legs
+            .iter()
+            .map(|l| {
+                format!(
+                    "{:.4} {}→{:.4} {} on {}",
+                    l.amount_in,
+                    mint_label(&l.mint_in),
+                    l.amount_out,
+                    mint_label(&l.mint_out),
+                    l.venue
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Symbol for known mints, shortened address otherwise.
+pub fn mint_label(mint: &Pubkey) -> String {
+    match get_token_info(mint) {
+        Some(token) => token.symbol.to_string(),
+        None => {
+            let s = mint.to_string();
+            format!("{}…{}", &s[..4], &s[s.len() - 4..])
+        }
+    }
+}
+
+fn decode_instruction(program_id: &Pubkey, accounts: &[u8], data: &[u8]) -> Option<(&'static str, SwapAccounts)> {
+    match program_id.to_string().as_str() {
+        dex::raydium::PROGRAM_ID => raydium::decode_swap(accounts, data).map(|a| ("Raydium", a)),
+        dex::orca::WHIRLPOOL_PROGRAM_ID => orca::decode_whirlpool_swap(accounts, data).map(|a| ("Orca", a)),
+        dex::orca::PROGRAM_ID => orca::decode_token_swap(accounts, data).map(|a| ("Orca", a)),
+        _ => None,
+    }
+}
+
+/// Decodes every Raydium/Orca swap in `tx`, including ones invoked through
+/// aggregators via CPI. Returns `None` for failed or undecodable transactions.
+pub fn decode_transaction(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Option<DecodedTx> {
+    let meta = tx.transaction.meta.as_ref()?;
+    if meta.err.is_some() {
+        return None;
+    }
+    let versioned = tx.transaction.transaction.decode()?;
+
+    // Static keys followed by keys loaded from address lookup tables
+    let mut keys = versioned.message.static_account_keys().to_vec();
+    let loaded: Option<UiLoadedAddresses> = meta.loaded_addresses.clone().into();
+    if let Some(loaded) = loaded {
+        for key in loaded.writable.iter().chain(loaded.readonly.iter()) {
+            keys.push(Pubkey::from_str(key).ok()?);
+        }
+    }
+
+    // Top-level instructions, each followed by its inner (CPI) instructions
+    let inner: Option<Vec<_>> = meta.inner_instructions.clone().into();
+    let inner = inner.unwrap_or_default();
+    let mut instructions: Vec<(u8, Vec<u8>, Vec<u8>)> = Vec::new();
+    for (i, ix) in versioned.message.instructions().iter().enumerate() {
+        instructions.push((ix.program_id_index, ix.accounts.clone(), ix.data.clone()));
+        for set in inner.iter().filter(|set| set.index as usize == i) {
+            for inner_ix in &set.instructions {
+                if let UiInstruction::Compiled(compiled) = inner_ix {
+                    let data = bs58::decode(&compiled.data).into_vec().ok()?;
+                    instructions.push((compiled.program_id_index, compiled.accounts.clone(), data));
+                }
+            }
+        }
+    }
+
+    // Account index -> (mint, pre amount, post amount)
+    let mut balances: HashMap<u8, (Pubkey, f64, f64)> = HashMap::new();
+    let pre: Option<Vec<_>> = meta.pre_token_balances.clone().into();
+    let post: Option<Vec<_>> = meta.post_token_balances.clone().into();
+    for (list, is_post) in [(pre, false), (post, true)] {
+        for balance in list.unwrap_or_default() {
+            let mint = Pubkey::from_str(&balance.mint).ok()?;
+            let amount = balance.ui_token_amount.ui_amount.unwrap_or_default();
+            let entry = balances.entry(balance.account_index).or_insert((mint, 0.0, 0.0));
+            if is_post {
+                entry.2 = amount;
+            } else {
+                entry.1 = amount;
+            }
+        }
+    }
+
+    let legs = instructions
+        .iter()
+        .filter_map(|(program_index, accounts, data)| {
+            let program_id = keys.get(*program_index as usize)?;
+            let (venue, swap) = decode_instruction(program_id, accounts, data)?;
+            let (mint_in, pre_in, post_in) = *balances.get(&swap.source)?;
+            let (mint_out, pre_out, post_out) = *balances.get(&swap.destination)?;
+            Some(SwapLeg {
+                venue,
+                mint_in,
+                mint_out,
+                amount_in: pre_in - post_in,
+                amount_out: post_out - pre_out,
+            })
+        })
+        .collect();
+
+    Some(DecodedTx { slot: tx.slot, legs })
+}
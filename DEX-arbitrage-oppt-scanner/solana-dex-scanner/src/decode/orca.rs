@@ -0,0 +1,35 @@
+use super::SwapAccounts;
+
+// Anchor discriminator: sha256("global:swap")[..8]
+const WHIRLPOOL_SWAP: [u8; 8] = [0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8];
+const TOKEN_SWAP_SWAP: u8 = 1;
+
+/// Whirlpool `swap`: data is `amount, other_amount_threshold, sqrt_price_limit,
+/// amount_specified_is_input, a_to_b`; accounts 3 and 5 are the user's A and B
+/// token accounts.
+pub fn decode_whirlpool_swap(accounts: &[u8], data: &[u8]) -> Option<SwapAccounts> {
+    if data.len() < 42 || data[..8] != WHIRLPOOL_SWAP || accounts.len() < 11 {
+        return None;
+    }
+    let a_to_b = data[41] != 0;
+
+    let (source, destination) = if a_to_b {
+        (accounts[3], accounts[5])
+    } else {
+        (accounts[5], accounts[3])
+    };
+    Some(SwapAccounts { source, destination })
+}
+
+/// Legacy token-swap `Swap`: accounts 3 and 6 are the user's source and
+/// destination token accounts.
+pub fn decode_token_swap(accounts: &[u8], data: &[u8]) -> Option<SwapAccounts> {
+    if data.first() != Some(&TOKEN_SWAP_SWAP) || accounts.len() < 10 {
+        return None;
+    }
+
+    Some(SwapAccounts {
+        source: accounts[3],
+        destination: accounts[6],
+    })
+}
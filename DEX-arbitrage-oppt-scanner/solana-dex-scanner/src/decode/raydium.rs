@@ -0,0 +1,18 @@
+use super::SwapAccounts;
+
+const SWAP_BASE_IN: u8 = 9;
+const SWAP_BASE_OUT: u8 = 11;
+
+/// AMM v4 `SwapBaseIn`/`SwapBaseOut`. The account list is 17 or 18 long
+/// depending on whether target orders are passed, but always ends with
+/// `[user_source, user_destination, user_owner]`.
+pub fn decode_swap(accounts: &[u8], data: &[u8]) -> Option<SwapAccounts> {
+    if !matches!(data.first(), Some(&SWAP_BASE_IN) | Some(&SWAP_BASE_OUT)) || accounts.len() < 17 {
+        return None;
+    }
+
+    Some(SwapAccounts {
+        source: accounts[accounts.len() - 3],
+        destination: accounts[accounts.len() - 2],
+    })
+}
@@ -1,2 +1,216 @@
 pub mod orca;
-pub mod raydium; 
\ No newline at end of file
+pub mod raydium;
+pub mod saber;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use once_cell::sync::OnceCell;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+
+use crate::models::pool::PoolInfo;
+use crate::models::token::TokenInfo;
+
+// An SPL token account's amount follows its mint and owner
+const TOKEN_AMOUNT_OFFSET: usize = 64;
+const TOKEN_ACCOUNT_SIZE: usize = 165;
+
+static PROGRAMS: OnceCell<Programs> = OnceCell::new();
+
+/// The programs pools are looked up under: the mainnet deployments unless
+/// the config file names others, e.g. on devnet.
+#[derive(Debug, Clone)]
+pub struct Programs {
+    pub raydium: Pubkey,
+    pub raydium_cp_swap: Pubkey,
+    /// Orca's Whirlpool program, and the `WhirlpoolsConfig` its pools are
+    /// derived under.
+    pub orca: Pubkey,
+    pub orca_config: Pubkey,
+    pub saber: Pubkey,
+}
+
+impl Default for Programs {
+    fn default() -> Self {
+        Programs {
+            raydium: raydium::PROGRAM_ID.parse().unwrap(),
+            raydium_cp_swap: raydium::CP_SWAP_PROGRAM_ID.parse().unwrap(),
+            orca: orca::WHIRLPOOL_PROGRAM_ID.parse().unwrap(),
+            orca_config: orca::WHIRLPOOLS_CONFIG.parse().unwrap(),
+            saber: saber::PROGRAM_ID.parse().unwrap(),
+        }
+    }
+}
+
+pub fn init_programs(programs: Programs) {
+    let _ = PROGRAMS.set(programs);
+}
+
+/// The programs set at startup, the mainnet ones if none were.
+pub fn programs() -> &'static Programs {
+    PROGRAMS.get_or_init(Programs::default)
+}
+
+/// An account's data, read field by field at the offsets a layout puts
+/// them. Only the fields an adapter needs are read, and bytes past the
+/// layout are ignored, so a program appending fields to its accounts
+/// doesn't break the read.
+pub(crate) struct AccountData<'a>(&'a [u8]);
+
+impl<'a> AccountData<'a> {
+    /// `data` to read with a layout `size` bytes long, which every offset
+    /// read falls within.
+    pub fn new(data: &'a [u8], size: usize, what: &str) -> Result<Self> {
+        if data.len() < size {
+            return Err(anyhow!("{} data too short ({} bytes, layout has {})", what, data.len(), size));
+        }
+        Ok(AccountData(data))
+    }
+
+    pub fn bytes<const N: usize>(&self, offset: usize) -> [u8; N] {
+        self.0[offset..offset + N].try_into().unwrap()
+    }
+
+    pub fn u8(&self, offset: usize) -> u8 {
+        self.0[offset]
+    }
+
+    pub fn bool(&self, offset: usize) -> bool {
+        self.0[offset] != 0
+    }
+
+    pub fn u16(&self, offset: usize) -> u16 {
+        u16::from_le_bytes(self.bytes(offset))
+    }
+
+    pub fn u64(&self, offset: usize) -> u64 {
+        u64::from_le_bytes(self.bytes(offset))
+    }
+
+    pub fn u128(&self, offset: usize) -> u128 {
+        u128::from_le_bytes(self.bytes(offset))
+    }
+
+    pub fn i64(&self, offset: usize) -> i64 {
+        i64::from_le_bytes(self.bytes(offset))
+    }
+
+    pub fn pubkey(&self, offset: usize) -> Pubkey {
+        Pubkey::new_from_array(self.bytes(offset))
+    }
+}
+
+/// The amount held by an SPL token account, e.g. a pool's vault.
+pub(crate) fn token_amount(data: &[u8]) -> Result<u64> {
+    Ok(AccountData::new(data, TOKEN_ACCOUNT_SIZE, "Token account")?.u64(TOKEN_AMOUNT_OFFSET))
+}
+
+/// A pool located for a pair: the accounts reading it takes and how their
+/// data decodes, so the pair's pools on every venue can be read together.
+pub trait LocatedPool: Send + Sync {
+    fn accounts(&self) -> Vec<Pubkey>;
+
+    /// Decodes `data`, the accounts' data in the order of `accounts`.
+    fn decode(&self, data: &[&[u8]]) -> Result<PoolInfo>;
+}
+
+/// A venue pools are read from. Adding one is implementing this and
+/// listing it in [`venues`].
+#[async_trait]
+pub trait Dex: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// The venue's pool for `token_a`/`token_b`, read in that order, or
+    /// `None` if it doesn't list the pair.
+    async fn locate(
+        &self,
+        client: &RpcClient,
+        token_a: &TokenInfo,
+        token_b: &TokenInfo,
+    ) -> Result<Option<Arc<dyn LocatedPool>>>;
+}
+
+/// Every venue scanned.
+pub fn venues() -> Vec<Box<dyn Dex>> {
+    vec![Box::new(raydium::Raydium), Box::new(orca::Orca), Box::new(saber::Saber)]
+}
+
+/// A pair's pools on each venue, read at the same slot.
+#[derive(Debug)]
+pub struct PoolRead {
+    pub slot: u64,
+    pub pools: Vec<(&'static str, PoolInfo)>,
+}
+
+/// A pair's located pools, each with the name of its venue.
+pub type LocatedPools = Vec<(&'static str, Arc<dyn LocatedPool>)>;
+
+/// Reads all the pools in one `getMultipleAccounts` call, so they come from the
+/// same slot and a price gap between them can't be one pool having traded
+/// in a slot the other wasn't read at. `min_slot` rejects a node behind a
+/// read already made, e.g. another node behind the same load balancer. A
+/// pool that can't be decoded, e.g. a paused one, is left out, but a read
+/// leaving fewer than two to compare fails.
+pub fn read_pools(
+    client: &RpcClient,
+    pools: &LocatedPools,
+    commitment: CommitmentConfig,
+    min_slot: u64,
+) -> Result<PoolRead> {
+    let accounts = pools.iter().map(|(_, pool)| pool.accounts()).collect::<Vec<_>>();
+    let response = client
+        .get_multiple_accounts_with_config(
+            &accounts.concat(),
+            RpcAccountInfoConfig {
+                commitment: Some(commitment),
+                min_context_slot: Some(min_slot),
+                ..RpcAccountInfoConfig::default()
+            },
+        )
+        .map_err(|e| anyhow!("Failed to get pool accounts: {}", e))?;
+    let slot = response.context.slot;
+    if slot < min_slot {
+        return Err(anyhow!("RPC node answered at slot {}, behind slot {}", slot, min_slot));
+    }
+    let expected = accounts.iter().map(Vec::len).sum::<usize>();
+    if response.value.len() != expected {
+        return Err(anyhow!("Expected {} accounts, got {}", expected, response.value.len()));
+    }
+
+    let mut read = response.value.into_iter();
+    let mut decoded = Vec::new();
+    let mut failures = Vec::new();
+    for ((venue, pool), addresses) in pools.iter().zip(&accounts) {
+        let data = addresses
+            .iter()
+            .zip(read.by_ref())
+            .map(|(address, account)| account.ok_or_else(|| anyhow!("{} account {} not found", venue, address)))
+            .collect::<Result<Vec<_>>>();
+        let info = data.and_then(|data| pool.decode(&data.iter().map(|account| &account.data[..]).collect::<Vec<_>>()));
+        match info {
+            Ok(info) => decoded.push((*venue, info)),
+            Err(e) => failures.push(format!("{}: {}", venue, e)),
+        }
+    }
+    if decoded.len() < 2 {
+        return Err(anyhow!("Fewer than two pools to compare ({})", failures.join(", ")));
+    }
+    Ok(PoolRead { slot, pools: decoded })
+}
+
+/// Reads one pool on its own, e.g. for an on-demand quote.
+pub fn read_pool(client: &RpcClient, pool: &dyn LocatedPool) -> Result<PoolInfo> {
+    let addresses = pool.accounts();
+    let accounts = client
+        .get_multiple_accounts(&addresses)
+        .map_err(|e| anyhow!("Failed to get pool accounts: {}", e))?;
+    let data = addresses
+        .iter()
+        .zip(accounts)
+        .map(|(address, account)| account.ok_or_else(|| anyhow!("Account {} not found", address)))
+        .collect::<Result<Vec<_>>>()?;
+    pool.decode(&data.iter().map(|account| &account.data[..]).collect::<Vec<_>>())
+}
@@ -1,91 +1,221 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
-use borsh::BorshDeserialize;
 use colored::*;
-use crate::models::pool::{PoolInfo, PoolReserves, OrcaPoolLayout};
+use std::sync::Arc;
+use crate::dex::{AccountData, Dex, LocatedPool};
+use crate::models::pool::{Curve, PoolInfo, PoolReserves};
+use crate::models::token::TokenInfo;
+use crate::utils::format::{format_amount, format_percent};
 
+/// Orca's legacy token-swap program, still seen in watched wallets' swaps.
 pub const PROGRAM_ID: &str = "9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP";
-pub const POOL_LAYOUT_SIZE: usize = 1440;
-pub const POOL_LAYOUT_VERSION: u8 = 1;
+pub const WHIRLPOOL_PROGRAM_ID: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+/// The `WhirlpoolsConfig` Orca's own pools are created under.
+pub const WHIRLPOOLS_CONFIG: &str = "2LecshUwdy9xi7meFgHtFJQNSKk4KdTrcpvaB56dP2NQ";
+pub const WHIRLPOOL_LAYOUT_SIZE: usize = 653;
+/// The tick spacings Orca creates pools at, each its own fee tier.
+pub const TICK_SPACINGS: [u16; 4] = [1, 8, 64, 128];
 pub const POOL_SEED_PREFIX: &[u8] = b"whirlpool";
 
-pub async fn find_pool(
-    client: &RpcClient,
-    token_a: Pubkey,
-    token_b: Pubkey,
-) -> Result<Pubkey> {
-    // Sort tokens to ensure consistent pool address
-    let (token_a, token_b) = if token_a < token_b {
-        (token_a, token_b)
-    } else {
-        (token_b, token_a)
-    };
-
-    // Generate pool address using PDA
-    let (pool_address, _) = Pubkey::find_program_address(
+// Anchor's account discriminator for `Whirlpool`
+const WHIRLPOOL_DISCRIMINATOR: [u8; 8] = [63, 149, 209, 12, 225, 128, 99, 9];
+
+// Offsets into the Whirlpool account of the fields read
+const TICK_SPACING: usize = 41;
+const FEE_RATE: usize = 45;
+const LIQUIDITY: usize = 49;
+const SQRT_PRICE: usize = 65;
+
+/// A pair's Whirlpool at one tick spacing, with what decoding its account
+/// needs.
+#[derive(Debug, Clone, Copy)]
+pub struct WhirlpoolRef {
+    pub address: Pubkey,
+    pub tick_spacing: u16,
+    /// Whether the pair's token A is the pool's token A, the lower mint.
+    pub a_is_pool_a: bool,
+    pub decimals_a: u8,
+    pub decimals_b: u8,
+}
+
+/// Address of the Whirlpool of `mint_a` and `mint_b`, in either order, at
+/// `tick_spacing` under `config`.
+pub fn whirlpool_address(config: &Pubkey, mint_a: Pubkey, mint_b: Pubkey, tick_spacing: u16) -> Pubkey {
+    let (mint_a, mint_b) = if mint_a < mint_b { (mint_a, mint_b) } else { (mint_b, mint_a) };
+    let (address, _) = Pubkey::find_program_address(
         &[
             POOL_SEED_PREFIX,
-            token_a.as_ref(),
-            token_b.as_ref(),
+            config.as_ref(),
+            mint_a.as_ref(),
+            mint_b.as_ref(),
+            &tick_spacing.to_le_bytes(),
         ],
-        &PROGRAM_ID.parse().unwrap(),
+        &super::programs().orca,
     );
+    address
+}
 
-    println!("{} Looking for Orca pool: {}", "[DEBUG]".bright_cyan(), pool_address);
-    println!("{} Token A: {}", "[DEBUG]".bright_cyan(), token_a);
-    println!("{} Token B: {}", "[DEBUG]".bright_cyan(), token_b);
+/// The pair's Whirlpools under the configured `WhirlpoolsConfig`, one per
+/// fee tier it has a pool at, each read once.
+pub fn whirlpools(
+    client: &RpcClient,
+    token_a: &TokenInfo,
+    token_b: &TokenInfo,
+) -> Result<Vec<(WhirlpoolRef, PoolInfo)>> {
+    let config = &super::programs().orca_config;
+    let candidates = TICK_SPACINGS
+        .iter()
+        .map(|&tick_spacing| WhirlpoolRef {
+            address: whirlpool_address(config, token_a.address, token_b.address, tick_spacing),
+            tick_spacing,
+            a_is_pool_a: token_a.address < token_b.address,
+            decimals_a: token_a.decimals,
+            decimals_b: token_b.decimals,
+        })
+        .collect::<Vec<_>>();
+    let accounts = client
+        .get_multiple_accounts(&candidates.iter().map(|pool| pool.address).collect::<Vec<_>>())
+        .map_err(|e| anyhow!("Failed to get Orca whirlpools: {}", e))?;
 
-    // Verify pool exists
-    match client.get_account(&pool_address) {
-        Ok(_) => {
-            println!("{} Found Orca pool at {}", "[SUCCESS]".bright_green(), pool_address);
-            Ok(pool_address)
-        }
-        Err(e) => {
-            println!("{} Failed to find Orca pool: {}", "[ERROR]".bright_red(), e);
-            Err(anyhow::anyhow!("Pool not found: {}", e))
+    let mut pools = Vec::new();
+    for (pool, account) in candidates.into_iter().zip(accounts) {
+        if let Some(account) = account {
+            pools.push((pool, decode(&account.data, &pool)?));
         }
     }
+    Ok(pools)
 }
 
-pub async fn get_pool_data(
-    client: &RpcClient,
-    token_a: Pubkey,
-    token_b: Pubkey,
-) -> Result<PoolInfo> {
-    let pool_address = find_pool(client, token_a, token_b).await?;
-    let account = client.get_account(&pool_address)
-        .map_err(|e| anyhow::anyhow!("Failed to get pool account: {}", e))?;
-
-    if account.data.len() != POOL_LAYOUT_SIZE {
-        return Err(anyhow::anyhow!("Invalid pool data size"));
+/// Decodes a Whirlpool account read for `pool`. Its liquidity at the
+/// current price is turned into the constant-product reserves that price
+/// swaps the same way, which holds until a swap crosses into the next
+/// initialized tick.
+pub fn decode(data: &[u8], pool: &WhirlpoolRef) -> Result<PoolInfo> {
+    let data = AccountData::new(data, WHIRLPOOL_LAYOUT_SIZE, "Whirlpool")?;
+    if data.bytes::<8>(0) != WHIRLPOOL_DISCRIMINATOR {
+        return Err(anyhow!("Not a Whirlpool account"));
     }
-
-    let pool_layout: OrcaPoolLayout = BorshDeserialize::try_from_slice(&account.data)
-        .map_err(|e| anyhow::anyhow!("Failed to deserialize pool data: {}", e))?;
-
-    if pool_layout.version != POOL_LAYOUT_VERSION {
-        return Err(anyhow::anyhow!("Unsupported pool version"));
+    if data.u16(TICK_SPACING) != pool.tick_spacing {
+        return Err(anyhow!("Whirlpool tick spacing isn't {}", pool.tick_spacing));
     }
 
-    if !pool_layout.is_initialized {
-        return Err(anyhow::anyhow!("Pool not initialized"));
+    // sqrt_price is Q64.64 and in pool token B per pool token A
+    let sqrt_price = data.u128(SQRT_PRICE) as f64 / 2f64.powi(64);
+    if sqrt_price <= 0.0 {
+        return Err(anyhow!("Whirlpool has no price"));
     }
-
-    // Get token decimals
-    let token_a_info = crate::models::token::get_token_info(&token_a)
-        .ok_or_else(|| anyhow::anyhow!("Token A info not found"))?;
-    let token_b_info = crate::models::token::get_token_info(&token_b)
-        .ok_or_else(|| anyhow::anyhow!("Token B info not found"))?;
+    let liquidity = data.u128(LIQUIDITY) as f64;
+    let (reserve_a, reserve_b) = ((liquidity / sqrt_price) as u64, (liquidity * sqrt_price) as u64);
+    let (token_a, token_b) = if pool.a_is_pool_a { (reserve_a, reserve_b) } else { (reserve_b, reserve_a) };
 
     Ok(PoolInfo {
         reserves: PoolReserves {
-            token_a: pool_layout.token_a_reserve,
-            token_b: pool_layout.token_b_reserve,
-            decimals_a: token_a_info.decimals,
-            decimals_b: token_b_info.decimals,
+            token_a,
+            token_b,
+            decimals_a: pool.decimals_a,
+            decimals_b: pool.decimals_b,
         },
-        fee: pool_layout.fee,
+        // Hundredths of a basis point, as fees are kept here
+        fee: data.u16(FEE_RATE) as u64,
+        curve: Curve::ConstantProduct,
     })
-} 
\ No newline at end of file
+}
+
+impl LocatedPool for WhirlpoolRef {
+    fn accounts(&self) -> Vec<Pubkey> {
+        vec![self.address]
+    }
+
+    fn decode(&self, data: &[&[u8]]) -> Result<PoolInfo> {
+        decode(data[0], self)
+    }
+}
+
+pub struct Orca;
+
+#[async_trait]
+impl Dex for Orca {
+    fn name(&self) -> &'static str {
+        "Orca"
+    }
+
+    /// The pair's deepest Whirlpool by its token A reserves, after listing
+    /// every fee tier found. `None` if no tier has liquidity.
+    async fn locate(
+        &self,
+        client: &RpcClient,
+        token_a: &TokenInfo,
+        token_b: &TokenInfo,
+    ) -> Result<Option<Arc<dyn LocatedPool>>> {
+        let pools = whirlpools(client, token_a, token_b)?;
+        if !pools.is_empty() {
+            println!(
+                "{} Orca {}/{} fee tiers: {}",
+                "[INFO]".bright_green(),
+                token_a.symbol,
+                token_b.symbol,
+                pools
+                    .iter()
+                    .map(|(pool, info)| format!(
+                        "{} (tick spacing {}, {} {} deep)",
+                        format_percent(info.fee as f64 / 1_000_000.0),
+                        pool.tick_spacing,
+                        format_amount(info.reserves.token_a as f64 / 10f64.powi(token_a.decimals as i32)),
+                        token_a.symbol
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        let deepest = pools
+            .into_iter()
+            .filter(|(_, info)| info.reserves.token_a > 0 && info.reserves.token_b > 0)
+            .max_by_key(|(_, info)| info.reserves.token_a);
+        Ok(deepest.map(|(pool, _)| Arc::new(pool) as Arc<dyn LocatedPool>))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHIRLPOOL: &[u8] = include_bytes!("../../tests/fixtures/orca_whirlpool.bin");
+
+    fn pool(a_is_pool_a: bool) -> WhirlpoolRef {
+        let (decimals_a, decimals_b) = if a_is_pool_a { (9, 6) } else { (6, 9) };
+        WhirlpoolRef { address: Pubkey::default(), tick_spacing: 64, a_is_pool_a, decimals_a, decimals_b }
+    }
+
+    #[test]
+    fn decodes_whirlpool() {
+        let info = decode(WHIRLPOOL, &pool(true)).unwrap();
+        assert_eq!((info.reserves.token_a, info.reserves.token_b), (2_000_000_000_000, 500_000_000_000));
+        assert_eq!(info.fee, 3_000);
+
+        let flipped = decode(WHIRLPOOL, &pool(false)).unwrap();
+        assert_eq!((flipped.reserves.token_a, flipped.reserves.token_b), (500_000_000_000, 2_000_000_000_000));
+    }
+
+    #[test]
+    fn rejects_other_tick_spacing() {
+        let other = WhirlpoolRef { tick_spacing: 8, ..pool(true) };
+        assert!(decode(WHIRLPOOL, &other).is_err());
+    }
+
+    #[test]
+    fn tolerates_appended_fields() {
+        let grown = [WHIRLPOOL, &[0xab; 1041]].concat();
+        assert_eq!(decode(&grown, &pool(true)).unwrap().reserves, decode(WHIRLPOOL, &pool(true)).unwrap().reserves);
+        assert!(decode(&WHIRLPOOL[..WHIRLPOOL_LAYOUT_SIZE - 1], &pool(true)).is_err());
+    }
+
+    #[test]
+    fn address_ignores_mint_order() {
+        let config: Pubkey = WHIRLPOOLS_CONFIG.parse().unwrap();
+        let (sol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(whirlpool_address(&config, sol, usdc, 64), whirlpool_address(&config, usdc, sol, 64));
+        assert_ne!(whirlpool_address(&config, sol, usdc, 64), whirlpool_address(&config, sol, usdc, 8));
+    }
+}
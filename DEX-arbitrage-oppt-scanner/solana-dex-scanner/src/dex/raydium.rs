@@ -1,15 +1,67 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use solana_account_decoder::UiAccountEncoding;
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
 use solana_sdk::pubkey::Pubkey;
-use borsh::BorshDeserialize;
 use colored::*;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::str::FromStr;
-use crate::models::pool::{PoolInfo, PoolReserves, RaydiumPoolLayout};
+use std::sync::Arc;
+use crate::dex::{token_amount, AccountData, Dex, LocatedPool};
+use crate::models::pool::{Curve, PoolInfo, PoolReserves};
+use crate::models::token::TokenInfo;
 
+/// AMM v4, the program most Raydium pools run on.
 pub const PROGRAM_ID: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
-pub const POOL_LAYOUT_SIZE: usize = 1440;
-pub const POOL_LAYOUT_VERSION: u8 = 4;
+pub const AMM_V3_PROGRAM_ID: &str = "27haf8L6oxUeXrHrgEgsexjSY5hbVUWEmvv9Nyxg8vQv";
+pub const AMM_V5_PROGRAM_ID: &str = "5quBtoiQqxF9Jv6KYKctB59NT3gtJD2Y65kdnB1Uev3h";
+pub const CP_SWAP_PROGRAM_ID: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+pub const AMM_V4_LAYOUT_SIZE: usize = 752;
+pub const CP_SWAP_LAYOUT_SIZE: usize = 637;
+pub const AMM_CONFIG_LAYOUT_SIZE: usize = 236;
+// Anchor's account discriminators, the first 8 bytes of
+// sha256("account:<name>")
+const CP_SWAP_POOL_DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
+const AMM_CONFIG_DISCRIMINATOR: [u8; 8] = [218, 244, 33, 104, 203, 203, 43, 111];
+
+// Offsets into an AMM v4 pool (`AmmInfo`) of the fields read. Reserves live
+// in the two vaults, less the PnL the pool owes its admin.
+const AMM_V4_STATUS: usize = 0;
+const AMM_V4_BASE_DECIMAL: usize = 32;
+const AMM_V4_QUOTE_DECIMAL: usize = 40;
+const AMM_V4_SWAP_FEE_NUMERATOR: usize = 176;
+const AMM_V4_SWAP_FEE_DENOMINATOR: usize = 184;
+const AMM_V4_BASE_NEED_TAKE_PNL: usize = 192;
+const AMM_V4_QUOTE_NEED_TAKE_PNL: usize = 200;
+const AMM_V4_BASE_VAULT: usize = 336;
+const AMM_V4_QUOTE_VAULT: usize = 368;
+const AMM_V4_BASE_MINT: usize = 400;
+const AMM_V4_QUOTE_MINT: usize = 432;
+
+// Offsets into a CP-Swap pool (`PoolState`). Reserves live in the two
+// vaults, less the protocol and fund fees accrued in them; the trade fee is
+// on the pool's config account (`AmmConfig`), in hundredths of a basis point.
+const CP_SWAP_AMM_CONFIG: usize = 8;
+const CP_SWAP_TOKEN_0_VAULT: usize = 72;
+const CP_SWAP_TOKEN_1_VAULT: usize = 104;
+const CP_SWAP_TOKEN_0_MINT: usize = 168;
+const CP_SWAP_TOKEN_1_MINT: usize = 200;
+// Bit flags of disabled actions: deposit, withdraw, swap
+const CP_SWAP_STATUS: usize = 329;
+const CP_SWAP_MINT_0_DECIMALS: usize = 331;
+const CP_SWAP_MINT_1_DECIMALS: usize = 332;
+const CP_SWAP_PROTOCOL_FEES_TOKEN_0: usize = 341;
+const CP_SWAP_PROTOCOL_FEES_TOKEN_1: usize = 349;
+const CP_SWAP_FUND_FEES_TOKEN_0: usize = 357;
+const CP_SWAP_FUND_FEES_TOKEN_1: usize = 365;
+const AMM_CONFIG_TRADE_FEE_RATE: usize = 12;
+// AMM v4 statuses that allow swaps: initialized, swap-only, and waiting for
+// its open time
+const AMM_V4_SWAPPABLE: [u64; 3] = [1, 6, 7];
+const CP_SWAP_SWAP_DISABLED: u8 = 1 << 2;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct RaydiumPoolInfo {
@@ -54,8 +106,12 @@ async fn fetch_pool_info(token_a: &Pubkey, token_b: &Pubkey) -> Result<RaydiumPo
         .json::<RaydiumApiResponse>()
         .await?;
 
-    // Find pool with matching token pair
+    // Find pool with matching token pair. Stable (v5) pools price off a
+    // curve table, so a pair with one of those and a v4 pool takes the v4.
     for pool in response.data {
+        if pool.programId == AMM_V3_PROGRAM_ID || pool.programId == AMM_V5_PROGRAM_ID {
+            continue;
+        }
         if (pool.baseMint == token_a.to_string() && pool.quoteMint == token_b.to_string()) ||
            (pool.baseMint == token_b.to_string() && pool.quoteMint == token_a.to_string()) {
             return Ok(pool);
@@ -90,45 +146,456 @@ pub async fn find_pool(
     }
 }
 
-pub async fn get_pool_data(
-    client: &RpcClient,
-    token_a: Pubkey,
-    token_b: Pubkey,
-) -> Result<PoolInfo> {
-    let pool_address = find_pool(client, token_a, token_b).await?;
-    let account = client.get_account(&pool_address)
-        .map_err(|e| anyhow::anyhow!("Failed to get pool account: {}", e))?;
+/// Which of Raydium's pool programs, and so account layouts, a pool is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    AmmV3,
+    AmmV4,
+    /// Stable pools, priced off a curve table in a separate model account.
+    AmmV5,
+    CpSwap,
+}
+
+impl Layout {
+    /// Detects the layout of a pool account from the program that owns it,
+    /// checking its data is at least that layout's size and carries its
+    /// discriminator, if it has one.
+    pub fn detect(owner: &Pubkey, data: &[u8]) -> Result<Layout> {
+        let programs = super::programs();
+        let layout = match owner.to_string().as_str() {
+            _ if *owner == programs.raydium => Layout::AmmV4,
+            _ if *owner == programs.raydium_cp_swap => Layout::CpSwap,
+            AMM_V3_PROGRAM_ID => Layout::AmmV3,
+            AMM_V5_PROGRAM_ID => Layout::AmmV5,
+            _ => return Err(anyhow!("{} isn't a Raydium pool program", owner)),
+        };
+        let valid = match layout {
+            Layout::AmmV4 => data.len() >= AMM_V4_LAYOUT_SIZE,
+            Layout::CpSwap => data.len() >= CP_SWAP_LAYOUT_SIZE && data.starts_with(&CP_SWAP_POOL_DISCRIMINATOR),
+            Layout::AmmV3 | Layout::AmmV5 => true,
+        };
+        if !valid {
+            return Err(anyhow!("Unrecognized {} pool data ({} bytes)", layout, data.len()));
+        }
+        Ok(layout)
+    }
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Layout::AmmV3 => "AMM v3",
+            Layout::AmmV4 => "AMM v4",
+            Layout::AmmV5 => "AMM v5",
+            Layout::CpSwap => "CP-Swap",
+        })
+    }
+}
+
+/// A located Raydium pool: its layout, and the vaults holding its reserves
+/// in the pair's order.
+#[derive(Debug, Clone, Copy)]
+pub struct RaydiumRef {
+    pub pool: Pubkey,
+    pub layout: Layout,
+    pub vault_a: Pubkey,
+    pub vault_b: Pubkey,
+    /// Whether the pair's token A is the pool's base (token 0).
+    pub a_is_base: bool,
+    pub decimals_a: u8,
+    pub decimals_b: u8,
+    /// CP-Swap's fee, from its config account when located; AMM v4's is
+    /// read with the pool.
+    pub config_fee: u64,
+}
 
-    if account.data.len() != POOL_LAYOUT_SIZE {
-        return Err(anyhow::anyhow!("Invalid pool data size"));
+impl RaydiumRef {
+    /// The accounts a read needs, in the order `decode` takes them.
+    pub fn accounts(&self) -> [Pubkey; 3] {
+        [self.pool, self.vault_a, self.vault_b]
     }
+}
 
-    let pool_layout: RaydiumPoolLayout = BorshDeserialize::try_from_slice(&account.data)
-        .map_err(|e| anyhow::anyhow!("Failed to deserialize pool data: {}", e))?;
+/// Where a pool's tokens are, in its own base/quote (token 0/1) order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PoolTokens {
+    base_mint: Pubkey,
+    quote_mint: Pubkey,
+    base_vault: Pubkey,
+    quote_vault: Pubkey,
+    base_decimals: u8,
+    quote_decimals: u8,
+    /// CP-Swap's config account, which holds its fee.
+    amm_config: Option<Pubkey>,
+}
 
-    if pool_layout.version != POOL_LAYOUT_VERSION {
-        return Err(anyhow::anyhow!("Unsupported pool version"));
+fn pool_tokens(layout: Layout, data: &[u8]) -> Result<PoolTokens> {
+    match layout {
+        Layout::AmmV4 => {
+            let data = AccountData::new(data, AMM_V4_LAYOUT_SIZE, "AMM v4 pool")?;
+            Ok(PoolTokens {
+                base_mint: data.pubkey(AMM_V4_BASE_MINT),
+                quote_mint: data.pubkey(AMM_V4_QUOTE_MINT),
+                base_vault: data.pubkey(AMM_V4_BASE_VAULT),
+                quote_vault: data.pubkey(AMM_V4_QUOTE_VAULT),
+                base_decimals: data.u64(AMM_V4_BASE_DECIMAL) as u8,
+                quote_decimals: data.u64(AMM_V4_QUOTE_DECIMAL) as u8,
+                amm_config: None,
+            })
+        }
+        Layout::CpSwap => {
+            let data = AccountData::new(data, CP_SWAP_LAYOUT_SIZE, "CP-Swap pool")?;
+            Ok(PoolTokens {
+                base_mint: data.pubkey(CP_SWAP_TOKEN_0_MINT),
+                quote_mint: data.pubkey(CP_SWAP_TOKEN_1_MINT),
+                base_vault: data.pubkey(CP_SWAP_TOKEN_0_VAULT),
+                quote_vault: data.pubkey(CP_SWAP_TOKEN_1_VAULT),
+                base_decimals: data.u8(CP_SWAP_MINT_0_DECIMALS),
+                quote_decimals: data.u8(CP_SWAP_MINT_1_DECIMALS),
+                amm_config: Some(data.pubkey(CP_SWAP_AMM_CONFIG)),
+            })
+        }
+        Layout::AmmV3 | Layout::AmmV5 => Err(anyhow!("Raydium {} pools aren't supported", layout)),
     }
+}
 
-    if !pool_layout.is_initialized {
-        return Err(anyhow::anyhow!("Pool not initialized"));
+/// `getProgramAccounts` filters matching `layout`'s pools with `base` and
+/// `quote` as its base and quote mints, so the node returns only those.
+fn mint_filters(layout: Layout, base: &Pubkey, quote: &Pubkey) -> Vec<RpcFilterType> {
+    let (size, base_offset, quote_offset) = match layout {
+        Layout::AmmV4 => (AMM_V4_LAYOUT_SIZE, AMM_V4_BASE_MINT, AMM_V4_QUOTE_MINT),
+        _ => (CP_SWAP_LAYOUT_SIZE, CP_SWAP_TOKEN_0_MINT, CP_SWAP_TOKEN_1_MINT),
+    };
+    let mut filters = vec![
+        RpcFilterType::DataSize(size as u64),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(base_offset, base.to_bytes().to_vec())),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(quote_offset, quote.to_bytes().to_vec())),
+    ];
+    if layout == Layout::CpSwap {
+        filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, CP_SWAP_POOL_DISCRIMINATOR.to_vec())));
     }
+    filters
+}
 
-    // Get token decimals from pool info
-    let pool_info = fetch_pool_info(&token_a, &token_b).await?;
-    let (decimals_a, decimals_b) = if pool_info.baseMint == token_a.to_string() {
-        (pool_info.baseDecimals, pool_info.quoteDecimals)
+/// Whether a pool account's status allows swaps, so it's worth scanning.
+fn swappable(layout: Layout, data: &[u8]) -> bool {
+    match layout {
+        Layout::AmmV4 => AccountData::new(data, AMM_V4_LAYOUT_SIZE, "AMM v4 pool")
+            .is_ok_and(|data| AMM_V4_SWAPPABLE.contains(&data.u64(AMM_V4_STATUS))),
+        Layout::CpSwap => AccountData::new(data, CP_SWAP_LAYOUT_SIZE, "CP-Swap pool")
+            .is_ok_and(|data| data.u8(CP_SWAP_STATUS) & CP_SWAP_SWAP_DISABLED == 0),
+        Layout::AmmV3 | Layout::AmmV5 => false,
+    }
+}
+
+/// Finds the pair's swappable AMM v4 and CP-Swap pools on chain with
+/// `getProgramAccounts`, either way round, and takes the deepest by its
+/// `token_a` vault. `None` if the pair has no such pool; an error if the
+/// node won't serve the query, as many public endpoints don't.
+fn discover(client: &RpcClient, token_a: Pubkey, token_b: Pubkey) -> Result<Option<(Pubkey, Layout, PoolTokens)>> {
+    let mut candidates = Vec::new();
+    let programs = super::programs();
+    for (layout, program) in [(Layout::AmmV4, programs.raydium), (Layout::CpSwap, programs.raydium_cp_swap)] {
+        for (base, quote) in [(token_a, token_b), (token_b, token_a)] {
+            let config = RpcProgramAccountsConfig {
+                filters: Some(mint_filters(layout, &base, &quote)),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            };
+            let accounts = client
+                .get_program_accounts_with_config(&program, config)
+                .map_err(|e| anyhow!("Failed to list Raydium {} pools: {}", layout, e))?;
+            for (pool, account) in accounts {
+                if swappable(layout, &account.data) {
+                    candidates.push((pool, layout, pool_tokens(layout, &account.data)?));
+                }
+            }
+        }
+    }
+    if candidates.len() <= 1 {
+        return Ok(candidates.pop());
+    }
+
+    let vaults = candidates
+        .iter()
+        .map(|(_, _, tokens)| if tokens.base_mint == token_a { tokens.base_vault } else { tokens.quote_vault })
+        .collect::<Vec<_>>();
+    let depths = client
+        .get_multiple_accounts(&vaults)
+        .map_err(|e| anyhow!("Failed to get Raydium vaults: {}", e))?
+        .into_iter()
+        .map(|vault| vault.and_then(|vault| token_amount(&vault.data).ok()).unwrap_or(0))
+        .collect::<Vec<_>>();
+    let deepest = (0..candidates.len()).max_by_key(|&i| depths[i]).unwrap_or(0);
+    Ok(Some(candidates.swap_remove(deepest)))
+}
+
+/// The trade fee of a CP-Swap config account.
+fn config_fee(data: &[u8]) -> Result<u64> {
+    let data = AccountData::new(data, AMM_CONFIG_LAYOUT_SIZE, "CP-Swap config")?;
+    if data.bytes::<8>(0) != AMM_CONFIG_DISCRIMINATOR {
+        return Err(anyhow!("Not a CP-Swap config account"));
+    }
+    Ok(data.u64(AMM_CONFIG_TRADE_FEE_RATE))
+}
+
+/// A reference to `pool` reading `token_a` and `token_b` in that order.
+fn orient(
+    pool: Pubkey,
+    layout: Layout,
+    tokens: &PoolTokens,
+    token_a: Pubkey,
+    token_b: Pubkey,
+    config_fee: u64,
+) -> Result<RaydiumRef> {
+    let a_is_base = if (tokens.base_mint, tokens.quote_mint) == (token_a, token_b) {
+        true
+    } else if (tokens.base_mint, tokens.quote_mint) == (token_b, token_a) {
+        false
+    } else {
+        return Err(anyhow!("Raydium pool {} doesn't hold {} and {}", pool, token_a, token_b));
+    };
+    let (vault_a, vault_b, decimals_a, decimals_b) = if a_is_base {
+        (tokens.base_vault, tokens.quote_vault, tokens.base_decimals, tokens.quote_decimals)
     } else {
-        (pool_info.quoteDecimals, pool_info.baseDecimals)
+        (tokens.quote_vault, tokens.base_vault, tokens.quote_decimals, tokens.base_decimals)
+    };
+    Ok(RaydiumRef {
+        pool,
+        layout,
+        vault_a,
+        vault_b,
+        a_is_base,
+        decimals_a,
+        decimals_b,
+        config_fee,
+    })
+}
+
+/// The pair's pool, with its layout detected from the pool account, and
+/// what reading it later needs. Pools are found on chain, see [`discover`],
+/// or through the Raydium API when the RPC node won't list them.
+pub async fn locate(
+    client: &RpcClient,
+    token_a: Pubkey,
+    token_b: Pubkey,
+) -> Result<RaydiumRef> {
+    let (pool, layout, tokens) = match discover(client, token_a, token_b) {
+        Ok(Some(found)) => found,
+        Ok(None) => return Err(anyhow!("No swappable Raydium pool for {} and {}", token_a, token_b)),
+        Err(e) => {
+            println!("{} {}, falling back to the Raydium API", "[INFO]".bright_green(), e);
+            let pool = find_pool(client, token_a, token_b).await?;
+            let account = client
+                .get_account(&pool)
+                .map_err(|e| anyhow!("Failed to get pool account: {}", e))?;
+            let layout = Layout::detect(&account.owner, &account.data)?;
+            (pool, layout, pool_tokens(layout, &account.data)?)
+        }
+    };
+    let fee = match tokens.amm_config {
+        Some(config) => {
+            let account = client
+                .get_account(&config)
+                .map_err(|e| anyhow!("Failed to get CP-Swap config {}: {}", config, e))?;
+            config_fee(&account.data)?
+        }
+        None => 0,
+    };
+    orient(pool, layout, &tokens, token_a, token_b, fee)
+}
+
+/// Decodes the pool and vault accounts read for `pool`, in the order of
+/// `RaydiumRef::accounts`, with the pool's layout.
+pub fn decode(data: &[u8], vault_a: &[u8], vault_b: &[u8], pool: &RaydiumRef) -> Result<PoolInfo> {
+    // What the vaults hold that isn't the pool's to trade, in base/quote order
+    let (owed_base, owed_quote, fee) = match pool.layout {
+        Layout::AmmV4 => {
+            let data = AccountData::new(data, AMM_V4_LAYOUT_SIZE, "AMM v4 pool")?;
+            let status = data.u64(AMM_V4_STATUS);
+            if !AMM_V4_SWAPPABLE.contains(&status) {
+                return Err(anyhow!("Pool not swappable (status {})", status));
+            }
+            let (numerator, denominator) = (data.u64(AMM_V4_SWAP_FEE_NUMERATOR), data.u64(AMM_V4_SWAP_FEE_DENOMINATOR));
+            if denominator == 0 {
+                return Err(anyhow!("Invalid pool fee"));
+            }
+            (
+                data.u64(AMM_V4_BASE_NEED_TAKE_PNL),
+                data.u64(AMM_V4_QUOTE_NEED_TAKE_PNL),
+                // In hundredths of a basis point, like the other venues'
+                numerator * 1_000_000 / denominator,
+            )
+        }
+        Layout::CpSwap => {
+            let data = AccountData::new(data, CP_SWAP_LAYOUT_SIZE, "CP-Swap pool")?;
+            if data.u8(CP_SWAP_STATUS) & CP_SWAP_SWAP_DISABLED != 0 {
+                return Err(anyhow!("Pool swaps disabled"));
+            }
+            (
+                data.u64(CP_SWAP_PROTOCOL_FEES_TOKEN_0) + data.u64(CP_SWAP_FUND_FEES_TOKEN_0),
+                data.u64(CP_SWAP_PROTOCOL_FEES_TOKEN_1) + data.u64(CP_SWAP_FUND_FEES_TOKEN_1),
+                pool.config_fee,
+            )
+        }
+        layout => return Err(anyhow!("Raydium {} pools aren't supported", layout)),
     };
+    let (owed_a, owed_b) = if pool.a_is_base { (owed_base, owed_quote) } else { (owed_quote, owed_base) };
 
     Ok(PoolInfo {
         reserves: PoolReserves {
-            token_a: pool_layout.token_a_reserve,
-            token_b: pool_layout.token_b_reserve,
-            decimals_a,
-            decimals_b,
+            token_a: token_amount(vault_a)?.saturating_sub(owed_a),
+            token_b: token_amount(vault_b)?.saturating_sub(owed_b),
+            decimals_a: pool.decimals_a,
+            decimals_b: pool.decimals_b,
         },
-        fee: pool_layout.fee,
+        fee,
+        curve: Curve::ConstantProduct,
     })
-} 
\ No newline at end of file
+}
+
+impl LocatedPool for RaydiumRef {
+    fn accounts(&self) -> Vec<Pubkey> {
+        RaydiumRef::accounts(self).to_vec()
+    }
+
+    fn decode(&self, data: &[&[u8]]) -> Result<PoolInfo> {
+        decode(data[0], data[1], data[2], self)
+    }
+}
+
+pub struct Raydium;
+
+#[async_trait]
+impl Dex for Raydium {
+    fn name(&self) -> &'static str {
+        "Raydium"
+    }
+
+    async fn locate(
+        &self,
+        client: &RpcClient,
+        token_a: &TokenInfo,
+        token_b: &TokenInfo,
+    ) -> Result<Option<Arc<dyn LocatedPool>>> {
+        let pool = locate(client, token_a.address, token_b.address).await?;
+        Ok(Some(Arc::new(pool)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMM_V4: &[u8] = include_bytes!("../../tests/fixtures/raydium_amm_v4.bin");
+    const CP_SWAP: &[u8] = include_bytes!("../../tests/fixtures/raydium_cp_swap.bin");
+    const AMM_CONFIG: &[u8] = include_bytes!("../../tests/fixtures/raydium_amm_config.bin");
+    const VAULT: &[u8] = include_bytes!("../../tests/fixtures/token_account.bin");
+
+    fn program(id: &str) -> Pubkey {
+        id.parse().unwrap()
+    }
+
+    fn sol() -> Pubkey {
+        "So11111111111111111111111111111111111111112".parse().unwrap()
+    }
+
+    fn usdc() -> Pubkey {
+        "EPjFWdd5AufqSSqeM2qN1xyybapC8G4wEGGkZwyTDt1v".parse().unwrap()
+    }
+
+    fn appended(data: &[u8]) -> Vec<u8> {
+        [data, &[0xab; 64]].concat()
+    }
+
+    #[test]
+    fn detects_layout_by_owner() {
+        assert_eq!(Layout::detect(&program(PROGRAM_ID), AMM_V4).unwrap(), Layout::AmmV4);
+        assert_eq!(Layout::detect(&program(CP_SWAP_PROGRAM_ID), CP_SWAP).unwrap(), Layout::CpSwap);
+        assert_eq!(Layout::detect(&program(AMM_V5_PROGRAM_ID), AMM_V4).unwrap(), Layout::AmmV5);
+        assert!(Layout::detect(&sol(), AMM_V4).is_err());
+        assert!(Layout::detect(&program(PROGRAM_ID), &AMM_V4[..700]).is_err());
+        // An AMM v4 pool isn't a CP-Swap pool whatever its size
+        assert!(Layout::detect(&program(CP_SWAP_PROGRAM_ID), &appended(AMM_V4)).is_err());
+    }
+
+    #[test]
+    fn decodes_amm_v4() {
+        let tokens = pool_tokens(Layout::AmmV4, AMM_V4).unwrap();
+        assert_eq!((tokens.base_mint, tokens.quote_mint), (sol(), usdc()));
+        assert_eq!((tokens.base_vault, tokens.quote_vault), (Pubkey::new_from_array([1; 32]), Pubkey::new_from_array([2; 32])));
+        assert_eq!((tokens.base_decimals, tokens.quote_decimals, tokens.amm_config), (9, 6, None));
+
+        // Read as USDC/SOL, so the other way round from the pool
+        let pool = orient(Pubkey::default(), Layout::AmmV4, &tokens, usdc(), sol(), 0).unwrap();
+        assert!(!pool.a_is_base);
+        assert_eq!((pool.vault_a, pool.decimals_a), (tokens.quote_vault, 6));
+        let info = decode(AMM_V4, VAULT, VAULT, &pool).unwrap();
+        assert_eq!((info.reserves.token_a, info.reserves.token_b), (4_999_998_000, 4_999_000_000));
+        assert_eq!(info.fee, 2_500);
+    }
+
+    #[test]
+    fn decodes_cp_swap() {
+        let tokens = pool_tokens(Layout::CpSwap, CP_SWAP).unwrap();
+        assert_eq!((tokens.base_mint, tokens.quote_mint), (sol(), usdc()));
+        assert_eq!(tokens.amm_config, Some(Pubkey::new_from_array([3; 32])));
+        assert_eq!((tokens.base_decimals, tokens.quote_decimals), (9, 6));
+        assert_eq!(config_fee(AMM_CONFIG).unwrap(), 2_500);
+        assert!(config_fee(CP_SWAP).is_err());
+
+        let pool = orient(Pubkey::default(), Layout::CpSwap, &tokens, sol(), usdc(), 2_500).unwrap();
+        let info = decode(CP_SWAP, VAULT, VAULT, &pool).unwrap();
+        assert_eq!((info.reserves.token_a, info.reserves.token_b), (4_999_600_000, 4_999_999_950));
+        assert_eq!(info.fee, 2_500);
+
+        let mut disabled = CP_SWAP.to_vec();
+        disabled[CP_SWAP_STATUS] |= CP_SWAP_SWAP_DISABLED;
+        assert!(decode(&disabled, VAULT, VAULT, &pool).is_err());
+    }
+
+    #[test]
+    fn tolerates_appended_fields() {
+        for (layout, data) in [(Layout::AmmV4, AMM_V4), (Layout::CpSwap, CP_SWAP)] {
+            let tokens = pool_tokens(layout, data).unwrap();
+            assert_eq!(pool_tokens(layout, &appended(data)).unwrap(), tokens);
+            let pool = orient(Pubkey::default(), layout, &tokens, sol(), usdc(), 2_500).unwrap();
+            let info = decode(data, VAULT, VAULT, &pool).unwrap();
+            let grown = decode(&appended(data), &appended(VAULT), &appended(VAULT), &pool).unwrap();
+            assert_eq!(grown.reserves, info.reserves);
+            assert_eq!(grown.fee, info.fee);
+        }
+        assert_eq!(config_fee(&appended(AMM_CONFIG)).unwrap(), 2_500);
+    }
+
+    #[test]
+    fn filters_match_pool_mints() {
+        for (layout, data) in [(Layout::AmmV4, AMM_V4), (Layout::CpSwap, CP_SWAP)] {
+            let matches = |base: &Pubkey, quote: &Pubkey| {
+                mint_filters(layout, base, quote).iter().all(|filter| match filter {
+                    RpcFilterType::DataSize(size) => data.len() as u64 == *size,
+                    RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(data),
+                    _ => false,
+                })
+            };
+            assert!(matches(&sol(), &usdc()));
+            assert!(!matches(&usdc(), &sol()));
+            assert!(swappable(layout, data));
+        }
+        // An AMM v4 pool doesn't pass CP-Swap's discriminator
+        let filters = mint_filters(Layout::CpSwap, &sol(), &usdc());
+        assert!(!filters.iter().all(|filter| match filter {
+            RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(AMM_V4),
+            _ => true,
+        }));
+    }
+
+    #[test]
+    fn rejects_other_pairs() {
+        let tokens = pool_tokens(Layout::AmmV4, AMM_V4).unwrap();
+        assert!(orient(Pubkey::default(), Layout::AmmV4, &tokens, sol(), sol(), 0).is_err());
+    }
+}
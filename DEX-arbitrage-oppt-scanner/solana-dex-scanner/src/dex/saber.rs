@@ -0,0 +1,244 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+
+use crate::dex::{token_amount, AccountData, Dex, LocatedPool};
+use crate::models::pool::{Curve, PoolInfo, PoolReserves};
+use crate::models::token::TokenInfo;
+
+pub const PROGRAM_ID: &str = "SSwpkEEcbUqx4vtoEByFjSkhKdCT862DNVb52nZg1UZ";
+pub const SWAP_LAYOUT_SIZE: usize = 395;
+
+// Offsets into the swap account of the fields read. Reserves live in the
+// two token accounts it points to; fees are numerator/denominator pairs.
+const IS_INITIALIZED: usize = 0;
+const IS_PAUSED: usize = 1;
+const INITIAL_AMP_FACTOR: usize = 3;
+const TARGET_AMP_FACTOR: usize = 11;
+const START_RAMP_TS: usize = 19;
+const STOP_RAMP_TS: usize = 27;
+const TOKEN_A_RESERVES: usize = 107;
+const TOKEN_B_RESERVES: usize = 139;
+const TOKEN_A_MINT: usize = 203;
+const TOKEN_B_MINT: usize = 235;
+const TRADE_FEE_NUMERATOR: usize = 363;
+const TRADE_FEE_DENOMINATOR: usize = 371;
+
+/// Saber's stable pools, by swap account; which tokens each holds is read
+/// from the account.
+const SWAPS: &[&str] = &[
+    // USDC-USDT
+    "YAkoNb6HKmSxQN9L8hiBE5tPJRsniSSMzND1boHmZxe",
+];
+
+/// A located Saber pool: the swap account, for its amplification and fee,
+/// and the reserve token accounts in the pair's order.
+#[derive(Debug, Clone, Copy)]
+pub struct SaberRef {
+    pub swap: Pubkey,
+    pub reserve_a: Pubkey,
+    pub reserve_b: Pubkey,
+    pub decimals_a: u8,
+    pub decimals_b: u8,
+}
+
+impl SaberRef {
+    /// The accounts a read needs, in the order `decode` takes them.
+    pub fn accounts(&self) -> [Pubkey; 3] {
+        [self.swap, self.reserve_a, self.reserve_b]
+    }
+}
+
+/// The fields of a swap account the scanner uses.
+#[derive(Debug)]
+struct Swap {
+    is_paused: bool,
+    initial_amp_factor: u64,
+    target_amp_factor: u64,
+    start_ramp_ts: i64,
+    stop_ramp_ts: i64,
+    token_a_reserves: Pubkey,
+    token_b_reserves: Pubkey,
+    token_a_mint: Pubkey,
+    token_b_mint: Pubkey,
+    trade_fee_numerator: u64,
+    trade_fee_denominator: u64,
+}
+
+fn parse_swap(data: &[u8]) -> Result<Swap> {
+    let data = AccountData::new(data, SWAP_LAYOUT_SIZE, "Swap")?;
+    if !data.bool(IS_INITIALIZED) {
+        return Err(anyhow!("Swap not initialized"));
+    }
+    Ok(Swap {
+        is_paused: data.bool(IS_PAUSED),
+        initial_amp_factor: data.u64(INITIAL_AMP_FACTOR),
+        target_amp_factor: data.u64(TARGET_AMP_FACTOR),
+        start_ramp_ts: data.i64(START_RAMP_TS),
+        stop_ramp_ts: data.i64(STOP_RAMP_TS),
+        token_a_reserves: data.pubkey(TOKEN_A_RESERVES),
+        token_b_reserves: data.pubkey(TOKEN_B_RESERVES),
+        token_a_mint: data.pubkey(TOKEN_A_MINT),
+        token_b_mint: data.pubkey(TOKEN_B_MINT),
+        trade_fee_numerator: data.u64(TRADE_FEE_NUMERATOR),
+        trade_fee_denominator: data.u64(TRADE_FEE_DENOMINATOR),
+    })
+}
+
+/// The Saber pool holding `token_a` and `token_b`, if there is one.
+pub fn locate(client: &RpcClient, token_a: Pubkey, token_b: Pubkey) -> Result<Option<SaberRef>> {
+    for address in SWAPS {
+        let swap_address: Pubkey = address.parse().unwrap();
+        let account = client
+            .get_account(&swap_address)
+            .map_err(|e| anyhow!("Failed to get Saber swap {}: {}", swap_address, e))?;
+        if account.owner != super::programs().saber {
+            return Err(anyhow!("{} isn't a Saber swap", swap_address));
+        }
+        let swap = parse_swap(&account.data)?;
+        let (reserve_a, reserve_b) = if (swap.token_a_mint, swap.token_b_mint) == (token_a, token_b) {
+            (swap.token_a_reserves, swap.token_b_reserves)
+        } else if (swap.token_a_mint, swap.token_b_mint) == (token_b, token_a) {
+            (swap.token_b_reserves, swap.token_a_reserves)
+        } else {
+            continue;
+        };
+
+        let decimals = |mint: &Pubkey| {
+            crate::models::token::get_token_info(mint)
+                .map(|token| token.decimals)
+                .ok_or_else(|| anyhow!("Token {} info not found", mint))
+        };
+        return Ok(Some(SaberRef {
+            swap: swap_address,
+            reserve_a,
+            reserve_b,
+            decimals_a: decimals(&token_a)?,
+            decimals_b: decimals(&token_b)?,
+        }));
+    }
+    Ok(None)
+}
+
+/// Amplification `now` (a Unix timestamp): the admin ramps it linearly
+/// from the initial to the target factor between the ramp timestamps.
+fn amp_at(swap: &Swap, now: i64) -> u64 {
+    let (start, stop) = (swap.start_ramp_ts, swap.stop_ramp_ts);
+    if now >= stop || stop <= start {
+        return swap.target_amp_factor;
+    }
+    let elapsed = (now - start).max(0) as i128;
+    let (initial, target) = (swap.initial_amp_factor as i128, swap.target_amp_factor as i128);
+    (initial + (target - initial) * elapsed / (stop - start) as i128) as u64
+}
+
+/// Decodes the swap and reserve accounts read for `pool`, in the order of
+/// `SaberRef::accounts`.
+pub fn decode(swap: &[u8], reserve_a: &[u8], reserve_b: &[u8], pool: &SaberRef) -> Result<PoolInfo> {
+    let swap = parse_swap(swap)?;
+    if swap.is_paused {
+        return Err(anyhow!("Swap paused"));
+    }
+    if swap.trade_fee_denominator == 0 {
+        return Err(anyhow!("Invalid swap fee"));
+    }
+    Ok(PoolInfo {
+        reserves: PoolReserves {
+            token_a: token_amount(reserve_a)?,
+            token_b: token_amount(reserve_b)?,
+            decimals_a: pool.decimals_a,
+            decimals_b: pool.decimals_b,
+        },
+        // In hundredths of a basis point, like the other venues'
+        fee: swap.trade_fee_numerator * 1_000_000 / swap.trade_fee_denominator,
+        curve: Curve::StableSwap {
+            amp: amp_at(&swap, chrono::Utc::now().timestamp()),
+        },
+    })
+}
+
+impl LocatedPool for SaberRef {
+    fn accounts(&self) -> Vec<Pubkey> {
+        SaberRef::accounts(self).to_vec()
+    }
+
+    fn decode(&self, data: &[&[u8]]) -> Result<PoolInfo> {
+        decode(data[0], data[1], data[2], self)
+    }
+}
+
+pub struct Saber;
+
+#[async_trait]
+impl Dex for Saber {
+    fn name(&self) -> &'static str {
+        "Saber"
+    }
+
+    /// Saber only lists stable pairs, so others aren't looked up.
+    async fn locate(
+        &self,
+        client: &RpcClient,
+        token_a: &TokenInfo,
+        token_b: &TokenInfo,
+    ) -> Result<Option<Arc<dyn LocatedPool>>> {
+        if !crate::is_usd_stable(token_a) || !crate::is_usd_stable(token_b) {
+            return Ok(None);
+        }
+        let pool = locate(client, token_a.address, token_b.address)?;
+        Ok(pool.map(|pool| Arc::new(pool) as Arc<dyn LocatedPool>))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SWAP: &[u8] = include_bytes!("../../tests/fixtures/saber_swap.bin");
+    const RESERVE: &[u8] = include_bytes!("../../tests/fixtures/token_account.bin");
+
+    fn pool() -> SaberRef {
+        SaberRef {
+            swap: Pubkey::default(),
+            reserve_a: Pubkey::new_from_array([6; 32]),
+            reserve_b: Pubkey::new_from_array([7; 32]),
+            decimals_a: 6,
+            decimals_b: 6,
+        }
+    }
+
+    #[test]
+    fn parses_swap() {
+        let swap = parse_swap(SWAP).unwrap();
+        assert!(!swap.is_paused);
+        assert_eq!((swap.token_a_reserves, swap.token_b_reserves), (pool().reserve_a, pool().reserve_b));
+        assert_eq!(swap.token_a_mint.to_string(), "EPjFWdd5AufqSSqeM2qN1xyybapC8G4wEGGkZwyTDt1v");
+        assert_eq!(swap.token_b_mint.to_string(), "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB");
+        assert_eq!((swap.trade_fee_numerator, swap.trade_fee_denominator), (4, 10_000));
+    }
+
+    #[test]
+    fn ramps_amplification() {
+        let swap = parse_swap(SWAP).unwrap();
+        assert_eq!(amp_at(&swap, 500), 100);
+        assert_eq!(amp_at(&swap, 1_500), 150);
+        assert_eq!(amp_at(&swap, 3_000), 200);
+    }
+
+    #[test]
+    fn decodes_pool() {
+        let info = decode(SWAP, RESERVE, RESERVE, &pool()).unwrap();
+        assert_eq!((info.reserves.token_a, info.reserves.token_b), (5_000_000_000, 5_000_000_000));
+        assert_eq!(info.fee, 400);
+        assert!(matches!(info.curve, Curve::StableSwap { amp: 200 }));
+    }
+
+    #[test]
+    fn tolerates_appended_fields() {
+        let grown = [SWAP, &[0xab; 64]].concat();
+        assert_eq!(decode(&grown, RESERVE, RESERVE, &pool()).unwrap().fee, 400);
+        assert!(parse_swap(&SWAP[..SWAP_LAYOUT_SIZE - 1]).is_err());
+    }
+}
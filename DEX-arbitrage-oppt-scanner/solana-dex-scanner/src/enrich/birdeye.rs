@@ -0,0 +1,44 @@
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+
+use super::TokenMarket;
+
+const TOKEN_OVERVIEW_URL: &str = "https://public-api.birdeye.so/defi/token_overview";
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    success: bool,
+    data: Option<Overview>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Overview {
+    #[serde(rename = "v24hUSD")]
+    volume_24h_usd: Option<f64>,
+    price_change_24h_percent: Option<f64>,
+}
+
+/// A token's 24h volume and price change from Birdeye's token overview.
+pub async fn fetch_token(client: &reqwest::Client, api_key: &str, mint: &Pubkey) -> Result<TokenMarket> {
+    let response = client
+        .get(TOKEN_OVERVIEW_URL)
+        .query(&[("address", mint.to_string())])
+        .header("X-API-KEY", api_key)
+        .header("x-chain", "solana")
+        .header("accept", "application/json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Response>()
+        .await?;
+
+    match response.data {
+        Some(overview) if response.success => Ok(TokenMarket {
+            volume_24h_usd: overview.volume_24h_usd,
+            price_change_24h: overview.price_change_24h_percent.map(|c| c / 100.0),
+        }),
+        _ => bail!("Birdeye returned no overview"),
+    }
+}
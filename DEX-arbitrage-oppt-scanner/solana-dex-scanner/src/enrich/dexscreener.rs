@@ -0,0 +1,84 @@
+use anyhow::Result;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+
+use super::TokenMarket;
+
+const TOKENS_URL: &str = "https://api.dexscreener.com/tokens/v1/solana";
+// The tokens endpoint accepts at most 30 addresses per request
+const MAX_TOKENS_PER_REQUEST: usize = 30;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Pair {
+    base_token: PairToken,
+    volume: Option<Window>,
+    price_change: Option<Window>,
+    liquidity: Option<Liquidity>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PairToken {
+    address: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Window {
+    h24: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Liquidity {
+    usd: Option<f64>,
+}
+
+/// 24h volume across every pair a token is the base of, and the 24h price
+/// change of its most liquid pair, from Dexscreener.
+pub async fn fetch_tokens(
+    client: &reqwest::Client,
+    mints: &[Pubkey],
+) -> Result<Vec<(Pubkey, TokenMarket)>> {
+    // Per mint: total volume, and liquidity and price change of the deepest pair
+    let mut totals: HashMap<Pubkey, (f64, f64, Option<f64>)> = HashMap::new();
+
+    for chunk in mints.chunks(MAX_TOKENS_PER_REQUEST) {
+        let addresses = chunk.iter().map(Pubkey::to_string).collect::<Vec<_>>().join(",");
+        let pairs = client
+            .get(format!("{}/{}", TOKENS_URL, addresses))
+            .header("accept", "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<Pair>>()
+            .await?;
+
+        for pair in pairs {
+            let mint = match pair.base_token.address.parse::<Pubkey>() {
+                Ok(mint) if chunk.contains(&mint) => mint,
+                _ => continue,
+            };
+            let liquidity = pair.liquidity.and_then(|l| l.usd).unwrap_or_default();
+            let total = totals.entry(mint).or_insert((0.0, -1.0, None));
+            total.0 += pair.volume.and_then(|v| v.h24).unwrap_or_default();
+            if liquidity > total.1 {
+                // Dexscreener reports price change in percent
+                total.1 = liquidity;
+                total.2 = pair.price_change.and_then(|c| c.h24).map(|c| c / 100.0);
+            }
+        }
+    }
+
+    Ok(totals
+        .into_iter()
+        .map(|(mint, (volume, _, price_change))| {
+            (
+                mint,
+                TokenMarket {
+                    volume_24h_usd: Some(volume),
+                    price_change_24h: price_change,
+                },
+            )
+        })
+        .collect())
+}
@@ -0,0 +1,75 @@
+pub mod birdeye;
+pub mod dexscreener;
+pub mod perps;
+
+use colored::*;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// 24h figures a market data site reports for a token.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenMarket {
+    pub volume_24h_usd: Option<f64>,
+    /// e.g. 0.034 for +3.4%.
+    pub price_change_24h: Option<f64>,
+}
+
+/// What each market data site last reported for a token.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarketInfo {
+    pub dexscreener: Option<TokenMarket>,
+    pub birdeye: Option<TokenMarket>,
+}
+
+pub fn dexscreener_url(mint: &Pubkey) -> String {
+    format!("https://dexscreener.com/solana/{}", mint)
+}
+
+pub fn birdeye_url(mint: &Pubkey) -> String {
+    format!("https://birdeye.so/token/{}?chain=solana", mint)
+}
+
+/// Mint -> market figures, shared between the refresh task and alerts.
+#[derive(Debug, Clone, Default)]
+pub struct MarketCache(Arc<RwLock<HashMap<Pubkey, MarketInfo>>>);
+
+impl MarketCache {
+    pub fn get(&self, mint: &Pubkey) -> MarketInfo {
+        self.0.read().unwrap().get(mint).copied().unwrap_or_default()
+    }
+}
+
+/// Refreshes `mints`' figures from Dexscreener and, given an API key, from
+/// Birdeye every `interval`. A source that fails keeps its last figures.
+pub fn spawn_refresh(
+    mints: Vec<Pubkey>,
+    birdeye_api_key: Option<String>,
+    cache: MarketCache,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        loop {
+            match dexscreener::fetch_tokens(&client, &mints).await {
+                Ok(markets) => {
+                    let mut cache = cache.0.write().unwrap();
+                    for (mint, market) in markets {
+                        cache.entry(mint).or_default().dexscreener = Some(market);
+                    }
+                }
+                Err(e) => println!("{} Failed to refresh Dexscreener data: {}", "[ERROR]".bright_red(), e),
+            }
+            if let Some(api_key) = &birdeye_api_key {
+                for mint in &mints {
+                    match birdeye::fetch_token(&client, api_key, mint).await {
+                        Ok(market) => cache.0.write().unwrap().entry(*mint).or_default().birdeye = Some(market),
+                        Err(e) => println!("{} Failed to refresh Birdeye data for {}: {}", "[ERROR]".bright_red(), mint, e),
+                    }
+                }
+            }
+            tokio::time::sleep(interval).await;
+        }
+    })
+}
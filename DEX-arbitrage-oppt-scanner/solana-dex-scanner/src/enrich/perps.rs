@@ -0,0 +1,130 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::models::token::TokenInfo;
+
+const DRIFT_L2_URL: &str = "https://dlob.drift.trade/l2";
+// Drift quotes prices with 6 decimals
+const DRIFT_PRICE_PRECISION: f64 = 1_000_000.0;
+
+/// A token's perpetual on a derivatives venue. `unit` is how many tokens
+/// one contract prices, e.g. a million for BONK.
+struct PerpMarket {
+    symbol: &'static str,
+    name: &'static str,
+    unit: f64,
+}
+
+const DRIFT_MARKETS: &[PerpMarket] = &[
+    PerpMarket { symbol: "SOL", name: "SOL-PERP", unit: 1.0 },
+    PerpMarket { symbol: "BONK", name: "1MBONK-PERP", unit: 1_000_000.0 },
+    PerpMarket { symbol: "WIF", name: "WIF-PERP", unit: 1.0 },
+    PerpMarket { symbol: "POPCAT", name: "POPCAT-PERP", unit: 1.0 },
+];
+
+/// A perp's mark price, in USD per token.
+#[derive(Debug, Clone, Copy)]
+pub struct PerpQuote {
+    pub venue: &'static str,
+    pub market: &'static str,
+    pub mark: f64,
+    pub read_at: DateTime<Utc>,
+}
+
+impl PerpQuote {
+    /// How far the perp trades above (positive) or below spot, e.g. 0.001
+    /// for +0.1%.
+    pub fn basis(&self, spot: f64) -> f64 {
+        self.mark / spot - 1.0
+    }
+}
+
+/// Mint -> latest perp mark price, shared between the refresh task and
+/// alerts.
+#[derive(Debug, Clone, Default)]
+pub struct PerpCache(Arc<RwLock<HashMap<Pubkey, PerpQuote>>>);
+
+impl PerpCache {
+    /// `mint`'s perp quote, unless it's older than `max_age`.
+    pub fn get(&self, mint: &Pubkey, max_age: chrono::Duration) -> Option<PerpQuote> {
+        self.0
+            .read()
+            .unwrap()
+            .get(mint)
+            .copied()
+            .filter(|quote| Utc::now() - quote.read_at <= max_age)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Level {
+    price: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct L2 {
+    bids: Vec<Level>,
+    asks: Vec<Level>,
+}
+
+/// Mid of the best bid and ask on Drift's orderbook for `market`.
+async fn fetch_drift(client: &reqwest::Client, market: &PerpMarket) -> Result<PerpQuote> {
+    let book = client
+        .get(DRIFT_L2_URL)
+        .query(&[("marketName", market.name), ("depth", "1")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<L2>()
+        .await?;
+    let best = |levels: &[Level]| -> Result<f64> {
+        let level = levels.first().ok_or_else(|| anyhow!("{} orderbook is empty", market.name))?;
+        Ok(level.price.parse::<f64>()? / DRIFT_PRICE_PRECISION)
+    };
+    let mid = (best(&book.bids)? + best(&book.asks)?) / 2.0;
+    Ok(PerpQuote {
+        venue: "Drift",
+        market: market.name,
+        mark: mid / market.unit,
+        read_at: Utc::now(),
+    })
+}
+
+/// Refreshes the Drift perp mark price of each of `tokens` that has a
+/// market every `interval`. A market that fails keeps its last price until
+/// it's too old to show.
+pub fn spawn_refresh(
+    tokens: Vec<&'static TokenInfo>,
+    cache: PerpCache,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    let markets = tokens
+        .into_iter()
+        .filter_map(|token| {
+            DRIFT_MARKETS
+                .iter()
+                .find(|market| market.symbol == token.symbol)
+                .map(|market| (token.address, market))
+        })
+        .collect::<Vec<_>>();
+    tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        loop {
+            for (mint, market) in &markets {
+                match fetch_drift(&client, market).await {
+                    Ok(quote) => {
+                        cache.0.write().unwrap().insert(*mint, quote);
+                    }
+                    Err(e) => println!("{} Failed to refresh Drift {}: {}", "[ERROR]".bright_red(), market.name, e),
+                }
+            }
+            tokio::time::sleep(interval).await;
+        }
+    })
+}
@@ -0,0 +1,187 @@
+pub use scanner_core::i18n::{fill, Lang};
+
+/// Alert strings for one language. `{name}` placeholders are filled with [`fill`].
+pub struct Catalog {
+    pub opportunity_title: &'static str,
+    pub pair: &'static str,
+    /// `{venue}`
+    pub price: &'static str,
+    pub profit_margin: &'static str,
+    pub pool_details: &'static str,
+    pub liquidity: &'static str,
+    pub fee: &'static str,
+    pub size: &'static str,
+    pub breakdown: &'static str,
+    pub gross_spread: &'static str,
+    pub dex_fees: &'static str,
+    pub price_impact: &'static str,
+    pub expected_profit: &'static str,
+    pub network_fee: &'static str,
+    pub flash_loan_fee: &'static str,
+    pub net_profit: &'static str,
+    pub fee_unknown: &'static str,
+    pub market_24h: &'static str,
+    pub volume: &'static str,
+    /// Slot both pools were read at.
+    pub slot: &'static str,
+    pub perp_basis: &'static str,
+    /// `{venue}`, `{market}`, `{price}`, `{basis}`
+    pub perp_line: &'static str,
+    /// Label of the button that claims an opportunity.
+    pub ack: &'static str,
+    /// `{name}`
+    pub acked_by: &'static str,
+}
+
+static EN: Catalog = Catalog {
+    opportunity_title: "Arbitrage Opportunity Found!",
+    pair: "Pair",
+    price: "{venue} Price",
+    profit_margin: "Profit Margin",
+    pool_details: "Pool Details",
+    liquidity: "Liquidity",
+    fee: "Fee",
+    size: "Size",
+    breakdown: "Profit Breakdown",
+    gross_spread: "Gross Spread",
+    dex_fees: "DEX Fees",
+    price_impact: "Price Impact",
+    expected_profit: "Expected Profit",
+    network_fee: "Network + Priority Fee",
+    flash_loan_fee: "Flash-loan Fee",
+    net_profit: "Net Profit",
+    fee_unknown: "n/a (SOL price unknown)",
+    market_24h: "Market (24h)",
+    volume: "Vol",
+    slot: "Slot",
+    perp_basis: "Spot vs Perp",
+    perp_line: "{venue} {market}: {price} (basis {basis})",
+    ack: "Ack",
+    acked_by: "Acked by {name}",
+};
+
+static ES: Catalog = Catalog {
+    opportunity_title: "¡Oportunidad de arbitraje encontrada!",
+    pair: "Par",
+    price: "Precio en {venue}",
+    profit_margin: "Margen de beneficio",
+    pool_details: "Detalles de los pools",
+    liquidity: "Liquidez",
+    fee: "Comisión",
+    size: "Tamaño",
+    breakdown: "Desglose del beneficio",
+    gross_spread: "Spread bruto",
+    dex_fees: "Comisiones DEX",
+    price_impact: "Impacto en el precio",
+    expected_profit: "Beneficio esperado",
+    network_fee: "Comisión de red y prioridad",
+    flash_loan_fee: "Comisión del préstamo flash",
+    net_profit: "Beneficio neto",
+    fee_unknown: "n/d (precio de SOL desconocido)",
+    market_24h: "Mercado (24h)",
+    volume: "Vol.",
+    slot: "Slot",
+    perp_basis: "Spot vs perpetuo",
+    perp_line: "{venue} {market}: {price} (base {basis})",
+    ack: "Lo tomo",
+    acked_by: "Tomado por {name}",
+};
+
+static DE: Catalog = Catalog {
+    opportunity_title: "Arbitrage-Gelegenheit gefunden!",
+    pair: "Paar",
+    price: "Preis auf {venue}",
+    profit_margin: "Gewinnmarge",
+    pool_details: "Pool-Details",
+    liquidity: "Liquidität",
+    fee: "Gebühr",
+    size: "Größe",
+    breakdown: "Gewinnaufschlüsselung",
+    gross_spread: "Brutto-Spread",
+    dex_fees: "DEX-Gebühren",
+    price_impact: "Preisauswirkung",
+    expected_profit: "Erwarteter Gewinn",
+    network_fee: "Netzwerk- und Prioritätsgebühr",
+    flash_loan_fee: "Flash-Loan-Gebühr",
+    net_profit: "Nettogewinn",
+    fee_unknown: "k. A. (SOL-Preis unbekannt)",
+    market_24h: "Markt (24h)",
+    volume: "Vol.",
+    slot: "Slot",
+    perp_basis: "Spot vs. Perp",
+    perp_line: "{venue} {market}: {price} (Basis {basis})",
+    ack: "Übernehmen",
+    acked_by: "Übernommen von {name}",
+};
+
+static FR: Catalog = Catalog {
+    opportunity_title: "Opportunité d'arbitrage trouvée !",
+    pair: "Paire",
+    price: "Prix sur {venue}",
+    profit_margin: "Marge de profit",
+    pool_details: "Détails des pools",
+    liquidity: "Liquidité",
+    fee: "Frais",
+    size: "Taille",
+    breakdown: "Détail du profit",
+    gross_spread: "Spread brut",
+    dex_fees: "Frais DEX",
+    price_impact: "Impact sur le prix",
+    expected_profit: "Profit attendu",
+    network_fee: "Frais réseau et de priorité",
+    flash_loan_fee: "Frais de flash loan",
+    net_profit: "Profit net",
+    fee_unknown: "n/d (prix du SOL inconnu)",
+    market_24h: "Marché (24h)",
+    volume: "Vol.",
+    slot: "Slot",
+    perp_basis: "Spot vs perp",
+    perp_line: "{venue} {market} : {price} (base {basis})",
+    ack: "Je prends",
+    acked_by: "Pris par {name}",
+};
+
+static PT: Catalog = Catalog {
+    opportunity_title: "Oportunidade de arbitragem encontrada!",
+    pair: "Par",
+    price: "Preço na {venue}",
+    profit_margin: "Margem de lucro",
+    pool_details: "Detalhes das pools",
+    liquidity: "Liquidez",
+    fee: "Taxa",
+    size: "Tamanho",
+    breakdown: "Detalhamento do lucro",
+    gross_spread: "Spread bruto",
+    dex_fees: "Taxas DEX",
+    price_impact: "Impacto no preço",
+    expected_profit: "Lucro esperado",
+    network_fee: "Taxa de rede e prioridade",
+    flash_loan_fee: "Taxa do flash loan",
+    net_profit: "Lucro líquido",
+    fee_unknown: "n/d (preço do SOL desconhecido)",
+    market_24h: "Mercado (24h)",
+    volume: "Vol.",
+    slot: "Slot",
+    perp_basis: "Spot vs perpétuo",
+    perp_line: "{venue} {market}: {price} (base {basis})",
+    ack: "Assumir",
+    acked_by: "Assumido por {name}",
+};
+
+/// Each language's [`Catalog`], as [`Lang`] itself is shared with the
+/// EVM scanner.
+pub trait Localize {
+    fn catalog(self) -> &'static Catalog;
+}
+
+impl Localize for Lang {
+    fn catalog(self) -> &'static Catalog {
+        match self {
+            Lang::En => &EN,
+            Lang::Es => &ES,
+            Lang::De => &DE,
+            Lang::Fr => &FR,
+            Lang::Pt => &PT,
+        }
+    }
+}
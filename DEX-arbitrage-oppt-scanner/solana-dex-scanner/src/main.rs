@@ -1,119 +1,870 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
 use colored::*;
 use dotenv::dotenv;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
 use std::env;
 use std::time::Duration;
+use tokio::sync::mpsc;
+use uuid::Uuid;
 
+mod ack;
+mod bot;
+mod check;
+mod config;
+mod decode;
 mod dex;
+mod enrich;
+mod i18n;
 mod models;
+mod report;
+mod storage;
+mod strategy;
 mod utils;
+mod watcher;
 
-use dex::{orca, raydium};
-use models::token::TOKENS;
-use utils::{price, telegram};
+use ack::AckBoard;
+use dex::LocatedPools;
+use enrich::perps::{PerpCache, PerpQuote};
+use enrich::{MarketCache, MarketInfo};
+use i18n::{Lang, Localize};
+use models::pool::{Curve, PoolInfo, PoolReserves};
+use models::token::{self, TokenInfo, TOKENS};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use storage::leader::Leader;
+use storage::shared::SharedState;
+use storage::{OpportunityRecord, Storage};
+use strategy::Strategy;
+use utils::errors::ErrorBudget;
+use utils::format::{self as fmt, format_amount, format_percent};
+use utils::telegram::{self, Notifier};
+use utils::price::{self, Waterfall};
+use utils::secrets;
+use watcher::balance::BalanceWatcher;
+use watcher::wallet::WalletWatcher;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+// One signature's base fee
+const BASE_FEE_LAMPORTS: f64 = 5_000.0;
+// Compute budget requested for a two-swap arbitrage transaction
+const ARB_COMPUTE_UNITS: u64 = 400_000;
+const MARKET_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+const PERP_REFRESH_INTERVAL: Duration = Duration::from_secs(15);
+// Perp prices older than this are left out of alerts
+const PERP_MAX_AGE: chrono::TimeDelta = chrono::TimeDelta::seconds(120);
+// Any recent slot will do for the sample alert
+const SAMPLE_SLOT: u64 = 312_000_000;
+// Alerts waiting to be sent; beyond this, new alerts are dropped so a slow
+// Telegram can't stall pool polling
+const ALERT_QUEUE: usize = 256;
+// A pair whose reads fail this many cycles in a row (about a minute) is
+// quarantined for an hour by default
+const DEFAULT_QUARANTINE_AFTER: u32 = 12;
+const DEFAULT_QUARANTINE_MINS: i64 = 60;
+// A few hundred transactions at a moderate priority fee
+const DEFAULT_FEE_PAYER_FLOOR_SOL: f64 = 0.1;
+
+/// An alert ready to send: its dedupe key, which also names the Ack thread,
+/// and its text for each of the notifier's chats, in order.
+struct PendingAlert {
+    key: String,
+    messages: Vec<String>,
+}
+
+/// Sends alerts one at a time. Repeat alerts reply to the first one, and stop
+/// offering the Ack button once someone has claimed it.
+async fn deliver_alerts(
+    notifier: Arc<Notifier>,
+    acks: Arc<AckBoard>,
+    mut alerts: mpsc::Receiver<PendingAlert>,
+) {
+    while let Some(alert) = alerts.recv().await {
+        let thread = acks.open(&alert.key);
+        for (chat, mut message) in notifier.chats().iter().zip(alert.messages) {
+            let text = chat.lang.catalog();
+            if let Some(owner) = &thread.acked_by {
+                let acked_by = i18n::fill(
+                    text.acked_by,
+                    &[("name", &teloxide::utils::html::escape(owner))],
+                );
+                message = format!("✅ <b>{}</b>\n\n{}", acked_by, message);
+            }
+            let button = match thread.acked_by {
+                Some(_) => None,
+                None => Some(ack::button(text.ack, thread.id)),
+            };
+            if let Some(sent) = notifier
+                .send_threaded(chat, &message, thread.root(&chat.id), button)
+                .await
+            {
+                acks.record(&alert.key, &chat.id, sent);
+            }
+        }
+    }
+}
+
+/// Scans Raydium and Orca pools for arbitrage and alerts on Telegram.
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    /// Strategy profile with its own tokens and thresholds: solana-memes
+    #[arg(long, global = true)]
+    strategy: Option<String>,
+
+    /// TOML file with extra tokens, program IDs, profit thresholds, RPC and Telegram settings, under env vars [default: config.toml if it exists]
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Validate the configuration, token mints and DEX programs, then exit
+    CheckConfig,
+    /// Send a sample alert to every configured chat, then exit
+    NotifyTest,
+}
+
+/// `lamports` priced in `quote`, through SOL's latest scanned price unless
+/// the quote is SOL itself.
+fn lamports_in(lamports: f64, quote: &TokenInfo, quotes: &bot::QuoteBoard) -> Option<f64> {
+    let sol = lamports / LAMPORTS_PER_SOL;
+    if quote.symbol == "SOL" {
+        return Some(sol);
+    }
+    let quotes = quotes.read().unwrap();
+    let latest = quotes.get(&format!("SOL/{}", quote.symbol))?;
+    Some(sol * latest.mean_price()?)
+}
+
+fn is_usd_stable(token: &TokenInfo) -> bool {
+    matches!(token.symbol, "USDC" | "USDT")
+}
+
+/// Dexscreener and Birdeye links for each of `tokens`, with the 24h volume
+/// and price change each site last reported, if any.
+fn format_market(lang: Lang, tokens: &[(&TokenInfo, MarketInfo)]) -> String {
+    if tokens.is_empty() {
+        return String::new();
+    }
+    let text = lang.catalog();
+    let figures = |market: Option<enrich::TokenMarket>| {
+        let market = market.unwrap_or_default();
+        let mut parts = Vec::new();
+        if let Some(volume) = market.volume_24h_usd {
+            parts.push(format!("{} {}", text.volume, fmt::format_usd(volume)));
+        }
+        if let Some(change) = market.price_change_24h {
+            let sign = if change >= 0.0 { "+" } else { "" };
+            parts.push(format!("{}{}", sign, format_percent(change)));
+        }
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", parts.join(", "))
+        }
+    };
+    let lines = tokens
+        .iter()
+        .map(|(token, market)| {
+            format!(
+                "{}: <a href=\"{}\">Dexscreener</a>{} · <a href=\"{}\">Birdeye</a>{}\n",
+                token.symbol,
+                enrich::dexscreener_url(&token.address),
+                figures(market.dexscreener),
+                enrich::birdeye_url(&token.address),
+                figures(market.birdeye),
+            )
+        })
+        .collect::<String>();
+    format!("<b>{}:</b>\n{}\n", text.market_24h, lines)
+}
+
+/// The base token's perp mark price against the pair's spot price, for
+/// pairs quoted in a USD stablecoin, as perps are.
+fn format_basis(lang: Lang, quote: &TokenInfo, spot: f64, perp: Option<PerpQuote>) -> String {
+    let Some(perp) = perp.filter(|_| is_usd_stable(quote) && spot > 0.0) else {
+        return String::new();
+    };
+    let text = lang.catalog();
+    let basis = perp.basis(spot);
+    format!(
+        "<b>{}:</b>\n{}\n\n",
+        text.perp_basis,
+        i18n::fill(
+            text.perp_line,
+            &[
+                ("venue", perp.venue),
+                ("market", perp.market),
+                ("price", &format!("{} {}", format_amount(perp.mark), quote.symbol)),
+                ("basis", &format!("{}{}", if basis >= 0.0 { "+" } else { "" }, format_percent(basis))),
+            ],
+        )
+    )
+}
+
+/// Base token reserves of `pool`, in whole tokens.
+fn base_liquidity(pool: &PoolInfo) -> f64 {
+    pool.reserves.token_a as f64 / 10f64.powi(pool.reserves.decimals_a as i32)
+}
+
+/// The cheapest and dearest of `venues`' pools, by index.
+fn price_extremes(venues: &[(&str, PoolInfo)]) -> (usize, usize) {
+    let prices = venues.iter().map(|(_, pool)| price::pool_price(pool)).collect::<Vec<_>>();
+    let by_price = |a: &usize, b: &usize| prices[*a].total_cmp(&prices[*b]);
+    (
+        (0..prices.len()).min_by(by_price).unwrap_or_default(),
+        (0..prices.len()).max_by(by_price).unwrap_or_default(),
+    )
+}
+
+/// Identifies an opportunity across alerts, logs, storage and the Redis
+/// stream. It's derived from the slot, pair and direction, so finding the
+/// same opportunity again after a restart gives the same ID.
+fn opportunity_id(slot: u64, pair: &str, buy_venue: &str, sell_venue: &str) -> Uuid {
+    let key = format!("solana:{}:{}:{}>{}", slot, pair, buy_venue, sell_venue);
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&solana_sdk::hash::hash(key.as_bytes()).to_bytes()[..16]);
+    Uuid::new_v8(bytes)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn format_alert(
+    lang: Lang,
+    token_a: &TokenInfo,
+    token_b: &TokenInfo,
+    venues: &[(&str, PoolInfo)],
+    waterfall: Option<&Waterfall>,
+    market: &str,
+    slot: u64,
+    id: Uuid,
+) -> String {
+    let text = lang.catalog();
+    let (cheap, dear) = price_extremes(venues);
+    let prices = venues
+        .iter()
+        .map(|(venue, pool)| {
+            format!(
+                "{}: {}\n",
+                i18n::fill(text.price, &[("venue", venue)]),
+                format_amount(price::pool_price(pool))
+            )
+        })
+        .collect::<String>();
+    let details = venues
+        .iter()
+        .map(|(venue, pool)| {
+            format!(
+                "{}:\n- {}: {} {}\n- {}: {}\n\n",
+                venue,
+                text.liquidity,
+                format_amount(base_liquidity(pool)),
+                token_a.symbol,
+                text.fee,
+                format_percent(pool.fee as f64 / 1_000_000.0)
+            )
+        })
+        .collect::<String>();
+    let breakdown = match waterfall {
+        Some(waterfall) => {
+            let amount = |value: f64| format!("{} {}", format_amount(value), token_b.symbol);
+            let cost = |value: f64| format!("-{}", amount(value));
+            format!(
+                "<b>{}</b>\n\
+                {}: {}\n\
+                {}: +{}\n\
+                {}: {}\n\
+                {}: {}\n\
+                {}: {}\n\
+                {}: {}\n\
+                {}: {}\n\
+                {}: <b>{}</b>\n\n",
+                text.breakdown,
+                text.size,
+                amount(waterfall.size),
+                text.gross_spread,
+                amount(waterfall.gross),
+                text.dex_fees,
+                cost(waterfall.dex_fees),
+                text.price_impact,
+                cost(waterfall.price_impact),
+                text.expected_profit,
+                amount(waterfall.profit()),
+                text.network_fee,
+                waterfall
+                    .network_fee
+                    .map(cost)
+                    .unwrap_or_else(|| text.fee_unknown.to_string()),
+                text.flash_loan_fee,
+                cost(waterfall.flash_loan_fee),
+                text.net_profit,
+                waterfall
+                    .net()
+                    .map(amount)
+                    .unwrap_or_else(|| text.fee_unknown.to_string()),
+            )
+        }
+        None => String::new(),
+    };
+    format!(
+        "🚨 <b>{}</b>\n\n\
+        {}: {}/{} ({}/{})\n\
+        {}\
+        {}: {}\n\n\
+        {}{}\
+        <b>{}:</b>\n\
+        {}\
+        🆔 <code>{}</code>\n\
+        <i>{} · {} {}</i>",
+        text.opportunity_title,
+        text.pair,
+        token_a.symbol,
+        token_b.symbol,
+        token_a.address,
+        token_b.address,
+        prices,
+        text.profit_margin,
+        format_percent(price::calculate_profit_margin(
+            price::pool_price(&venues[cheap].1),
+            price::pool_price(&venues[dear].1)
+        )),
+        breakdown,
+        market,
+        text.pool_details,
+        details,
+        id,
+        fmt::format_time(chrono::Utc::now()),
+        text.slot,
+        slot
+    )
+}
+
+/// A made-up SOL/USDC opportunity, rendered exactly like a real alert, for
+/// checking that notifications arrive and display correctly.
+fn sample_alert(lang: Lang) -> String {
+    let sol = &TOKENS[0];
+    let usdc = &TOKENS[1];
+    let pool = |sol_reserve: u64, usdc_reserve: u64| PoolInfo {
+        reserves: PoolReserves {
+            token_a: sol_reserve * 10u64.pow(sol.decimals as u32),
+            token_b: usdc_reserve * 10u64.pow(usdc.decimals as u32),
+            decimals_a: sol.decimals,
+            decimals_b: usdc.decimals,
+        },
+        fee: 2_500,
+        curve: Curve::ConstantProduct,
+    };
+    let (cheap, dear) = (pool(50_000, 7_500_000), pool(40_000, 6_120_000));
+    let waterfall = price::waterfall(&cheap, &dear, Some(0.02), 0.0005);
+    let market = MarketInfo {
+        dexscreener: Some(enrich::TokenMarket {
+            volume_24h_usd: Some(1_850_000_000.0),
+            price_change_24h: Some(0.034),
+        }),
+        birdeye: None,
+    };
+    let perp = PerpQuote {
+        venue: "Drift",
+        market: "SOL-PERP",
+        mark: 151.8,
+        read_at: chrono::Utc::now(),
+    };
+    format!(
+        "🧪 <b>TEST</b> · sample data, not a real opportunity\n\n{}",
+        format_alert(
+            lang,
+            sol,
+            usdc,
+            &[("Raydium", cheap), ("Orca", dear)],
+            waterfall.as_ref(),
+            &format!("{}{}", format_basis(lang, usdc, 151.5, Some(perp)), format_market(lang, &[(sol, market)])),
+            SAMPLE_SLOT,
+            Uuid::new_v4(),
+        )
+    )
+}
+
+/// Sends [`sample_alert`] to every chat, regardless of leadership, and
+/// returns each chat's outcome.
+async fn send_test_alerts(notifier: &Notifier) -> Vec<(String, Result<()>)> {
+    let mut results = Vec::with_capacity(notifier.chats().len());
+    for chat in notifier.chats() {
+        let result = notifier.try_send(chat, &sample_alert(chat.lang)).await;
+        results.push((chat.id.clone(), result));
+    }
+    results
+}
+
+/// Records a failed read of `pair`, logging the error the first time it's
+/// seen this hour. Returns whether the pair was just quarantined.
+fn report_failure(errors: &mut ErrorBudget, pair: &str, error: &str) -> bool {
+    let failure = errors.fail(pair, error);
+    if failure.first {
+        println!("{} {} for {}", "[ERROR]".bright_red(), error, pair);
+    }
+    if failure.quarantined {
+        println!(
+            "{} Quarantining {} after repeated failures: {}",
+            "[ERROR]".bright_red(),
+            pair,
+            error
+        );
+    }
+    failure.quarantined
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
     colored::control::set_override(true);
+    let cli = Cli::parse();
+    let file = config::load(cli.config.as_deref())?;
+    file.apply_env();
+    file.apply()?;
+    if let Some(value) = env::var("QUOTE_PRIORITY").ok().filter(|s| !s.is_empty()) {
+        token::set_quote_priority(&value);
+    }
+    let pair_thresholds = file.pair_thresholds()?;
+    let strategy = cli.strategy.as_deref().map(Strategy::by_name).transpose()?;
+    let tokens: Vec<&'static TokenInfo> = match strategy {
+        Some(strategy) => strategy.tokens(),
+        None => TOKENS.iter().chain(token::configured()).collect(),
+    };
+    if let Some(Command::CheckConfig) = cli.command {
+        return check::run(&tokens).await;
+    }
 
-    let rpc_url = env::var("SOLANA_RPC_URL").expect("SOLANA_RPC_URL must be set");
-    let telegram_bot_token = env::var("TELEGRAM_BOT_TOKEN").expect("TELEGRAM_BOT_TOKEN must be set");
-    let telegram_chat_id = env::var("TELEGRAM_CHAT_ID").expect("TELEGRAM_CHAT_ID must be set");
-    let min_profit_threshold = env::var("MIN_PROFIT_THRESHOLD")
-        .unwrap_or_else(|_| "0.01".to_string())
-        .parse::<f64>()
-        .expect("MIN_PROFIT_THRESHOLD must be a valid number");
+    let rpc_url = secrets::require("SOLANA_RPC_URL").await?;
+    let telegram_bot_token = secrets::require("TELEGRAM_BOT_TOKEN").await?;
+    let default_lang = match env::var("ALERT_LANGUAGE").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<Lang>().context("Invalid ALERT_LANGUAGE")?,
+        None => Lang::default(),
+    };
+    let chats = telegram::parse_chats(
+        &env::var("TELEGRAM_CHAT_ID").context("TELEGRAM_CHAT_ID not set")?,
+        default_lang,
+    )
+    .context("Invalid TELEGRAM_CHAT_ID")?;
+    let min_profit_threshold = match env::var("MIN_PROFIT_THRESHOLD").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<f64>().context("Invalid MIN_PROFIT_THRESHOLD")?,
+        None => strategy.map_or(0.01, |s| s.min_profit_threshold),
+    };
+    let priority_fee = match env::var("PRIORITY_FEE_MICROLAMPORTS").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<u64>().context("Invalid PRIORITY_FEE_MICROLAMPORTS")?,
+        None => 0,
+    };
+    let network_fee_lamports =
+        BASE_FEE_LAMPORTS + (priority_fee * ARB_COMPUTE_UNITS) as f64 / 1_000_000.0;
+    let flash_loan_fee = match env::var("FLASH_LOAN_FEE").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<f64>().context("Invalid FLASH_LOAN_FEE")?,
+        None => 0.0,
+    };
+    let commitment = match env::var("SOLANA_COMMITMENT").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<CommitmentConfig>().context("Invalid SOLANA_COMMITMENT")?,
+        None => CommitmentConfig::confirmed(),
+    };
+    let quarantine_after = match env::var("QUARANTINE_AFTER_FAILURES").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<u32>().context("Invalid QUARANTINE_AFTER_FAILURES")?,
+        None => DEFAULT_QUARANTINE_AFTER,
+    };
+    let quarantine_mins = match env::var("QUARANTINE_MINS").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<i64>().context("Invalid QUARANTINE_MINS")?,
+        None => DEFAULT_QUARANTINE_MINS,
+    };
+    let locale = fmt::Locale::from_env()?;
+    fmt::init(locale.clone());
+    if let Some(Command::NotifyTest) = cli.command {
+        let notifier = Notifier::new(telegram_bot_token, chats, Leader::always());
+        let results = send_test_alerts(&notifier).await;
+        let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+        for (id, result) in results {
+            match result {
+                Ok(()) => println!("{} Test alert sent to {}", "[INFO]".bright_green(), id),
+                Err(e) => println!("{} Test alert to {} failed: {}", "[ERROR]".bright_red(), id, e),
+            }
+        }
+        anyhow::ensure!(failed == 0, "{} of {} chats unreachable", failed, notifier.chats().len());
+        return Ok(());
+    }
+
+    let watched_wallets = env::var("WATCHED_WALLETS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<Pubkey>())
+        .collect::<Result<Vec<_>, _>>()
+        .context("Invalid WATCHED_WALLETS")?;
+    let fee_payers = env::var("FEE_PAYER_WALLETS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<Pubkey>())
+        .collect::<Result<Vec<_>, _>>()
+        .context("Invalid FEE_PAYER_WALLETS")?;
+    let fee_payer_floor = match env::var("FEE_PAYER_FLOOR_SOL").ok().filter(|s| !s.is_empty()) {
+        Some(value) => value.parse::<f64>().context("Invalid FEE_PAYER_FLOOR_SOL")?,
+        None => DEFAULT_FEE_PAYER_FLOOR_SOL,
+    };
+
+    let database_path = env::var("DATABASE_PATH").unwrap_or_else(|_| "scanner.db".to_string());
+    let storage = Arc::new(Storage::open(&database_path)?);
+    if let Some(dir) = env::var("PARQUET_EXPORT_DIR").ok().filter(|d| !d.is_empty()) {
+        println!("{} Exporting daily Parquet files to {}", "[INFO]".bright_green(), dir);
+        let storage = Arc::clone(&storage);
+        tokio::spawn(async move { storage::parquet::run(&storage, Path::new(&dir)).await });
+    }
+    let alert_cooldown = Duration::from_secs(
+        match env::var("ALERT_COOLDOWN_SECS").ok().filter(|s| !s.is_empty()) {
+            Some(value) => value.parse::<u64>().context("Invalid ALERT_COOLDOWN_SECS")?,
+            None => strategy.map_or(60, |s| s.alert_cooldown_secs),
+        },
+    );
+    let redis_url = secrets::load("REDIS_URL").await?;
+    let redis_prefix = env::var("REDIS_KEY_PREFIX").unwrap_or_else(|_| "arb-scanner".to_string());
+    let shared = match &redis_url {
+        Some(url) => {
+            println!("{} Connecting to Redis...", "[INFO]".bright_green());
+            SharedState::redis(url, &redis_prefix).await?
+        }
+        None => SharedState::local(),
+    };
+    let leader_election = env::var("LEADER_ELECTION")
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false);
+    let leader = if leader_election {
+        let url = redis_url.as_deref().context("LEADER_ELECTION requires REDIS_URL")?;
+        let lease_secs = env::var("LEADER_LEASE_SECS")
+            .unwrap_or_else(|_| "15".to_string())
+            .parse::<u64>()
+            .context("Invalid LEADER_LEASE_SECS")?;
+        // The lease is renewed every third of it, so zero would spin
+        anyhow::ensure!(lease_secs > 0, "LEADER_LEASE_SECS must be at least 1");
+        let lease = Duration::from_secs(lease_secs);
+        Leader::elect(url, &redis_prefix, lease).await?
+    } else {
+        Leader::always()
+    };
+    let notifier = Arc::new(Notifier::new(telegram_bot_token, chats, leader));
+    if !notifier.is_leader() {
+        println!(
+            "{} Standing by: another replica holds the leader lease",
+            "[INFO]".bright_green()
+        );
+    }
+
+    let perps = PerpCache::default();
+    enrich::perps::spawn_refresh(tokens.clone(), perps.clone(), PERP_REFRESH_INTERVAL);
+    let markets = MarketCache::default();
+    enrich::spawn_refresh(
+        tokens.iter().map(|t| t.address).collect(),
+        secrets::load("BIRDEYE_API_KEY").await?,
+        markets.clone(),
+        MARKET_REFRESH_INTERVAL,
+    );
+
+    let client = Arc::new(solana_client::rpc_client::RpcClient::new(rpc_url.clone()));
+    let quotes = bot::QuoteBoard::default();
+    let acks = Arc::new(AckBoard::default());
+    tokio::spawn(bot::run(Arc::new(bot::BotContext {
+        notifier: Arc::clone(&notifier),
+        storage: Arc::clone(&storage),
+        quotes: Arc::clone(&quotes),
+        client: Arc::clone(&client),
+        acks: Arc::clone(&acks),
+        strategy: strategy.map(|s| s.name),
+        tokens: tokens.clone(),
+        min_profit_threshold,
+        pair_thresholds: pair_thresholds.clone(),
+        alert_cooldown,
+        redis: redis_url.is_some(),
+    })));
+    let (alerts, alert_queue) = mpsc::channel(ALERT_QUEUE);
+    tokio::spawn(deliver_alerts(Arc::clone(&notifier), Arc::clone(&acks), alert_queue));
+    tokio::spawn(report::run(
+        Arc::clone(&storage),
+        Arc::clone(&notifier),
+        Duration::from_secs(24 * 3600),
+    ));
+
+    if !watched_wallets.is_empty() {
+        println!(
+            "{} Watching {} wallets",
+            "[INFO]".bright_green(),
+            watched_wallets.len()
+        );
+        let watcher = WalletWatcher::new(rpc_url.clone(), watched_wallets);
+        tokio::spawn(watcher.run(Arc::clone(&notifier), Duration::from_secs(3600)));
+    }
+    if !fee_payers.is_empty() {
+        println!(
+            "{} Watching {} fee payers, floor {} SOL",
+            "[INFO]".bright_green(),
+            fee_payers.len(),
+            format_amount(fee_payer_floor)
+        );
+        let watcher = BalanceWatcher::new(rpc_url.clone(), fee_payers, fee_payer_floor);
+        tokio::spawn(watcher.run(Arc::clone(&notifier)));
+    }
 
-    let client = solana_client::rpc_client::RpcClient::new(rpc_url);
 
     println!(
         "{} Starting DEX arbitrage scanner...",
         "[INFO]".bright_green()
     );
+    let run_id = Uuid::new_v4();
+    println!("{} Run: {}", "[INFO]".bright_green(), run_id);
+    if let Some(strategy) = strategy {
+        println!("{} Strategy: {}", "[INFO]".bright_green(), strategy.name);
+    }
+    println!(
+        "{} Minimum profit threshold: {}",
+        "[INFO]".bright_green(),
+        format_percent(min_profit_threshold)
+    );
+    for (base, quote, threshold) in &pair_thresholds {
+        println!(
+            "{} Minimum profit threshold for {}/{}: {}",
+            "[INFO]".bright_green(),
+            base.symbol,
+            quote.symbol,
+            format_percent(*threshold)
+        );
+    }
+    // Pairs' own thresholds, either way round
+    let pair_thresholds: HashMap<String, f64> = pair_thresholds
+        .iter()
+        .flat_map(|(base, quote, threshold)| {
+            [
+                (format!("{}/{}", base.symbol, quote.symbol), *threshold),
+                (format!("{}/{}", quote.symbol, base.symbol), *threshold),
+            ]
+        })
+        .collect();
     println!(
-        "{} Minimum profit threshold: {:.2}%",
+        "{} Timezone: {}",
         "[INFO]".bright_green(),
-        min_profit_threshold * 100.0
+        locale.timezone_name()
     );
 
+    // Reserves of each pair's pools when it was last evaluated
+    let mut last_seen: HashMap<String, Vec<PoolReserves>> = HashMap::new();
+    let dexes = dex::venues();
+    // Each pair's pools by venue, located once
+    let mut located: HashMap<String, LocatedPools> = HashMap::new();
+    // Latest slot read at; reads never go back past it
+    let mut last_slot = 0;
+    let mut errors = ErrorBudget::new(quarantine_after, chrono::Duration::minutes(quarantine_mins));
     loop {
-        for token_a in TOKENS.iter() {
-            for token_b in TOKENS.iter() {
-                if token_a.address == token_b.address {
+        for &token_a in &tokens {
+            for &token_b in &tokens {
+                // Each pair is scanned once, the way it's shown
+                if token_a.address == token_b.address || token::orient(token_a, token_b).0.address != token_a.address {
+                    continue;
+                }
+
+                let pair = format!("{}/{}", token_a.symbol, token_b.symbol);
+                if errors.is_quarantined(&pair) {
                     continue;
                 }
+                let pools = match located.get(&pair) {
+                    Some(pools) => pools.clone(),
+                    None => {
+                        let mut pools = Vec::new();
+                        let mut failures = Vec::new();
+                        for venue in &dexes {
+                            match venue.locate(&client, token_a, token_b).await {
+                                Ok(Some(pool)) => pools.push((venue.name(), pool)),
+                                Ok(None) => {}
+                                Err(e) => failures.push(format!("{}: {}", venue.name(), e)),
+                            }
+                        }
+                        if pools.len() < 2 {
+                            let error = format!("Failed to locate pools: {}", failures.join(", "));
+                            report_failure(&mut errors, &pair, &error);
+                            continue;
+                        }
+                        if !failures.is_empty() {
+                            println!(
+                                "{} Scanning {} without some venues: {}",
+                                "[ERROR]".bright_red(),
+                                pair,
+                                failures.join(", ")
+                            );
+                        }
+                        located.insert(pair.clone(), pools.clone());
+                        pools
+                    }
+                };
+
+                match dex::read_pools(&client, &pools, commitment, last_slot) {
+                    Ok(read) => {
+                        errors.succeed(&pair);
+                        last_slot = last_slot.max(read.slot);
+                        let slot = read.slot;
+                        quotes.write().unwrap().insert(
+                            pair.clone(),
+                            bot::PairQuote {
+                                venues: read
+                                    .pools
+                                    .iter()
+                                    .map(|(venue, pool)| bot::VenueQuote {
+                                        venue,
+                                        price: price::pool_price(pool),
+                                        liquidity: base_liquidity(pool),
+                                    })
+                                    .collect(),
+                                read_at: chrono::Utc::now(),
+                            },
+                        );
+                        let venues = read.pools;
+                        let (cheap_index, dear_index) = price_extremes(&venues);
+                        let (buy_venue, cheap) = (venues[cheap_index].0, &venues[cheap_index].1);
+                        let (sell_venue, dear) = (venues[dear_index].0, &venues[dear_index].1);
+                        let (buy_price, sell_price) = (price::pool_price(cheap), price::pool_price(dear));
+                        let profit_margin = price::calculate_profit_margin(buy_price, sell_price);
+
+                        // No pool traded since the last cycle, so the
+                        // verdict can't have changed either
+                        let reserves = venues.iter().map(|(_, pool)| pool.reserves).collect::<Vec<_>>();
+                        if last_seen.get(&pair) == Some(&reserves) {
+                            continue;
+                        }
+                        last_seen.insert(pair.clone(), reserves);
 
-                match (
-                    raydium::get_pool_data(&client, token_a.address, token_b.address).await,
-                    orca::get_pool_data(&client, token_a.address, token_b.address).await,
-                ) {
-                    (Ok(raydium_pool), Ok(orca_pool)) => {
-                        let raydium_price = price::calculate_price(&raydium_pool.reserves);
-                        let orca_price = price::calculate_price(&orca_pool.reserves);
-                        let profit_margin = price::calculate_profit_margin(raydium_price, orca_price);
-
-                        if profit_margin >= min_profit_threshold {
-                            let message = format!(
-                                "🚨 <b>Arbitrage Opportunity Found!</b>\n\n\
-                                Pair: {}/{} ({}/{})\n\
-                                Raydium Price: {:.6}\n\
-                                Orca Price: {:.6}\n\
-                                Profit Margin: {:.2}%\n\n\
-                                <b>Pool Details:</b>\n\
-                                Raydium:\n\
-                                - Liquidity: {:.2} {}\n\
-                                - Fee: {:.2}%\n\n\
-                                Orca:\n\
-                                - Liquidity: {:.2} {}\n\
-                                - Fee: {:.2}%",
-                                token_a.symbol,
-                                token_b.symbol,
-                                token_a.address,
-                                token_b.address,
-                                raydium_price,
-                                orca_price,
-                                profit_margin * 100.0,
-                                raydium_pool.reserves.token_a as f64
-                                    / 10f64.powi(raydium_pool.reserves.decimals_a as i32),
-                                token_a.symbol,
-                                raydium_pool.fee as f64 / 10000.0,
-                                orca_pool.reserves.token_a as f64
-                                    / 10f64.powi(orca_pool.reserves.decimals_a as i32),
-                                token_a.symbol,
-                                orca_pool.fee as f64 / 10000.0
+                        let threshold = pair_thresholds.get(&pair).copied().unwrap_or(min_profit_threshold);
+                        if profit_margin >= threshold {
+                            let alert_key = format!(
+                                "solana:{}/{}:{}>{}",
+                                token_a.symbol, token_b.symbol, buy_venue, sell_venue
+                            );
+                            if !notifier.is_leader() {
+                                continue;
+                            }
+                            let id = opportunity_id(slot, &pair, buy_venue, sell_venue);
+                            // A restart can reread the slot it stopped at
+                            if storage.has_opportunity(id).unwrap_or(false) {
+                                println!(
+                                    "{} Opportunity {} already recorded, skipping",
+                                    "[INFO]".bright_green(),
+                                    id
+                                );
+                                continue;
+                            }
+                            if !shared.claim_alert(&alert_key, alert_cooldown).await {
+                                println!(
+                                    "{} Already alerted on {} recently, skipping",
+                                    "[INFO]".bright_green(),
+                                    alert_key
+                                );
+                                continue;
+                            }
+                            println!(
+                                "{} Arbitrage opportunity {} on {}: {} > {}, margin {}",
+                                "[ALERT]".bright_yellow(),
+                                id,
+                                pair,
+                                buy_venue,
+                                sell_venue,
+                                format_percent(profit_margin)
                             );
 
-                            if let Err(e) = telegram::send_telegram_message(
-                                &telegram_bot_token,
-                                &telegram_chat_id,
-                                &message,
-                            )
-                            .await
-                            {
+                            let network_fee = lamports_in(network_fee_lamports, token_b, &quotes);
+                            let waterfall =
+                                price::waterfall(cheap, dear, network_fee, flash_loan_fee);
+
+                            let market = [token_a, token_b]
+                                .into_iter()
+                                .filter(|token| !is_usd_stable(token))
+                                .map(|token| (token, markets.get(&token.address)))
+                                .collect::<Vec<_>>();
+                            let perp = perps.get(&token_a.address, PERP_MAX_AGE);
+                            let spot = (buy_price + sell_price) / 2.0;
+                            let messages = notifier
+                                .chats()
+                                .iter()
+                                .map(|chat| {
+                                    let market = format!(
+                                        "{}{}",
+                                        format_basis(chat.lang, token_b, spot, perp),
+                                        format_market(chat.lang, &market)
+                                    );
+                                    format_alert(
+                                        chat.lang,
+                                        token_a,
+                                        token_b,
+                                        &venues,
+                                        waterfall.as_ref(),
+                                        &market,
+                                        slot,
+                                        id,
+                                    )
+                                })
+                                .collect();
+                            if let Err(e) = alerts.try_send(PendingAlert {
+                                key: alert_key.clone(),
+                                messages,
+                            }) {
                                 println!(
-                                    "{} Failed to send Telegram message: {}",
+                                    "{} Dropping alert on {}: {}",
                                     "[ERROR]".bright_red(),
+                                    alert_key,
                                     e
                                 );
                             }
+
+                            if let Some(waterfall) = waterfall {
+                                let (size, profit) = (waterfall.size, waterfall.profit());
+                                let stable_quote = is_usd_stable(token_b);
+                                let record = OpportunityRecord {
+                                    id,
+                                    run_id,
+                                    timestamp: chrono::Utc::now(),
+                                    chain: "solana".to_string(),
+                                    pair: pair.clone(),
+                                    buy_venue: buy_venue.to_string(),
+                                    sell_venue: sell_venue.to_string(),
+                                    buy_price,
+                                    sell_price,
+                                    spread: profit_margin,
+                                    size,
+                                    profit,
+                                    profit_usd: stable_quote.then_some(profit),
+                                };
+                                if let Err(e) = storage.record_opportunity(&record) {
+                                    println!(
+                                        "{} Failed to store opportunity {}: {}",
+                                        "[ERROR]".bright_red(),
+                                        id,
+                                        e
+                                    );
+                                }
+                                shared.publish_opportunity(&record).await;
+                            }
                         }
                     }
-                    (Err(e1), Err(e2)) => {
-                        println!(
-                            "{} Failed to get pool data for {}/{}: Raydium: {}, Orca: {}",
-                            "[ERROR]".bright_red(),
-                            token_a.symbol,
-                            token_b.symbol,
-                            e1,
-                            e2
-                        );
+                    Err(e) => {
+                        let error = format!("Failed to read pools: {}", e);
+                        if report_failure(&mut errors, &pair, &error) {
+                            // The pools may have moved, so they're located
+                            // afresh once the quarantine ends
+                            located.remove(&pair);
+                        }
                     }
-                    _ => {}
                 }
             }
         }
 
+        if let Some(summary) = errors.summary() {
+            notifier.broadcast(&summary).await;
+        }
+
         tokio::time::sleep(Duration::from_secs(5)).await;
     }
 }
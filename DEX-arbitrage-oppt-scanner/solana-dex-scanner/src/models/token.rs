@@ -1,5 +1,6 @@
 use solana_sdk::pubkey::Pubkey;
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
+use std::cmp::Ordering;
 
 #[derive(Debug)]
 pub struct TokenInfo {
@@ -28,6 +29,88 @@ pub static TOKENS: Lazy<Vec<TokenInfo>> = Lazy::new(|| {
     ]
 });
 
-pub fn get_token_info(address: &Pubkey) -> Option<&TokenInfo> {
-    TOKENS.iter().find(|t| &t.address == address)
+/// Only scanned by the `solana-memes` strategy.
+pub static MEME_TOKENS: Lazy<Vec<TokenInfo>> = Lazy::new(|| {
+    vec![
+        TokenInfo {
+            address: "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263".parse().unwrap(),
+            symbol: "BONK",
+            decimals: 5,
+        },
+        TokenInfo {
+            address: "EKpQGSJtjMFqKZ9KQanSqYXRcF8fBopzLHYxdM65zcjm".parse().unwrap(),
+            symbol: "WIF",
+            decimals: 6,
+        },
+        TokenInfo {
+            address: "7GCihgDB8fe6KNjn2MYtkzZcRjQy3t9GHdC8uHYmW2hr".parse().unwrap(),
+            symbol: "POPCAT",
+            decimals: 9,
+        },
+    ]
+});
+
+/// Tokens added from the config file, scanned with `TOKENS`.
+static CONFIGURED: OnceCell<Vec<TokenInfo>> = OnceCell::new();
+
+pub fn register(tokens: Vec<TokenInfo>) {
+    let _ = CONFIGURED.set(tokens);
+}
+
+/// Tokens added from the config file.
+pub fn configured() -> &'static [TokenInfo] {
+    CONFIGURED.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// Every token the scanner knows, built in or configured.
+pub fn known() -> impl Iterator<Item = &'static TokenInfo> + Clone {
+    TOKENS.iter().chain(MEME_TOKENS.iter()).chain(configured())
+}
+
+pub fn get_token_info(address: &Pubkey) -> Option<&'static TokenInfo> {
+    known().find(|t| &t.address == address)
+}
+
+/// Looks up a known token by symbol, ignoring case.
+pub fn find_by_symbol(symbol: &str) -> Option<&'static TokenInfo> {
+    known().find(|t| t.symbol.eq_ignore_ascii_case(symbol))
+}
+
+/// Symbols preferred as a pair's quote, most preferred first, replacing the
+/// default order. See [`set_quote_priority`].
+static QUOTE_PRIORITY: OnceCell<Vec<String>> = OnceCell::new();
+
+/// Sets the symbols preferred as a pair's quote from `QUOTE_PRIORITY`,
+/// comma-separated and most preferred first, ignoring case. Tokens not
+/// listed are never preferred.
+pub fn set_quote_priority(value: &str) {
+    let symbols = value.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect();
+    let _ = QUOTE_PRIORITY.set(symbols);
+}
+
+/// Where `token` ranks as a pair's quote, lowest first, or `None` if it's
+/// never preferred. By default USD stables come first, then SOL.
+fn quote_rank(token: &TokenInfo) -> Option<usize> {
+    match QUOTE_PRIORITY.get() {
+        Some(symbols) => symbols.iter().position(|s| s.eq_ignore_ascii_case(token.symbol)),
+        None => match token.symbol {
+            "USDC" | "USDT" => Some(0),
+            "SOL" => Some(1),
+            _ => None,
+        },
+    }
+}
+
+/// `a` and `b` as `(base, quote)`, the way their pair is scanned and shown
+/// everywhere, e.g. SOL in USDC rather than USDC in SOL. The quote is the
+/// one ranked higher by the quote priority; with neither ranked higher, the
+/// lower mint is the base.
+pub fn orient<'a>(a: &'a TokenInfo, b: &'a TokenInfo) -> (&'a TokenInfo, &'a TokenInfo) {
+    let rank = |token| quote_rank(token).unwrap_or(usize::MAX);
+    match rank(a).cmp(&rank(b)) {
+        Ordering::Less => (b, a),
+        Ordering::Greater => (a, b),
+        Ordering::Equal if a.address < b.address => (a, b),
+        Ordering::Equal => (b, a),
+    }
 } 
\ No newline at end of file
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use chrono::{Duration as ChronoDuration, Utc};
+use colored::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::storage::Storage;
+use crate::utils::format::{format_percent, format_time, format_usd};
+use crate::utils::telegram::Notifier;
+
+fn build_report(storage: &Storage, window: ChronoDuration) -> Result<Option<String>> {
+    let to = Utc::now();
+    let from = to - window;
+    let stats = storage.profit_by_venue_pair(from)?;
+    if stats.is_empty() {
+        return Ok(None);
+    }
+
+    let total_count: u64 = stats.iter().map(|s| s.count).sum();
+    let total_profit: f64 = stats.iter().map(|s| s.profit_usd).sum();
+    let lines = stats
+        .iter()
+        .map(|s| {
+            format!(
+                "• <code>{}</code>: <b>{}</b> over {} opps (max spread {})",
+                s.venues,
+                format_usd(s.profit_usd),
+                s.count,
+                format_percent(s.max_spread)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(Some(format!(
+        "📊 <b>Daily Report</b>\n\
+        <i>{} – {}</i>\n\n\
+        Opportunities: <b>{}</b>\n\
+        Theoretical Profit: <b>{}</b> (before fees)\n\n\
+        <b>Profit by Venue Pair</b>\n{}",
+        format_time(from),
+        format_time(to),
+        total_count,
+        format_usd(total_profit),
+        lines
+    )))
+}
+
+/// Sends a report covering the previous `interval` every `interval`.
+pub async fn run(
+    storage: Arc<Storage>,
+    notifier: Arc<Notifier>,
+    interval: Duration,
+) {
+    loop {
+        tokio::time::sleep(interval).await;
+
+        let window = ChronoDuration::from_std(interval).unwrap_or(ChronoDuration::days(1));
+        match build_report(&storage, window) {
+            Ok(Some(message)) => notifier.broadcast(&message).await,
+            Ok(None) => {}
+            Err(e) => println!("{} Failed to build report: {}", "[ERROR]".bright_red(), e),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+pub mod leader;
+pub mod parquet;
+pub mod shared;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+pub use scanner_core::models::{OpportunityRecord, PairStats, VenuePairStats};
+
+const RECORD_COLUMNS: &str =
+    "timestamp, chain, pair, buy_venue, sell_venue, buy_price, sell_price, spread, size, profit, profit_usd, \
+     opportunity_id, run_id";
+
+/// Aggregate over one pair's opportunities.
+#[derive(Debug, Clone)]
+pub struct PairRanking {
+    pub pair: String,
+    pub count: u64,
+    pub profit_usd: f64,
+    pub max_spread: f64,
+}
+
+fn read_record(row: &Row) -> rusqlite::Result<OpportunityRecord> {
+    Ok(OpportunityRecord {
+        timestamp: DateTime::from_timestamp(row.get(0)?, 0).unwrap_or_default(),
+        chain: row.get(1)?,
+        pair: row.get(2)?,
+        buy_venue: row.get(3)?,
+        sell_venue: row.get(4)?,
+        buy_price: row.get(5)?,
+        sell_price: row.get(6)?,
+        spread: row.get(7)?,
+        size: row.get(8)?,
+        profit: row.get(9)?,
+        profit_usd: row.get(10)?,
+        id: read_uuid(row, 11)?,
+        run_id: read_uuid(row, 12)?,
+    })
+}
+
+fn read_uuid(row: &Row, index: usize) -> rusqlite::Result<Uuid> {
+    Ok(row
+        .get::<_, Option<String>>(index)?
+        .and_then(|id| id.parse().ok())
+        .unwrap_or_default())
+}
+
+/// SQLite-backed opportunity history.
+pub struct Storage {
+    conn: Mutex<Connection>,
+}
+
+impl Storage {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path).with_context(|| format!("Failed to open database {}", path))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS opportunities (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                chain TEXT NOT NULL,
+                pair TEXT NOT NULL,
+                buy_venue TEXT NOT NULL,
+                sell_venue TEXT NOT NULL,
+                buy_price REAL NOT NULL,
+                sell_price REAL NOT NULL,
+                spread REAL NOT NULL,
+                size REAL NOT NULL,
+                profit REAL NOT NULL,
+                profit_usd REAL,
+                opportunity_id TEXT,
+                run_id TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_opportunities_timestamp ON opportunities(timestamp);
+            CREATE INDEX IF NOT EXISTS idx_opportunities_pair ON opportunities(pair, timestamp);",
+        )?;
+        // Databases created before opportunities had IDs
+        if conn.prepare("SELECT opportunity_id FROM opportunities LIMIT 0").is_err() {
+            conn.execute_batch(
+                "ALTER TABLE opportunities ADD COLUMN opportunity_id TEXT;
+                ALTER TABLE opportunities ADD COLUMN run_id TEXT;",
+            )?;
+        }
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_opportunities_id ON opportunities(opportunity_id)",
+            [],
+        )?;
+
+        Ok(Storage {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Records `record`, unless one with its ID already is.
+    pub fn record_opportunity(&self, record: &OpportunityRecord) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            &format!(
+                "INSERT OR IGNORE INTO opportunities ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                RECORD_COLUMNS
+            ),
+            params![
+                record.timestamp.timestamp(),
+                record.chain,
+                record.pair,
+                record.buy_venue,
+                record.sell_venue,
+                record.buy_price,
+                record.sell_price,
+                record.spread,
+                record.size,
+                record.profit,
+                record.profit_usd,
+                record.id.to_string(),
+                record.run_id.to_string(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Whether an opportunity with ID `id` was recorded, by this run or an
+    /// earlier one.
+    pub fn has_opportunity(&self, id: Uuid) -> Result<bool> {
+        Ok(self.conn.lock().unwrap().query_row(
+            "SELECT EXISTS (SELECT 1 FROM opportunities WHERE opportunity_id = ?1)",
+            params![id.to_string()],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Theoretical USD profit per venue combination since `since`, most
+    /// profitable first.
+    pub fn profit_by_venue_pair(&self, since: DateTime<Utc>) -> Result<Vec<VenuePairStats>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT
+                CASE WHEN buy_venue < sell_venue
+                    THEN buy_venue || ' ↔ ' || sell_venue
+                    ELSE sell_venue || ' ↔ ' || buy_venue
+                END AS venues,
+                COUNT(*),
+                COALESCE(SUM(profit_usd), 0.0),
+                MAX(spread)
+             FROM opportunities
+             WHERE timestamp >= ?1
+             GROUP BY venues
+             ORDER BY 3 DESC",
+        )?;
+
+        let rows = stmt.query_map(params![since.timestamp()], |row| {
+            Ok(VenuePairStats {
+                venues: row.get(0)?,
+                count: row.get(1)?,
+                profit_usd: row.get(2)?,
+                max_spread: row.get(3)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// The `limit` pairs with the most theoretical USD profit since `since`,
+    /// ties broken by opportunity count.
+    pub fn top_pairs(&self, since: DateTime<Utc>, limit: usize) -> Result<Vec<PairRanking>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT pair, COUNT(*), COALESCE(SUM(profit_usd), 0.0), MAX(spread)
+             FROM opportunities
+             WHERE timestamp >= ?1
+             GROUP BY pair
+             ORDER BY 3 DESC, 2 DESC
+             LIMIT ?2",
+        )?;
+
+        let rows = stmt.query_map(params![since.timestamp(), limit as i64], |row| {
+            Ok(PairRanking {
+                pair: row.get(0)?,
+                count: row.get(1)?,
+                profit_usd: row.get(2)?,
+                max_spread: row.get(3)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// All opportunities recorded in `[from, to)`, oldest first.
+    pub fn opportunities_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<OpportunityRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM opportunities
+             WHERE timestamp >= ?1 AND timestamp < ?2
+             ORDER BY timestamp",
+            RECORD_COLUMNS
+        ))?;
+
+        let rows = stmt.query_map(params![from.timestamp(), to.timestamp()], read_record)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Statistics over opportunities on `base`/`quote`, in either order and
+    /// ignoring case, since `since`.
+    pub fn pair_stats(&self, base: &str, quote: &str, since: DateTime<Utc>) -> Result<PairStats> {
+        let conn = self.conn.lock().unwrap();
+        let filter = "WHERE timestamp >= ?1 AND LOWER(pair) IN (LOWER(?2), LOWER(?3))";
+        let forward = format!("{}/{}", base, quote);
+        let reverse = format!("{}/{}", quote, base);
+        let args = params![since.timestamp(), forward, reverse];
+
+        let (count, min_spread, avg_spread, max_spread, profit_usd) = conn.query_row(
+            &format!(
+                "SELECT COUNT(*), MIN(spread), AVG(spread), MAX(spread), COALESCE(SUM(profit_usd), 0.0)
+                 FROM opportunities {}",
+                filter
+            ),
+            args,
+            |row| {
+                Ok((
+                    row.get::<_, u64>(0)?,
+                    row.get::<_, Option<f64>>(1)?,
+                    row.get::<_, Option<f64>>(2)?,
+                    row.get::<_, Option<f64>>(3)?,
+                    row.get::<_, f64>(4)?,
+                ))
+            },
+        )?;
+
+        let widest = conn
+            .prepare(&format!(
+                "SELECT {} FROM opportunities {} ORDER BY spread DESC LIMIT 1",
+                RECORD_COLUMNS, filter
+            ))?
+            .query_map(args, read_record)?
+            .next()
+            .transpose()?;
+
+        Ok(PairStats {
+            count,
+            min_spread: min_spread.unwrap_or_default(),
+            avg_spread: avg_spread.unwrap_or_default(),
+            max_spread: max_spread.unwrap_or_default(),
+            profit_usd,
+            widest,
+        })
+    }
+}
@@ -0,0 +1,115 @@
+use anyhow::{Context, Result};
+use colored::*;
+use redis::aio::ConnectionManager;
+use redis::streams::StreamMaxlen;
+use redis::AsyncCommands;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::OpportunityRecord;
+
+// Approximate cap on the shared opportunity stream
+const MAX_SHARED_HISTORY: usize = 100_000;
+
+/// Alert dedupe/throttle state and opportunity history. With Redis it is
+/// shared by every instance pointing at the same server and key prefix, so
+/// horizontally scaled scanners don't alert twice on the same opportunity.
+pub enum SharedState {
+    Local(Mutex<HashMap<String, Instant>>),
+    Redis {
+        conn: Box<ConnectionManager>,
+        prefix: String,
+    },
+}
+
+impl SharedState {
+    pub fn local() -> Self {
+        SharedState::Local(Mutex::new(HashMap::new()))
+    }
+
+    pub async fn redis(url: &str, prefix: &str) -> Result<Self> {
+        let client = redis::Client::open(url).context("Invalid REDIS_URL")?;
+        let conn = ConnectionManager::new(client)
+            .await
+            .context("Failed to connect to Redis")?;
+        Ok(SharedState::Redis {
+            conn: Box::new(conn),
+            prefix: prefix.to_string(),
+        })
+    }
+
+    /// Claims the right to alert on `key` for `cooldown`. Returns `false` if
+    /// this or another instance already alerted on it within the cooldown.
+    pub async fn claim_alert(&self, key: &str, cooldown: Duration) -> bool {
+        match self {
+            SharedState::Local(claims) => {
+                let mut claims = claims.lock().unwrap();
+                let now = Instant::now();
+                claims.retain(|_, claimed_at| now.duration_since(*claimed_at) < cooldown);
+                if claims.contains_key(key) {
+                    return false;
+                }
+                claims.insert(key.to_string(), now);
+                true
+            }
+            SharedState::Redis { conn, prefix } => {
+                let result: redis::RedisResult<Option<String>> = redis::cmd("SET")
+                    .arg(format!("{}:alert:{}", prefix, key))
+                    .arg(1)
+                    .arg("NX")
+                    .arg("PX")
+                    .arg(cooldown.as_millis().max(1) as u64)
+                    .query_async(&mut ConnectionManager::clone(conn))
+                    .await;
+                match result {
+                    Ok(reply) => reply.is_some(),
+                    Err(e) => {
+                        // A duplicate alert is better than a missed one
+                        println!("{} Redis alert claim failed: {}", "[ERROR]".bright_red(), e);
+                        true
+                    }
+                }
+            }
+        }
+    }
+
+    /// Appends `record` to the shared opportunity stream. No-op without Redis,
+    /// where the local database is the only history.
+    pub async fn publish_opportunity(&self, record: &OpportunityRecord) {
+        let SharedState::Redis { conn, prefix } = self else {
+            return;
+        };
+
+        let fields = [
+            ("id", record.id.to_string()),
+            ("run_id", record.run_id.to_string()),
+            ("timestamp", record.timestamp.timestamp().to_string()),
+            ("chain", record.chain.clone()),
+            ("pair", record.pair.clone()),
+            ("buy_venue", record.buy_venue.clone()),
+            ("sell_venue", record.sell_venue.clone()),
+            ("buy_price", record.buy_price.to_string()),
+            ("sell_price", record.sell_price.to_string()),
+            ("spread", record.spread.to_string()),
+            ("size", record.size.to_string()),
+            ("profit", record.profit.to_string()),
+            (
+                "profit_usd",
+                record.profit_usd.map(|p| p.to_string()).unwrap_or_default(),
+            ),
+        ];
+
+        let result: redis::RedisResult<String> = ConnectionManager::clone(conn)
+            .xadd_maxlen(
+                format!("{}:opportunities", prefix),
+                StreamMaxlen::Approx(MAX_SHARED_HISTORY),
+                "*",
+                &fields,
+            )
+            .await;
+        if let Err(e) = result {
+            println!("{} Failed to publish opportunity to Redis: {}", "[ERROR]".bright_red(), e);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use anyhow::{bail, Result};
+
+use crate::models::token::{self, TokenInfo};
+
+/// A named strategy profile: the tokens to scan and the thresholds and
+/// alert cooldown to scan them with.
+pub struct Strategy {
+    pub name: &'static str,
+    pub symbols: &'static [&'static str],
+    pub min_profit_threshold: f64,
+    pub alert_cooldown_secs: u64,
+}
+
+// Memecoin pools are thin and jumpy: small gaps close before they can be
+// traded, and one pump would otherwise alert every scan
+static SOLANA_MEMES: Strategy = Strategy {
+    name: "solana-memes",
+    symbols: &["SOL", "USDC", "BONK", "WIF", "POPCAT"],
+    min_profit_threshold: 0.02,
+    alert_cooldown_secs: 120,
+};
+
+impl Strategy {
+    pub fn by_name(name: &str) -> Result<&'static Strategy> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "solana-memes" => Ok(&SOLANA_MEMES),
+            "eth-majors" | "stable-depeg" | "lst-basis" => {
+                bail!("{} runs on the EVM scanner (DEX-scanner-rust)", name)
+            }
+            other => bail!("Unknown strategy {:?} (expected solana-memes)", other),
+        }
+    }
+
+    pub fn tokens(&self) -> Vec<&'static TokenInfo> {
+        token::known()
+            .filter(|t| self.symbols.contains(&t.symbol))
+            .collect()
+    }
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use teloxide::utils::html;
+
+use crate::utils::format::format_time;
+
+// Repeats of an error are summarized once per window
+const SUMMARY_WINDOW_MINS: i64 = 60;
+
+/// What a failure means for logging and scanning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Failure {
+    /// The first time this error was seen for the subject this window, so
+    /// worth logging; repeats only count towards the summary.
+    pub first: bool,
+    /// The subject just failed too many times in a row and is out of the
+    /// scan until its quarantine ends.
+    pub quarantined: bool,
+}
+
+/// Keeps a subject (a pair) that fails every cycle from flooding the log:
+/// each distinct error is logged once an hour, with a summary of how often
+/// it repeated, and a subject failing `quarantine_after` reads in a row is
+/// skipped for `quarantine_for` before it's retried.
+pub struct ErrorBudget {
+    quarantine_after: u32,
+    quarantine_for: Duration,
+    window_start: DateTime<Utc>,
+    /// (subject, error) -> times seen this window
+    repeats: HashMap<(String, String), u64>,
+    /// Subject -> failures since it last succeeded
+    streaks: HashMap<String, u32>,
+    /// Subject -> when it's retried
+    quarantined: HashMap<String, DateTime<Utc>>,
+    /// Subjects quarantined this window, for the summary
+    newly_quarantined: Vec<String>,
+}
+
+impl ErrorBudget {
+    pub fn new(quarantine_after: u32, quarantine_for: Duration) -> Self {
+        ErrorBudget {
+            quarantine_after,
+            quarantine_for,
+            window_start: Utc::now(),
+            repeats: HashMap::new(),
+            streaks: HashMap::new(),
+            quarantined: HashMap::new(),
+            newly_quarantined: Vec::new(),
+        }
+    }
+
+    /// Whether `subject` is quarantined, releasing it once its time is up.
+    pub fn is_quarantined(&mut self, subject: &str) -> bool {
+        match self.quarantined.get(subject) {
+            Some(&until) if Utc::now() < until => true,
+            Some(_) => {
+                self.quarantined.remove(subject);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Records that reading `subject` failed with `error`.
+    pub fn fail(&mut self, subject: &str, error: &str) -> Failure {
+        let count = self
+            .repeats
+            .entry((subject.to_string(), error.to_string()))
+            .or_default();
+        *count += 1;
+        let first = *count == 1;
+
+        let streak = self.streaks.entry(subject.to_string()).or_default();
+        *streak += 1;
+        let quarantined = self.quarantine_after > 0 && *streak >= self.quarantine_after;
+        if quarantined {
+            self.streaks.remove(subject);
+            self.quarantined.insert(subject.to_string(), Utc::now() + self.quarantine_for);
+            self.newly_quarantined.push(subject.to_string());
+        }
+        Failure { first, quarantined }
+    }
+
+    /// Records that reading `subject` succeeded, ending its failure streak.
+    pub fn succeed(&mut self, subject: &str) {
+        self.streaks.remove(subject);
+    }
+
+    /// Once a window has passed, a Telegram summary of the errors that
+    /// repeated in it and the subjects quarantined, if there were any, and
+    /// a fresh window.
+    pub fn summary(&mut self) -> Option<String> {
+        let now = Utc::now();
+        if now - self.window_start < Duration::minutes(SUMMARY_WINDOW_MINS) {
+            return None;
+        }
+        let from = std::mem::replace(&mut self.window_start, now);
+        let mut repeated = std::mem::take(&mut self.repeats)
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .collect::<Vec<_>>();
+        let newly_quarantined = std::mem::take(&mut self.newly_quarantined);
+        if repeated.is_empty() && newly_quarantined.is_empty() {
+            return None;
+        }
+        repeated.sort_by_key(|r| std::cmp::Reverse(r.1));
+
+        let mut message = format!(
+            "🔇 <b>Repeated Errors</b>\n<i>{} – {}</i>",
+            format_time(from),
+            format_time(now)
+        );
+        if !repeated.is_empty() {
+            message.push_str("\n\n");
+            message.push_str(
+                &repeated
+                    .iter()
+                    .map(|((subject, error), count)| {
+                        format!("• <code>{}</code> ×{}: {}", html::escape(subject), count, html::escape(error))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+        if !newly_quarantined.is_empty() {
+            message.push_str(&format!(
+                "\n\n<b>Quarantined</b> after {} failures in a row, retried after {}m:\n{}",
+                self.quarantine_after,
+                self.quarantine_for.num_minutes(),
+                newly_quarantined
+                    .iter()
+                    .map(|subject| format!("• <code>{}</code>", html::escape(subject)))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ));
+        }
+        Some(message)
+    }
+}
@@ -1,2 +1,6 @@
+pub mod errors;
 pub mod price;
-pub mod telegram; 
\ No newline at end of file
+pub mod secrets;
+pub mod telegram;
+
+pub use scanner_core::format;
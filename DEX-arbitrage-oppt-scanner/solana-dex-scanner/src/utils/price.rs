@@ -1,4 +1,6 @@
-use crate::models::pool::PoolReserves;
+pub use scanner_core::price::{calculate_profit_margin, get_amount_out, optimal_arb_input};
+
+use crate::models::pool::{Curve, PoolInfo, PoolReserves};
 
 pub fn calculate_price(reserves: &PoolReserves) -> f64 {
     let amount_a = reserves.token_a as f64 / 10f64.powi(reserves.decimals_a as i32);
@@ -11,12 +13,182 @@ pub fn calculate_price(reserves: &PoolReserves) -> f64 {
     amount_b / amount_a
 }
 
-pub fn calculate_profit_margin(price_a: f64, price_b: f64) -> f64 {
-    if price_a > price_b && price_b > 0.0 {
-        price_a / price_b - 1.0
-    } else if price_b > price_a && price_a > 0.0 {
-        price_b / price_a - 1.0
-    } else {
-        0.0
+/// Spot price of token A in token B on `pool`'s curve.
+pub fn pool_price(pool: &PoolInfo) -> f64 {
+    match pool.curve {
+        Curve::ConstantProduct => calculate_price(&pool.reserves),
+        Curve::StableSwap { amp } => {
+            let (x, y) = units(&pool.reserves);
+            if x <= 0.0 || y <= 0.0 {
+                return 0.0;
+            }
+            // Slope of the invariant's level curve through the reserves
+            let leverage = amp as f64 * 2.0;
+            let d = stable_swap_d(amp as f64, x, y);
+            let d3 = d * d * d / 4.0;
+            (leverage + d3 / (x * x * y)) / (leverage + d3 / (x * y * y))
+        }
+    }
+}
+
+/// StableSwap invariant `D` of a two-token pool, by Newton's method the way
+/// Saber computes it; its `amp` already carries Curve's `n^(n-1)` factor.
+pub fn stable_swap_d(amp: f64, x: f64, y: f64) -> f64 {
+    if x <= 0.0 || y <= 0.0 {
+        return 0.0;
+    }
+    let (sum, leverage) = (x + y, amp * 2.0);
+    let mut d = sum;
+    for _ in 0..256 {
+        let d_p = d * d / (x * 2.0) * d / (y * 2.0);
+        let previous = d;
+        d = (leverage * sum + d_p * 2.0) * d / ((leverage - 1.0) * d + 3.0 * d_p);
+        if (d - previous).abs() <= previous * 1e-12 {
+            break;
+        }
+    }
+    d
+}
+
+/// The other reserve that keeps the invariant at `d` once one is `x`.
+fn stable_swap_y(amp: f64, x: f64, d: f64) -> f64 {
+    let leverage = amp * 2.0;
+    let c = d * d / (x * 2.0) * d / (leverage * 2.0);
+    let b = x + d / leverage;
+    let mut y = d;
+    for _ in 0..256 {
+        let previous = y;
+        y = (y * y + c) / (2.0 * y + b - d);
+        if (y - previous).abs() <= previous * 1e-12 {
+            break;
+        }
+    }
+    y
+}
+
+/// StableSwap output for `amount_in`, all amounts in whole-token units. The
+/// fee comes off the output, as Saber takes it.
+pub fn get_stable_amount_out(amount_in: f64, reserve_in: f64, reserve_out: f64, fee: f64, amp: u64) -> f64 {
+    if amount_in <= 0.0 || reserve_in <= 0.0 || reserve_out <= 0.0 {
+        return 0.0;
     }
-} 
\ No newline at end of file
+    let d = stable_swap_d(amp as f64, reserve_in, reserve_out);
+    let out = reserve_out - stable_swap_y(amp as f64, reserve_in + amount_in, d);
+    out.max(0.0) * (1.0 - fee)
+}
+
+/// Output of swapping `amount_in` through `pool`, token B for A when
+/// `b_to_a`, else A for B.
+fn swap_out(pool: &PoolInfo, amount_in: f64, b_to_a: bool, fee: f64) -> f64 {
+    let (a, b) = units(&pool.reserves);
+    let (reserve_in, reserve_out) = if b_to_a { (b, a) } else { (a, b) };
+    match pool.curve {
+        Curve::ConstantProduct => get_amount_out(amount_in, reserve_in, reserve_out, fee),
+        Curve::StableSwap { amp } => get_stable_amount_out(amount_in, reserve_in, reserve_out, fee, amp),
+    }
+}
+
+/// Where the spread goes when buying token A with token B on the cheaper
+/// pool and selling it on the dearer one at the profit-maximising size, in
+/// token B units.
+/// The lines add up:
+/// `gross - dex_fees - price_impact - network_fee - flash_loan_fee = net`.
+#[derive(Debug, Clone, Copy)]
+pub struct Waterfall {
+    /// Token B spent on the buy leg.
+    pub size: f64,
+    pub gross: f64,
+    pub dex_fees: f64,
+    pub price_impact: f64,
+    /// Transaction and priority fee, if SOL can be priced in token B.
+    pub network_fee: Option<f64>,
+    pub flash_loan_fee: f64,
+}
+
+impl Waterfall {
+    /// Profit after swap fees and price impact, before transaction costs.
+    pub fn profit(&self) -> f64 {
+        self.gross - self.dex_fees - self.price_impact
+    }
+
+    pub fn net(&self) -> Option<f64> {
+        self.network_fee
+            .map(|fee| self.profit() - fee - self.flash_loan_fee)
+    }
+}
+
+/// Sizes the arbitrage between `cheap` and `dear` and breaks its profit down
+/// by cost, or `None` when no size is profitable. `flash_loan_rate` is the
+/// fee on borrowing the input, 0 when trading own capital.
+pub fn waterfall(
+    cheap: &PoolInfo,
+    dear: &PoolInfo,
+    network_fee: Option<f64>,
+    flash_loan_rate: f64,
+) -> Option<Waterfall> {
+    let legs = arb_legs(cheap, dear)?;
+    let sell_price = pool_price(dear);
+    // Trading the input at the two spot prices with no fees or impact
+    let gross = legs.input * (sell_price / pool_price(cheap) - 1.0);
+    let dex_fees = legs.input * legs.fee + legs.bought * legs.fee * sell_price;
+    Some(Waterfall {
+        size: legs.input,
+        gross,
+        dex_fees,
+        // Whatever the fees don't explain is slippage along the curves
+        price_impact: gross - dex_fees - (legs.proceeds - legs.input),
+        network_fee,
+        flash_loan_fee: legs.input * flash_loan_rate,
+    })
+}
+
+struct ArbLegs {
+    input: f64,
+    bought: f64,
+    proceeds: f64,
+    fee: f64,
+}
+
+/// Reserves in whole-token units.
+fn units(r: &PoolReserves) -> (f64, f64) {
+    (
+        r.token_a as f64 / 10f64.powi(r.decimals_a as i32),
+        r.token_b as f64 / 10f64.powi(r.decimals_b as i32),
+    )
+}
+
+fn arb_legs(cheap: &PoolInfo, dear: &PoolInfo) -> Option<ArbLegs> {
+    let (cheap_a, cheap_b) = units(&cheap.reserves);
+    let (dear_a, dear_b) = units(&dear.reserves);
+    // Fees are stored in hundredths of a basis point
+    let fee = cheap.fee.max(dear.fee) as f64 / 1_000_000.0;
+    let round_trip = |input: f64| swap_out(dear, swap_out(cheap, input, true, fee), false, fee);
+
+    let input = if (cheap.curve, dear.curve) == (Curve::ConstantProduct, Curve::ConstantProduct) {
+        optimal_arb_input(cheap_b, cheap_a, dear_a, dear_b, fee, fee)?
+    } else {
+        // No closed form across a stable curve, but the profit is concave
+        // in the input, so a golden-section search finds its peak
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        let (mut low, mut high) = (0.0, cheap_b.min(dear_b));
+        for _ in 0..100 {
+            let left = high - ratio * (high - low);
+            let right = low + ratio * (high - low);
+            if round_trip(left) - left < round_trip(right) - right {
+                low = left;
+            } else {
+                high = right;
+            }
+        }
+        let input = (low + high) / 2.0;
+        (round_trip(input) > input).then_some(input)?
+    };
+    let bought = swap_out(cheap, input, true, fee);
+    let proceeds = swap_out(dear, bought, false, fee);
+    Some(ArbLegs {
+        input,
+        bought,
+        proceeds,
+        fee,
+    })
+}
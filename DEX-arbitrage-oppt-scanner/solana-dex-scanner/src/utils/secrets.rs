@@ -0,0 +1,80 @@
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::Value;
+use std::env;
+use std::fs;
+
+/// Looks up secret `name` from, in order:
+///
+/// - the `NAME` env var
+/// - the file named by `NAME_FILE` (Docker/Kubernetes secrets convention)
+/// - HashiCorp Vault, when `NAME_VAULT` is set to `<path>#<field>` (e.g.
+///   `secret/data/scanner#telegram_token`), using `VAULT_ADDR` and
+///   `VAULT_TOKEN`/`VAULT_TOKEN_FILE`
+///
+/// Returns `None` if none of them is set.
+pub async fn load(name: &str) -> Result<Option<String>> {
+    if let Some(value) = var(name) {
+        return Ok(Some(value));
+    }
+    if let Some(value) = from_file(name)? {
+        return Ok(Some(value));
+    }
+    if let Some(reference) = var(&format!("{}_VAULT", name)) {
+        let value = from_vault(&reference)
+            .await
+            .with_context(|| format!("Failed to read {} from Vault", name))?;
+        return Ok(Some(value));
+    }
+    Ok(None)
+}
+
+/// Like [`load`], but errors if the secret isn't configured anywhere.
+pub async fn require(name: &str) -> Result<String> {
+    load(name)
+        .await?
+        .ok_or_else(|| anyhow!("{} not set (also checked {}_FILE and {}_VAULT)", name, name, name))
+}
+
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn from_file(name: &str) -> Result<Option<String>> {
+    match var(&format!("{}_FILE", name)) {
+        Some(path) => {
+            let contents = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}_FILE {}", name, path))?;
+            Ok(Some(contents.trim().to_string()))
+        }
+        None => Ok(None),
+    }
+}
+
+async fn from_vault(reference: &str) -> Result<String> {
+    let (path, field) = reference
+        .split_once('#')
+        .ok_or_else(|| anyhow!("expected <path>#<field>, got {:?}", reference))?;
+    let addr = var("VAULT_ADDR").context("VAULT_ADDR not set")?;
+    let token = match var("VAULT_TOKEN") {
+        Some(token) => token,
+        None => from_file("VAULT_TOKEN")?.context("VAULT_TOKEN not set")?,
+    };
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/v1/{}", addr.trim_end_matches('/'), path.trim_start_matches('/')))
+        .header("X-Vault-Token", token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Value>()
+        .await?;
+
+    // KV v2 nests the secret under data.data, KV v1 directly under data
+    let data = &response["data"];
+    let secret = if data["data"].is_object() { &data["data"] } else { data };
+    match &secret[field] {
+        Value::String(value) => Ok(value.clone()),
+        Value::Null => bail!("field {:?} not found at {}", field, path),
+        other => Ok(other.to_string()),
+    }
+}
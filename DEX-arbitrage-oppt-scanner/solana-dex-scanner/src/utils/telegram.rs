@@ -1,36 +1,187 @@
 use anyhow::Result;
 use colored::*;
 use reqwest::Client;
-use serde_json::json;
+use serde_json::{json, Value};
+
+use std::sync::Arc;
+
+use crate::i18n::Lang;
+use crate::storage::leader::Leader;
+
+/// A Telegram chat that receives alerts, and the language to send them in.
+#[derive(Debug, Clone)]
+pub struct Chat {
+    pub id: String,
+    pub lang: Lang,
+}
+
+/// Parses `TELEGRAM_CHAT_ID`: comma-separated chat ids, each optionally
+/// suffixed with `:<lang>`, e.g. `-1001234:es,5678`.
+pub fn parse_chats(value: &str, default_lang: Lang) -> Result<Vec<Chat>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|entry| {
+            let (id, lang) = match entry.split_once(':') {
+                Some((id, lang)) => (id, lang.parse()?),
+                None => (entry, default_lang),
+            };
+            Ok(Chat {
+                id: id.trim().to_string(),
+                lang,
+            })
+        })
+        .collect()
+}
 
 pub async fn send_telegram_message(
     bot_token: &str,
     chat_id: &str,
     message: &str,
 ) -> Result<()> {
+    post_message(
+        bot_token,
+        json!({
+            "chat_id": chat_id,
+            "text": message,
+            "parse_mode": "HTML"
+        }),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Sends a `sendMessage` request, returning the sent message's id.
+async fn post_message(bot_token: &str, body: Value) -> Result<i32> {
     let client = Client::new();
     let url = format!(
         "https://api.telegram.org/bot{}/sendMessage",
         bot_token
     );
 
-    let response = client
-        .post(&url)
-        .json(&json!({
-            "chat_id": chat_id,
-            "text": message,
-            "parse_mode": "HTML"
-        }))
+    let response = client.post(&url).json(&body).send().await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("{}", response.text().await?);
+    }
+
+    let sent = response.json::<Value>().await?;
+    Ok(sent["result"]["message_id"].as_i64().unwrap_or_default() as i32)
+}
+
+/// Checks `bot_token` with Telegram's `getMe`, printing the bot's username.
+pub async fn get_me(bot_token: &str) -> Result<()> {
+    let url = format!("https://api.telegram.org/bot{}/getMe", bot_token);
+    let response = Client::new()
+        .get(&url)
         .send()
+        .await?
+        .json::<serde_json::Value>()
         .await?;
 
-    if !response.status().is_success() {
-        println!(
-            "{} Failed to send Telegram message: {}",
-            "[ERROR]".bright_red(),
-            response.text().await?
+    if response["ok"] != true {
+        anyhow::bail!(
+            "Telegram rejected TELEGRAM_BOT_TOKEN: {}",
+            response["description"].as_str().unwrap_or("unknown error")
         );
     }
-
+    println!(
+        "  bot is @{}",
+        response["result"]["username"].as_str().unwrap_or("?")
+    );
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// The configured chats, gated on this replica being the leader.
+pub struct Notifier {
+    bot_token: String,
+    chats: Vec<Chat>,
+    leader: Arc<Leader>,
+}
+
+impl Notifier {
+    pub fn new(bot_token: String, chats: Vec<Chat>, leader: Arc<Leader>) -> Self {
+        Notifier {
+            bot_token,
+            chats,
+            leader,
+        }
+    }
+
+    pub fn chats(&self) -> &[Chat] {
+        &self.chats
+    }
+
+    pub fn bot_token(&self) -> &str {
+        &self.bot_token
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.leader.is_leader()
+    }
+
+    /// Sends `message` to one chat even on a standby, returning the error
+    /// instead of logging it.
+    pub async fn try_send(&self, chat: &Chat, message: &str) -> Result<()> {
+        send_telegram_message(&self.bot_token, &chat.id, message).await
+    }
+
+    /// Sends `message` to one chat, logging failures.
+    pub async fn send(&self, chat: &Chat, message: &str) {
+        if !self.is_leader() {
+            return;
+        }
+        if let Err(e) = send_telegram_message(&self.bot_token, &chat.id, message).await {
+            println!(
+                "{} Failed to send Telegram message: {}",
+                "[ERROR]".bright_red(),
+                e
+            );
+        }
+    }
+
+    /// Sends `message` to one chat as a reply to `reply_to`, with an optional
+    /// inline keyboard. Returns the sent message's id, or `None` on a standby
+    /// or failure (which is logged).
+    pub async fn send_threaded(
+        &self,
+        chat: &Chat,
+        message: &str,
+        reply_to: Option<i32>,
+        reply_markup: Option<Value>,
+    ) -> Option<i32> {
+        if !self.is_leader() {
+            return None;
+        }
+        let mut body = json!({
+            "chat_id": chat.id,
+            "text": message,
+            "parse_mode": "HTML"
+        });
+        if let Some(reply_to) = reply_to {
+            body["reply_to_message_id"] = json!(reply_to);
+        }
+        if let Some(reply_markup) = reply_markup {
+            body["reply_markup"] = reply_markup;
+        }
+        match post_message(&self.bot_token, body).await {
+            Ok(id) => Some(id),
+            Err(e) => {
+                println!(
+                    "{} Failed to send Telegram message: {}",
+                    "[ERROR]".bright_red(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Sends an untranslated message to every chat.
+    pub async fn broadcast(&self, message: &str) {
+        for chat in &self.chats {
+            self.send(chat, message).await;
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use colored::*;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::utils::format::{format_amount, format_time};
+use crate::utils::telegram::Notifier;
+use crate::LAMPORTS_PER_SOL;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Watches fee-payer wallets and alerts when one holds less SOL than the
+/// floor, since a fee payer that runs dry stops an executor without a word.
+pub struct BalanceWatcher {
+    client: RpcClient,
+    wallets: Vec<Pubkey>,
+    floor_sol: f64,
+    // Wallets alerted on and not topped up since
+    low: HashSet<Pubkey>,
+}
+
+impl BalanceWatcher {
+    pub fn new(rpc_url: String, wallets: Vec<Pubkey>, floor_sol: f64) -> Self {
+        BalanceWatcher {
+            client: RpcClient::new(rpc_url),
+            wallets,
+            floor_sol,
+            low: HashSet::new(),
+        }
+    }
+
+    /// Polls every wallet's balance, alerting once per dip below the floor
+    /// and again only after the wallet has been topped back up.
+    pub async fn run(mut self, notifier: Arc<Notifier>) {
+        loop {
+            for wallet in self.wallets.clone() {
+                let balance = match self.client.get_balance(&wallet) {
+                    Ok(lamports) => lamports as f64 / LAMPORTS_PER_SOL,
+                    Err(e) => {
+                        println!(
+                            "{} Failed to read balance of {}: {}",
+                            "[ERROR]".bright_red(),
+                            wallet,
+                            e
+                        );
+                        continue;
+                    }
+                };
+
+                if balance >= self.floor_sol {
+                    if self.low.remove(&wallet) {
+                        println!(
+                            "{} Fee payer {} topped up to {} SOL",
+                            "[INFO]".bright_green(),
+                            wallet,
+                            format_amount(balance)
+                        );
+                    }
+                    continue;
+                }
+                if !self.low.insert(wallet) {
+                    continue;
+                }
+
+                println!(
+                    "{} Fee payer {} down to {} SOL, below the {} SOL floor",
+                    "[ALERT]".bright_yellow(),
+                    wallet,
+                    format_amount(balance),
+                    format_amount(self.floor_sol)
+                );
+                let message = format!(
+                    "⛽ <b>Low Fee Payer Balance</b>\n\n<code>{}</code>\n{} SOL left, below the {} SOL floor\n\n<i>{}</i>",
+                    wallet,
+                    format_amount(balance),
+                    format_amount(self.floor_sol),
+                    format_time(chrono::Utc::now())
+                );
+                notifier.broadcast(&message).await;
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::Result;
+use colored::*;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::decode;
+use crate::utils::telegram::Notifier;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Tracks known arbitrageur wallets and tallies the routes they trade.
+pub struct WalletWatcher {
+    client: RpcClient,
+    wallets: Vec<Pubkey>,
+    last_seen: HashMap<Pubkey, Signature>,
+    // route description -> (arb count, wallets seen)
+    routes: HashMap<String, (usize, Vec<Pubkey>)>,
+}
+
+impl WalletWatcher {
+    pub fn new(rpc_url: String, wallets: Vec<Pubkey>) -> Self {
+        WalletWatcher {
+            client: RpcClient::new(rpc_url),
+            wallets,
+            last_seen: HashMap::new(),
+            routes: HashMap::new(),
+        }
+    }
+
+    fn poll_wallet(&mut self, wallet: Pubkey) -> Result<()> {
+        let until = self.last_seen.get(&wallet).copied();
+        let signatures = self.client.get_signatures_for_address_with_config(
+            &wallet,
+            GetConfirmedSignaturesForAddress2Config {
+                until,
+                ..Default::default()
+            },
+        )?;
+
+        if let Some(newest) = signatures.first() {
+            self.last_seen.insert(wallet, Signature::from_str(&newest.signature)?);
+        }
+        // The first poll only establishes where to start from
+        if until.is_none() {
+            return Ok(());
+        }
+
+        for status in signatures.iter().filter(|s| s.err.is_none()) {
+            let signature = Signature::from_str(&status.signature)?;
+            let tx = self.client.get_transaction_with_config(
+                &signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    max_supported_transaction_version: Some(0),
+                    ..Default::default()
+                },
+            )?;
+
+            let decoded = match decode::decode_transaction(&tx) {
+                Some(decoded) if decoded.is_arb() => decoded,
+                _ => continue,
+            };
+
+            println!(
+                "{} Watched wallet {} arbed at slot {}: {} (tx {})",
+                "[WATCH]".bright_magenta(),
+                wallet,
+                decoded.slot,
+                decoded.describe(),
+                signature
+            );
+            let entry = self.routes.entry(decoded.route()).or_insert((0, Vec::new()));
+            entry.0 += 1;
+            if !entry.1.contains(&wallet) {
+                entry.1.push(wallet);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Takes the tallies collected since the last digest as a Telegram message.
+    fn take_digest(&mut self) -> Option<String> {
+        if self.routes.is_empty() {
+            return None;
+        }
+
+        let mut routes = std::mem::take(&mut self.routes).into_iter().collect::<Vec<_>>();
+        routes.sort_by_key(|(_, (count, _))| std::cmp::Reverse(*count));
+
+        let lines = routes
+            .iter()
+            .map(|(route, (count, wallets))| {
+                format!("• <code>{}</code> ×{} ({} wallet(s))", route, count, wallets.len())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Some(format!("👀 <b>Watched Wallet Activity</b>\n\n{}", lines))
+    }
+
+    /// Polls every watched wallet and sends a digest of observed routes every
+    /// `digest_interval`.
+    pub async fn run(
+        mut self,
+        notifier: Arc<Notifier>,
+        digest_interval: Duration,
+    ) {
+        let mut last_digest = std::time::Instant::now();
+
+        loop {
+            for wallet in self.wallets.clone() {
+                if let Err(e) = self.poll_wallet(wallet) {
+                    println!(
+                        "{} Failed to poll watched wallet {}: {}",
+                        "[ERROR]".bright_red(),
+                        wallet,
+                        e
+                    );
+                }
+            }
+
+            if last_digest.elapsed() >= digest_interval {
+                last_digest = std::time::Instant::now();
+                if let Some(message) = self.take_digest() {
+                    notifier.broadcast(&message).await;
+                }
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}